@@ -11,6 +11,7 @@ Supported benchmark targets:
   - temporal: temporal_mean, temporal_std, median
   - spatial distances: euclidean_distance, manhattan_distance,
                        chebyshev_distance, minkowski_distance
+  - morphology: binary_dilation, binary_erosion, binary_opening, binary_closing
 
 Optional baseline comparison:
   Use --compare-numpy to time an equivalent pure NumPy expression (where feasible)
@@ -30,6 +31,9 @@ Examples:
   Compare against NumPy:
     python scripts/benchmark.py --group spectral --compare-numpy
 
+  Sweep morphology kernel sizes (separable path: time should stay flat):
+    python scripts/benchmark.py --group morphology --kernel-sizes 3 7 15 31 --compare-numpy
+
   Write JSON results:
     python scripts/benchmark.py --group spectral --json-out benchmark_results.json --compare-numpy
 
@@ -189,6 +193,13 @@ def parse_args(argv: Sequence[str]) -> argparse.Namespace:
     parser.add_argument(
         "--texture-window", type=int, default=3, help="Window size for texture entropy."
     )
+    parser.add_argument(
+        "--kernel-sizes",
+        type=int,
+        nargs="+",
+        default=[3],
+        help="Square kernel sizes for morphology benchmarks (one run per size).",
+    )
     parser.add_argument("--point-dim", type=int, default=4)
     parser.add_argument("--minkowski-p", type=float, default=3.0)
     parser.add_argument("--ma-window", type=int, default=5)
@@ -280,7 +291,13 @@ def compute_elements(func_name: str, shape_info: dict[str, int], args) -> Option
     }:
         t, h, w = shape_info["time"], shape_info["height"], shape_info["width"]
         return t * h * w
-    if func_name == "haralick_features":
+    if func_name in {
+        "haralick_features",
+        "binary_dilation",
+        "binary_erosion",
+        "binary_opening",
+        "binary_closing",
+    }:
         h, w = shape_info["height"], shape_info["width"]
         return h * w
     if func_name == "trend_analysis":
@@ -356,6 +373,7 @@ def run_single_benchmark(
     ma_stride: int = 4,
     ma_baseline_style: str = "naive",
     zones_count: int = 100,
+    kernel_size: int = 3,
 ) -> BenchmarkResult:
     # Predeclare delta arrays to satisfy static type checkers (overwritten when used).
     pre_nir: np.ndarray = np.empty((0, 0))
@@ -550,7 +568,6 @@ def run_single_benchmark(
         data = np.random.randint(
             0, 2, size=(shape_info["height"], shape_info["width"]), dtype=np.uint8
         )
-        kernel_size = 3
 
         call = lambda: getattr(eo_processor, func_name)(data, kernel_size)
         shape_desc = (
@@ -1192,6 +1209,12 @@ def main(argv: Optional[Sequence[str]] = None) -> int:
                 "chebyshev_distance",
                 "minkowski_distance",
             }
+            is_morphology = f in {
+                "binary_dilation",
+                "binary_erosion",
+                "binary_opening",
+                "binary_closing",
+            }
             if is_distance and args.distance_baseline == "both":
                 for mode in ("broadcast", "streaming"):
                     res = run_single_benchmark(
@@ -1206,6 +1229,21 @@ def main(argv: Optional[Sequence[str]] = None) -> int:
                         name_override=f"{f}[{mode}]",
                     )
                     results.append(res)
+            elif is_morphology and len(args.kernel_sizes) > 1:
+                # Kernel-size sweep: one row per size shows how cost scales.
+                for k in args.kernel_sizes:
+                    res = run_single_benchmark(
+                        func_name=f,
+                        loops=args.loops,
+                        warmups=args.warmups,
+                        shape_info=shp,
+                        args=args,
+                        seed=args.seed,
+                        compare_numpy=args.compare_numpy,
+                        name_override=f"{f}[k={k}]",
+                        kernel_size=k,
+                    )
+                    results.append(res)
             else:
                 res = run_single_benchmark(
                     func_name=f,
@@ -1219,6 +1257,7 @@ def main(argv: Optional[Sequence[str]] = None) -> int:
                     name_override=None,
                     ma_window=args.ma_window,
                     ma_stride=args.ma_stride,
+                    kernel_size=args.kernel_sizes[0],
                 )
                 results.append(res)
 
@@ -1295,6 +1334,7 @@ def main(argv: Optional[Sequence[str]] = None) -> int:
                 "functions": funcs,
                 "shape_info": shape_info,
                 "size_sweep": args.size_sweep,
+                "kernel_sizes": args.kernel_sizes,
                 "sweep_shape_infos": shape_infos if args.size_sweep else None,
             },
             "results": [asdict(r) for r in results],
@@ -1327,6 +1367,7 @@ def main(argv: Optional[Sequence[str]] = None) -> int:
         "MA Stride": str(args.ma_stride),
         "MA Baseline": args.ma_baseline,
         "Zones Count": str(args.zones_count),
+        "Kernel Sizes": str(args.kernel_sizes),
     }
 
     if getattr(args, "md_out", None):
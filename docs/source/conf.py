@@ -131,6 +131,8 @@ exclude_patterns = [
     "_build",
     "Thumbs.db",
     ".DS_Store",
+    # Generated by _write_index_table; only pulled in via ``.. include::``.
+    "api/index_table.rst",
 ]
 
 # ---------------------------------------------------------------------------
@@ -188,6 +190,58 @@ def _link_external_markdown():
 
 _link_external_markdown()
 
+
+# ---------------------------------------------------------------------------
+# Spectral index table generated from the Rust-side registry
+# ---------------------------------------------------------------------------
+INDEX_TABLE = Path(__file__).parent / "api" / "index_table.rst"
+
+
+def _write_index_table():
+    """
+    Render ``eo_processor.index_registry()`` as a list-table so the docs never
+    drift from the compiled core. Falls back to a note when the extension is
+    not built (e.g. RTD before the wheel is available).
+    """
+    try:
+        from eo_processor import index_registry
+
+        registry = index_registry()
+    except Exception as e:
+        print(f"[conf.py] Warning: index registry unavailable: {e}")
+        INDEX_TABLE.write_text(
+            ".. note::\n\n   Build the Rust extension to render the index table.\n",
+            encoding="utf-8",
+        )
+        return
+
+    def fmt(bound):
+        return "unbounded" if abs(bound) == float("inf") else f"{bound:g}"
+
+    lines = [
+        ".. list-table:: Spectral Index Registry",
+        "   :header-rows: 1",
+        "",
+        "   * - Index",
+        "     - Formula",
+        "     - Bands",
+        "     - Valid range",
+        "     - Reference",
+    ]
+    for info in registry.values():
+        lo, hi = info.valid_range
+        lines += [
+            f"   * - ``{info.name}`` ({info.long_name})",
+            f"     - ``{info.formula}``",
+            f"     - {', '.join(f'``{b}``' for b in info.bands)}",
+            f"     - [{fmt(lo)}, {fmt(hi)}]",
+            f"     - {info.reference or '-'}",
+        ]
+    INDEX_TABLE.write_text("\n".join(lines) + "\n", encoding="utf-8")
+
+
+_write_index_table()
+
 # ---------------------------------------------------------------------------
 # High-level Rust UDF architecture note (injected into a page)
 # ---------------------------------------------------------------------------
@@ -4,20 +4,36 @@ High-performance Earth Observation processing library.
 This library provides Rust-accelerated functions for common EO/geospatial
 computations that can be used within XArray/Dask workflows to bypass Python's GIL.
 
-NOTE: All public spectral and temporal functions accept any numeric NumPy dtype
-(int, uint, float32, float64, etc.). Inputs are automatically coerced to float64
-in the Rust layer for consistent and stable computation.
+NOTE: All public spectral, masking and temporal functions accept any numeric
+NumPy dtype (int, uint, float32, float64, etc.). float64, float32 and uint16
+arrays are read natively without a copy; other dtypes are coerced to float64.
+Formulas are evaluated in float64 and results are float32 for float32 / uint16
+input, float64 otherwise. Spectral and temporal functions also accept optional
+``scale`` / ``offset`` arguments to convert integer DNs to reflectance.
 """
 
 from ._core import (
+    band_math as _band_math,
     chebyshev_distance as _chebyshev_distance,
     composite_mean as _composite_mean,
     composite_std as _composite_std,
+    compute_indices as _compute_indices,
+    index_registry as _index_registry,
+    band_roles as _band_roles,
+    sensor_indices as _sensor_indices,
+    sensor_profiles as _sensor_profiles,
+    tasseled_cap as _tasseled_cap,
+    linear_unmixing as _linear_unmixing,
+    spectral_similarity as _spectral_similarity,
+    pca_transform as _pca_transform,
+    mnf_transform as _mnf_transform,
+    UnknownBandRoleError,
     delta_nbr as _delta_nbr,
     delta_ndvi as _delta_ndvi,
     enhanced_vegetation_index as _enhanced_vegetation_index,
     evi2 as _evi2,
     euclidean_distance as _euclidean_distance,
+    distance_transform as _distance_transform,
     gci as _gci,
     manhattan_distance as _manhattan_distance,
     mask_in_range as _mask_in_range,
@@ -25,6 +41,11 @@ from ._core import (
     mask_out_range as _mask_out_range,
     mask_scl as _mask_scl,
     mask_with_scl as _mask_with_scl,
+    qa_mask as _qa_mask,
+    mask_with_qa as _mask_with_qa,
+    buffer_mask as _buffer_mask,
+    cloud_shadow_mask as _cloud_shadow_mask,
+    scene_cloud_stats as _scene_cloud_stats,
     mask_vals as _mask_vals,
     median as _median,
     minkowski_distance as _minkowski_distance,
@@ -45,8 +66,22 @@ from ._core import (
     lai as _lai,
     dnbr as _dnbr,
     rbr as _rbr,
+    change_vector_analysis as _change_vector_analysis,
     ci_re as _ci_re,
     mtci as _mtci,
+    ndbi as _ndbi,
+    mndwi as _mndwi,
+    awei_nsh as _awei_nsh,
+    awei_sh as _awei_sh,
+    bsi as _bsi,
+    kndvi as _kndvi,
+    nirv as _nirv,
+    sipi as _sipi,
+    psri as _psri,
+    arvi as _arvi,
+    vari as _vari,
+    tcari_osavi as _tcari_osavi,
+    ndci as _ndci,
     pixelwise_transform as _pixelwise_transform,
     replace_nans as _replace_nans,
     savi as _savi,
@@ -59,8 +94,23 @@ from ._core import (
     binary_erosion as _binary_erosion,
     binary_opening as _binary_opening,
     binary_closing as _binary_closing,
+    structuring_element as _structuring_element,
+    grey_erosion as _grey_erosion,
+    grey_dilation as _grey_dilation,
+    grey_opening as _grey_opening,
+    grey_closing as _grey_closing,
+    white_tophat as _white_tophat,
+    black_tophat as _black_tophat,
+    morphological_gradient as _morphological_gradient,
+    label_components as _label_components,
+    ComponentProps as _ComponentProps,
+    sieve as _sieve,
+    fill_holes as _fill_holes,
+    majority_filter as _majority_filter,
     bfast_monitor as _bfast_monitor,
     complex_classification as _complex_classification,
+    burn_severity as _burn_severity,
+    fmask as _fmask,
     random_forest_predict as _random_forest_predict,
     random_forest_train as _random_forest_train,
     haralick_features as _haralick_features,
@@ -69,7 +119,9 @@ import logging
 import structlog
 import numpy as np
 import xarray as xr
+from dataclasses import dataclass
 from functools import partial
+from typing import Callable, Mapping, Optional
 
 # Configure structlog for structured, extensible logging
 structlog.configure(
@@ -103,15 +155,20 @@ log.setLevel(logging.INFO)
 __version__ = "0.23.2"
 
 __all__ = [
+    "band_math",
+    "band_roles",
     "chebyshev_distance",
     "composite",
+    "compute_indices",
     "delta_nbr",
     "delta_ndvi",
     "enhanced_vegetation_index",
     "euclidean_distance",
+    "distance_transform",
     "evi",
     "evi2",
     "gci",
+    "index_registry",
     "log",
     "manhattan_distance",
     "mask_in_range",
@@ -119,6 +176,11 @@ __all__ = [
     "mask_out_range",
     "mask_scl",
     "mask_with_scl",
+    "qa_mask",
+    "mask_with_qa",
+    "buffer_mask",
+    "cloud_shadow_mask",
+    "scene_cloud_stats",
     "mask_vals",
     "median",
     "minkowski_distance",
@@ -139,11 +201,31 @@ __all__ = [
     "lai",
     "dnbr",
     "rbr",
+    "change_vector_analysis",
     "ci_re",
     "mtci",
+    "ndbi",
+    "mndwi",
+    "awei_nsh",
+    "awei_sh",
+    "bsi",
+    "kndvi",
+    "nirv",
+    "sipi",
+    "psri",
+    "arvi",
+    "vari",
+    "tcari_osavi",
+    "ndci",
     "pixelwise_transform",
     "replace_nans",
     "savi",
+    "sensor_profiles",
+    "tasseled_cap",
+    "linear_unmixing",
+    "spectral_similarity",
+    "pca_transform",
+    "mnf_transform",
     "temporal_mean",
     "temporal_std",
     "temporal_sum",
@@ -155,11 +237,29 @@ __all__ = [
     "binary_erosion",
     "binary_opening",
     "binary_closing",
+    "structuring_element",
+    "grey_erosion",
+    "grey_dilation",
+    "grey_opening",
+    "grey_closing",
+    "white_tophat",
+    "black_tophat",
+    "morphological_gradient",
+    "label_components",
+    "ComponentProps",
+    "sieve",
+    "fill_holes",
+    "majority_filter",
     "bfast_monitor",
     "complex_classification",
+    "burn_severity",
+    "fmask",
     "haralick_features",
     "random_forest_predict",
     "random_forest_train",
+    "SensorProfileInfo",
+    "SpectralIndexInfo",
+    "UnknownBandRoleError",
 ]
 
 
@@ -218,6 +318,171 @@ def complex_classification(blue, green, red, nir, swir1, swir2, temp):
     return _complex_classification(blue, green, red, nir, swir1, swir2, temp)
 
 
+def burn_severity(
+    pre_nir,
+    pre_swir2,
+    post_nir,
+    post_swir2,
+    index="dnbr",
+    thresholds=None,
+    reference=None,
+    water_mask=None,
+    scale=None,
+    offset=None,
+    pixel_area=None,
+):
+    """
+    Burn severity index and classes from pre/post-fire NIR and SWIR2.
+
+    Parameters
+    ----------
+    pre_nir, pre_swir2, post_nir, post_swir2 : numpy.ndarray
+        Bands of equal shape.
+    index : {"dnbr", "rbr", "rdnbr"}, default "dnbr"
+        ``NBR_pre - NBR_post``, ``dNBR / (NBR_pre + 1)`` or
+        ``dNBR / sqrt(|NBR_pre|)`` (Miller & Thode 2007).
+    thresholds : sequence of float, optional
+        Strictly increasing class breaks. Defaults to Key & Benson (2006) for
+        dNBR: ``[-0.25, -0.1, 0.1, 0.27, 0.44, 0.66]`` giving enhanced
+        regrowth high (1) / low (2), unburned (3), low (4), moderate-low (5),
+        moderate-high (6) and high (7) severity; to Parks et al. (2014) for
+        RBR: ``[0.035, 0.130, 0.298]``; and to Miller & Thode (2007) for
+        RdNBR: ``[0.069, 0.316, 0.641]``. Both of the latter give unchanged
+        (1), low (2), moderate (3) and high (4).
+    reference : numpy.ndarray of bool, optional
+        Unburned reference pixels. Their mean dNBR is subtracted as an offset
+        before the index is formed.
+    water_mask : numpy.ndarray of bool, optional
+        Pixels to exclude (class 0, NaN index).
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to the bands.
+    pixel_area : float, optional
+        Area of one pixel in the unit wanted for the output (e.g. ``0.09``
+        ha for 30 m pixels). When given, per-class areas are also returned.
+
+    Returns
+    -------
+    index : numpy.ndarray
+        Offset-corrected severity index; NaN for excluded or NaN pixels.
+    classes : numpy.ndarray
+        uint8 class map; 0 for excluded or NaN pixels.
+    counts : numpy.ndarray
+        int64 pixel count (not area) per class code; ``counts[0]`` is
+        excluded pixels.
+    dnbr_offset : float
+        The subtracted dNBR offset (0.0 without a reference).
+    areas : numpy.ndarray
+        Only with ``pixel_area``: float64 area per class code,
+        ``counts * pixel_area``.
+
+    Examples
+    --------
+    >>> rbr, classes, counts, off, areas = burn_severity(
+    ...     pre_nir, pre_swir2, post_nir, post_swir2, index="rbr",
+    ...     reference=unburned, water_mask=water, pixel_area=0.09,
+    ... )
+    >>> high_ha = areas[4]  # 30 m pixels, hectares
+    """
+    return _burn_severity(
+        pre_nir,
+        pre_swir2,
+        post_nir,
+        post_swir2,
+        index=index,
+        thresholds=None if thresholds is None else [float(t) for t in thresholds],
+        reference=None if reference is None else np.asarray(reference, dtype=bool),
+        water_mask=None if water_mask is None else np.asarray(water_mask, dtype=bool),
+        scale=scale,
+        offset=offset,
+        pixel_area=pixel_area,
+    )
+
+
+def fmask(
+    blue,
+    green,
+    red,
+    nir,
+    swir1,
+    swir2,
+    cirrus=None,
+    thermal=None,
+    sun_azimuth=None,
+    sun_zenith=None,
+    pixel_size=30.0,
+    cloud_probability=0.2,
+    cloud_heights=(200.0, 12000.0),
+    scale=None,
+    offset=None,
+):
+    """
+    Fmask-style rule-based cloud, cloud shadow and snow mask.
+
+    Implements the Fmask pass-one tests (Zhu & Woodcock 2012): potential
+    cloud pixels (basic, whiteness, HOT and NIR/SWIR1 ratio tests), the
+    water test, and land/water cloud probabilities with temperature terms
+    calibrated on the scene's clear pixels, plus the Fmask 3.3 cirrus term.
+
+    Parameters
+    ----------
+    blue, green, red, nir, swir1, swir2 : numpy.ndarray
+        ``(y, x)`` reflectance bands (float64, float32 or uint16 DN).
+    cirrus : numpy.ndarray, optional
+        Cirrus band reflectance (Landsat 8/9 B9, Sentinel-2 B10).
+    thermal : numpy.ndarray, optional
+        Brightness temperature in kelvin. Without it the cloud
+        probabilities use spectral terms only.
+    sun_azimuth, sun_zenith : float, optional
+        Solar angles in degrees. When given, cloud shadows are projected
+        from the cloud layer as in :func:`cloud_shadow_mask`.
+    pixel_size : float, default 30.0
+        Pixel size in metres, for shadow projection.
+    cloud_probability : float, default 0.2
+        Added to the 82.5th percentile of the clear-land cloud probability
+        to form the land cloud threshold; raise it to flag fewer clouds.
+    cloud_heights : (float, float), default (200, 12000)
+        Candidate cloud heights in metres, for shadow projection.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to the reflectance
+        bands (not to ``thermal``).
+
+    Returns
+    -------
+    classes : numpy.ndarray
+        uint8 mask with Sentinel-2 SCL codes: 0 nodata, 3 cloud shadow,
+        4 vegetation, 5 not vegetated, 6 water, 9 cloud, 11 snow. It can be
+        passed to :func:`mask_with_scl` directly.
+    probability : numpy.ndarray
+        Land or water cloud probability; NaN where a band is invalid.
+
+    Examples
+    --------
+    >>> classes, prob = fmask(b2, b3, b4, b8, b11, b12, cirrus=b10,
+    ...                       sun_azimuth=150.2, sun_zenith=38.5,
+    ...                       pixel_size=20, scale=1e-4)
+    >>> clear = mask_with_scl(cube, classes)
+    """
+    if thermal is not None:
+        thermal = np.asarray(thermal, dtype=np.float64)
+    return _fmask(
+        blue,
+        green,
+        red,
+        nir,
+        swir1,
+        swir2,
+        cirrus=cirrus,
+        thermal=thermal,
+        sun_azimuth=None if sun_azimuth is None else float(sun_azimuth),
+        sun_zenith=None if sun_zenith is None else float(sun_zenith),
+        pixel_size=float(pixel_size),
+        cloud_probability=float(cloud_probability),
+        cloud_heights=tuple(float(h) for h in cloud_heights),
+        scale=scale,
+        offset=offset,
+    )
+
+
 def _apply_haralick(data_block, window_size, levels, boundary, dtype):
     """Helper to apply Haralick features and handle dask chunk boundaries."""
     # If the original block is smaller than the window, no features can be calculated.
@@ -331,19 +596,53 @@ def haralick_features(
     return result
 
 
-def normalized_difference(a, b):
+def band_math(expression, bands):
+    """
+    Evaluate a custom band-math expression in the Rust core.
+
+    The expression is compiled once and evaluated in parallel over arrays of
+    any rank. Band names in the expression are looked up in ``bands``.
+
+    Supported syntax: ``+ - * / ^`` (``**`` is accepted as power), unary minus,
+    parentheses, numeric literals and the functions ``abs``, ``sqrt``, ``exp``,
+    ``log``, ``log10``, ``min(a, b)`` and ``max(a, b)``. Division by a
+    near-zero denominator yields 0.0, matching the built-in indices.
+
+    Parameters
+    ----------
+    expression : str
+        Expression such as ``"(nir - red) / (nir + red + 0.5) * 1.5"``.
+    bands : dict[str, numpy.ndarray]
+        Mapping of band name to array. Referenced bands may have any rank and
+        are broadcast NumPy-style. Any numeric dtype is accepted (coerced to
+        float64).
+
+    Returns
+    -------
+    numpy.ndarray
+        float64 array with the broadcast shape of the input bands.
+    """
+    return _band_math(expression, bands)
+
+
+def normalized_difference(a, b, scale=None, offset=None):
     """
     Compute normalized difference (a - b) / (a + b) using the Rust core.
-    Supports 1D or 2D numpy float arrays; dimensional dispatch occurs in Rust.
+    Accepts arrays of any rank; shapes are broadcast NumPy-style (size-1 axes
+    stretch to match).
+    Optional ``scale``/``offset`` convert DN inputs (``value * scale + offset``)
+    before the formula.
     """
-    return _normalized_difference(a, b)
+    return _normalized_difference(a, b, scale=scale, offset=offset)
 
 
-def ndvi(nir, red):
+def ndvi(nir, red, scale=None, offset=None):
     """
-    Compute NDVI = (NIR - Red) / (NIR + Red) via Rust core (1D or 2D).
+    Compute NDVI = (NIR - Red) / (NIR + Red) via Rust core (any rank).
+    Optional ``scale``/``offset`` convert DN inputs (``value * scale + offset``)
+    before the formula.
     """
-    return _ndvi(nir, red)
+    return _ndvi(nir, red, scale=scale, offset=offset)
 
 
 def linear_regression(y):
@@ -372,70 +671,327 @@ def zonal_stats(values: np.ndarray, zones: np.ndarray) -> dict[int, ZoneStats]:
     return _zonal_stats(values, zones)
 
 
-def binary_dilation(input: np.ndarray, kernel_size: int = 3) -> np.ndarray:
+def structuring_element(shape: str, size: int = 3, angle: float = 0.0) -> np.ndarray:
+    """
+    Build a structuring element for the binary morphology functions.
+
+    Args:
+        shape: "square", "disk" (Euclidean), "diamond" (city-block),
+            "cross" (plus sign) or "line".
+        size: Width in pixels; the radius is ``size // 2``.
+        angle: Line orientation in degrees, counter-clockwise from the x axis.
+
+    Returns:
+        Square boolean footprint of side ``2 * (size // 2) + 1``.
+    """
+    return _structuring_element(shape, size, float(angle))
+
+
+def _footprint_arg(footprint):
+    """Pass footprint names through and convert masks to bool."""
+    if footprint is None or isinstance(footprint, str):
+        return footprint
+    return np.asarray(footprint, dtype=bool)
+
+
+def binary_dilation(input: np.ndarray, kernel_size: int = 3, footprint=None) -> np.ndarray:
     """
     Perform binary dilation on a 2D boolean/int array.
 
     Args:
         input: 2D input array (treated as boolean: >0 is True).
         kernel_size: Size of the square structuring element (default 3).
+        footprint: Optional footprint: a name ("disk", "diamond", "cross",
+            "line", "square") sized by ``kernel_size``, or a 2D boolean mask
+            such as ``structuring_element("line", 15, angle=45)``.
 
     Returns:
         Dilated 2D array (uint8: 0 or 1).
     """
-    return _binary_dilation(input, kernel_size)
+    return _binary_dilation(input, kernel_size, _footprint_arg(footprint))
 
 
-def binary_erosion(input: np.ndarray, kernel_size: int = 3) -> np.ndarray:
+def binary_erosion(input: np.ndarray, kernel_size: int = 3, footprint=None) -> np.ndarray:
     """
     Perform binary erosion on a 2D boolean/int array.
 
     Args:
         input: 2D input array (treated as boolean: >0 is True).
         kernel_size: Size of the square structuring element (default 3).
+        footprint: Optional footprint: a name ("disk", "diamond", "cross",
+            "line", "square") sized by ``kernel_size``, or a 2D boolean mask
+            such as ``structuring_element("line", 15, angle=45)``.
 
     Returns:
         Eroded 2D array (uint8: 0 or 1).
     """
-    return _binary_erosion(input, kernel_size)
+    return _binary_erosion(input, kernel_size, _footprint_arg(footprint))
 
 
-def binary_opening(input: np.ndarray, kernel_size: int = 3) -> np.ndarray:
+def binary_opening(input: np.ndarray, kernel_size: int = 3, footprint=None) -> np.ndarray:
     """
     Perform binary opening (erosion followed by dilation).
 
     Args:
         input: 2D input array (treated as boolean: >0 is True).
         kernel_size: Size of the square structuring element (default 3).
+        footprint: Optional footprint: a name ("disk", "diamond", "cross",
+            "line", "square") sized by ``kernel_size``, or a 2D boolean mask
+            such as ``structuring_element("line", 15, angle=45)``.
 
     Returns:
         Opened 2D array (uint8: 0 or 1).
     """
-    return _binary_opening(input, kernel_size)
+    return _binary_opening(input, kernel_size, _footprint_arg(footprint))
 
 
-def binary_closing(input: np.ndarray, kernel_size: int = 3) -> np.ndarray:
+def binary_closing(input: np.ndarray, kernel_size: int = 3, footprint=None) -> np.ndarray:
     """
     Perform binary closing (dilation followed by erosion).
 
     Args:
         input: 2D input array (treated as boolean: >0 is True).
         kernel_size: Size of the square structuring element (default 3).
+        footprint: Optional footprint: a name ("disk", "diamond", "cross",
+            "line", "square") sized by ``kernel_size``, or a 2D boolean mask
+            such as ``structuring_element("line", 15, angle=45)``.
 
     Returns:
         Closed 2D array (uint8: 0 or 1).
     """
-    return _binary_closing(input, kernel_size)
+    return _binary_closing(input, kernel_size, _footprint_arg(footprint))
+
+
+def grey_erosion(input, kernel_size: int = 3, footprint=None) -> np.ndarray:
+    """
+    Grayscale erosion: minimum over the footprint.
+
+    Args:
+        input: 2D raster (float64 stays float64; float32 and uint16 give
+            float32). NaN pixels are ignored inside windows and stay NaN;
+            pixels outside the image are ignored.
+        kernel_size: Size of the square structuring element (default 3).
+        footprint: Optional footprint name or 2D boolean mask, as for the
+            binary operations.
+
+    Returns:
+        Eroded 2D array.
+    """
+    return _grey_erosion(input, kernel_size, _footprint_arg(footprint))
+
+
+def grey_dilation(input, kernel_size: int = 3, footprint=None) -> np.ndarray:
+    """
+    Grayscale dilation: maximum over the (reflected) footprint.
+
+    Args:
+        input: 2D raster (float64 stays float64; float32 and uint16 give
+            float32). NaN pixels are ignored inside windows and stay NaN;
+            pixels outside the image are ignored.
+        kernel_size: Size of the square structuring element (default 3).
+        footprint: Optional footprint name or 2D boolean mask, as for the
+            binary operations.
+
+    Returns:
+        Dilated 2D array.
+    """
+    return _grey_dilation(input, kernel_size, _footprint_arg(footprint))
+
+
+def grey_opening(input, kernel_size: int = 3, footprint=None) -> np.ndarray:
+    """
+    Grayscale opening (erosion followed by dilation).
+
+    Args:
+        input: 2D raster (float64 stays float64; float32 and uint16 give
+            float32). NaN pixels are ignored inside windows and stay NaN;
+            pixels outside the image are ignored.
+        kernel_size: Size of the square structuring element (default 3).
+        footprint: Optional footprint name or 2D boolean mask, as for the
+            binary operations.
+
+    Returns:
+        Opened 2D array.
+    """
+    return _grey_opening(input, kernel_size, _footprint_arg(footprint))
+
+
+def grey_closing(input, kernel_size: int = 3, footprint=None) -> np.ndarray:
+    """
+    Grayscale closing (dilation followed by erosion).
+
+    Args:
+        input: 2D raster (float64 stays float64; float32 and uint16 give
+            float32). NaN pixels are ignored inside windows and stay NaN;
+            pixels outside the image are ignored.
+        kernel_size: Size of the square structuring element (default 3).
+        footprint: Optional footprint name or 2D boolean mask, as for the
+            binary operations.
+
+    Returns:
+        Closed 2D array.
+    """
+    return _grey_closing(input, kernel_size, _footprint_arg(footprint))
+
+
+def white_tophat(input, kernel_size: int = 3, footprint=None) -> np.ndarray:
+    """
+    White top-hat ``input - grey_opening(input)``: bright features
+    smaller than the footprint (e.g. built-up objects).
+
+    Args:
+        input: 2D raster (float64 stays float64; float32 and uint16 give
+            float32). NaN pixels are ignored inside windows and stay NaN;
+            pixels outside the image are ignored.
+        kernel_size: Size of the square structuring element (default 3).
+        footprint: Optional footprint name or 2D boolean mask, as for the
+            binary operations.
+
+    Returns:
+        Top-hat 2D array.
+    """
+    return _white_tophat(input, kernel_size, _footprint_arg(footprint))
+
+
+def black_tophat(input, kernel_size: int = 3, footprint=None) -> np.ndarray:
+    """
+    Black top-hat ``grey_closing(input) - input``: dark features
+    smaller than the footprint (e.g. narrow water channels in SWIR).
+
+    Args:
+        input: 2D raster (float64 stays float64; float32 and uint16 give
+            float32). NaN pixels are ignored inside windows and stay NaN;
+            pixels outside the image are ignored.
+        kernel_size: Size of the square structuring element (default 3).
+        footprint: Optional footprint name or 2D boolean mask, as for the
+            binary operations.
+
+    Returns:
+        Top-hat 2D array.
+    """
+    return _black_tophat(input, kernel_size, _footprint_arg(footprint))
+
+
+def morphological_gradient(input, kernel_size: int = 3, footprint=None) -> np.ndarray:
+    """
+    Morphological gradient ``grey_dilation(input) - grey_erosion(input)``.
+
+    Args:
+        input: 2D raster (float64 stays float64; float32 and uint16 give
+            float32). NaN pixels are ignored inside windows and stay NaN;
+            pixels outside the image are ignored.
+        kernel_size: Size of the square structuring element (default 3).
+        footprint: Optional footprint name or 2D boolean mask, as for the
+            binary operations.
+
+    Returns:
+        Gradient 2D array.
+    """
+    return _morphological_gradient(input, kernel_size, _footprint_arg(footprint))
+
+
+ComponentProps = _ComponentProps
+
+
+def label_components(mask, connectivity: int = 8, values=None):
+    """
+    Label connected components of a binary mask.
+
+    Args:
+        mask: 2D mask; values > 0 are foreground (e.g. ``binary_opening``
+            output).
+        connectivity: 4 (edge neighbours) or 8 (default, also corners).
+        values: Optional 2D raster of the same shape (coerced to float64);
+            each component gets the mean of its non-NaN values.
+
+    Returns:
+        ``(labels, props)``: an int64 label array (0 is background,
+        components are numbered from 1 in raster order) that can be passed
+        directly to ``zonal_stats`` as ``zones``, and a dict mapping each
+        label to a ``ComponentProps`` with ``area``, ``bbox`` (``(min_row,
+        min_col, max_row, max_col)``, max exclusive), ``centroid``
+        (``(row, col)``), ``perimeter`` (exposed pixel edges) and ``mean``.
+    """
+    mask = np.asarray(mask) > 0
+    if values is not None:
+        values = np.asarray(values, dtype=np.float64)
+    return _label_components(mask, connectivity, values)
+
+
+def _class_map(classes):
+    """Validate an integer class map and return it with its dtype as int64."""
+    classes = np.asarray(classes)
+    if not np.issubdtype(classes.dtype, np.integer):
+        raise TypeError(f"class map must have an integer dtype, got {classes.dtype}")
+    return classes.astype(np.int64, copy=False)
+
+
+def sieve(classes, threshold: int, connectivity: int = 4, nodata=None) -> np.ndarray:
+    """
+    Remove regions smaller than ``threshold`` pixels from a class map, like
+    GDAL's sieve: each small region is merged into its largest neighbouring
+    region, smallest regions first.
+
+    Args:
+        classes: 2D integer class map (e.g. ``random_forest_predict`` or
+            ``complex_classification`` output).
+        threshold: Minimum region size in pixels.
+        connectivity: 4 (default) or 8.
+        nodata: Optional class that is left untouched and never merged into.
+
+    Returns:
+        Sieved class map with the input dtype.
+    """
+    arr = np.asarray(classes)
+    return _sieve(_class_map(arr), threshold, connectivity, nodata).astype(arr.dtype, copy=False)
+
+
+def fill_holes(input, connectivity: int = 4) -> np.ndarray:
+    """
+    Fill holes in a binary mask: unset regions that do not touch the image
+    border become set.
+
+    Args:
+        input: 2D input array (treated as boolean: >0 is True).
+        connectivity: Connectivity of the background regions, 4 (default)
+            or 8. With 8, a diagonal gap is enough to open a hole.
+
+    Returns:
+        Filled 2D array (uint8: 0 or 1).
+    """
+    return _fill_holes((np.asarray(input) > 0).astype(np.uint8), connectivity)
+
+
+def majority_filter(classes, kernel_size: int = 3, footprint=None, nodata=None) -> np.ndarray:
+    """
+    Majority (mode) filter for class maps.
+
+    Args:
+        classes: 2D integer class map.
+        kernel_size: Size of the square window (default 3).
+        footprint: Optional footprint name or 2D boolean mask, as for the
+            binary operations.
+        nodata: Optional class that is not counted and stays unchanged.
+
+    Returns:
+        Filtered class map with the input dtype. Ties keep the centre class
+        if it is among the most frequent, otherwise the smallest tied class.
+    """
+    arr = np.asarray(classes)
+    out = _majority_filter(_class_map(arr), kernel_size, _footprint_arg(footprint), nodata)
+    return out.astype(arr.dtype, copy=False)
 
 
-def ndwi(green, nir):
+def ndwi(green, nir, scale=None, offset=None):
     """
-    Compute NDWI = (Green - NIR) / (Green + NIR) via Rust core (1D or 2D).
+    Compute NDWI = (Green - NIR) / (Green + NIR) via Rust core (any rank).
+    Optional ``scale``/``offset`` convert DN inputs (``value * scale + offset``)
+    before the formula.
     """
-    return _ndwi(green, nir)
+    return _ndwi(green, nir, scale=scale, offset=offset)
 
 
-def ndsi(green, swir1):
+def ndsi(green, swir1, scale=None, offset=None):
     """
     Normalized Difference Snow Index (NDSI)
 
@@ -447,16 +1003,19 @@ def ndsi(green, swir1):
         Green band.
     swir1 : numpy.ndarray
         Short-wave infrared 1 band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
     numpy.ndarray
         NDSI values (typically in [-1, 1]).
     """
-    return _ndsi(green, swir1)
+    return _ndsi(green, swir1, scale=scale, offset=offset)
 
 
-def savi(nir, red, L=0.5, **kwargs):
+def savi(nir, red, L=0.5, scale=None, offset=None, **kwargs):
     """
     Compute Soil Adjusted Vegetation Index (SAVI).
 
@@ -473,6 +1032,9 @@ def savi(nir, red, L=0.5, **kwargs):
         Larger L reduces soil background influence.
     **kwargs :
         May contain 'l' to specify the soil adjustment factor instead of 'L'.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
@@ -488,10 +1050,10 @@ def savi(nir, red, L=0.5, **kwargs):
     If both L and l are provided, 'l' takes precedence.
     """
     l_val = kwargs.get("l", L)
-    return _savi(nir, red, l_val)
+    return _savi(nir, red, l_val, scale=scale, offset=offset)
 
 
-def osavi(nir, red):
+def osavi(nir, red, scale=None, offset=None):
     """
     Compute Optimized Soil Adjusted Vegetation Index (OSAVI).
 
@@ -503,6 +1065,9 @@ def osavi(nir, red):
         Near-infrared band.
     red : numpy.ndarray
         Red band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
@@ -514,10 +1079,10 @@ def osavi(nir, red):
     OSAVI uses a fixed L=0.16, optimized for environments with
     moderate vegetation cover. It requires no parameter tuning.
     """
-    return _osavi(nir, red)
+    return _osavi(nir, red, scale=scale, offset=offset)
 
 
-def msavi(nir, red):
+def msavi(nir, red, scale=None, offset=None):
     """
     Compute Modified Soil Adjusted Vegetation Index (MSAVI).
 
@@ -529,6 +1094,9 @@ def msavi(nir, red):
         Near-infrared band.
     red : numpy.ndarray
         Red band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
@@ -540,10 +1108,10 @@ def msavi(nir, red):
     MSAVI is a modification of SAVI that uses a self-adjustment mechanism
     to avoid the need for a soil brightness correction factor (L).
     """
-    return _msavi(nir, red)
+    return _msavi(nir, red, scale=scale, offset=offset)
 
 
-def gndvi(nir, green):
+def gndvi(nir, green, scale=None, offset=None):
     """
     Compute Green Normalized Difference Vegetation Index (GNDVI).
 
@@ -555,6 +1123,9 @@ def gndvi(nir, green):
         Near-infrared band.
     green : numpy.ndarray
         Green band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
@@ -567,10 +1138,10 @@ def gndvi(nir, green):
     It is more sensitive to chlorophyll concentration and can be used
     to assess vegetation health and nitrogen content.
     """
-    return _gndvi(nir, green)
+    return _gndvi(nir, green, scale=scale, offset=offset)
 
 
-def ndre(nir, rededge):
+def ndre(nir, rededge, scale=None, offset=None):
     """
     Compute Normalized Difference Red Edge (NDRE).
 
@@ -582,6 +1153,9 @@ def ndre(nir, rededge):
         Near-infrared band.
     rededge : numpy.ndarray
         Red edge band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
@@ -594,10 +1168,10 @@ def ndre(nir, rededge):
     and plant stress. It is useful for assessing vegetation health and
     detecting early signs of stress before visible symptoms appear.
     """
-    return _ndre(nir, rededge)
+    return _ndre(nir, rededge, scale=scale, offset=offset)
 
 
-def ndvi_re2(nir, rededge, red):
+def ndvi_re2(nir, rededge, red, scale=None, offset=None):
     """
     Compute Normalized Difference Vegetation Index Red Edge (NDVIre).
 
@@ -611,16 +1185,19 @@ def ndvi_re2(nir, rededge, red):
         Red edge band.
     red : numpy.ndarray
         Red band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
     numpy.ndarray
         NDVIre values with same shape as inputs.
     """
-    return _ndvi_re2(nir, rededge, red)
+    return _ndvi_re2(nir, rededge, red, scale=scale, offset=offset)
 
 
-def lai(nir, red, blue):
+def lai(nir, red, blue, scale=None, offset=None):
     """
     Compute Leaf Area Index (LAI) from Enhanced Vegetation Index.
 
@@ -634,16 +1211,19 @@ def lai(nir, red, blue):
         Red band.
     blue : numpy.ndarray
         Blue band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
     numpy.ndarray
         LAI values with same shape as inputs.
     """
-    return _lai(nir, red, blue)
+    return _lai(nir, red, blue, scale=scale, offset=offset)
 
 
-def dnbr(pre_nir, pre_swir2, post_nir, post_swir2):
+def dnbr(pre_nir, pre_swir2, post_nir, post_swir2, scale=None, offset=None):
     """
     Compute Differenced Normalized Burn Ratio (dNBR).
 
@@ -659,16 +1239,19 @@ def dnbr(pre_nir, pre_swir2, post_nir, post_swir2):
         Post-fire NIR band.
     post_swir2 : numpy.ndarray
         Post-fire SWIR2 band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
     numpy.ndarray
         dNBR values with same shape as inputs.
     """
-    return _dnbr(pre_nir, pre_swir2, post_nir, post_swir2)
+    return _dnbr(pre_nir, pre_swir2, post_nir, post_swir2, scale=scale, offset=offset)
 
 
-def rbr(pre_nir, pre_swir2, post_nir, post_swir2):
+def rbr(pre_nir, pre_swir2, post_nir, post_swir2, scale=None, offset=None):
     """
     Compute Relative Burn Ratio (RBR).
 
@@ -684,16 +1267,72 @@ def rbr(pre_nir, pre_swir2, post_nir, post_swir2):
         Post-fire NIR band.
     post_swir2 : numpy.ndarray
         Post-fire SWIR2 band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
     numpy.ndarray
         RBR values with same shape as inputs.
     """
-    return _rbr(pre_nir, pre_swir2, post_nir, post_swir2)
+    return _rbr(pre_nir, pre_swir2, post_nir, post_swir2, scale=scale, offset=offset)
+
+
+def change_vector_analysis(
+    pre, post, direction="angle", threshold=None, scale=None, offset=None
+):
+    """
+    Change vector analysis (CVA) of two multi-band images.
 
+    The change vector of each pixel is ``post - pre`` across the bands; its
+    length is the change magnitude and its orientation the kind of change.
 
-def ci_re(nir, rededge):
+    Parameters
+    ----------
+    pre, post : numpy.ndarray
+        ``(band, y, x)`` cubes (any rank with bands first) of the same shape.
+    direction : {"angle", "sector"}, default "angle"
+        ``"angle"``: degrees; the polar angle ``atan2(d1, d0)`` in
+        ``[0, 360)`` for two bands, otherwise the angle between the change
+        vector and the ``(1, ..., 1)`` diagonal in ``[0, 180]``.
+        ``"sector"``: int32 code ``1 + sum(2**b for bands b that increased)``.
+    threshold : float or {"otsu", "kittler"}, optional
+        Magnitude threshold for the change mask. ``"otsu"`` and ``"kittler"``
+        (Kittler-Illingworth minimum error) pick it from a 256-bin histogram
+        of the finite magnitudes.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to both cubes.
+
+    Returns
+    -------
+    magnitude : numpy.ndarray
+        ``(y, x)`` change magnitude; NaN where any band is NaN.
+    direction : numpy.ndarray
+        ``(y, x)`` angle (NaN for NaN pixels) or sector code (0 for NaN
+        pixels).
+    change : numpy.ndarray or None
+        Boolean ``magnitude > threshold`` mask, or None without a threshold.
+    threshold : float or None
+        The threshold used.
+
+    Examples
+    --------
+    >>> mag, sector, change, t = change_vector_analysis(
+    ...     pre, post, direction="sector", threshold="otsu"
+    ... )
+    """
+    return _change_vector_analysis(
+        pre,
+        post,
+        direction=direction,
+        threshold=threshold,
+        scale=scale,
+        offset=offset,
+    )
+
+
+def ci_re(nir, rededge, scale=None, offset=None):
     """
     Compute Chlorophyll Index Red Edge (CIre).
 
@@ -705,16 +1344,19 @@ def ci_re(nir, rededge):
         Near-infrared band.
     rededge : numpy.ndarray
         Red edge band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
     numpy.ndarray
         CIre values with same shape as inputs.
     """
-    return _ci_re(nir, rededge)
+    return _ci_re(nir, rededge, scale=scale, offset=offset)
 
 
-def mtci(rededge, red, green):
+def mtci(rededge, red, green, scale=None, offset=None):
     """
     Compute MERIS Terrestrial Chlorophyll Index (MTCI).
 
@@ -728,175 +1370,949 @@ def mtci(rededge, red, green):
         Red band.
     green : numpy.ndarray
         Green band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
     numpy.ndarray
         MTCI values with same shape as inputs.
     """
-    return _mtci(rededge, red, green)
+    return _mtci(rededge, red, green, scale=scale, offset=offset)
 
 
-def ndmi(nir, swir1):
+def ndbi(swir1, nir, scale=None, offset=None):
     """
-    Normalized Difference Moisture Index (NDMI)
+    Compute Normalized Difference Built-up Index (NDBI).
 
-    NDMI = (NIR - SWIR1) / (NIR + SWIR1)
+    NDBI = (SWIR1 - NIR) / (SWIR1 + NIR)
 
     Parameters
     ----------
+    swir1 : numpy.ndarray
+        Short-wave infrared band (~1.6 µm).
     nir : numpy.ndarray
         Near-infrared band.
-    swir1 : numpy.ndarray
-        Short-wave infrared 1 band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
     numpy.ndarray
-        NDMI values (-1 .. 1).
+        NDBI values in [-1, 1]; positive over built-up and bare surfaces.
     """
-    return _ndmi(nir, swir1)
+    return _ndbi(swir1, nir, scale=scale, offset=offset)
 
 
-def nbr2(swir1, swir2):
+def mndwi(green, swir1, scale=None, offset=None):
     """
-    Normalized Burn Ratio 2 (NBR2)
+    Compute Modified Normalized Difference Water Index (MNDWI).
 
-    NBR2 = (SWIR1 - SWIR2) / (SWIR1 + SWIR2)
+    MNDWI = (Green - SWIR1) / (Green + SWIR1)
 
     Parameters
     ----------
+    green : numpy.ndarray
+        Green band.
     swir1 : numpy.ndarray
-        Short-wave infrared 1 band.
-    swir2 : numpy.ndarray
-        Short-wave infrared 2 band.
+        Short-wave infrared band (~1.6 µm).
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
     numpy.ndarray
-        NBR2 values (-1 .. 1).
+        MNDWI values in [-1, 1]; positive over open water.
     """
-    return _nbr2(swir1, swir2)
+    return _mndwi(green, swir1, scale=scale, offset=offset)
 
 
-def gci(nir, green):
+def awei_nsh(green, swir1, nir, swir2, scale=None, offset=None):
     """
-    Green Chlorophyll Index (GCI)
+    Compute Automated Water Extraction Index, no-shadow variant (AWEInsh).
 
-    GCI = (NIR / Green) - 1
+    AWEInsh = 4 * (Green - SWIR1) - (0.25 * NIR + 2.75 * SWIR2)
 
     Parameters
     ----------
-    nir : numpy.ndarray
-        Near-infrared band (any numeric dtype; auto-coerced to float64).
-    green : numpy.ndarray
-        Green band (any numeric dtype).
+    green, swir1, nir, swir2 : numpy.ndarray
+        Green, SWIR1 (~1.6 µm), near-infrared and SWIR2 (~2.2 µm) bands.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
     numpy.ndarray
-        GCI values (unbounded; typical vegetation > 0).
-
-    Notes
-    -----
-    Division-by-near-zero guarded; returns 0 where Green is ~0.
+        AWEInsh values; water is positive. Inputs should be reflectance.
     """
-    return _gci(nir, green)
+    return _awei_nsh(green, swir1, nir, swir2, scale=scale, offset=offset)
 
 
-def delta_ndvi(pre_nir, pre_red, post_nir, post_red):
+def awei_sh(blue, green, nir, swir1, swir2, scale=None, offset=None):
     """
-    Change in NDVI (pre - post).
+    Compute Automated Water Extraction Index, shadow variant (AWEIsh).
+
+    AWEIsh = Blue + 2.5 * Green - 1.5 * (NIR + SWIR1) - 0.25 * SWIR2
 
     Parameters
     ----------
-    pre_nir, pre_red : numpy.ndarray
-        Pre-event near-infrared and red bands.
-    post_nir, post_red : numpy.ndarray
-        Post-event near-infrared and red bands.
+    blue, green, nir, swir1, swir2 : numpy.ndarray
+        Blue, green, near-infrared, SWIR1 (~1.6 µm) and SWIR2 (~2.2 µm) bands.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
     numpy.ndarray
-        ΔNDVI array (same shape as inputs), positive values often indicate vegetation loss.
-
-    Notes
-    -----
-    Inputs may be any numeric dtype; values are coerced to float64 internally.
+        AWEIsh values; water is positive while terrain and building shadow
+        stays negative. Inputs should be reflectance.
     """
-    return _delta_ndvi(pre_nir, pre_red, post_nir, post_red)
+    return _awei_sh(blue, green, nir, swir1, swir2, scale=scale, offset=offset)
 
 
-def delta_nbr(pre_nir, pre_swir2, post_nir, post_swir2):
+def bsi(swir1, red, nir, blue, scale=None, offset=None):
     """
-    Change in NBR (pre - post) for burn severity analysis.
+    Compute Bare Soil Index (BSI).
+
+    BSI = ((SWIR1 + Red) - (NIR + Blue)) / ((SWIR1 + Red) + (NIR + Blue))
 
     Parameters
     ----------
-    pre_nir, pre_swir2 : numpy.ndarray
-        Pre-event NIR and SWIR2 bands.
-    post_nir, post_swir2 : numpy.ndarray
-        Post-event NIR and SWIR2 bands.
+    swir1, red, nir, blue : numpy.ndarray
+        SWIR1 (~1.6 µm), red, near-infrared and blue bands.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
     numpy.ndarray
-        ΔNBR array (same shape as inputs). Larger positive values generally indicate higher burn severity.
-
-    Notes
-    -----
-    Inputs may be any numeric dtype; internal coercion to float64.
+        BSI values in [-1, 1]; higher over bare soil.
     """
-    return _delta_nbr(pre_nir, pre_swir2, post_nir, post_swir2)
+    return _bsi(swir1, red, nir, blue, scale=scale, offset=offset)
 
 
-def nbr(nir, swir2):
+def kndvi(nir, red, sigma=None, scale=None, offset=None):
     """
-    Compute Normalized Burn Ratio (NBR).
+    Compute kernel NDVI (kNDVI) with an RBF kernel.
 
-    NBR = (NIR - SWIR2) / (NIR + SWIR2)
+    kNDVI = tanh(((NIR - Red) / (2 * sigma))^2)
 
     Parameters
     ----------
     nir : numpy.ndarray
         Near-infrared band.
-    swir2 : numpy.ndarray
-        Short-wave infrared (SWIR2) band.
+    red : numpy.ndarray
+        Red band.
+    sigma : float, optional
+        RBF length scale in reflectance units (must be positive). Defaults to
+        the per-pixel ``0.5 * (NIR + Red)``, for which kNDVI = tanh(NDVI^2).
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
     Returns
     -------
     numpy.ndarray
-        NBR values with same shape as inputs.
+        kNDVI values in [0, 1).
     """
-    return _nbr(nir, swir2)
+    return _kndvi(nir, red, sigma=sigma, scale=scale, offset=offset)
 
 
-def enhanced_vegetation_index(nir, red, blue):
+def nirv(nir, red, scale=None, offset=None):
     """
-    Compute EVI = 2.5 * (NIR - Red) / (NIR + 6*Red - 7.5*Blue + 1) via Rust core (1D or 2D).
-    """
-    return _enhanced_vegetation_index(nir, red, blue)
+    Compute Near-Infrared Reflectance of Vegetation (NIRv).
+
+    NIRv = NDVI * NIR
 
+    Parameters
+    ----------
+    nir : numpy.ndarray
+        Near-infrared band.
+    red : numpy.ndarray
+        Red band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
-def evi2(nir, red):
+    Returns
+    -------
+    numpy.ndarray
+        NIRv values (reflectance units).
     """
-    Compute EVI2 = 2.5 * (NIR - Red) / (NIR + 2.4*Red + 1) via Rust core.
+    return _nirv(nir, red, scale=scale, offset=offset)
+
+
+def sipi(nir, blue, red, scale=None, offset=None):
     """
-    return _evi2(nir, red)
+    Compute Structure Insensitive Pigment Index (SIPI).
 
+    SIPI = (NIR - Blue) / (NIR - Red)
 
-# Alias
-evi = enhanced_vegetation_index
+    Parameters
+    ----------
+    nir : numpy.ndarray
+        Near-infrared band.
+    blue : numpy.ndarray
+        Blue band.
+    red : numpy.ndarray
+        Red band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
 
+    Returns
+    -------
+    numpy.ndarray
+        SIPI values; 0 where NIR ≈ Red.
+    """
+    return _sipi(nir, blue, red, scale=scale, offset=offset)
 
-def median(arr, axis=None, skip_na=True):
+
+def psri(red, blue, nir, scale=None, offset=None):
     """
-    Compute median over the time axis of a 1D, 2D, 3D, or 4D array.
+    Compute Plant Senescence Reflectance Index (PSRI).
+
+    PSRI = (Red - Blue) / NIR
+
+    The original 750 nm denominator is taken from the NIR band.
+
+    Parameters
+    ----------
+    red : numpy.ndarray
+        Red band.
+    blue : numpy.ndarray
+        Blue band.
+    nir : numpy.ndarray
+        Near-infrared band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
+
+    Returns
+    -------
+    numpy.ndarray
+        PSRI values; 0 where NIR ≈ 0.
+    """
+    return _psri(red, blue, nir, scale=scale, offset=offset)
+
+
+def arvi(nir, red, blue, scale=None, offset=None):
+    """
+    Compute Atmospherically Resistant Vegetation Index (ARVI).
+
+    ARVI = (NIR - RB) / (NIR + RB), with RB = 2 * Red - Blue
+
+    Parameters
+    ----------
+    nir : numpy.ndarray
+        Near-infrared band.
+    red : numpy.ndarray
+        Red band.
+    blue : numpy.ndarray
+        Blue band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
+
+    Returns
+    -------
+    numpy.ndarray
+        ARVI values.
+    """
+    return _arvi(nir, red, blue, scale=scale, offset=offset)
+
+
+def vari(green, red, blue, scale=None, offset=None):
+    """
+    Compute Visible Atmospherically Resistant Index (VARI).
+
+    VARI = (Green - Red) / (Green + Red - Blue)
+
+    Parameters
+    ----------
+    green : numpy.ndarray
+        Green band.
+    red : numpy.ndarray
+        Red band.
+    blue : numpy.ndarray
+        Blue band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
+
+    Returns
+    -------
+    numpy.ndarray
+        VARI values; 0 where the denominator ≈ 0.
+    """
+    return _vari(green, red, blue, scale=scale, offset=offset)
+
+
+def tcari_osavi(rededge, red, green, nir, scale=None, offset=None):
+    """
+    Compute the TCARI/OSAVI chlorophyll ratio.
+
+    TCARI = 3 * ((RedEdge - Red) - 0.2 * (RedEdge - Green) * (RedEdge / Red))
+    OSAVI = 1.16 * (NIR - Red) / (NIR + Red + 0.16)
+
+    Parameters
+    ----------
+    rededge : numpy.ndarray
+        Red edge band (~700 nm).
+    red : numpy.ndarray
+        Red band.
+    green : numpy.ndarray
+        Green band.
+    nir : numpy.ndarray
+        Near-infrared band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
+
+    Returns
+    -------
+    numpy.ndarray
+        TCARI / OSAVI values; 0 where Red ≈ 0 or OSAVI ≈ 0.
+    """
+    return _tcari_osavi(rededge, red, green, nir, scale=scale, offset=offset)
+
+
+def ndci(rededge, red, scale=None, offset=None):
+    """
+    Compute Normalized Difference Chlorophyll Index (NDCI).
+
+    NDCI = (RedEdge - Red) / (RedEdge + Red)
+
+    Parameters
+    ----------
+    rededge : numpy.ndarray
+        Red edge band (~705 nm, e.g. Sentinel-2 B05).
+    red : numpy.ndarray
+        Red band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
+
+    Returns
+    -------
+    numpy.ndarray
+        NDCI values in [-1, 1]; higher with chlorophyll-a in water.
+    """
+    return _ndci(rededge, red, scale=scale, offset=offset)
+
+
+def ndmi(nir, swir1, scale=None, offset=None):
+    """
+    Normalized Difference Moisture Index (NDMI)
+
+    NDMI = (NIR - SWIR1) / (NIR + SWIR1)
+
+    Parameters
+    ----------
+    nir : numpy.ndarray
+        Near-infrared band.
+    swir1 : numpy.ndarray
+        Short-wave infrared 1 band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
+
+    Returns
+    -------
+    numpy.ndarray
+        NDMI values (-1 .. 1).
+    """
+    return _ndmi(nir, swir1, scale=scale, offset=offset)
+
+
+def nbr2(swir1, swir2, scale=None, offset=None):
+    """
+    Normalized Burn Ratio 2 (NBR2)
+
+    NBR2 = (SWIR1 - SWIR2) / (SWIR1 + SWIR2)
+
+    Parameters
+    ----------
+    swir1 : numpy.ndarray
+        Short-wave infrared 1 band.
+    swir2 : numpy.ndarray
+        Short-wave infrared 2 band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
+
+    Returns
+    -------
+    numpy.ndarray
+        NBR2 values (-1 .. 1).
+    """
+    return _nbr2(swir1, swir2, scale=scale, offset=offset)
+
+
+def gci(nir, green, scale=None, offset=None):
+    """
+    Green Chlorophyll Index (GCI)
+
+    GCI = (NIR / Green) - 1
+
+    Parameters
+    ----------
+    nir : numpy.ndarray
+        Near-infrared band (any numeric dtype).
+    green : numpy.ndarray
+        Green band (any numeric dtype).
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
+
+    Returns
+    -------
+    numpy.ndarray
+        GCI values (unbounded; typical vegetation > 0).
+
+    Notes
+    -----
+    Division-by-near-zero guarded; returns 0 where Green is ~0.
+    """
+    return _gci(nir, green, scale=scale, offset=offset)
+
+
+def delta_ndvi(pre_nir, pre_red, post_nir, post_red, scale=None, offset=None):
+    """
+    Change in NDVI (pre - post).
+
+    Parameters
+    ----------
+    pre_nir, pre_red : numpy.ndarray
+        Pre-event near-infrared and red bands.
+    post_nir, post_red : numpy.ndarray
+        Post-event near-infrared and red bands.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
+
+    Returns
+    -------
+    numpy.ndarray
+        ΔNDVI array (same shape as inputs), positive values often indicate vegetation loss.
+
+    Notes
+    -----
+    Inputs may be any numeric dtype; float32 / uint16 inputs yield float32.
+    """
+    return _delta_ndvi(pre_nir, pre_red, post_nir, post_red, scale=scale, offset=offset)
+
+
+def delta_nbr(pre_nir, pre_swir2, post_nir, post_swir2, scale=None, offset=None):
+    """
+    Change in NBR (pre - post) for burn severity analysis.
+
+    Parameters
+    ----------
+    pre_nir, pre_swir2 : numpy.ndarray
+        Pre-event NIR and SWIR2 bands.
+    post_nir, post_swir2 : numpy.ndarray
+        Post-event NIR and SWIR2 bands.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
+
+    Returns
+    -------
+    numpy.ndarray
+        ΔNBR array (same shape as inputs). Larger positive values generally indicate higher burn severity.
+
+    Notes
+    -----
+    Inputs may be any numeric dtype; float32 / uint16 inputs yield float32.
+    """
+    return _delta_nbr(pre_nir, pre_swir2, post_nir, post_swir2, scale=scale, offset=offset)
+
+
+def nbr(nir, swir2, scale=None, offset=None):
+    """
+    Compute Normalized Burn Ratio (NBR).
+
+    NBR = (NIR - SWIR2) / (NIR + SWIR2)
+
+    Parameters
+    ----------
+    nir : numpy.ndarray
+        Near-infrared band.
+    swir2 : numpy.ndarray
+        Short-wave infrared (SWIR2) band.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every input before
+        the formula (e.g. ``scale=1e-4`` for Sentinel-2 L2A uint16 reflectance).
+
+    Returns
+    -------
+    numpy.ndarray
+        NBR values with same shape as inputs.
+    """
+    return _nbr(nir, swir2, scale=scale, offset=offset)
+
+
+def enhanced_vegetation_index(nir, red, blue, scale=None, offset=None):
+    """
+    Compute EVI = 2.5 * (NIR - Red) / (NIR + 6*Red - 7.5*Blue + 1) via Rust core (any rank).
+    Optional ``scale``/``offset`` convert DN inputs (``value * scale + offset``)
+    before the formula.
+    """
+    return _enhanced_vegetation_index(nir, red, blue, scale=scale, offset=offset)
+
+
+def evi2(nir, red, scale=None, offset=None):
+    """
+    Compute EVI2 = 2.5 * (NIR - Red) / (NIR + 2.4*Red + 1) via Rust core.
+    Optional ``scale``/``offset`` convert DN inputs (``value * scale + offset``)
+    before the formula.
+    """
+    return _evi2(nir, red, scale=scale, offset=offset)
+
+
+# Alias
+evi = enhanced_vegetation_index
+
+
+def compute_indices(
+    bands, names, scale=None, offset=None, sensor=None, band_axis=0, band_names=None
+):
+    """
+    Compute several spectral indices in a single fused pass.
+
+    Each pixel's bands are read once and every requested index is written into
+    one stacked output, instead of re-reading the bands and allocating an
+    output per index call. Formulas and near-zero denominator handling are
+    shared with the single-index functions, so results match them exactly.
+
+    Parameters
+    ----------
+    bands : Mapping[str, numpy.ndarray] or numpy.ndarray
+        Band role (see :func:`band_roles`, case-insensitive) to array. Arrays
+        may have any rank and are broadcast NumPy-style; bands not needed are
+        ignored. With ``sensor``, a single cube holding the sensor's bands
+        along ``band_axis`` instead.
+    names : sequence of str
+        Indices to compute (case-insensitive); any name in
+        :func:`index_registry`. SAVI uses L=0.5.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to every band before
+        the formulas. With ``sensor``, these override the preset's scaling.
+    sensor : str, optional
+        Sensor preset (see :func:`sensor_profiles`). Band roles are resolved
+        from the cube by band name and the sensor's reflectance scaling is
+        applied.
+    band_axis : int, default 0
+        Axis of the cube holding the bands (``sensor`` only).
+    band_names : sequence of str, optional
+        Band names along ``band_axis``; defaults to the sensor's canonical band
+        order (``sensor`` only).
+
+    Returns
+    -------
+    numpy.ndarray
+        Array of shape ``(len(names), *broadcast_shape)``; float32 for float32 /
+        uint16 bands, float64 otherwise. With ``sensor`` the shape is the cube's
+        shape with ``band_axis`` removed.
+
+    Examples
+    --------
+    >>> stack = compute_indices({"nir": nir, "red": red, "green": green}, ["ndvi", "ndwi"])
+    >>> ndvi_vals, ndwi_vals = stack
+    >>> # Sentinel-2 L2A uint16 cube of shape (12, y, x), bands B01..B12
+    >>> ndvi_vals, nbr_vals = compute_indices(cube, ["ndvi", "nbr"], sensor="sentinel2")
+    """
+    if sensor is not None:
+        return _sensor_indices(
+            bands,
+            sensor,
+            list(names),
+            band_axis=band_axis,
+            band_names=None if band_names is None else list(band_names),
+            scale=scale,
+            offset=offset,
+        )
+    return _compute_indices(bands, list(names), scale=scale, offset=offset)
+
+
+@dataclass(frozen=True)
+class SpectralIndexInfo:
+    """Registry entry for one spectral index (see :func:`index_registry`)."""
+
+    name: str
+    long_name: str
+    formula: str
+    bands: tuple[str, ...]
+    valid_range: tuple[float, float]
+    reference: str
+    func: Callable
+
+    def missing_bands(self, provided):
+        """Required band roles absent from ``provided``."""
+        return [b for b in self.bands if b not in provided]
+
+    def from_cube(
+        self, cube, sensor, band_axis=0, band_names=None, scale=None, offset=None
+    ):
+        """
+        Compute this index from a band-indexed cube of a known sensor.
+
+        Shorthand for ``compute_indices(cube, [self.name], sensor=sensor, ...)[0]``.
+        """
+        return compute_indices(
+            cube,
+            [self.name],
+            scale=scale,
+            offset=offset,
+            sensor=sensor,
+            band_axis=band_axis,
+            band_names=band_names,
+        )[0]
+
+
+def index_registry():
+    """
+    Describe every spectral index implemented in the Rust core.
+
+    The registry is the single source for index names, formulas, band
+    requirements and valid ranges; the CLI and documentation are built from it.
+
+    Returns
+    -------
+    dict[str, SpectralIndexInfo]
+        Index name to its entry, in registry order. ``bands`` lists the
+        required band roles in the argument order of ``func``; ``valid_range``
+        is the expected output range for valid surface reflectance (infinite
+        where the formula is unbounded); ``reference`` is empty when no
+        literature reference is recorded.
+
+    Examples
+    --------
+    >>> info = index_registry()["ndvi"]
+    >>> info.bands
+    ('nir', 'red')
+    >>> info.func(nir, red)  # same as ndvi(nir, red)
+    """
+    module = globals()
+    return {
+        entry["name"]: SpectralIndexInfo(
+            name=entry["name"],
+            long_name=entry["long_name"],
+            formula=entry["formula"],
+            bands=tuple(entry["bands"]),
+            valid_range=tuple(entry["valid_range"]),
+            reference=entry["reference"],
+            func=module[entry["name"]],
+        )
+        for entry in _index_registry()
+    }
+
+
+def band_roles():
+    """
+    Band roles accepted by :func:`compute_indices` and listed in
+    :func:`index_registry` (``nir``, ``red``, ``pre_nir``, ...).
+
+    Any other band name passed to :func:`compute_indices` raises
+    :class:`UnknownBandRoleError`, a ``ValueError`` subclass carrying the
+    offending ``role`` and the ``valid_roles``.
+    """
+    return list(_band_roles())
+
+
+@dataclass(frozen=True)
+class SensorProfileInfo:
+    """Band layout and reflectance scaling of one sensor (see :func:`sensor_profiles`)."""
+
+    name: str
+    description: str
+    bands: tuple[str, ...]
+    roles: dict[str, str]
+    scale: float
+    offset: float
+    scl_band: Optional[str]
+
+
+def sensor_profiles():
+    """
+    Describe the built-in sensor band presets.
+
+    Each preset maps band roles (``nir``, ``red``, ...) to the sensor's band
+    names and records the DN-to-reflectance scaling of its surface reflectance
+    product, so :func:`compute_indices` and :func:`mask_with_scl` can take a
+    band-indexed cube plus a sensor name.
+
+    Returns
+    -------
+    dict[str, SensorProfileInfo]
+        Sensor name to its preset. ``bands`` is the canonical band order of a
+        cube; ``roles`` maps band role to band name; ``scl_band`` is the scene
+        classification band, or None when the sensor has none.
+
+    Examples
+    --------
+    >>> s2 = sensor_profiles()["sentinel2"]
+    >>> s2.roles["nir"], s2.scale, s2.offset
+    ('B08', 0.0001, -0.1)
+    """
+    return {
+        entry["name"]: SensorProfileInfo(
+            name=entry["name"],
+            description=entry["description"],
+            bands=tuple(entry["bands"]),
+            roles=dict(entry["roles"]),
+            scale=entry["scale"],
+            offset=entry["offset"],
+            scl_band=entry["scl_band"],
+        )
+        for entry in _sensor_profiles()
+    }
+
+
+
+def tasseled_cap(
+    cube,
+    sensor=None,
+    coefficients=None,
+    band_axis=None,
+    band_names=None,
+    scale=None,
+    offset=None,
+):
+    """
+    Tasseled Cap transformation of a band-indexed cube.
+
+    Projects each pixel's reflectance spectrum onto the brightness, greenness
+    and wetness axes.
+
+    Parameters
+    ----------
+    cube : numpy.ndarray
+        ``(band, ...)`` or ``(time, band, y, x)`` array.
+    sensor : str, optional
+        Use the published reflectance coefficients for this sensor:
+        ``landsat5`` (Crist 1985), ``landsat7`` (Huang et al. 2002),
+        ``landsat8`` / ``landsat9`` (Baig et al. 2014) or ``sentinel2`` /
+        ``sentinel2_legacy`` (Nedkov 2017). The blue, green, red, NIR, SWIR1
+        and SWIR2 bands are resolved through the sensor preset (see
+        :func:`sensor_profiles`) and its reflectance scaling is applied.
+    coefficients : numpy.ndarray, optional
+        User-supplied ``(n_components, n_bands)`` matrix applied to every band
+        along ``band_axis`` in order. Exactly one of ``sensor`` and
+        ``coefficients`` must be given.
+    band_axis : int, optional
+        Axis of ``cube`` holding the bands. Defaults to 1 for 4-D cubes and 0
+        otherwise.
+    band_names : sequence of str, optional
+        Band names along ``band_axis``; defaults to the sensor's canonical band
+        order (``sensor`` only).
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset``; with ``sensor`` these
+        override the preset's scaling.
+
+    Returns
+    -------
+    numpy.ndarray
+        ``cube``'s shape with the band axis replaced by the components
+        (brightness, greenness, wetness for the built-in sets); float32 for
+        float32 / uint16 cubes, float64 otherwise.
+
+    Examples
+    --------
+    >>> # Landsat 8 C2 L2 uint16 cube of shape (7, y, x), bands SR_B1..SR_B7
+    >>> brightness, greenness, wetness = tasseled_cap(cube, sensor="landsat8")
+    >>> # (time, band, y, x) stack -> (time, 3, y, x)
+    >>> tc = tasseled_cap(stack, sensor="sentinel2")
+    """
+    if coefficients is not None:
+        coefficients = np.asarray(coefficients, dtype=np.float64)
+    return _tasseled_cap(
+        cube,
+        sensor=sensor,
+        coefficients=coefficients,
+        band_axis=band_axis,
+        band_names=None if band_names is None else list(band_names),
+        scale=scale,
+        offset=offset,
+    )
+
+
+def linear_unmixing(cube, endmembers, method="fcls", scale=None, offset=None):
+    """
+    Per-pixel linear spectral unmixing.
+
+    Each pixel spectrum is modelled as a linear mixture of the endmember
+    spectra and the fractions are estimated by constrained least squares.
+
+    Parameters
+    ----------
+    cube : numpy.ndarray
+        ``(band, y, x)`` array (any rank with bands first).
+    endmembers : numpy.ndarray
+        ``(n_endmembers, n_bands)`` matrix, one endmember spectrum per row,
+        in the same units as the (scaled) cube.
+    method : {"fcls", "nnls", "scls", "ucls"}, default "fcls"
+        Fully constrained (non-negative and sum-to-one, Heinz & Chang 2001;
+        both constraints hold exactly), non-negative only, sum-to-one only,
+        or unconstrained least squares.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to the cube.
+
+    Returns
+    -------
+    fractions : numpy.ndarray
+        ``(n_endmembers, y, x)`` fraction maps.
+    rmse : numpy.ndarray
+        ``(y, x)`` root-mean-square reconstruction error over the bands.
+        Pixels with a NaN band are NaN in both outputs. float32 for float32 /
+        uint16 cubes, float64 otherwise.
+
+    Examples
+    --------
+    >>> lib = np.array([veg_spectrum, soil_spectrum, impervious_spectrum])
+    >>> (veg, soil, imp), rmse = linear_unmixing(cube, lib)
+    """
+    return _linear_unmixing(
+        cube,
+        np.asarray(endmembers, dtype=np.float64),
+        method=method,
+        scale=scale,
+        offset=offset,
+    )
+
+
+def spectral_similarity(
+    cube, library, metric="sam", threshold=None, scale=None, offset=None
+):
+    """
+    Compare each pixel spectrum with a library of reference spectra.
+
+    Parameters
+    ----------
+    cube : numpy.ndarray
+        ``(band, y, x)`` array (any rank with bands first).
+    library : numpy.ndarray
+        ``(n_refs, n_bands)`` reference spectra, one per row, in the same
+        units as the (scaled) cube.
+    metric : {"sam", "sid", "scs"}, default "sam"
+        Spectral angle in radians (lower is more similar), spectral
+        information divergence (symmetric Kullback-Leibler divergence of the
+        band distributions; lower is more similar) or spectral correlation
+        similarity (Pearson correlation; higher is more similar).
+    threshold : float, optional
+        Pixels whose best score is worse than this (angle or divergence
+        above, correlation below) are left unclassified.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to the cube.
+
+    Returns
+    -------
+    scores : numpy.ndarray
+        ``(n_refs, y, x)`` score against each reference.
+    classes : numpy.ndarray
+        ``(y, x)`` int32 index of the best-matching reference; -1 for
+        unclassified pixels and pixels with a NaN band.
+    best : numpy.ndarray
+        ``(y, x)`` best score (the minimum angle for ``"sam"``). Float
+        outputs are float32 for float32 / uint16 cubes, float64 otherwise.
+
+    Examples
+    --------
+    >>> lib = np.array([veg_spectrum, soil_spectrum, water_spectrum])
+    >>> angles, classes, min_angle = spectral_similarity(cube, lib, threshold=0.1)
+    """
+    return _spectral_similarity(
+        cube,
+        np.asarray(library, dtype=np.float64),
+        metric=metric,
+        threshold=threshold,
+        scale=scale,
+        offset=offset,
+    )
+
+
+def pca_transform(cube, n_components=None, scale=None, offset=None):
+    """
+    Principal component analysis of a band cube.
+
+    The band covariance matrix is accumulated over every pixel without a NaN
+    band and eigendecomposed; each pixel's mean-centred spectrum is projected
+    onto the leading eigenvectors.
+
+    Parameters
+    ----------
+    cube : numpy.ndarray
+        ``(band, ...)`` array with bands first, e.g. ``(band, y, x)``.
+    n_components : int, optional
+        Number of components to keep; defaults to the number of bands.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to the cube.
+
+    Returns
+    -------
+    components : numpy.ndarray
+        ``(n_components, ...)`` component images; NaN where the pixel had a
+        NaN band. float32 for float32 / uint16 cubes, float64 otherwise.
+    eigenvalues : numpy.ndarray
+        ``(n_components,)`` component variances in decreasing order.
+    loadings : numpy.ndarray
+        ``(n_components, n_bands)`` eigenvectors, one per row.
+
+    Examples
+    --------
+    >>> comps, eigvals, loadings = pca_transform(cube, n_components=3)
+    >>> explained = eigvals / pca_transform(cube)[1].sum()
+    """
+    return _pca_transform(cube, n_components=n_components, scale=scale, offset=offset)
+
+
+def mnf_transform(cube, n_components=None, scale=None, offset=None):
+    """
+    Minimum noise fraction (MNF) transform of a band cube.
+
+    Noise is estimated from differences between horizontally and vertically
+    adjacent pixels; the data are whitened with respect to that noise and
+    rotated onto their principal components (Green et al., 1988), so
+    components are ordered by decreasing signal-to-noise ratio.
+
+    Parameters
+    ----------
+    cube : numpy.ndarray
+        ``(band, y, x)`` array.
+    n_components : int, optional
+        Number of components to keep; defaults to the number of bands.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied to the cube.
+
+    Returns
+    -------
+    components : numpy.ndarray
+        ``(n_components, y, x)`` component images.
+    eigenvalues : numpy.ndarray
+        ``(n_components,)`` signal-to-noise ratio plus one of each component,
+        in decreasing order.
+    loadings : numpy.ndarray
+        ``(n_components, n_bands)`` transform rows.
+    """
+    return _mnf_transform(cube, n_components=n_components, scale=scale, offset=offset)
+
+
+def median(arr, axis=None, skip_na=True, scale=None, offset=None):
+    """
+    Compute median over the time axis of a 1D, 2D, 3D, or 4D array.
 
     Parameters
     ----------
     arr : numpy.ndarray
-        Input array.
+        Input array. float64, float32 and uint16 are read natively; other
+        numeric dtypes are converted to float64.
     axis : int, optional
         Axis along which to compute the median. If None, the median is
         computed over the first axis. Currently, this is only supported for 4D
@@ -904,8 +2320,16 @@ def median(arr, axis=None, skip_na=True):
     skip_na : bool, optional
         Whether to skip NaN values, by default True. If False, the median
         of any pixel containing a NaN will be NaN.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied before aggregation.
+
+    Returns
+    -------
+    numpy.ndarray
+        Median with the reduced axis removed; float32 for float32 / uint16
+        input, float64 otherwise. Scalar for 1D input.
     """
-    return _median(arr, axis=axis, skip_na=skip_na)
+    return _median(arr, axis=axis, skip_na=skip_na, scale=scale, offset=offset)
 
 
 def composite(arr, method="median", **kwargs):
@@ -921,18 +2345,21 @@ def composite(arr, method="median", **kwargs):
     Parameters
     ----------
     arr : numpy.ndarray
-        Input array (1D–4D). Any numeric dtype accepted; coerced to float64 internally.
+        Input array (any rank). float64, float32 and uint16 are read natively
+        by every method; other numeric dtypes are converted to float64.
     method : str, optional
         Name of compositing method, one of {"median", "mean", "std"}.
     **kwargs
         Passed through to the underlying method. This includes `skip_na` (bool)
-        to control NaN handling (default True).
+        to control NaN handling (default True) and `scale` / `offset` for DN
+        conversion before aggregation.
 
     Returns
     -------
     numpy.ndarray
-        Composite with time axis removed. Output dimensionality:
-          - 1D input -> scalar (float64)
+        Composite with time axis removed; float32 for float32 / uint16 input,
+        float64 otherwise. Output dimensionality:
+          - 1D input -> scalar (float)
           - 2D input -> (bands,)
           - 3D input -> (y, x)
           - 4D input -> (band, y, x)
@@ -952,47 +2379,53 @@ def composite(arr, method="median", **kwargs):
         raise ValueError(f"Unknown composite method: {method}")
 
 
-def temporal_mean(arr, skip_na=True):
+def temporal_mean(arr, skip_na=True, scale=None, offset=None):
     """
-    Compute the mean along the leading time axis of a 1D–4D time‑first array.
+    Compute the mean along the leading time axis of a time‑first array of any rank.
 
     Parameters
     ----------
     arr : numpy.ndarray
-        Time‑first array (1D–4D). Shapes:
+        Time‑first array (any rank). Shapes:
         (T,), (T, F), (T, Y, X), (T, B, Y, X).
     skip_na : bool, default True
         If True, NaNs are excluded per pixel/band; all‑NaN series produce NaN.
         If False, any NaN in a series propagates NaN to the output position.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied before aggregation.
 
     Returns
     -------
     numpy.ndarray
-        Mean with time axis removed; float64 dtype. Scalar for 1D input.
+        Mean with time axis removed; float32 for float32 / uint16 input,
+        float64 otherwise. Scalar for 1D input.
     """
-    return composite(arr, method="mean", skip_na=skip_na)
+    return composite(arr, method="mean", skip_na=skip_na, scale=scale, offset=offset)
 
 
-def temporal_std(arr, skip_na=True):
+def temporal_std(arr, skip_na=True, scale=None, offset=None):
     """
     Compute the sample standard deviation (ddof=1) along the leading time axis
-    of a 1D–4D time‑first array.
+    of a time‑first array of any rank.
 
     Parameters
     ----------
     arr : numpy.ndarray
-        Time‑first array (1D–4D). Shapes:
+        Time‑first array (any rank). Shapes:
         (T,), (T, F), (T, Y, X), (T, B, Y, X).
     skip_na : bool, default True
         If True, NaNs are excluded before variance; fewer than 2 valid values
         yield NaN. If False, any NaN in a series propagates NaN.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied before aggregation.
 
     Returns
     -------
     numpy.ndarray
-        Standard deviation with time axis removed; float64 dtype. Scalar for 1D input.
+        Standard deviation with time axis removed; float32 for float32 / uint16 input,
+        float64 otherwise. Scalar for 1D input.
     """
-    return composite(arr, method="std", skip_na=skip_na)
+    return composite(arr, method="std", skip_na=skip_na, scale=scale, offset=offset)
 
 
 def euclidean_distance(points_a, points_b):
@@ -1055,14 +2488,47 @@ def minkowski_distance(points_a, points_b, p):
     return _minkowski_distance(points_a, points_b, p)
 
 
+def distance_transform(features, pixel_size=None, return_indices=False):
+    """
+    Exact Euclidean distance transform of a raster.
+
+    Computes the distance from every pixel centre to the nearest feature
+    pixel (Felzenszwalb & Huttenlocher), e.g. distance to water, roads or
+    clouds as a model covariate.
+
+    Parameters
+    ----------
+    features : numpy.ndarray (y, x)
+        Feature mask; non-zero pixels are features.
+    pixel_size : float or (float, float), optional
+        Pixel size in the output unit, or ``(dy, dx)`` for non-square
+        pixels. Defaults to 1 (distances in pixels).
+    return_indices : bool, default False
+        Also return the index of the nearest feature pixel.
+
+    Returns
+    -------
+    numpy.ndarray (y, x)
+        float64 distances; ``inf`` everywhere when there are no features.
+    (numpy.ndarray, numpy.ndarray)
+        With ``return_indices=True``, the distances and an int64
+        ``(2, y, x)`` array of nearest feature rows and columns (``-1`` when
+        there are no features), so ``values[tuple(indices)]`` samples the
+        nearest feature.
+    """
+    features = np.asarray(features) > 0
+    return _distance_transform(features, pixel_size, return_indices)
+
+
 def mask_vals(arr, values=None, fill_value=None, nan_to=None):
     """
     Mask specified values (exact equality) and optionally replace NaNs.
 
     Parameters
     ----------
-    arr : numpy.ndarray (1D–4D)
-        Input array; any numeric dtype accepted (coerced to float64 internally).
+    arr : numpy.ndarray (any rank)
+        Input array; any numeric dtype accepted (float32 / uint16 read natively,
+        others coerced to float64).
     values : sequence, optional
         Iterable of numeric codes to mask. If None, no value masking is performed.
     fill_value : float, optional
@@ -1073,7 +2539,7 @@ def mask_vals(arr, values=None, fill_value=None, nan_to=None):
 
     Returns
     -------
-    numpy.ndarray (float64)
+    numpy.ndarray (float32 for float32 / uint16 input, else float64)
         Masked array preserving original shape.
 
     Notes
@@ -1090,7 +2556,7 @@ def replace_nans(arr, value):
     Parameters
     ----------
     arr : numpy.ndarray
-        Input array (1D–4D supported).
+        Input array (any rank supported).
     value : float
         Replacement for every NaN.
 
@@ -1109,7 +2575,7 @@ def mask_out_range(arr, min_val=None, max_val=None, fill_value=None):
     Parameters
     ----------
     arr : numpy.ndarray
-        Input array (1D–4D).
+        Input array (any rank).
     min_val : float, optional
         Minimum valid value (inclusive).
     max_val : float, optional
@@ -1153,7 +2619,7 @@ def mask_in_range(arr, min_val=None, max_val=None, fill_value=None):
     Parameters
     ----------
     arr : numpy.ndarray
-        Input array (1D–4D).
+        Input array (any rank).
     min_val : float, optional
         Minimum value of range to mask (inclusive).
     max_val : float, optional
@@ -1169,30 +2635,48 @@ def mask_in_range(arr, min_val=None, max_val=None, fill_value=None):
     return _mask_in_range(arr, min=min_val, max=max_val, fill_value=fill_value)
 
 
-def mask_scl(scl, keep_codes=None, fill_value=None):
+def mask_scl(scl, keep_codes=None, fill_value=None, output="values"):
     """
-    Mask a Sentinel-2 Scene Classification Layer (SCL) array.
+    Mask a Sentinel-2 Scene Classification Layer (SCL) array of any rank.
 
-    By default, keeps vegetation, water, bare soil, and snow.
+    By default, keeps vegetation, water, bare soil, and snow. Class lookup
+    uses a 256-entry table, and uint8 SCL is read without conversion.
 
     Parameters
     ----------
     scl : numpy.ndarray
-        SCL array.
+        SCL array (1D-4D, e.g. ``(y, x)`` or ``(time, y, x)``).
     keep_codes : sequence, optional
         List of SCL codes to keep. Defaults to [4, 5, 6, 7, 11].
     fill_value : float, optional
-        Value for masked positions (default NaN).
+        Value for masked positions in ``"values"`` output (default NaN).
+    output : {"values", "bool", "uint8"}, default "values"
+        ``"values"`` returns the SCL as floats with dropped classes filled;
+        ``"bool"`` / ``"uint8"`` return a compact valid mask (True / 1 where
+        the class is kept) that can be reused across bands.
 
     Returns
     -------
     numpy.ndarray
-        Masked SCL array.
+        Masked SCL array, or the valid mask.
+
+    Examples
+    --------
+    >>> valid = mask_scl(scl, output="bool")  # (time, y, x)
+    >>> clear = np.where(valid[:, None], stack, np.nan)  # (time, band, y, x)
     """
-    return _mask_scl(scl, keep_codes=keep_codes, fill_value=fill_value)
+    return _mask_scl(scl, keep_codes=keep_codes, fill_value=fill_value, output=output)
 
 
-def mask_with_scl(data, scl, mask_codes=None, fill_value=None):
+def mask_with_scl(
+    data,
+    scl=None,
+    mask_codes=None,
+    fill_value=None,
+    sensor=None,
+    band_axis=None,
+    band_names=None,
+):
     """
     Apply SCL-based masking to a data array.
 
@@ -1201,21 +2685,33 @@ def mask_with_scl(data, scl, mask_codes=None, fill_value=None):
     this function applies the mask to actual data (e.g., spectral bands).
 
     Supported array shapes:
-    - 2D data (y, x) with 2D SCL (y, x)
-    - 3D data (time, y, x) with 3D SCL (time, y, x)
+    - data and SCL of any rank whose shapes broadcast NumPy-style, e.g.
+      2D (y, x) with 2D SCL (y, x) or 3D (time, y, x) with 3D SCL (time, y, x)
     - 4D data (time, band, y, x) with 3D SCL (time, y, x) - SCL broadcast across bands
+    - a sensor cube holding the SCL as one of its bands (``sensor`` without
+      ``scl``); the SCL band itself is left unmasked
 
     Parameters
     ----------
     data : numpy.ndarray
-        The data array to mask (2D, 3D, or 4D).
-    scl : numpy.ndarray
-        The SCL array (2D or 3D). For 4D data, SCL should be 3D (time, y, x).
+        The data array to mask.
+    scl : numpy.ndarray, optional
+        The SCL array. For 4D data, SCL may be 3D (time, y, x). Required unless
+        ``sensor`` is given.
     mask_codes : sequence of float, optional
         SCL codes to mask (set to fill_value). Defaults to clouds/shadows/etc:
         [0, 1, 2, 3, 8, 9, 10] (no data, saturated, dark, shadow, cloud med/high, cirrus).
     fill_value : float, optional
         Value to assign to masked pixels. Defaults to NaN.
+    sensor : str, optional
+        Sensor preset with an SCL band (see :func:`sensor_profiles`). Without
+        ``scl``, the SCL is read from ``data`` along ``band_axis``.
+    band_axis : int, optional
+        Band axis of ``data``. With ``scl``, an SCL of one dimension fewer than
+        ``data`` is broadcast across this axis; with ``sensor`` it defaults to 0.
+    band_names : sequence of str, optional
+        Band names along ``band_axis`` of a sensor cube, including the SCL band.
+        Needed in sensor mode, as the canonical band order has no SCL band.
 
     Returns
     -------
@@ -1235,7 +2731,274 @@ def mask_with_scl(data, scl, mask_codes=None, fill_value=None):
     >>> np.isnan(result[0, 0, 2])  # SCL=9 (cloud high)
     True
     """
-    return _mask_with_scl(data, scl, mask_codes=mask_codes, fill_value=fill_value)
+    return _mask_with_scl(
+        data,
+        scl,
+        mask_codes=mask_codes,
+        fill_value=fill_value,
+        sensor=sensor,
+        band_axis=band_axis,
+        band_names=None if band_names is None else list(band_names),
+    )
+
+
+def _qa_args(qa, flags):
+    """Normalise QA inputs: uint16 words and flag entries as str / tuple."""
+    qa = np.asarray(qa)
+    if not np.issubdtype(qa.dtype, np.integer):
+        raise ValueError(f"QA band must have an integer dtype, got {qa.dtype}")
+    qa = qa.astype(np.uint16, copy=False)
+    if flags is not None:
+        flags = [f if isinstance(f, str) else tuple(f) for f in flags]
+    return qa, flags
+
+
+def qa_mask(qa, flags=None, qa_band="qa_pixel"):
+    """
+    Decode a bit-packed QA band (e.g. Landsat Collection 2 ``QA_PIXEL``)
+    into a boolean mask.
+
+    Parameters
+    ----------
+    qa : numpy.ndarray
+        Integer QA band of any rank.
+    flags : sequence, optional
+        Flags to test; a pixel is flagged when any of them fires. Each entry
+        is a preset name, a ``(name, values)`` pair overriding the preset's
+        values, or a custom ``(bit, width, values)`` bit field; ``values`` is
+        an int or a list of ints. Defaults to fill, dilated_cloud, cirrus,
+        cloud, cloud_shadow and snow for ``QA_PIXEL`` and every flag for
+        ``QA_RADSAT``.
+    qa_band : {"qa_pixel", "qa_radsat"}, default "qa_pixel"
+        Preset table. ``QA_PIXEL``: fill, dilated_cloud, cirrus, cloud,
+        cloud_shadow, snow, clear, water (single bits) and cloud_confidence,
+        cloud_shadow_confidence, snow_confidence, cirrus_confidence (two bits:
+        0 none, 1 low, 2 medium, 3 high; by name medium and high are
+        flagged). ``QA_RADSAT``: b1_saturated ... b7_saturated, b9_saturated,
+        dropped_pixel, terrain_occlusion.
+
+    Returns
+    -------
+    numpy.ndarray
+        Boolean array, True where the pixel is flagged.
+
+    Examples
+    --------
+    >>> qa_mask(np.array([21824, 22280, 1]))  # clear, cloud, fill
+    array([False,  True,  True])
+    >>> qa_mask(qa, flags=["cloud", ("cloud_shadow_confidence", 3), (7, 1, 1)])
+    """
+    qa, flags = _qa_args(qa, flags)
+    return _qa_mask(qa, flags=flags, qa_band=qa_band)
+
+
+def mask_with_qa(
+    data, qa, flags=None, qa_band="qa_pixel", fill_value=None, band_axis=None
+):
+    """
+    Apply a QA bit-flag mask to a data array.
+
+    Pixels whose QA word fires any of ``flags`` (see :func:`qa_mask`) are set
+    to ``fill_value``. Shapes follow :func:`mask_with_scl`: QA of the same
+    shape as ``data``, 3D QA (time, y, x) with 4D data (time, band, y, x), or
+    QA lacking only ``band_axis``.
+
+    Parameters
+    ----------
+    data : numpy.ndarray
+        The data array to mask.
+    qa : numpy.ndarray
+        Integer QA band.
+    flags : sequence, optional
+        Flags to mask; see :func:`qa_mask`.
+    qa_band : {"qa_pixel", "qa_radsat"}, default "qa_pixel"
+        Preset table for flag names and the default flags.
+    fill_value : float, optional
+        Value to assign to masked pixels. Defaults to NaN.
+    band_axis : int, optional
+        Band axis of ``data`` to broadcast a QA of one dimension fewer across.
+
+    Returns
+    -------
+    numpy.ndarray
+        Data array with flagged pixels replaced by ``fill_value``.
+
+    Examples
+    --------
+    >>> clear = mask_with_qa(stack, qa_pixel)  # (time, band, y, x), (time, y, x)
+    >>> clear = mask_with_qa(clear, qa_radsat, qa_band="qa_radsat")
+    """
+    qa, flags = _qa_args(qa, flags)
+    return _mask_with_qa(
+        data,
+        qa,
+        flags=flags,
+        qa_band=qa_band,
+        fill_value=fill_value,
+        band_axis=band_axis,
+    )
+
+
+def buffer_mask(mask, distance_m, pixel_size):
+    """
+    Buffer a cloud (or any class) mask by a metric distance.
+
+    Every pixel within ``distance_m`` of a masked pixel (Euclidean distance
+    between pixel centres, from an exact distance transform) is set, giving
+    a circular buffer instead of the square of :func:`binary_dilation`.
+
+    Parameters
+    ----------
+    mask : numpy.ndarray
+        ``(y, x)`` mask or ``(time, y, x)`` stack (bool or integer codes
+        0-255). With a scalar ``distance_m`` every non-zero pixel is
+        buffered.
+    distance_m : float or mapping of int to float
+        Buffer distance, or per-class distances for a class-coded mask such
+        as an SCL, e.g. ``{8: 300, 9: 300, 3: 150}``. Classes not listed are
+        ignored.
+    pixel_size : float or (float, float)
+        Pixel size in the same unit as ``distance_m``, or ``(dy, dx)``.
+
+    Returns
+    -------
+    numpy.ndarray
+        Boolean buffered mask shaped like ``mask``. Planes of a stack are
+        processed in parallel.
+
+    Examples
+    --------
+    >>> buffered = buffer_mask(scl, {8: 300, 9: 300, 10: 300, 3: 150}, 20)
+    >>> clear = mask_with_scl(stack, buffered.astype(np.uint8), mask_codes=[1])
+    """
+    mask = np.asarray(mask)
+    if mask.dtype != np.bool_ and not np.issubdtype(mask.dtype, np.integer):
+        raise ValueError(f"mask must be boolean or integer, got {mask.dtype}")
+    if mask.dtype != np.uint8:
+        if mask.size and (mask.min() < 0 or mask.max() > 255):
+            raise ValueError("mask codes must lie in 0..255")
+        mask = mask.astype(np.uint8)
+    if isinstance(distance_m, Mapping):
+        distance_m = {int(k): float(v) for k, v in distance_m.items()}
+    if not np.isscalar(pixel_size):
+        pixel_size = tuple(float(p) for p in pixel_size)
+    return _buffer_mask(mask, distance_m, pixel_size)
+
+
+def cloud_shadow_mask(
+    cloud,
+    nir,
+    sun_azimuth,
+    sun_zenith,
+    pixel_size,
+    swir=None,
+    cloud_heights=(200.0, 12000.0),
+    nir_threshold=0.15,
+    swir_threshold=0.10,
+    scale=None,
+    offset=None,
+):
+    """
+    Cloud shadow mask from sun geometry.
+
+    The cloud mask is projected away from the sun for every candidate cloud
+    height, and projected pixels that are dark in NIR (and SWIR, if given)
+    are flagged as shadow. This is more reliable than the SCL shadow class.
+
+    Parameters
+    ----------
+    cloud : numpy.ndarray
+        ``(y, x)`` cloud mask (converted to bool).
+    nir : numpy.ndarray
+        ``(y, x)`` NIR reflectance (float64, float32 or uint16 DN).
+    sun_azimuth, sun_zenith : float
+        Solar azimuth (clockwise from north) and zenith angles in degrees.
+    pixel_size : float
+        Pixel size in the unit of ``cloud_heights`` (metres by default).
+    swir : numpy.ndarray, optional
+        ``(y, x)`` SWIR reflectance; shadow pixels must also be dark here.
+    cloud_heights : (float, float), default (200, 12000)
+        Range of candidate cloud heights.
+    nir_threshold, swir_threshold : float, default 0.15, 0.10
+        Reflectance below which a pixel counts as dark (after scaling).
+    scale, offset : float, optional
+        Reflectance scaling applied as ``value * scale + offset``.
+
+    Returns
+    -------
+    numpy.ndarray
+        Boolean shadow mask; cloud pixels are never shadow.
+
+    Examples
+    --------
+    >>> clouds = np.isin(scl, [8, 9, 10])
+    >>> shadow = cloud_shadow_mask(clouds, b08, 150.2, 38.5, 10, swir=b11,
+    ...                            scale=1e-4)
+    """
+    cloud = np.asarray(cloud, dtype=bool)
+    if not np.isscalar(cloud_heights):
+        cloud_heights = tuple(float(h) for h in cloud_heights)
+    return _cloud_shadow_mask(
+        cloud,
+        nir,
+        float(sun_azimuth),
+        float(sun_zenith),
+        float(pixel_size),
+        swir=swir,
+        cloud_heights=cloud_heights,
+        nir_threshold=nir_threshold,
+        swir_threshold=swir_threshold,
+        scale=scale,
+        offset=offset,
+    )
+
+
+def scene_cloud_stats(layer, source="scl", aoi=None):
+    """
+    Per-scene cloud cover and valid-pixel statistics.
+
+    Classifies every pixel of an SCL or QA stack as clear, cloud, shadow,
+    snow or nodata in one parallel pass, e.g. to rank scenes by clear-sky
+    fraction before compositing.
+
+    Parameters
+    ----------
+    layer : numpy.ndarray
+        ``(time, y, x)`` Sentinel-2 SCL or Landsat C2 ``QA_PIXEL`` stack
+        (integer codes 0-65535); a single ``(y, x)`` scene is accepted.
+    source : {"scl", "qa_pixel"}, default "scl"
+        Code convention. SCL: 0, 1 nodata, 2-3 shadow, 8-10 cloud, 11 snow,
+        4-7 clear. ``QA_PIXEL``: fill is nodata, dilated cloud / cirrus /
+        cloud are cloud, then cloud shadow and snow.
+    aoi : numpy.ndarray, optional
+        ``(y, x)`` area-of-interest mask; only pixels inside it are counted.
+
+    Returns
+    -------
+    dict
+        ``clear``, ``cloud``, ``shadow``, ``snow``, ``nodata``: ``(time,)``
+        fractions of AOI pixels, summing to 1 per timestep (NaN for an
+        empty AOI); ``clear_count``: ``(y, x)`` uint32 count of clear
+        observations (0 outside the AOI); ``aoi_pixels``: AOI size.
+
+    Examples
+    --------
+    >>> stats = scene_cloud_stats(scl_stack, aoi=field_mask)
+    >>> best = np.argsort(stats["clear"])[::-1][:5]
+    >>> cloud_cover = stats["cloud"] / (1 - stats["nodata"])
+    """
+    layer = np.asarray(layer)
+    if not np.issubdtype(layer.dtype, np.integer):
+        raise ValueError(f"layer must hold integer codes, got {layer.dtype}")
+    if layer.ndim == 2:
+        layer = layer[None]
+    if layer.dtype != np.uint16:
+        if layer.size and (layer.min() < 0 or layer.max() > 65535):
+            raise ValueError("layer codes must lie in 0..65535")
+        layer = layer.astype(np.uint16)
+    if aoi is not None:
+        aoi = np.asarray(aoi, dtype=bool)
+    return _scene_cloud_stats(layer, source=source, aoi=aoi)
 
 
 def moving_average_temporal(arr, window, skip_na=True, mode="same"):
@@ -1320,23 +3083,26 @@ def pixelwise_transform(arr, scale=1.0, offset=0.0, clamp_min=None, clamp_max=No
     )
 
 
-def temporal_sum(arr, skip_na=True):
+def temporal_sum(arr, skip_na=True, scale=None, offset=None):
     """
-    Compute the sum along the leading time axis of a 1D–4D time‑first array.
+    Compute the sum along the leading time axis of a time‑first array of any rank.
 
     Parameters
     ----------
     arr : numpy.ndarray
-        Time‑first array (1D–4D).
+        Time‑first array (any rank).
     skip_na : bool, default True
         If True, NaNs are excluded.
+    scale, offset : float, optional
+        DN conversion ``value * scale + offset`` applied before aggregation.
 
     Returns
     -------
     numpy.ndarray
-        Sum with time axis removed; float64 dtype. Scalar for 1D input.
+        Sum with time axis removed; float32 for float32 / uint16 input,
+        float64 otherwise. Scalar for 1D input.
     """
-    return _temporal_sum(arr, skip_na=skip_na)
+    return _temporal_sum(arr, skip_na=skip_na, scale=scale, offset=offset)
 
 
 def temporal_composite(arr, weights, skip_na=True):
@@ -1,34 +1,38 @@
 """Type stubs for eo_processor.
 
 Notes:
-- All spectral, temporal, processes & masking functions accept any numeric numpy dtype; Rust layer coerces to float64.
+- All spectral, temporal, processes & masking functions accept any numeric numpy dtype.
+  Spectral, temporal-reducer and masking functions read float64 / float32 / uint16 natively
+  (float32 / uint16 input -> float32 output) and coerce other dtypes to float64.
 - Dimensional support:
-  * Spectral indices (ndvi, ndwi, ndsi, savi, nbr, ndmi, nbr2, gci, enhanced_vegetation_index/evi, evi2) + delta indices (delta_ndvi, delta_nbr): public wrappers 1D/2D (some internal paths support higher ranks).
-  * normalized_difference: 1D–4D.
-  * temporal_mean, temporal_std, median, composite: 1D–4D (time-first).
+  * normalized_difference and all spectral indices: any rank, NumPy-style broadcasting.
+  * temporal_mean, temporal_std, temporal_sum: any rank (time-first).
+  * median, composite(method="median"): 1D–4D (time-first).
   * moving_average_temporal, moving_average_temporal_stride: 1D–4D (time-first).
   * pixelwise_transform: 1D–4D.
-  * masking functions: 1D–4D.
+  * masking functions: any rank.
   * distance functions: 2D only (N, D).
-- Delta indices: pre/post inputs must have identical shapes.
+- Delta indices: pre/post inputs must have broadcast-compatible shapes.
 """
 
-from typing import Literal, Optional, Sequence
-from typing_extensions import TypeAlias
+from typing import Callable, Literal, Mapping, Optional, Sequence, Union, overload
+from typing_extensions import TypeAlias, TypedDict
 
 import numpy as np
 import structlog
 from numpy.typing import NDArray
 
 # Dimensional summary kept in sync with README & Sphinx:
-#   - normalized_difference: 1D–4D
-#   - ndvi, ndwi, ndsi, savi, nbr, ndmi, nbr2, gci, enhanced_vegetation_index (evi), evi2, delta_ndvi, delta_nbr: primarily 1D–2D
-#   - temporal_mean, temporal_std, median, composite: 1D–4D
+#   - normalized_difference / spectral indices: any rank (broadcast)
+#   - temporal_mean, temporal_std, temporal_sum: any rank
+#   - median, composite: 1D–4D
 #   - moving_average_temporal, moving_average_temporal_stride: 1D–4D
 #   - pixelwise_transform: 1D–4D
-#   - masking utilities: 1D–4D
+#   - masking utilities: any rank
 #   - distance functions: 2D (N,D)
 NumericArray: TypeAlias = NDArray[np.generic]
+# float32 for float32 / uint16 input, float64 otherwise.
+FloatArray: TypeAlias = NDArray[np.floating]
 
 __version__: Literal["0.20.0"]
 
@@ -36,53 +40,385 @@ __version__: Literal["0.20.0"]
 log: structlog.stdlib.BoundLogger
 
 # Spectral & change detection
-def normalized_difference(a: NumericArray, b: NumericArray) -> NDArray[np.float64]: ...
-def ndvi(nir: NumericArray, red: NumericArray) -> NDArray[np.float64]: ...
-def ndsi(green: NumericArray, swir1: NumericArray) -> NDArray[np.float64]: ...
-def ndwi(green: NumericArray, nir: NumericArray) -> NDArray[np.float64]: ...
+def normalized_difference(
+    a: NumericArray,
+    b: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def ndvi(
+    nir: NumericArray,
+    red: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def ndsi(
+    green: NumericArray,
+    swir1: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def ndwi(
+    green: NumericArray,
+    nir: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
 def savi(
-    nir: NumericArray, red: NumericArray, L: float = ...
-) -> NDArray[np.float64]: ...
-def osavi(nir: NumericArray, red: NumericArray) -> NDArray[np.float64]: ...
-def msavi(nir: NumericArray, red: NumericArray) -> NDArray[np.float64]: ...
-def gndvi(nir: NumericArray, green: NumericArray) -> NDArray[np.float64]: ...
-def ndre(nir: NumericArray, rededge: NumericArray) -> NDArray[np.float64]: ...
-def ndvi_re2(nir: NumericArray, rededge: NumericArray, red: NumericArray) -> NDArray[np.float64]: ...
-def lai(nir: NumericArray, red: NumericArray, blue: NumericArray) -> NDArray[np.float64]: ...
-def dnbr(pre_nir: NumericArray, pre_swir2: NumericArray, post_nir: NumericArray, post_swir2: NumericArray) -> NDArray[np.float64]: ...
-def rbr(pre_nir: NumericArray, pre_swir2: NumericArray, post_nir: NumericArray, post_swir2: NumericArray) -> NDArray[np.float64]: ...
-def ci_re(nir: NumericArray, rededge: NumericArray) -> NDArray[np.float64]: ...
-def mtci(rededge: NumericArray, red: NumericArray, green: NumericArray) -> NDArray[np.float64]: ...
-def nbr(nir: NumericArray, swir2: NumericArray) -> NDArray[np.float64]: ...
-def ndmi(nir: NumericArray, swir1: NumericArray) -> NDArray[np.float64]: ...
-def nbr2(swir1: NumericArray, swir2: NumericArray) -> NDArray[np.float64]: ...
-def gci(nir: NumericArray, green: NumericArray) -> NDArray[np.float64]: ...
+    nir: NumericArray,
+    red: NumericArray,
+    L: float = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def osavi(
+    nir: NumericArray,
+    red: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def msavi(
+    nir: NumericArray,
+    red: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def gndvi(
+    nir: NumericArray,
+    green: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def ndre(
+    nir: NumericArray,
+    rededge: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def ndvi_re2(
+    nir: NumericArray,
+    rededge: NumericArray,
+    red: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def lai(
+    nir: NumericArray,
+    red: NumericArray,
+    blue: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def dnbr(
+    pre_nir: NumericArray,
+    pre_swir2: NumericArray,
+    post_nir: NumericArray,
+    post_swir2: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def rbr(
+    pre_nir: NumericArray,
+    pre_swir2: NumericArray,
+    post_nir: NumericArray,
+    post_swir2: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def change_vector_analysis(
+    pre: NumericArray,
+    post: NumericArray,
+    direction: Literal["angle", "sector"] = ...,
+    threshold: Optional[Union[float, Literal["otsu", "kittler"]]] = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> tuple[
+    FloatArray,
+    Union[FloatArray, NDArray[np.int32]],
+    Optional[NDArray[np.bool_]],
+    Optional[float],
+]: ...
+def ci_re(
+    nir: NumericArray,
+    rededge: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def mtci(
+    rededge: NumericArray,
+    red: NumericArray,
+    green: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def ndbi(
+    swir1: NumericArray,
+    nir: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def mndwi(
+    green: NumericArray,
+    swir1: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def awei_nsh(
+    green: NumericArray,
+    swir1: NumericArray,
+    nir: NumericArray,
+    swir2: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def awei_sh(
+    blue: NumericArray,
+    green: NumericArray,
+    nir: NumericArray,
+    swir1: NumericArray,
+    swir2: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def bsi(
+    swir1: NumericArray,
+    red: NumericArray,
+    nir: NumericArray,
+    blue: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def kndvi(
+    nir: NumericArray,
+    red: NumericArray,
+    sigma: Optional[float] = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def nirv(
+    nir: NumericArray,
+    red: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def sipi(
+    nir: NumericArray,
+    blue: NumericArray,
+    red: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def psri(
+    red: NumericArray,
+    blue: NumericArray,
+    nir: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def arvi(
+    nir: NumericArray,
+    red: NumericArray,
+    blue: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def vari(
+    green: NumericArray,
+    red: NumericArray,
+    blue: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def tcari_osavi(
+    rededge: NumericArray,
+    red: NumericArray,
+    green: NumericArray,
+    nir: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def ndci(
+    rededge: NumericArray,
+    red: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def nbr(
+    nir: NumericArray,
+    swir2: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def ndmi(
+    nir: NumericArray,
+    swir1: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def nbr2(
+    swir1: NumericArray,
+    swir2: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def gci(
+    nir: NumericArray,
+    green: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
 def delta_ndvi(
     pre_nir: NumericArray,
     pre_red: NumericArray,
     post_nir: NumericArray,
     post_red: NumericArray,
-) -> NDArray[np.float64]: ...
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
 def delta_nbr(
     pre_nir: NumericArray,
     pre_swir2: NumericArray,
     post_nir: NumericArray,
     post_swir2: NumericArray,
-) -> NDArray[np.float64]: ...
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
 def enhanced_vegetation_index(
-    nir: NumericArray, red: NumericArray, blue: NumericArray
-) -> NDArray[np.float64]: ...
-def evi2(nir: NumericArray, red: NumericArray) -> NDArray[np.float64]: ...
+    nir: NumericArray,
+    red: NumericArray,
+    blue: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def evi2(
+    nir: NumericArray,
+    red: NumericArray,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
 
 evi = enhanced_vegetation_index
 
-# Temporal reducers & composites
-def median(arr: NumericArray, skip_na: bool = ...) -> NDArray[np.float64]: ...
-def composite(
-    arr: NumericArray, method: str = ..., **kwargs
+# Fused multi-index
+def compute_indices(
+    bands: Union[Mapping[str, NumericArray], NumericArray],
+    names: Sequence[str],
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+    sensor: Optional[str] = ...,
+    band_axis: int = ...,
+    band_names: Optional[Sequence[str]] = ...,
+) -> FloatArray: ...
+
+# Index registry
+class SpectralIndexInfo:
+    name: str
+    long_name: str
+    formula: str
+    bands: tuple[str, ...]
+    valid_range: tuple[float, float]
+    reference: str
+    func: Callable[..., FloatArray]
+    def missing_bands(self, provided: Mapping[str, object]) -> list[str]: ...
+    def from_cube(
+        self,
+        cube: NumericArray,
+        sensor: str,
+        band_axis: int = ...,
+        band_names: Optional[Sequence[str]] = ...,
+        scale: Optional[float] = ...,
+        offset: Optional[float] = ...,
+    ) -> FloatArray: ...
+
+class UnknownBandRoleError(ValueError):
+    role: str
+    valid_roles: list[str]
+
+def index_registry() -> dict[str, SpectralIndexInfo]: ...
+def band_roles() -> list[str]: ...
+
+# Sensor presets
+class SensorProfileInfo:
+    name: str
+    description: str
+    bands: tuple[str, ...]
+    roles: dict[str, str]
+    scale: float
+    offset: float
+    scl_band: Optional[str]
+
+def sensor_profiles() -> dict[str, SensorProfileInfo]: ...
+
+# Band transforms
+def tasseled_cap(
+    cube: NumericArray,
+    sensor: Optional[str] = ...,
+    coefficients: Optional[NumericArray] = ...,
+    band_axis: Optional[int] = ...,
+    band_names: Optional[Sequence[str]] = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def linear_unmixing(
+    cube: NumericArray,
+    endmembers: NumericArray,
+    method: str = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> tuple[FloatArray, FloatArray]: ...
+def spectral_similarity(
+    cube: NumericArray,
+    library: NumericArray,
+    metric: str = ...,
+    threshold: Optional[float] = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> tuple[FloatArray, NDArray[np.int32], FloatArray]: ...
+def pca_transform(
+    cube: NumericArray,
+    n_components: Optional[int] = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> tuple[FloatArray, NDArray[np.float64], NDArray[np.float64]]: ...
+def mnf_transform(
+    cube: NumericArray,
+    n_components: Optional[int] = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> tuple[FloatArray, NDArray[np.float64], NDArray[np.float64]]: ...
+
+# Band math
+def band_math(
+    expression: str, bands: Mapping[str, NumericArray]
 ) -> NDArray[np.float64]: ...
-def temporal_mean(arr: NumericArray, skip_na: bool = ...) -> NDArray[np.float64]: ...
-def temporal_std(arr: NumericArray, skip_na: bool = ...) -> NDArray[np.float64]: ...
+
+# Temporal reducers & composites
+def median(
+    arr: NumericArray,
+    axis: Optional[int] = ...,
+    skip_na: bool = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def composite(arr: NumericArray, method: str = ..., **kwargs) -> FloatArray: ...
+def temporal_mean(
+    arr: NumericArray,
+    skip_na: bool = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def temporal_std(
+    arr: NumericArray,
+    skip_na: bool = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
+def temporal_sum(
+    arr: NumericArray,
+    skip_na: bool = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> FloatArray: ...
 
 # Advanced temporal processes
 def moving_average_temporal(
@@ -122,56 +458,231 @@ def minkowski_distance(
     points_a: NumericArray, points_b: NumericArray, p: float
 ) -> NDArray[np.float64]: ...
 
+# Raster distance transform
+@overload
+def distance_transform(
+    features: Union[NumericArray, NDArray[np.bool_]],
+    pixel_size: Optional[Union[float, tuple[float, float]]] = ...,
+    return_indices: Literal[False] = ...,
+) -> NDArray[np.float64]: ...
+@overload
+def distance_transform(
+    features: Union[NumericArray, NDArray[np.bool_]],
+    pixel_size: Optional[Union[float, tuple[float, float]]],
+    return_indices: Literal[True],
+) -> tuple[NDArray[np.float64], NDArray[np.int64]]: ...
+@overload
+def distance_transform(
+    features: Union[NumericArray, NDArray[np.bool_]],
+    pixel_size: Optional[Union[float, tuple[float, float]]] = ...,
+    *,
+    return_indices: Literal[True],
+) -> tuple[NDArray[np.float64], NDArray[np.int64]]: ...
+
 # Masking utilities
 def mask_vals(
     arr: NumericArray,
     values: Optional[Sequence[float]] = ...,
     fill_value: Optional[float] = ...,
     nan_to: Optional[float] = ...,
-) -> NDArray[np.float64]: ...
-def replace_nans(arr: NumericArray, value: float) -> NDArray[np.float64]: ...
+) -> FloatArray: ...
+def replace_nans(arr: NumericArray, value: float) -> FloatArray: ...
 def mask_out_range(
     arr: NumericArray,
     min_val: Optional[float] = ...,
     max_val: Optional[float] = ...,
     fill_value: Optional[float] = ...,
-) -> NDArray[np.float64]: ...
+) -> FloatArray: ...
 def mask_invalid(
     arr: NumericArray,
     invalid_values: Sequence[float],
     fill_value: Optional[float] = ...,
-) -> NDArray[np.float64]: ...
+) -> FloatArray: ...
 def mask_in_range(
     arr: NumericArray,
     min_val: Optional[float] = ...,
     max_val: Optional[float] = ...,
     fill_value: Optional[float] = ...,
-) -> NDArray[np.float64]: ...
+) -> FloatArray: ...
+@overload
 def mask_scl(
     scl: NumericArray,
     keep_codes: Optional[Sequence[float]] = ...,
     fill_value: Optional[float] = ...,
-) -> NDArray[np.float64]: ...
+    output: Literal["values"] = ...,
+) -> FloatArray: ...
+@overload
+def mask_scl(
+    scl: NumericArray,
+    keep_codes: Optional[Sequence[float]] = ...,
+    fill_value: Optional[float] = ...,
+    *,
+    output: Literal["bool"],
+) -> NDArray[np.bool_]: ...
+@overload
+def mask_scl(
+    scl: NumericArray,
+    keep_codes: Optional[Sequence[float]] = ...,
+    fill_value: Optional[float] = ...,
+    *,
+    output: Literal["uint8"],
+) -> NDArray[np.uint8]: ...
 def mask_with_scl(
     data: NumericArray,
-    scl: NumericArray,
+    scl: Optional[NumericArray] = ...,
     mask_codes: Optional[Sequence[float]] = ...,
     fill_value: Optional[float] = ...,
-) -> NDArray[np.float64]: ...
+    sensor: Optional[str] = ...,
+    band_axis: Optional[int] = ...,
+    band_names: Optional[Sequence[str]] = ...,
+) -> FloatArray: ...
+
+QaFlag: TypeAlias = Union[
+    str,
+    tuple[str, Union[int, Sequence[int]]],
+    tuple[int, int, Union[int, Sequence[int]]],
+]
+
+def qa_mask(
+    qa: NumericArray,
+    flags: Optional[Sequence[QaFlag]] = ...,
+    qa_band: Literal["qa_pixel", "qa_radsat"] = ...,
+) -> NDArray[np.bool_]: ...
+def mask_with_qa(
+    data: NumericArray,
+    qa: NumericArray,
+    flags: Optional[Sequence[QaFlag]] = ...,
+    qa_band: Literal["qa_pixel", "qa_radsat"] = ...,
+    fill_value: Optional[float] = ...,
+    band_axis: Optional[int] = ...,
+) -> FloatArray: ...
+def buffer_mask(
+    mask: NumericArray,
+    distance_m: Union[float, Mapping[int, float]],
+    pixel_size: Union[float, tuple[float, float]],
+) -> NDArray[np.bool_]: ...
+def cloud_shadow_mask(
+    cloud: NDArray[np.bool_],
+    nir: NumericArray,
+    sun_azimuth: float,
+    sun_zenith: float,
+    pixel_size: float,
+    swir: Optional[NumericArray] = ...,
+    cloud_heights: tuple[float, float] = ...,
+    nir_threshold: float = ...,
+    swir_threshold: float = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> NDArray[np.bool_]: ...
+
+class SceneCloudStats(TypedDict):
+    clear: FloatArray
+    cloud: FloatArray
+    shadow: FloatArray
+    snow: FloatArray
+    nodata: FloatArray
+    clear_count: NDArray[np.uint32]
+    aoi_pixels: int
+
+def scene_cloud_stats(
+    layer: NumericArray,
+    source: Literal["scl", "qa_pixel"] = ...,
+    aoi: Optional[NDArray[np.bool_]] = ...,
+) -> SceneCloudStats: ...
 
 # Morphology functions
+FootprintShape: TypeAlias = Literal["square", "disk", "diamond", "cross", "line"]
+Footprint: TypeAlias = Union[FootprintShape, NDArray[np.bool_]]
+
+def structuring_element(
+    shape: FootprintShape, size: int = ..., angle: float = ...
+) -> NDArray[np.bool_]: ...
 def binary_dilation(
-    input: NDArray[np.uint8], kernel_size: int = ...
+    input: NDArray[np.uint8],
+    kernel_size: int = ...,
+    footprint: Optional[Footprint] = ...,
 ) -> NDArray[np.uint8]: ...
 def binary_erosion(
-    input: NDArray[np.uint8], kernel_size: int = ...
+    input: NDArray[np.uint8],
+    kernel_size: int = ...,
+    footprint: Optional[Footprint] = ...,
 ) -> NDArray[np.uint8]: ...
 def binary_opening(
-    input: NDArray[np.uint8], kernel_size: int = ...
+    input: NDArray[np.uint8],
+    kernel_size: int = ...,
+    footprint: Optional[Footprint] = ...,
 ) -> NDArray[np.uint8]: ...
 def binary_closing(
-    input: NDArray[np.uint8], kernel_size: int = ...
+    input: NDArray[np.uint8],
+    kernel_size: int = ...,
+    footprint: Optional[Footprint] = ...,
+) -> NDArray[np.uint8]: ...
+
+def grey_erosion(
+    input: NumericArray,
+    kernel_size: int = ...,
+    footprint: Optional[Footprint] = ...,
+) -> FloatArray: ...
+def grey_dilation(
+    input: NumericArray,
+    kernel_size: int = ...,
+    footprint: Optional[Footprint] = ...,
+) -> FloatArray: ...
+def grey_opening(
+    input: NumericArray,
+    kernel_size: int = ...,
+    footprint: Optional[Footprint] = ...,
+) -> FloatArray: ...
+def grey_closing(
+    input: NumericArray,
+    kernel_size: int = ...,
+    footprint: Optional[Footprint] = ...,
+) -> FloatArray: ...
+def white_tophat(
+    input: NumericArray,
+    kernel_size: int = ...,
+    footprint: Optional[Footprint] = ...,
+) -> FloatArray: ...
+def black_tophat(
+    input: NumericArray,
+    kernel_size: int = ...,
+    footprint: Optional[Footprint] = ...,
+) -> FloatArray: ...
+def morphological_gradient(
+    input: NumericArray,
+    kernel_size: int = ...,
+    footprint: Optional[Footprint] = ...,
+) -> FloatArray: ...
+
+class ComponentProps:
+    label: int
+    area: int
+    bbox: tuple[int, int, int, int]
+    centroid: tuple[float, float]
+    perimeter: int
+    mean: Optional[float]
+
+def label_components(
+    mask: Union[NumericArray, NDArray[np.bool_]],
+    connectivity: Literal[4, 8] = ...,
+    values: Optional[NumericArray] = ...,
+) -> tuple[NDArray[np.int64], dict[int, ComponentProps]]: ...
+def sieve(
+    classes: NDArray[np.integer],
+    threshold: int,
+    connectivity: Literal[4, 8] = ...,
+    nodata: Optional[int] = ...,
+) -> NDArray[np.integer]: ...
+def fill_holes(
+    input: Union[NumericArray, NDArray[np.bool_]],
+    connectivity: Literal[4, 8] = ...,
 ) -> NDArray[np.uint8]: ...
+def majority_filter(
+    classes: NDArray[np.integer],
+    kernel_size: int = ...,
+    footprint: Optional[Footprint] = ...,
+    nodata: Optional[int] = ...,
+) -> NDArray[np.integer]: ...
 
 # Workflows
 def bfast_monitor(
@@ -183,5 +694,53 @@ def bfast_monitor(
     h: float = ...,
     alpha: float = ...,
 ) -> NDArray[np.float64]: ...
+@overload
+def burn_severity(
+    pre_nir: NumericArray,
+    pre_swir2: NumericArray,
+    post_nir: NumericArray,
+    post_swir2: NumericArray,
+    index: Literal["dnbr", "rbr", "rdnbr"] = ...,
+    thresholds: Optional[Sequence[float]] = ...,
+    reference: Optional[NDArray[np.bool_]] = ...,
+    water_mask: Optional[NDArray[np.bool_]] = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+    pixel_area: None = ...,
+) -> tuple[FloatArray, NDArray[np.uint8], NDArray[np.int64], float]: ...
+@overload
+def burn_severity(
+    pre_nir: NumericArray,
+    pre_swir2: NumericArray,
+    post_nir: NumericArray,
+    post_swir2: NumericArray,
+    index: Literal["dnbr", "rbr", "rdnbr"] = ...,
+    thresholds: Optional[Sequence[float]] = ...,
+    reference: Optional[NDArray[np.bool_]] = ...,
+    water_mask: Optional[NDArray[np.bool_]] = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+    *,
+    pixel_area: float,
+) -> tuple[
+    FloatArray, NDArray[np.uint8], NDArray[np.int64], float, NDArray[np.float64]
+]: ...
+def fmask(
+    blue: NumericArray,
+    green: NumericArray,
+    red: NumericArray,
+    nir: NumericArray,
+    swir1: NumericArray,
+    swir2: NumericArray,
+    cirrus: Optional[NumericArray] = ...,
+    thermal: Optional[NumericArray] = ...,
+    sun_azimuth: Optional[float] = ...,
+    sun_zenith: Optional[float] = ...,
+    pixel_size: float = ...,
+    cloud_probability: float = ...,
+    cloud_heights: tuple[float, float] = ...,
+    scale: Optional[float] = ...,
+    offset: Optional[float] = ...,
+) -> tuple[NDArray[np.uint8], FloatArray]: ...
 
 # Raises ValueError if p < 1.0
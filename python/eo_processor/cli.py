@@ -13,6 +13,7 @@ or (if console_scripts entry point is later added):
     eo-processor --index ndvi ...
 
 Features:
+  - Every index in the Rust-side registry (``--list``); band flags follow its band roles
   - Single or multiple index computation in one invocation
   - Batch delta (change detection) indices (delta_ndvi, delta_nbr)
   - Optional cloud / validity mask (0 = masked, non-zero = keep)
@@ -35,107 +36,20 @@ import argparse
 import os
 import sys
 from collections.abc import Iterable, Mapping
-from dataclasses import dataclass
 from typing import Optional
 
 import numpy as np
 
-# Import public API (ensures delta indices are available)
-from . import (
-    log,
-    normalized_difference,
-    ndvi,
-    ndsi,
-    ndwi,
-    evi,
-    evi2,
-    savi,
-    nbr,
-    ndmi,
-    nbr2,
-    gci,
-    delta_ndvi,
-    delta_nbr,
-)
-
-
 from numpy.typing import NDArray
-from typing import Protocol, Any
+
+from . import SpectralIndexInfo, band_roles, index_registry, log
 
 # Public numeric array type (inputs may be any numeric dtype; outputs coerced to float64)
 NumericArray = NDArray[np.float64]
 
-
-class IndexFunctionProtocol(Protocol):
-    def __call__(self, *args: Any, **kwargs: Any) -> NumericArray: ...
-
-
-IndexFunction = IndexFunctionProtocol
-
-
-@dataclass(frozen=True)
-class IndexSpec:
-    name: str
-    func: IndexFunction
-    required_bands: list[str]
-    description: str
-
-    def missing_bands(self, provided: Mapping[str, NumericArray]) -> list[str]:
-        return [b for b in self.required_bands if b not in provided]
-
-    # (Method moved into class definition above with annotations)
-
-
-# Registry of supported indices
-INDEX_SPECS: dict[str, IndexSpec] = {
-    "normalized_difference": IndexSpec(
-        "normalized_difference",
-        normalized_difference,
-        ["a", "b"],
-        "(a - b) / (a + b)",
-    ),
-    "ndvi": IndexSpec("ndvi", ndvi, ["nir", "red"], "(NIR - Red)/(NIR + Red)"),
-    "ndwi": IndexSpec("ndwi", ndwi, ["green", "nir"], "(Green - NIR)/(Green + NIR)"),
-    "ndsi": IndexSpec(
-        "ndsi", ndsi, ["green", "swir1"], "(Green - SWIR1)/(Green + SWIR1)"
-    ),
-    "evi": IndexSpec(
-        "evi",
-        evi,
-        ["nir", "red", "blue"],
-        "2.5*(NIR-Red)/(NIR+6*Red-7.5*Blue+1)",
-    ),
-    "evi2": IndexSpec(
-        "evi2",
-        evi2,
-        ["nir", "red"],
-        "2.5*(NIR-Red)/(NIR+2.4*Red+1)",
-    ),
-    "savi": IndexSpec(
-        "savi",
-        savi,
-        ["nir", "red"],
-        "(NIR-Red)/(NIR+Red+L)*(1+L)",
-    ),
-    "nbr": IndexSpec("nbr", nbr, ["nir", "swir2"], "(NIR - SWIR2)/(NIR + SWIR2)"),
-    "ndmi": IndexSpec("ndmi", ndmi, ["nir", "swir1"], "(NIR - SWIR1)/(NIR + SWIR1)"),
-    "nbr2": IndexSpec(
-        "nbr2", nbr2, ["swir1", "swir2"], "(SWIR1 - SWIR2)/(SWIR1 + SWIR2)"
-    ),
-    "gci": IndexSpec("gci", gci, ["nir", "green"], "(NIR/Green) - 1"),
-    "delta_ndvi": IndexSpec(
-        "delta_ndvi",
-        delta_ndvi,
-        ["pre_nir", "pre_red", "post_nir", "post_red"],
-        "NDVI(pre) - NDVI(post)",
-    ),
-    "delta_nbr": IndexSpec(
-        "delta_nbr",
-        delta_nbr,
-        ["pre_nir", "pre_swir2", "post_nir", "post_swir2"],
-        "NBR(pre) - NBR(post)",
-    ),
-}
+# Supported indices and their band requirements, taken from the Rust-side
+# registry so the CLI cannot drift from the compiled core.
+INDEX_SPECS: dict[str, SpectralIndexInfo] = index_registry()
 
 
 def build_parser() -> argparse.ArgumentParser:
@@ -148,22 +62,14 @@ def build_parser() -> argparse.ArgumentParser:
         nargs="+",
         help="One or more index names. Use --list to see supported indices. (Either --index or --list is required.)",
     )
-    # Standard bands
-    p.add_argument("--nir")
-    p.add_argument("--red")
-    p.add_argument("--green")
-    p.add_argument("--blue")
-    p.add_argument("--swir1")
-    p.add_argument("--swir2")
-    p.add_argument("--a")
-    p.add_argument("--b")
-    # Delta bands
-    p.add_argument("--pre-nir")
-    p.add_argument("--pre-red")
-    p.add_argument("--post-nir")
-    p.add_argument("--post-red")
-    p.add_argument("--pre-swir2")
-    p.add_argument("--post-swir2")
+    # One flag per band role, e.g. --nir, --swir1, --pre-nir
+    for role in band_roles():
+        p.add_argument(
+            f"--{role.replace('_', '-')}",
+            dest=role,
+            metavar="NPY",
+            help=f"Path to the {role} band (.npy).",
+        )
     # SAVI
     p.add_argument(
         "--savi-l",
@@ -218,8 +124,11 @@ def build_parser() -> argparse.ArgumentParser:
 def list_indices() -> None:
     print("Supported indices:")
     for spec in INDEX_SPECS.values():
-        bands = ",".join(spec.required_bands)
-        print(f"  {spec.name:15} {spec.description:35} bands=[{bands}]")
+        bands = ",".join(spec.bands)
+        lo, hi = spec.valid_range
+        print(
+            f"  {spec.name:22} {spec.formula:52} bands=[{bands}] range=[{lo:g}, {hi:g}]"
+        )
 
 
 def load_npy(path: str) -> NumericArray:
@@ -277,48 +186,13 @@ def save_png(path: str, arr: NumericArray, clamp: Optional[list[float]]) -> None
 
 
 def compute(
-    spec: IndexSpec, bands: Mapping[str, NumericArray], savi_l: float
+    spec: SpectralIndexInfo, bands: Mapping[str, NumericArray], savi_l: float
 ) -> NumericArray:
-    # Dispatch based on spec.name (explicit to keep signature clarity)
-    name = spec.name
-    f = spec.func
-    if name == "normalized_difference":
-        return f(bands["a"], bands["b"])
-    if name == "ndvi":
-        return f(bands["nir"], bands["red"])
-    if name == "ndwi":
-        return f(bands["green"], bands["nir"])
-    if name == "ndsi":
-        return f(bands["green"], bands["swir1"])
-    if name == "evi":
-        return f(bands["nir"], bands["red"], bands["blue"])
-    if name == "evi2":
-        return f(bands["nir"], bands["red"])
-    if name == "savi":
-        return f(bands["nir"], bands["red"], L=savi_l)
-    if name == "nbr":
-        return f(bands["nir"], bands["swir2"])
-    if name == "ndmi":
-        return f(bands["nir"], bands["swir1"])
-    if name == "nbr2":
-        return f(bands["swir1"], bands["swir2"])
-    if name == "gci":
-        return f(bands["nir"], bands["green"])
-    if name == "delta_ndvi":
-        return f(
-            bands["pre_nir"],
-            bands["pre_red"],
-            bands["post_nir"],
-            bands["post_red"],
-        )
-    if name == "delta_nbr":
-        return f(
-            bands["pre_nir"],
-            bands["pre_swir2"],
-            bands["post_nir"],
-            bands["post_swir2"],
-        )
-    raise ValueError(f"Unhandled index: {name}")
+    # Registry bands are listed in the argument order of spec.func.
+    args = [bands[b] for b in spec.bands]
+    if spec.name == "savi":
+        return spec.func(*args, L=savi_l)
+    return spec.func(*args)
 
 
 def _gather_required_bands(indices: Iterable[str]) -> list[str]:
@@ -326,7 +200,7 @@ def _gather_required_bands(indices: Iterable[str]) -> list[str]:
     for idx in indices:
         if idx not in INDEX_SPECS:
             raise KeyError(idx)
-        for b in INDEX_SPECS[idx].required_bands:
+        for b in INDEX_SPECS[idx].bands:
             if b not in needed:
                 needed.append(b)
     return needed
@@ -363,22 +237,7 @@ def cli(argv: Optional[list[str]] = None) -> int:
             return 1
 
     # Map of band name -> numpy array
-    band_path_map = {
-        "nir": args.nir,
-        "red": args.red,
-        "green": args.green,
-        "blue": args.blue,
-        "swir1": args.swir1,
-        "swir2": args.swir2,
-        "a": args.a,
-        "b": args.b,
-        "pre_nir": args.pre_nir,
-        "pre_red": args.pre_red,
-        "post_nir": args.post_nir,
-        "post_red": args.post_red,
-        "pre_swir2": args.pre_swir2,
-        "post_swir2": args.post_swir2,
-    }
+    band_path_map = {role: getattr(args, role) for role in band_roles()}
 
     try:
         required = _gather_required_bands(indices)
@@ -411,12 +270,12 @@ def cli(argv: Optional[list[str]] = None) -> int:
         if not spec:
             log.error("Unsupported index", index=idx)
             return 1
-        if any(b not in loaded for b in spec.required_bands):
+        missing = spec.missing_bands(loaded)
+        if missing:
             if args.allow_missing:
                 log.info("Skipping index (missing bands)", index=idx)
                 continue
             else:
-                missing = [b for b in spec.required_bands if b not in loaded]
                 log.error("Missing bands for index", index=idx, missing=missing)
                 return 1
         try:
@@ -0,0 +1,703 @@
+//! Expression-based band math.
+//!
+//! Compiles a user supplied arithmetic expression such as
+//! `"(nir - red) / (nir + red + 0.5) * 1.5"` once into a small stack bytecode,
+//! then evaluates it over N-dimensional band arrays in parallel.
+//!
+//! Grammar (highest precedence last):
+//! ```text
+//! expr    := term (('+' | '-') term)*
+//! term    := unary (('*' | '/') unary)*
+//! unary   := ('-' | '+') unary | power
+//! power   := primary (('^' | '**') unary)?
+//! primary := number | band | func '(' expr (',' expr)* ')' | '(' expr ')'
+//! ```
+//!
+//! Supported functions: `abs`, `sqrt`, `exp`, `log` (natural), `log10`,
+//! `min(a, b)`, `max(a, b)`.
+//!
+//! Division follows the same convention as the built-in indices: when the
+//! denominator magnitude is below `EPSILON` the result is 0.0 instead of
+//! Inf/NaN. NaN inputs propagate.
+//!
+//! Evaluation is block-wise: every instruction is applied to a block of
+//! `BLOCK_SIZE` pixels at a time, which keeps the interpreter overhead per
+//! pixel small. Blocks are distributed across threads with rayon.
+
+use crate::kernels;
+use crate::CoreError;
+use ndarray::{ArrayD, ArrayViewD, CowArray, IxDyn};
+use numpy::{IntoPyArray, PyReadonlyArrayDyn};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rayon::prelude::*;
+
+/// Threshold for detecting near-zero denominators (mirrors `indices.rs`).
+const EPSILON: f64 = 1e-10;
+
+/// Number of pixels processed per instruction pass (and per rayon work unit).
+const BLOCK_SIZE: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Caret,
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, CoreError> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' | '\n' | '\r' => i += 1,
+            '+' => {
+                tokens.push(Token::Plus);
+                i += 1;
+            }
+            '-' => {
+                tokens.push(Token::Minus);
+                i += 1;
+            }
+            '*' => {
+                if i + 1 < chars.len() && chars[i + 1] == '*' {
+                    tokens.push(Token::Caret);
+                    i += 2;
+                } else {
+                    tokens.push(Token::Star);
+                    i += 1;
+                }
+            }
+            '/' => {
+                tokens.push(Token::Slash);
+                i += 1;
+            }
+            '^' => {
+                tokens.push(Token::Caret);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                // Optional exponent part (e.g. 1e-3, 2.5E+2)
+                if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                    let mut j = i + 1;
+                    if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                        j += 1;
+                    }
+                    if j < chars.len() && chars[j].is_ascii_digit() {
+                        i = j;
+                        while i < chars.len() && chars[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                }
+                let text: String = chars[start..i].iter().collect();
+                let value = text.parse::<f64>().map_err(|_| {
+                    CoreError::InvalidArgument(format!(
+                        "Invalid number '{}' at position {}",
+                        text, start
+                    ))
+                })?;
+                tokens.push(Token::Number(value));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            other => {
+                return Err(CoreError::InvalidArgument(format!(
+                    "Unexpected character '{}' at position {}",
+                    other, i
+                )))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UnaryOp {
+    Neg,
+    Abs,
+    Sqrt,
+    Exp,
+    Log,
+    Log10,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Band(usize),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+
+#[inline(always)]
+fn apply_binary(op: BinOp, a: f64, b: f64) -> f64 {
+    match op {
+        BinOp::Add => a + b,
+        BinOp::Sub => a - b,
+        BinOp::Mul => a * b,
+        BinOp::Div => {
+            if b.abs() < EPSILON {
+                0.0
+            } else {
+                a / b
+            }
+        }
+        BinOp::Pow => a.powf(b),
+        BinOp::Min => a.min(b),
+        BinOp::Max => a.max(b),
+    }
+}
+
+#[inline(always)]
+fn apply_unary(op: UnaryOp, a: f64) -> f64 {
+    match op {
+        UnaryOp::Neg => -a,
+        UnaryOp::Abs => a.abs(),
+        UnaryOp::Sqrt => a.sqrt(),
+        UnaryOp::Exp => a.exp(),
+        UnaryOp::Log => a.ln(),
+        UnaryOp::Log10 => a.log10(),
+    }
+}
+
+/// Recursive-descent parser. Band identifiers are interned in order of first
+/// appearance so the compiled program can refer to them by index.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    bands: Vec<String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        tok
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), CoreError> {
+        match self.next() {
+            Some(ref tok) if *tok == expected => Ok(()),
+            Some(tok) => Err(CoreError::InvalidArgument(format!(
+                "Expected {:?}, found {:?}",
+                expected, tok
+            ))),
+            None => Err(CoreError::InvalidArgument(format!(
+                "Expected {:?}, found end of expression",
+                expected
+            ))),
+        }
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, CoreError> {
+        let mut lhs = self.parse_term()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Plus) => BinOp::Add,
+                Some(Token::Minus) => BinOp::Sub,
+                _ => break,
+            };
+            self.pos += 1;
+            let rhs = self.parse_term()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_term(&mut self) -> Result<Expr, CoreError> {
+        let mut lhs = self.parse_unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Star) => BinOp::Mul,
+                Some(Token::Slash) => BinOp::Div,
+                _ => break,
+            };
+            self.pos += 1;
+            let rhs = self.parse_unary()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, CoreError> {
+        match self.peek() {
+            Some(Token::Minus) => {
+                self.pos += 1;
+                let inner = self.parse_unary()?;
+                Ok(Expr::Unary(UnaryOp::Neg, Box::new(inner)))
+            }
+            Some(Token::Plus) => {
+                self.pos += 1;
+                self.parse_unary()
+            }
+            _ => self.parse_power(),
+        }
+    }
+
+    fn parse_power(&mut self) -> Result<Expr, CoreError> {
+        let base = self.parse_primary()?;
+        if let Some(Token::Caret) = self.peek() {
+            self.pos += 1;
+            // Right associative: a ^ b ^ c == a ^ (b ^ c)
+            let exponent = self.parse_unary()?;
+            return Ok(Expr::Binary(BinOp::Pow, Box::new(base), Box::new(exponent)));
+        }
+        Ok(base)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, CoreError> {
+        match self.next() {
+            Some(Token::Number(v)) => Ok(Expr::Number(v)),
+            Some(Token::LParen) => {
+                let inner = self.parse_expr()?;
+                self.expect(Token::RParen)?;
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) => {
+                if let Some(Token::LParen) = self.peek() {
+                    self.pos += 1;
+                    let mut args = vec![self.parse_expr()?];
+                    while let Some(Token::Comma) = self.peek() {
+                        self.pos += 1;
+                        args.push(self.parse_expr()?);
+                    }
+                    self.expect(Token::RParen)?;
+                    build_call(&name, args)
+                } else {
+                    let idx = match self.bands.iter().position(|b| *b == name) {
+                        Some(idx) => idx,
+                        None => {
+                            self.bands.push(name);
+                            self.bands.len() - 1
+                        }
+                    };
+                    Ok(Expr::Band(idx))
+                }
+            }
+            Some(tok) => Err(CoreError::InvalidArgument(format!(
+                "Unexpected token {:?} in expression",
+                tok
+            ))),
+            None => Err(CoreError::InvalidArgument(
+                "Unexpected end of expression".to_string(),
+            )),
+        }
+    }
+}
+
+fn build_call(name: &str, mut args: Vec<Expr>) -> Result<Expr, CoreError> {
+    let unary = match name {
+        "abs" => Some(UnaryOp::Abs),
+        "sqrt" => Some(UnaryOp::Sqrt),
+        "exp" => Some(UnaryOp::Exp),
+        "log" => Some(UnaryOp::Log),
+        "log10" => Some(UnaryOp::Log10),
+        _ => None,
+    };
+    if let Some(op) = unary {
+        if args.len() != 1 {
+            return Err(CoreError::InvalidArgument(format!(
+                "Function '{}' expects 1 argument, got {}",
+                name,
+                args.len()
+            )));
+        }
+        return Ok(Expr::Unary(op, Box::new(args.remove(0))));
+    }
+    let binary = match name {
+        "min" => BinOp::Min,
+        "max" => BinOp::Max,
+        _ => {
+            return Err(CoreError::InvalidArgument(format!(
+                "Unknown function '{}'",
+                name
+            )))
+        }
+    };
+    if args.len() != 2 {
+        return Err(CoreError::InvalidArgument(format!(
+            "Function '{}' expects 2 arguments, got {}",
+            name,
+            args.len()
+        )));
+    }
+    let rhs = args.pop().unwrap();
+    let lhs = args.pop().unwrap();
+    Ok(Expr::Binary(binary, Box::new(lhs), Box::new(rhs)))
+}
+
+/// Fold sub-expressions that only involve literals.
+fn fold_constants(expr: Expr) -> Expr {
+    match expr {
+        Expr::Unary(op, inner) => match fold_constants(*inner) {
+            Expr::Number(v) => Expr::Number(apply_unary(op, v)),
+            other => Expr::Unary(op, Box::new(other)),
+        },
+        Expr::Binary(op, lhs, rhs) => match (fold_constants(*lhs), fold_constants(*rhs)) {
+            (Expr::Number(a), Expr::Number(b)) => Expr::Number(apply_binary(op, a, b)),
+            (a, b) => Expr::Binary(op, Box::new(a), Box::new(b)),
+        },
+        other => other,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Instr {
+    Const(f64),
+    Load(usize),
+    Unary(UnaryOp),
+    Binary(BinOp),
+}
+
+/// A compiled band-math expression.
+///
+/// Compile once with [`BandProgram::compile`], then evaluate against any number
+/// of band sets with [`BandProgram::evaluate`].
+#[derive(Debug, Clone)]
+pub struct BandProgram {
+    bands: Vec<String>,
+    code: Vec<Instr>,
+    max_stack: usize,
+}
+
+impl BandProgram {
+    /// Parse and compile an expression into stack bytecode.
+    pub fn compile(expression: &str) -> Result<Self, CoreError> {
+        let tokens = tokenize(expression)?;
+        if tokens.is_empty() {
+            return Err(CoreError::InvalidArgument(
+                "Expression must not be empty".to_string(),
+            ));
+        }
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            bands: Vec::new(),
+        };
+        let ast = parser.parse_expr()?;
+        if let Some(tok) = parser.peek() {
+            return Err(CoreError::InvalidArgument(format!(
+                "Unexpected trailing token {:?} in expression",
+                tok
+            )));
+        }
+        let ast = fold_constants(ast);
+
+        let mut code = Vec::new();
+        let mut depth = 0usize;
+        let mut max_stack = 0usize;
+        emit(&ast, &mut code, &mut depth, &mut max_stack);
+
+        Ok(BandProgram {
+            bands: parser.bands,
+            code,
+            max_stack,
+        })
+    }
+
+    /// Band identifiers referenced by the expression, in order of first use.
+    pub fn band_names(&self) -> &[String] {
+        &self.bands
+    }
+
+    /// Evaluate the program. `bands[i]` must correspond to `band_names()[i]`
+    /// and the band shapes must broadcast together (see `kernels`).
+    pub fn evaluate(&self, bands: &[ArrayViewD<f64>]) -> Result<ArrayD<f64>, CoreError> {
+        if bands.len() != self.bands.len() {
+            return Err(CoreError::InvalidArgument(format!(
+                "Expression references {} bands but {} were supplied",
+                self.bands.len(),
+                bands.len()
+            )));
+        }
+        if bands.is_empty() {
+            return Err(CoreError::InvalidArgument(
+                "Expression must reference at least one band".to_string(),
+            ));
+        }
+        let shapes: Vec<&[usize]> = bands.iter().map(|b| b.shape()).collect();
+        let shape = kernels::broadcast_shape(&shapes).ok_or_else(|| {
+            let detail: Vec<String> = self
+                .bands
+                .iter()
+                .zip(shapes.iter())
+                .map(|(name, shape)| format!("'{}' {:?}", name, shape))
+                .collect();
+            CoreError::InvalidArgument(format!(
+                "Shape mismatch for bands: {} cannot be broadcast together",
+                detail.join(", ")
+            ))
+        })?;
+
+        let standard: Vec<_> = bands
+            .iter()
+            .map(|b| {
+                if b.shape() == shape.as_slice() {
+                    b.as_standard_layout()
+                } else {
+                    CowArray::from(
+                        b.broadcast(IxDyn(&shape))
+                            .expect("shape was validated by broadcast_shape")
+                            .as_standard_layout()
+                            .into_owned(),
+                    )
+                }
+            })
+            .collect();
+        let slices: Vec<&[f64]> = standard
+            .iter()
+            .map(|b| b.as_slice().expect("standard layout array is contiguous"))
+            .collect();
+
+        let mut out = ArrayD::<f64>::zeros(shape);
+        let out_slice = out
+            .as_slice_mut()
+            .expect("freshly allocated array is contiguous");
+
+        out_slice
+            .par_chunks_mut(BLOCK_SIZE)
+            .enumerate()
+            .for_each(|(block_idx, out_block)| {
+                let start = block_idx * BLOCK_SIZE;
+                let len = out_block.len();
+                let mut stack: Vec<Vec<f64>> = vec![vec![0.0; len]; self.max_stack];
+                let mut sp = 0usize;
+                for instr in &self.code {
+                    match *instr {
+                        Instr::Const(v) => {
+                            stack[sp].iter_mut().for_each(|x| *x = v);
+                            sp += 1;
+                        }
+                        Instr::Load(i) => {
+                            stack[sp].copy_from_slice(&slices[i][start..start + len]);
+                            sp += 1;
+                        }
+                        Instr::Unary(op) => {
+                            stack[sp - 1]
+                                .iter_mut()
+                                .for_each(|x| *x = apply_unary(op, *x));
+                        }
+                        Instr::Binary(op) => {
+                            let (lo, hi) = stack.split_at_mut(sp - 1);
+                            let lhs = &mut lo[sp - 2];
+                            let rhs = &hi[0];
+                            lhs.iter_mut()
+                                .zip(rhs.iter())
+                                .for_each(|(a, &b)| *a = apply_binary(op, *a, b));
+                            sp -= 1;
+                        }
+                    }
+                }
+                out_block.copy_from_slice(&stack[0]);
+            });
+
+        Ok(out)
+    }
+}
+
+fn emit(expr: &Expr, code: &mut Vec<Instr>, depth: &mut usize, max_stack: &mut usize) {
+    match expr {
+        Expr::Number(v) => {
+            code.push(Instr::Const(*v));
+            *depth += 1;
+        }
+        Expr::Band(i) => {
+            code.push(Instr::Load(*i));
+            *depth += 1;
+        }
+        Expr::Unary(op, inner) => {
+            emit(inner, code, depth, max_stack);
+            code.push(Instr::Unary(*op));
+        }
+        Expr::Binary(op, lhs, rhs) => {
+            emit(lhs, code, depth, max_stack);
+            emit(rhs, code, depth, max_stack);
+            code.push(Instr::Binary(*op));
+            *depth -= 1;
+        }
+    }
+    *max_stack = (*max_stack).max(*depth);
+}
+
+/// Evaluate a band-math expression over named band arrays.
+///
+/// # Arguments
+/// * `expression` - Arithmetic expression using band names as variables,
+///   e.g. `"(nir - red) / (nir + red + 0.5) * 1.5"`.
+/// * `bands` - Mapping of band name to NumPy array (any numeric dtype, coerced
+///   to float64). Referenced bands may have any rank; their shapes are
+///   broadcast NumPy-style. Bands not referenced by the expression are ignored.
+///
+/// # Returns
+/// float64 array with the broadcast shape of the input bands.
+///
+/// # Example (from Python)
+/// ```python
+/// import numpy as np
+/// from eo_processor import band_math
+///
+/// nir = np.array([0.8, 0.7])
+/// red = np.array([0.2, 0.1])
+/// savi = band_math("(nir - red) / (nir + red + 0.5) * 1.5", {"nir": nir, "red": red})
+/// ```
+#[pyfunction]
+pub fn band_math(py: Python<'_>, expression: &str, bands: &PyDict) -> PyResult<PyObject> {
+    let program = BandProgram::compile(expression)?;
+
+    let mut arrays: Vec<PyReadonlyArrayDyn<f64>> = Vec::with_capacity(program.band_names().len());
+    for name in program.band_names() {
+        let obj = bands.get_item(name)?.ok_or_else(|| {
+            let mut provided: Vec<String> = bands
+                .keys()
+                .iter()
+                .map(|k| k.to_string())
+                .collect::<Vec<_>>();
+            provided.sort();
+            CoreError::InvalidArgument(format!(
+                "Expression references unknown band '{}'; provided bands: {:?}",
+                name, provided
+            ))
+        })?;
+        arrays.push(kernels::coerce_dyn(obj)?);
+    }
+
+    let views: Vec<ArrayViewD<f64>> = arrays.iter().map(|a| a.as_array()).collect();
+    let out = py.allow_threads(|| program.evaluate(&views))?;
+    Ok(out.into_pyarray(py).into_py(py))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{array, IxDyn};
+
+    #[test]
+    fn test_precedence_and_associativity() {
+        let p = BandProgram::compile("a + 2 * 3 ^ 2 ^ 1 - -1").unwrap();
+        let a = ArrayD::from_elem(IxDyn(&[1]), 1.0);
+        let out = p.evaluate(&[a.view()]).unwrap();
+        assert_eq!(out[[0]], 1.0 + 2.0 * 9.0 + 1.0);
+    }
+
+    #[test]
+    fn test_savi_expression_matches_formula() {
+        let p = BandProgram::compile("(nir - red) / (nir + red + 0.5) * 1.5").unwrap();
+        assert_eq!(p.band_names(), &["nir".to_string(), "red".to_string()]);
+        let nir = array![[0.8, 0.7], [0.6, 0.5]].into_dyn();
+        let red = array![[0.2, 0.1], [0.3, 0.5]].into_dyn();
+        let out = p.evaluate(&[nir.view(), red.view()]).unwrap();
+        for (o, (&n, &r)) in out.iter().zip(nir.iter().zip(red.iter())) {
+            assert!((o - (n - r) / (n + r + 0.5) * 1.5).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_zero_denominator_returns_zero() {
+        let p = BandProgram::compile("(a - b) / (a + b)").unwrap();
+        let a = array![0.0, 0.5].into_dyn();
+        let b = array![0.0, -0.5].into_dyn();
+        let out = p.evaluate(&[a.view(), b.view()]).unwrap();
+        assert_eq!(out[[0]], 0.0);
+        assert_eq!(out[[1]], 0.0);
+    }
+
+    #[test]
+    fn test_functions_and_constant_folding() {
+        let p = BandProgram::compile("max(abs(x), sqrt(16)) + min(x, 0) * log(exp(2))").unwrap();
+        assert_eq!(
+            p.code
+                .iter()
+                .filter(|i| matches!(i, Instr::Const(_)))
+                .count(),
+            3
+        );
+        let x = array![-5.0, 1.0].into_dyn();
+        let out = p.evaluate(&[x.view()]).unwrap();
+        assert!((out[[0]] - (5.0 - 10.0)).abs() < 1e-12);
+        assert!((out[[1]] - 4.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(BandProgram::compile("").is_err());
+        assert!(BandProgram::compile("(a + b").is_err());
+        assert!(BandProgram::compile("a +").is_err());
+        assert!(BandProgram::compile("a $ b").is_err());
+        assert!(BandProgram::compile("foo(a)").is_err());
+        assert!(BandProgram::compile("min(a)").is_err());
+        assert!(BandProgram::compile("a b").is_err());
+    }
+
+    #[test]
+    fn test_shape_mismatch() {
+        let p = BandProgram::compile("a + b").unwrap();
+        let a = ArrayD::<f64>::zeros(IxDyn(&[2, 3]));
+        let b = ArrayD::<f64>::zeros(IxDyn(&[3, 2]));
+        let err = p.evaluate(&[a.view(), b.view()]).unwrap_err();
+        assert!(err.to_string().contains("Shape mismatch"));
+    }
+
+    #[test]
+    fn test_bands_broadcast() {
+        let p = BandProgram::compile("a - b").unwrap();
+        let a = array![[1.0, 2.0], [3.0, 4.0]].into_dyn();
+        let b = array![[1.0], [2.0]].into_dyn();
+        let out = p.evaluate(&[a.view(), b.view()]).unwrap();
+        assert_eq!(out, array![[0.0, 1.0], [1.0, 2.0]].into_dyn());
+    }
+
+    #[test]
+    fn test_high_rank_and_multiple_blocks() {
+        let p = BandProgram::compile("a * 2").unwrap();
+        let a = ArrayD::from_shape_fn(IxDyn(&[2, 3, 4, 5, 100]), |ix| ix[4] as f64);
+        let out = p.evaluate(&[a.view()]).unwrap();
+        assert_eq!(out.shape(), a.shape());
+        assert!(out.iter().zip(a.iter()).all(|(&o, &v)| o == 2.0 * v));
+    }
+}
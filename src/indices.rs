@@ -1,91 +1,223 @@
+use crate::kernels::{self, py_map2, py_map3, py_map4, py_map5, Operand, Real, Sample, Scaling};
 use crate::CoreError;
-use ndarray::{Array1, Array2, Array3, Array4, Zip};
-use numpy::{
-    IntoPyArray, PyArray1, PyArray2, PyArray3, PyArray4, PyReadonlyArray1, PyReadonlyArray2,
-    PyReadonlyArray3, PyReadonlyArray4,
-};
+use ndarray::{Array2, ArrayD, ArrayView1, ArrayViewD, Axis, CowArray, IxDyn, Zip};
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rayon::prelude::*;
 
 /// Threshold for detecting near-zero values to avoid division by zero
 const EPSILON: f64 = 1e-10;
 
-/// Coerce a Python object to a readonly 1D float64 NumPy array.
-/// Tries direct extraction; on failure, attempts `.astype("float64")`.
-fn try_coerce_array1<'py>(obj: &'py PyAny) -> PyResult<PyReadonlyArray1<'py, f64>> {
-    if let Ok(arr) = obj.extract::<PyReadonlyArray1<f64>>() {
-        return Ok(arr);
+//
+// Per-pixel formulas.
+// Each index is a scalar function; `kernels::py_map*` handles coercion,
+// broadcasting, rank dispatch, DN scaling and parallel evaluation.
+//
+// Every `#[pyfunction]` below also takes optional `scale` / `offset`
+// arguments, applied to each input as `value * scale + offset` before the
+// formula (e.g. `scale=1e-4` for Sentinel-2 L2A uint16 reflectance).
+//
+
+#[inline]
+pub(crate) fn normalized_difference_px(a: f64, b: f64) -> f64 {
+    let denom = a + b;
+    if denom.abs() < EPSILON {
+        0.0
+    } else {
+        (a - b) / denom
     }
-    let coerced = obj.call_method1("astype", ("float64",))?;
-    let coerced_arr = coerced.extract::<PyReadonlyArray1<f64>>()?;
-    Ok(coerced_arr)
 }
 
-/// Coerce a Python object to a readonly 2D float64 NumPy array.
-/// Tries direct extraction; on failure, attempts `.astype("float64")`.
-fn try_coerce_array2<'py>(obj: &'py PyAny) -> PyResult<PyReadonlyArray2<'py, f64>> {
-    if let Ok(arr) = obj.extract::<PyReadonlyArray2<f64>>() {
-        return Ok(arr);
+#[inline]
+pub(crate) fn savi_px(nir: f64, red: f64, l: f64) -> f64 {
+    let denom = nir + red + l;
+    if denom.abs() < EPSILON {
+        0.0
+    } else {
+        (nir - red) / denom * (1.0 + l)
     }
-    let coerced = obj.call_method1("astype", ("float64",))?;
-    let coerced_arr = coerced.extract::<PyReadonlyArray2<f64>>()?;
-    Ok(coerced_arr)
 }
 
-/// Public function to compute normalized difference between two arrays.
-/// Acts as a wrapper to expose both 1D and 2D versions.
-#[pyfunction]
-pub fn normalized_difference(py: Python<'_>, a: &PyAny, b: &PyAny) -> PyResult<PyObject> {
-    // Attempt direct float64 extraction first; if that fails, coerce dtype to float64.
-    if let Ok(a_1d) = try_coerce_array1(a) {
-        let b_1d = try_coerce_array1(b)?;
-        if a_1d.shape() != b_1d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 1D arrays: a {:?} vs b {:?}",
-                a_1d.shape(),
-                b_1d.shape()
-            ))
-            .into());
-        }
-        normalized_difference_1d(py, a_1d, b_1d).map(|res| res.into_py(py))
-    } else if let Ok(a_2d) = try_coerce_array2(a) {
-        let b_2d = try_coerce_array2(b)?;
-        if a_2d.shape() != b_2d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 2D arrays: a {:?} vs b {:?}",
-                a_2d.shape(),
-                b_2d.shape()
-            ))
-            .into());
-        }
-        normalized_difference_2d(py, a_2d, b_2d).map(|res| res.into_py(py))
-    } else if let Ok(a_3d) = a.extract::<PyReadonlyArray3<f64>>() {
-        let b_3d = b.extract::<PyReadonlyArray3<f64>>()?;
-        if a_3d.shape() != b_3d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 3D arrays: a {:?} vs b {:?}",
-                a_3d.shape(),
-                b_3d.shape()
-            ))
-            .into());
-        }
-        normalized_difference_3d(py, a_3d, b_3d).map(|res| res.into_py(py))
-    } else if let Ok(a_4d) = a.extract::<PyReadonlyArray4<f64>>() {
-        let b_4d = b.extract::<PyReadonlyArray4<f64>>()?;
-        if a_4d.shape() != b_4d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 4D arrays: a {:?} vs b {:?}",
-                a_4d.shape(),
-                b_4d.shape()
-            ))
-            .into());
-        }
-        normalized_difference_4d(py, a_4d, b_4d).map(|res| res.into_py(py))
+#[inline]
+pub(crate) fn ratio_minus_one_px(num: f64, den: f64) -> f64 {
+    if den.abs() < EPSILON {
+        0.0
     } else {
-        Err(CoreError::InvalidArgument(
-            "Inputs must be numeric 1D, 2D, 3D, or 4D numpy arrays (will be coerced to float64)."
-                .to_string(),
-        )
-        .into())
+        num / den - 1.0
+    }
+}
+
+#[inline]
+pub(crate) fn evi_px(nir: f64, red: f64, blue: f64) -> f64 {
+    const G: f64 = 2.5;
+    const C1: f64 = 6.0;
+    const C2: f64 = 7.5;
+    const L: f64 = 1.0;
+    let denom = nir + C1 * red - C2 * blue + L;
+    if denom.abs() < EPSILON {
+        0.0
+    } else {
+        G * (nir - red) / denom
+    }
+}
+
+#[inline]
+pub(crate) fn evi2_px(nir: f64, red: f64) -> f64 {
+    const G: f64 = 2.5;
+    const C1: f64 = 2.4;
+    const L: f64 = 1.0;
+    let denom = nir + C1 * red + L;
+    if denom.abs() < EPSILON {
+        0.0
+    } else {
+        G * (nir - red) / denom
+    }
+}
+
+#[inline]
+pub(crate) fn osavi_px(nir: f64, red: f64) -> f64 {
+    const L: f64 = 0.16;
+    let denom = nir + red + L;
+    if denom.abs() < EPSILON {
+        0.0
+    } else {
+        (nir - red) / denom
+    }
+}
+
+#[inline]
+pub(crate) fn msavi_px(nir: f64, red: f64) -> f64 {
+    let term = 2.0 * nir + 1.0;
+    let discriminant = term * term - 8.0 * (nir - red);
+    if discriminant < 0.0 {
+        0.0
+    } else {
+        (term - discriminant.sqrt()) / 2.0
+    }
+}
+
+#[inline]
+pub(crate) fn ndvi_re2_px(nir: f64, rededge: f64, red: f64) -> f64 {
+    let denom = nir - red;
+    if denom.abs() < EPSILON {
+        0.0
+    } else {
+        (nir - rededge) / denom
+    }
+}
+
+#[inline]
+pub(crate) fn lai_px(nir: f64, red: f64, blue: f64) -> f64 {
+    const A: f64 = 3.618;
+    const B: f64 = -0.118;
+    A * evi_px(nir, red, blue) + B
+}
+
+#[inline]
+pub(crate) fn mtci_px(rededge: f64, red: f64, green: f64) -> f64 {
+    let denom = red - green;
+    if denom.abs() < EPSILON {
+        0.0
+    } else {
+        (rededge - red) / denom
+    }
+}
+
+#[inline]
+pub(crate) fn delta_nd_px(pre_a: f64, pre_b: f64, post_a: f64, post_b: f64) -> f64 {
+    normalized_difference_px(pre_a, pre_b) - normalized_difference_px(post_a, post_b)
+}
+
+#[inline]
+pub(crate) fn rbr_px(pre_nir: f64, pre_swir2: f64, post_nir: f64, post_swir2: f64) -> f64 {
+    let pre_nbr = normalized_difference_px(pre_nir, pre_swir2);
+    let post_nbr = normalized_difference_px(post_nir, post_swir2);
+    let denom = pre_nbr + 1.0;
+    if denom.abs() < EPSILON {
+        0.0
+    } else {
+        (pre_nbr - post_nbr) / denom
+    }
+}
+
+#[inline]
+pub(crate) fn awei_nsh_px(green: f64, swir1: f64, nir: f64, swir2: f64) -> f64 {
+    4.0 * (green - swir1) - (0.25 * nir + 2.75 * swir2)
+}
+
+#[inline]
+pub(crate) fn awei_sh_px(blue: f64, green: f64, nir: f64, swir1: f64, swir2: f64) -> f64 {
+    blue + 2.5 * green - 1.5 * (nir + swir1) - 0.25 * swir2
+}
+
+#[inline]
+pub(crate) fn bsi_px(swir1: f64, red: f64, nir: f64, blue: f64) -> f64 {
+    normalized_difference_px(swir1 + red, nir + blue)
+}
+
+/// kNDVI with an RBF kernel of length scale `sigma`; `None` uses the
+/// per-pixel default sigma = 0.5 * (NIR + Red), which reduces to tanh(NDVI^2).
+#[inline]
+pub(crate) fn kndvi_px(nir: f64, red: f64, sigma: Option<f64>) -> f64 {
+    let x = match sigma {
+        Some(sigma) => (nir - red) / (2.0 * sigma),
+        None => normalized_difference_px(nir, red),
+    };
+    (x * x).tanh()
+}
+
+#[inline]
+pub(crate) fn nirv_px(nir: f64, red: f64) -> f64 {
+    normalized_difference_px(nir, red) * nir
+}
+
+#[inline]
+pub(crate) fn sipi_px(nir: f64, blue: f64, red: f64) -> f64 {
+    let denom = nir - red;
+    if denom.abs() < EPSILON {
+        0.0
+    } else {
+        (nir - blue) / denom
+    }
+}
+
+#[inline]
+pub(crate) fn psri_px(red: f64, blue: f64, nir: f64) -> f64 {
+    if nir.abs() < EPSILON {
+        0.0
+    } else {
+        (red - blue) / nir
+    }
+}
+
+#[inline]
+pub(crate) fn arvi_px(nir: f64, red: f64, blue: f64) -> f64 {
+    // Atmospherically corrected red, gamma = 1.
+    normalized_difference_px(nir, 2.0 * red - blue)
+}
+
+#[inline]
+pub(crate) fn vari_px(green: f64, red: f64, blue: f64) -> f64 {
+    let denom = green + red - blue;
+    if denom.abs() < EPSILON {
+        0.0
+    } else {
+        (green - red) / denom
+    }
+}
+
+#[inline]
+pub(crate) fn tcari_osavi_px(rededge: f64, red: f64, green: f64, nir: f64) -> f64 {
+    if red.abs() < EPSILON {
+        return 0.0;
+    }
+    let tcari = 3.0 * ((rededge - red) - 0.2 * (rededge - green) * (rededge / red));
+    // Haboudane et al. use the (1 + 0.16)-scaled OSAVI in the ratio.
+    let osavi = 1.16 * osavi_px(nir, red);
+    if osavi.abs() < EPSILON {
+        0.0
+    } else {
+        tcari / osavi
     }
 }
 
@@ -94,12 +226,18 @@ pub fn normalized_difference(py: Python<'_>, a: &PyAny, b: &PyAny) -> PyResult<P
 /// This function computes (a - b) / (a + b) element-wise, handling division by zero
 /// by returning 0.0 when the denominator is zero.
 ///
+/// Inputs may have any rank; shapes are broadcast NumPy-style, so size-1 axes
+/// stretch to match the other operand. float64, float32 and uint16 arrays are
+/// read without copying; other numeric dtypes are coerced to float64.
+///
 /// # Arguments
 /// * `a` - First input array (e.g., NIR band for NDVI)
 /// * `b` - Second input array (e.g., Red band for NDVI)
+/// * `scale`, `offset` - Optional DN conversion applied to both inputs
 ///
 /// # Returns
-/// Array with the same shape as inputs containing the normalized difference values
+/// Array with the broadcast shape of the inputs containing the normalized difference values.
+/// float64 for float64 inputs, float32 for float32 / uint16 inputs.
 ///
 /// # Example (from Python)
 /// ```python
@@ -110,162 +248,23 @@ pub fn normalized_difference(py: Python<'_>, a: &PyAny, b: &PyAny) -> PyResult<P
 /// red = np.array([0.2, 0.1, 0.3])
 /// ndvi = normalized_difference(nir, red)
 /// ```
-fn normalized_difference_1d<'py>(
-    py: Python<'py>,
-    a: PyReadonlyArray1<f64>,
-    b: PyReadonlyArray1<f64>,
-) -> PyResult<&'py PyArray1<f64>> {
-    let a_arr = a.as_array();
-    let b_arr = b.as_array();
-    let mut out = Array1::<f64>::zeros(a_arr.dim());
-
-    py.allow_threads(|| {
-        Zip::from(&mut out)
-            .and(&a_arr)
-            .and(&b_arr)
-            .for_each(|r, &a, &b| {
-                let denom = a + b;
-                *r = if denom.abs() < EPSILON {
-                    0.0
-                } else {
-                    (a - b) / denom
-                };
-            });
-    });
-    Ok(out.into_pyarray(py))
-}
-
-/// Compute normalized difference between two 2D arrays.
-///
-/// This function computes (a - b) / (a + b) element-wise for 2D arrays.
-/// Division by zero yields NaN or Inf per IEEE 754.
-///
-/// # Arguments
-/// * `a` - First input 2D array (e.g., NIR band for NDVI)
-/// * `b` - Second input 2D array (e.g., Red band for NDVI)
-///
-/// # Returns
-/// 2D array with the same shape as inputs containing the normalized difference values
-///
-/// # Example (from Python)
-/// ```python
-/// import numpy as np
-/// from eo_processor import normalized_difference_2d
-///
-/// nir = np.random.rand(100, 100)
-/// red = np.random.rand(100, 100)
-/// ndvi = normalized_difference_2d(nir, red)
-/// ```
-fn normalized_difference_2d<'py>(
-    py: Python<'py>,
-    a: PyReadonlyArray2<f64>,
-    b: PyReadonlyArray2<f64>,
-) -> PyResult<&'py PyArray2<f64>> {
-    let a_arr = a.as_array();
-    let b_arr = b.as_array();
-    let mut out = Array2::<f64>::zeros(a_arr.dim());
-
-    py.allow_threads(|| {
-        Zip::from(&mut out)
-            .and(&a_arr)
-            .and(&b_arr)
-            .for_each(|r, &a, &b| {
-                let denom = a + b;
-                *r = if denom.abs() < EPSILON {
-                    0.0
-                } else {
-                    (a - b) / denom
-                };
-            });
-    });
-    Ok(out.into_pyarray(py))
-}
-
-/// Compute normalized difference between two 3D arrays.
-///
-/// This function computes (a - b) / (a + b) element-wise for 3D arrays.
-/// Division by zero yields NaN or Inf per IEEE 754.
-/// # Arguments
-/// * `a` - First input 3D array (e.g., NIR band for NDVI)
-/// * `b` - Second input 3D array (e.g., Red band for NDVI)
-/// # Returns
-/// 3D array with the same shape as inputs containing the normalized difference values
-/// # Example (from Python)
-/// ```python
-/// import numpy as np
-/// from eo_processor import normalized_difference_3d
-///
-/// nir = np.random.rand(10, 100, 100)
-/// red = np.random.rand(10, 100, 100)
-/// ndvi = normalized_difference_3d(nir, red)
-/// ```
-fn normalized_difference_3d<'py>(
-    py: Python<'py>,
-    a: PyReadonlyArray3<f64>,
-    b: PyReadonlyArray3<f64>,
-) -> PyResult<&'py PyArray3<f64>> {
-    let a_arr = a.as_array();
-    let b_arr = b.as_array();
-    let mut out = Array3::<f64>::zeros(a_arr.dim());
-
-    py.allow_threads(|| {
-        Zip::from(&mut out)
-            .and(&a_arr)
-            .and(&b_arr)
-            .for_each(|r, &a, &b| {
-                let denom = a + b;
-                *r = if denom.abs() < EPSILON {
-                    0.0
-                } else {
-                    (a - b) / denom
-                };
-            });
-    });
-    Ok(out.into_pyarray(py))
-}
-
-/// Compute normalized difference between two 4D arrays.
-///
-/// This function computes (a - b) / (a + b) element-wise for 4D arrays,
-/// handling division by zero by returning 0.0 when the denominator is zero.
-///
-/// # Arguments
-/// * `a` - First input 4D array (e.g., NIR band for NDVI)
-/// * `b` - Second input 4D array (e.g., Red band for NDVI)
-/// # Returns
-/// 4D array with the same shape as inputs containing the normalized difference values
-/// # Example (from Python)
-/// ```python
-/// import numpy as np
-/// from eo_processor import normalized_difference_4d
-///
-/// nir = np.random.rand(5, 10, 100, 100)
-/// red = np.random.rand(5, 10, 100, 100)
-/// ndvi = normalized_difference_4d(nir, red)
-/// ```
-fn normalized_difference_4d<'py>(
-    py: Python<'py>,
-    a: PyReadonlyArray4<f64>,
-    b: PyReadonlyArray4<f64>,
-) -> PyResult<&'py PyArray4<f64>> {
-    let a_arr = a.as_array();
-    let b_arr = b.as_array();
-    let mut out = Array4::<f64>::zeros(a_arr.dim());
-
-    py.allow_threads(|| {
-        Zip::from(&mut out)
-            .and(&a_arr)
-            .and(&b_arr)
-            .for_each(|r, &a, &b| {
-                let denom = a + b;
-                *r = if denom.abs() < EPSILON {
-                    0.0
-                } else {
-                    (a - b) / denom
-                };
-            });
-    });
-    Ok(out.into_pyarray(py))
+#[pyfunction]
+#[pyo3(signature = (a, b, scale=None, offset=None))]
+pub fn normalized_difference(
+    py: Python<'_>,
+    a: &PyAny,
+    b: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map2(
+        py,
+        "normalized difference",
+        a,
+        b,
+        Scaling::from_args(scale, offset)?,
+        normalized_difference_px,
+    )
 }
 
 /// Compute NDVI (Normalized Difference Vegetation Index) from NIR and Red bands.
@@ -274,15 +273,12 @@ fn normalized_difference_4d<'py>(
 ///
 /// NDVI = (NIR - Red) / (NIR + Red)
 ///
-/// This is a convenience wrapper around `normalized_difference` for both 1D and 2D arrays.
-/// It will dispatch based on the dimensionality of the provided numpy arrays.
-///
 /// # Arguments
-/// * `nir` - Near-infrared band values (1D or 2D float64 numpy array)
-/// * `red` - Red band values (same shape and type as `nir`)
+/// * `nir` - Near-infrared band values (any rank, any numeric dtype)
+/// * `red` - Red band values (shape broadcast-compatible with `nir`)
 ///
 /// # Returns
-/// NDVI values ranging from -1 to 1 with the same shape as inputs
+/// NDVI values ranging from -1 to 1 with the broadcast shape of the inputs
 ///
 /// # Example (1D)
 /// ```python
@@ -309,63 +305,40 @@ fn normalized_difference_4d<'py>(
 /// print(ndvi_vals.shape)  # (2, 2)
 /// ```
 #[pyfunction]
-pub fn ndvi(py: Python<'_>, nir: &PyAny, red: &PyAny) -> PyResult<PyObject> {
-    normalized_difference(py, nir, red)
+#[pyo3(signature = (nir, red, scale=None, offset=None))]
+pub fn ndvi(
+    py: Python<'_>,
+    nir: &PyAny,
+    red: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    normalized_difference(py, nir, red, scale, offset)
 }
 
 /// Delta NDVI (pre - post) for change detection.
-/// Accepts any numeric dtype; coerces to float64.
+/// Accepts any numeric dtype; float32 / uint16 inputs yield float32.
 #[pyfunction]
+#[pyo3(signature = (pre_nir, pre_red, post_nir, post_red, scale=None, offset=None))]
 pub fn delta_ndvi(
     py: Python<'_>,
     pre_nir: &PyAny,
     pre_red: &PyAny,
     post_nir: &PyAny,
     post_red: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
 ) -> PyResult<PyObject> {
-    // Use normalized_difference for coercion & computation
-    let pre = normalized_difference(py, pre_nir, pre_red)?;
-    let post = normalized_difference(py, post_nir, post_red)?;
-    // Attempt 1D extract first, else 2D
-    if let Ok(pre1) = pre.extract::<&PyArray1<f64>>(py) {
-        let post1 = post.extract::<&PyArray1<f64>>(py)?;
-        if pre1.len() != post1.len() {
-            return Err(CoreError::InvalidArgument(
-                "Shape mismatch in delta_ndvi (1D)".to_string(),
-            )
-            .into());
-        }
-        let pre_view = pre1.readonly();
-        let post_view = post1.readonly();
-        let a = pre_view.as_array();
-        let b = post_view.as_array();
-        let mut out = Array1::<f64>::zeros(a.len());
-        Zip::from(&mut out)
-            .and(a)
-            .and(b)
-            .for_each(|r, &p, &q| *r = p - q);
-        Ok(out.into_pyarray(py).into_py(py))
-    } else {
-        let pre2 = pre.extract::<&PyArray2<f64>>(py)?;
-        let post2 = post.extract::<&PyArray2<f64>>(py)?;
-        if pre2.shape() != post2.shape() {
-            return Err(CoreError::InvalidArgument(
-                "Shape mismatch in delta_ndvi (2D)".to_string(),
-            )
-            .into());
-        }
-        let pre_view = pre2.readonly();
-        let post_view = post2.readonly();
-        let a = pre_view.as_array();
-        let b = post_view.as_array();
-        let shape = a.dim();
-        let mut out = Array2::<f64>::zeros(shape);
-        Zip::from(&mut out)
-            .and(a)
-            .and(b)
-            .for_each(|r, &p, &q| *r = p - q);
-        Ok(out.into_pyarray(py).into_py(py))
-    }
+    py_map4(
+        py,
+        "delta_ndvi",
+        pre_nir,
+        pre_red,
+        post_nir,
+        post_red,
+        Scaling::from_args(scale, offset)?,
+        delta_nd_px,
+    )
 }
 
 /// Compute NDWI (Normalized Difference Water Index) from Green and NIR bands.
@@ -374,14 +347,12 @@ pub fn delta_ndvi(
 ///
 /// NDWI = (Green - NIR) / (Green + NIR)
 ///
-/// Dispatches for 1D and 2D arrays automatically.
-///
 /// # Arguments
-/// * `green` - Green band values (1D or 2D float64 numpy array)
-/// * `nir` - Near-infrared band values (same shape and type as `green`)
+/// * `green` - Green band values (any rank, any numeric dtype)
+/// * `nir` - Near-infrared band values (shape broadcast-compatible with `green`)
 ///
 /// # Returns
-/// NDWI values ranging from -1 to 1 with the same shape as inputs
+/// NDWI values ranging from -1 to 1 with the broadcast shape of the inputs
 ///
 /// # Example (1D)
 /// ```python
@@ -407,8 +378,15 @@ pub fn delta_ndvi(
 /// print(ndwi_vals.shape)  # (2, 2)
 /// ```
 #[pyfunction]
-pub fn ndwi(py: Python<'_>, green: &PyAny, nir: &PyAny) -> PyResult<PyObject> {
-    normalized_difference(py, green, nir)
+#[pyo3(signature = (green, nir, scale=None, offset=None))]
+pub fn ndwi(
+    py: Python<'_>,
+    green: &PyAny,
+    nir: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    normalized_difference(py, green, nir, scale, offset)
 }
 
 /// Compute NDSI (Normalized Difference Snow Index) from Green and SWIR1 bands.
@@ -417,8 +395,15 @@ pub fn ndwi(py: Python<'_>, green: &PyAny, nir: &PyAny) -> PyResult<PyObject> {
 ///
 /// NDSI = (Green - SWIR1) / (Green + SWIR1)
 #[pyfunction]
-pub fn ndsi(py: Python<'_>, green: &PyAny, swir1: &PyAny) -> PyResult<PyObject> {
-    normalized_difference(py, green, swir1)
+#[pyo3(signature = (green, swir1, scale=None, offset=None))]
+pub fn ndsi(
+    py: Python<'_>,
+    green: &PyAny,
+    swir1: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    normalized_difference(py, green, swir1, scale, offset)
 }
 
 // Normalized Burn Ratio (NBR)
@@ -426,58 +411,39 @@ pub fn ndsi(py: Python<'_>, green: &PyAny, swir1: &PyAny) -> PyResult<PyObject>
 // Implemented as a thin wrapper around normalized_difference.
 //
 #[pyfunction]
-pub fn nbr(py: Python<'_>, nir: &PyAny, swir2: &PyAny) -> PyResult<PyObject> {
-    normalized_difference(py, nir, swir2)
+#[pyo3(signature = (nir, swir2, scale=None, offset=None))]
+pub fn nbr(
+    py: Python<'_>,
+    nir: &PyAny,
+    swir2: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    normalized_difference(py, nir, swir2, scale, offset)
 }
 
 /// Delta NBR (pre - post) for burn severity change detection.
 #[pyfunction]
+#[pyo3(signature = (pre_nir, pre_swir2, post_nir, post_swir2, scale=None, offset=None))]
 pub fn delta_nbr(
     py: Python<'_>,
     pre_nir: &PyAny,
     pre_swir2: &PyAny,
     post_nir: &PyAny,
     post_swir2: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
 ) -> PyResult<PyObject> {
-    let pre = normalized_difference(py, pre_nir, pre_swir2)?;
-    let post = normalized_difference(py, post_nir, post_swir2)?;
-    if let Ok(pre1) = pre.extract::<&PyArray1<f64>>(py) {
-        let post1 = post.extract::<&PyArray1<f64>>(py)?;
-        if pre1.len() != post1.len() {
-            return Err(
-                CoreError::InvalidArgument("Shape mismatch in delta_nbr (1D)".to_string()).into(),
-            );
-        }
-        let pre_view = pre1.readonly();
-        let post_view = post1.readonly();
-        let a = pre_view.as_array();
-        let b = post_view.as_array();
-        let mut out = Array1::<f64>::zeros(a.len());
-        Zip::from(&mut out)
-            .and(a)
-            .and(b)
-            .for_each(|r, &p, &q| *r = p - q);
-        Ok(out.into_pyarray(py).into_py(py))
-    } else {
-        let pre2 = pre.extract::<&PyArray2<f64>>(py)?;
-        let post2 = post.extract::<&PyArray2<f64>>(py)?;
-        if pre2.shape() != post2.shape() {
-            return Err(
-                CoreError::InvalidArgument("Shape mismatch in delta_nbr (2D)".to_string()).into(),
-            );
-        }
-        let pre_view = pre2.readonly();
-        let post_view = post2.readonly();
-        let a = pre_view.as_array();
-        let b = post_view.as_array();
-        let shape = a.dim();
-        let mut out = Array2::<f64>::zeros(shape);
-        Zip::from(&mut out)
-            .and(a)
-            .and(b)
-            .for_each(|r, &p, &q| *r = p - q);
-        Ok(out.into_pyarray(py).into_py(py))
-    }
+    py_map4(
+        py,
+        "delta_nbr",
+        pre_nir,
+        pre_swir2,
+        post_nir,
+        post_swir2,
+        Scaling::from_args(scale, offset)?,
+        delta_nd_px,
+    )
 }
 
 //
@@ -486,167 +452,29 @@ pub fn delta_nbr(
 // Default L = 0.5 but user may supply alternative (0 <= L <= 1 usually).
 // Zero-denominator safeguard using EPSILON similar to other indices.
 //
-#[pyfunction(signature = (nir, red, l=0.5))]
-pub fn savi(py: Python<'_>, nir: &PyAny, red: &PyAny, l: f64) -> PyResult<PyObject> {
+#[pyfunction]
+#[pyo3(signature = (nir, red, l=0.5, scale=None, offset=None))]
+pub fn savi(
+    py: Python<'_>,
+    nir: &PyAny,
+    red: &PyAny,
+    l: f64,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
     if l < 0.0 {
         return Err(
             CoreError::InvalidArgument(format!("SAVI L must be non-negative, got {}", l)).into(),
         );
     }
-    if let Ok(nir_1d) = nir.extract::<PyReadonlyArray1<f64>>() {
-        let red_1d = red.extract::<PyReadonlyArray1<f64>>()?;
-        if nir_1d.shape() != red_1d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 1D SAVI inputs: nir {:?}, red {:?}",
-                nir_1d.shape(),
-                red_1d.shape()
-            ))
-            .into());
-        }
-        savi_1d(py, nir_1d, red_1d, l).map(|res| res.into_py(py))
-    } else if let Ok(nir_2d) = nir.extract::<PyReadonlyArray2<f64>>() {
-        let red_2d = red.extract::<PyReadonlyArray2<f64>>()?;
-        if nir_2d.shape() != red_2d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 2D SAVI inputs: nir {:?}, red {:?}",
-                nir_2d.shape(),
-                red_2d.shape()
-            ))
-            .into());
-        }
-        savi_2d(py, nir_2d, red_2d, l).map(|res| res.into_py(py))
-    } else if let Ok(nir_3d) = nir.extract::<PyReadonlyArray3<f64>>() {
-        let red_3d = red.extract::<PyReadonlyArray3<f64>>()?;
-        if nir_3d.shape() != red_3d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 3D SAVI inputs: nir {:?}, red {:?}",
-                nir_3d.shape(),
-                red_3d.shape()
-            ))
-            .into());
-        }
-        savi_3d(py, nir_3d, red_3d, l).map(|res| res.into_py(py))
-    } else if let Ok(nir_4d) = nir.extract::<PyReadonlyArray4<f64>>() {
-        let red_4d = red.extract::<PyReadonlyArray4<f64>>()?;
-        if nir_4d.shape() != red_4d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 4D SAVI inputs: nir {:?}, red {:?}",
-                nir_4d.shape(),
-                red_4d.shape()
-            ))
-            .into());
-        }
-        savi_4d(py, nir_4d, red_4d, l).map(|res| res.into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument(
-            "Input arrays must be either 1D, 2D, 3D, or 4D numpy arrays of type float64 for SAVI."
-                .to_string(),
-        )
-        .into())
-    }
-}
-
-fn savi_1d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray1<f64>,
-    red: PyReadonlyArray1<f64>,
-    l: f64,
-) -> PyResult<&'py PyArray1<f64>> {
-    let nir = nir.as_array();
-    let red = red.as_array();
-    let mut result = Array1::<f64>::zeros(nir.len());
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let denom = nir_v + red_v + l;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - red_v) / denom * (1.0 + l)
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn savi_2d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray2<f64>,
-    red: PyReadonlyArray2<f64>,
-    l: f64,
-) -> PyResult<&'py PyArray2<f64>> {
-    let nir = nir.as_array();
-    let red = red.as_array();
-    let shape = nir.dim();
-    let mut result = Array2::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let denom = nir_v + red_v + l;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - red_v) / denom * (1.0 + l)
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn savi_3d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray3<f64>,
-    red: PyReadonlyArray3<f64>,
-    l: f64,
-) -> PyResult<&'py PyArray3<f64>> {
-    let nir = nir.as_array();
-    let red = red.as_array();
-    let shape = nir.dim();
-    let mut result = ndarray::Array3::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let denom = nir_v + red_v + l;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - red_v) / denom * (1.0 + l)
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn savi_4d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray4<f64>,
-    red: PyReadonlyArray4<f64>,
-    l: f64,
-) -> PyResult<&'py PyArray4<f64>> {
-    let nir = nir.as_array();
-    let red = red.as_array();
-    let shape = nir.dim();
-    let mut result = ndarray::Array4::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let denom = nir_v + red_v + l;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - red_v) / denom * (1.0 + l)
-            };
-        });
-
-    Ok(result.into_pyarray(py))
+    py_map2(
+        py,
+        "SAVI",
+        nir,
+        red,
+        Scaling::from_args(scale, offset)?,
+        move |n, r| savi_px(n, r, l),
+    )
 }
 
 //
@@ -657,122 +485,46 @@ fn savi_4d<'py>(
 //
 
 #[pyfunction]
-pub fn ndmi(py: Python<'_>, nir: &PyAny, swir1: &PyAny) -> PyResult<PyObject> {
-    normalized_difference(py, nir, swir1)
+#[pyo3(signature = (nir, swir1, scale=None, offset=None))]
+pub fn ndmi(
+    py: Python<'_>,
+    nir: &PyAny,
+    swir1: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    normalized_difference(py, nir, swir1, scale, offset)
 }
 
 #[pyfunction]
-pub fn nbr2(py: Python<'_>, swir1: &PyAny, swir2: &PyAny) -> PyResult<PyObject> {
-    normalized_difference(py, swir1, swir2)
+#[pyo3(signature = (swir1, swir2, scale=None, offset=None))]
+pub fn nbr2(
+    py: Python<'_>,
+    swir1: &PyAny,
+    swir2: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    normalized_difference(py, swir1, swir2, scale, offset)
 }
 
 #[pyfunction]
-pub fn gci(py: Python<'_>, nir: &PyAny, green: &PyAny) -> PyResult<PyObject> {
-    if let Ok(nir_1d) = nir.extract::<PyReadonlyArray1<f64>>() {
-        let green_1d = green.extract::<PyReadonlyArray1<f64>>()?;
-        if nir_1d.shape() != green_1d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 1D GCI inputs: nir {:?}, green {:?}",
-                nir_1d.shape(),
-                green_1d.shape()
-            ))
-            .into());
-        }
-        let nir_arr = nir_1d.as_array();
-        let green_arr = green_1d.as_array();
-        let mut out = Array1::<f64>::zeros(nir_arr.len());
-        Zip::from(&mut out)
-            .and(&nir_arr)
-            .and(&green_arr)
-            .for_each(|r, &n, &g| {
-                *r = if g.abs() < EPSILON {
-                    0.0
-                } else {
-                    (n / g) - 1.0
-                };
-            });
-        Ok(out.into_pyarray(py).into_py(py))
-    } else if let Ok(nir_2d) = nir.extract::<PyReadonlyArray2<f64>>() {
-        let green_2d = green.extract::<PyReadonlyArray2<f64>>()?;
-        if nir_2d.shape() != green_2d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 2D GCI inputs: nir {:?}, green {:?}",
-                nir_2d.shape(),
-                green_2d.shape()
-            ))
-            .into());
-        }
-        let nir_arr = nir_2d.as_array();
-        let green_arr = green_2d.as_array();
-        let shape = nir_arr.dim();
-        let mut out = Array2::<f64>::zeros(shape);
-        Zip::from(&mut out)
-            .and(&nir_arr)
-            .and(&green_arr)
-            .for_each(|r, &n, &g| {
-                *r = if g.abs() < EPSILON {
-                    0.0
-                } else {
-                    (n / g) - 1.0
-                };
-            });
-        Ok(out.into_pyarray(py).into_py(py))
-    } else if let Ok(nir_3d) = nir.extract::<PyReadonlyArray3<f64>>() {
-        let green_3d = green.extract::<PyReadonlyArray3<f64>>()?;
-        if nir_3d.shape() != green_3d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 3D GCI inputs: nir {:?}, green {:?}",
-                nir_3d.shape(),
-                green_3d.shape()
-            ))
-            .into());
-        }
-        let nir_arr = nir_3d.as_array();
-        let green_arr = green_3d.as_array();
-        let shape = nir_arr.dim();
-        let mut out = ndarray::Array3::<f64>::zeros(shape);
-        Zip::from(&mut out)
-            .and(&nir_arr)
-            .and(&green_arr)
-            .for_each(|r, &n, &g| {
-                *r = if g.abs() < EPSILON {
-                    0.0
-                } else {
-                    (n / g) - 1.0
-                };
-            });
-        Ok(out.into_pyarray(py).into_py(py))
-    } else if let Ok(nir_4d) = nir.extract::<PyReadonlyArray4<f64>>() {
-        let green_4d = green.extract::<PyReadonlyArray4<f64>>()?;
-        if nir_4d.shape() != green_4d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 4D GCI inputs: nir {:?}, green {:?}",
-                nir_4d.shape(),
-                green_4d.shape()
-            ))
-            .into());
-        }
-        let nir_arr = nir_4d.as_array();
-        let green_arr = green_4d.as_array();
-        let shape = nir_arr.dim();
-        let mut out = ndarray::Array4::<f64>::zeros(shape);
-        Zip::from(&mut out)
-            .and(&nir_arr)
-            .and(&green_arr)
-            .for_each(|r, &n, &g| {
-                *r = if g.abs() < EPSILON {
-                    0.0
-                } else {
-                    (n / g) - 1.0
-                };
-            });
-        Ok(out.into_pyarray(py).into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument(
-            "Input arrays must be 1D, 2D, 3D, or 4D numpy float64 arrays for GCI.".to_string(),
-        )
-        .into())
-    }
+#[pyo3(signature = (nir, green, scale=None, offset=None))]
+pub fn gci(
+    py: Python<'_>,
+    nir: &PyAny,
+    green: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map2(
+        py,
+        "GCI",
+        nir,
+        green,
+        Scaling::from_args(scale, offset)?,
+        ratio_minus_one_px,
+    )
 }
 
 /// Compute Enhanced Vegetation Index (EVI).
@@ -783,17 +535,15 @@ pub fn gci(py: Python<'_>, nir: &PyAny, green: &PyAny) -> PyResult<PyObject> {
 /// Constants (MODIS standard):
 /// G = 2.5, C1 = 6.0, C2 = 7.5, L = 1.0
 ///
-/// Automatically dispatches for 1D or 2D float64 numpy arrays.
-///
 /// # Arguments
 /// * `nir`  - Near-infrared band values
 /// * `red`  - Red band values
 /// * `blue` - Blue band values
 ///
-/// All three inputs must be the same shape & type (1D or 2D float64).
+/// Inputs may have any rank; shapes must be broadcast-compatible.
 ///
 /// # Returns
-/// EVI values (same shape as input).
+/// EVI values with the broadcast shape of the inputs.
 ///
 /// # Example (1D)
 /// ```python
@@ -822,208 +572,24 @@ pub fn gci(py: Python<'_>, nir: &PyAny, green: &PyAny) -> PyResult<PyObject> {
 /// print(evi_vals.shape)  # (2, 2)
 /// ```
 #[pyfunction]
+#[pyo3(signature = (nir, red, blue, scale=None, offset=None))]
 pub fn enhanced_vegetation_index(
     py: Python<'_>,
     nir: &PyAny,
     red: &PyAny,
     blue: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
 ) -> PyResult<PyObject> {
-    if let Ok(nir_1d) = nir.extract::<PyReadonlyArray1<f64>>() {
-        let red_1d = red.extract::<PyReadonlyArray1<f64>>()?;
-        let blue_1d = blue.extract::<PyReadonlyArray1<f64>>()?;
-        if nir_1d.shape() != red_1d.shape() || nir_1d.shape() != blue_1d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 1D EVI inputs: nir {:?}, red {:?}, blue {:?}",
-                nir_1d.shape(),
-                red_1d.shape(),
-                blue_1d.shape()
-            ))
-            .into());
-        }
-        enhanced_vegetation_index_1d(py, nir_1d, red_1d, blue_1d).map(|res| res.into_py(py))
-    } else if let Ok(nir_2d) = nir.extract::<PyReadonlyArray2<f64>>() {
-        let red_2d = red.extract::<PyReadonlyArray2<f64>>()?;
-        let blue_2d = blue.extract::<PyReadonlyArray2<f64>>()?;
-        if nir_2d.shape() != red_2d.shape() || nir_2d.shape() != blue_2d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 2D EVI inputs: nir {:?}, red {:?}, blue {:?}",
-                nir_2d.shape(),
-                red_2d.shape(),
-                blue_2d.shape()
-            ))
-            .into());
-        }
-        enhanced_vegetation_index_2d(py, nir_2d, red_2d, blue_2d).map(|res| res.into_py(py))
-    } else if let Ok(nir_3d) = nir.extract::<PyReadonlyArray3<f64>>() {
-        let red_3d = red.extract::<PyReadonlyArray3<f64>>()?;
-        let blue_3d = blue.extract::<PyReadonlyArray3<f64>>()?;
-        if nir_3d.shape() != red_3d.shape() || nir_3d.shape() != blue_3d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 3D EVI inputs: nir {:?}, red {:?}, blue {:?}",
-                nir_3d.shape(),
-                red_3d.shape(),
-                blue_3d.shape()
-            ))
-            .into());
-        }
-        enhanced_vegetation_index_3d(py, nir_3d, red_3d, blue_3d).map(|res| res.into_py(py))
-    } else if let Ok(nir_4d) = nir.extract::<PyReadonlyArray4<f64>>() {
-        let red_4d = red.extract::<PyReadonlyArray4<f64>>()?;
-        let blue_4d = blue.extract::<PyReadonlyArray4<f64>>()?;
-        if nir_4d.shape() != red_4d.shape() || nir_4d.shape() != blue_4d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 4D EVI inputs: nir {:?}, red {:?}, blue {:?}",
-                nir_4d.shape(),
-                red_4d.shape(),
-                blue_4d.shape()
-            ))
-            .into());
-        }
-        enhanced_vegetation_index_4d(py, nir_4d, red_4d, blue_4d).map(|res| res.into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument(
-            "Input arrays must be either 1D, 2D, 3D, or 4D numpy arrays of type float64."
-                .to_string(),
-        )
-        .into())
-    }
-}
-
-// 1D Enhanced Vegetation Index helper
-fn enhanced_vegetation_index_1d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray1<f64>,
-    red: PyReadonlyArray1<f64>,
-    blue: PyReadonlyArray1<f64>,
-) -> PyResult<&'py PyArray1<f64>> {
-    const G: f64 = 2.5;
-    const C1: f64 = 6.0;
-    const C2: f64 = 7.5;
-    const L: f64 = 1.0;
-
-    let nir = nir.as_array();
-    let red = red.as_array();
-    let blue = blue.as_array();
-
-    let mut result = Array1::<f64>::zeros(nir.len());
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .and(&blue)
-        .for_each(|r, &nir_v, &red_v, &blue_v| {
-            let denom = nir_v + C1 * red_v - C2 * blue_v + L;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                G * (nir_v - red_v) / denom
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-// 2D Enhanced Vegetation Index helper
-fn enhanced_vegetation_index_2d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray2<f64>,
-    red: PyReadonlyArray2<f64>,
-    blue: PyReadonlyArray2<f64>,
-) -> PyResult<&'py PyArray2<f64>> {
-    const G: f64 = 2.5;
-    const C1: f64 = 6.0;
-    const C2: f64 = 7.5;
-    const L: f64 = 1.0;
-
-    let nir = nir.as_array();
-    let red = red.as_array();
-    let blue = blue.as_array();
-
-    let shape = nir.dim();
-    let mut result = Array2::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .and(&blue)
-        .for_each(|r, &nir_v, &red_v, &blue_v| {
-            let denom = nir_v + C1 * red_v - C2 * blue_v + L;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                G * (nir_v - red_v) / denom
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn enhanced_vegetation_index_3d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray3<f64>,
-    red: PyReadonlyArray3<f64>,
-    blue: PyReadonlyArray3<f64>,
-) -> PyResult<&'py PyArray3<f64>> {
-    const G: f64 = 2.5;
-    const C1: f64 = 6.0;
-    const C2: f64 = 7.5;
-    const L: f64 = 1.0;
-
-    let nir = nir.as_array();
-    let red = red.as_array();
-    let blue = blue.as_array();
-
-    let shape = nir.dim();
-    let mut result = ndarray::Array3::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .and(&blue)
-        .for_each(|r, &nir_v, &red_v, &blue_v| {
-            let denom = nir_v + C1 * red_v - C2 * blue_v + L;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                G * (nir_v - red_v) / denom
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn enhanced_vegetation_index_4d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray4<f64>,
-    red: PyReadonlyArray4<f64>,
-    blue: PyReadonlyArray4<f64>,
-) -> PyResult<&'py PyArray4<f64>> {
-    const G: f64 = 2.5;
-    const C1: f64 = 6.0;
-    const C2: f64 = 7.5;
-    const L: f64 = 1.0;
-
-    let nir = nir.as_array();
-    let red = red.as_array();
-    let blue = blue.as_array();
-
-    let shape = nir.dim();
-    let mut result = ndarray::Array4::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .and(&blue)
-        .for_each(|r, &nir_v, &red_v, &blue_v| {
-            let denom = nir_v + C1 * red_v - C2 * blue_v + L;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                G * (nir_v - red_v) / denom
-            };
-        });
-
-    Ok(result.into_pyarray(py))
+    py_map3(
+        py,
+        "EVI",
+        nir,
+        red,
+        blue,
+        Scaling::from_args(scale, offset)?,
+        evi_px,
+    )
 }
 
 /// Compute Enhanced Vegetation Index 2-band variant (EVI2).
@@ -1034,171 +600,24 @@ fn enhanced_vegetation_index_4d<'py>(
 /// Constants:
 /// G = 2.5, C1 = 2.4, L = 1.0
 ///
-/// Dispatches for 1D–4D float64 numpy arrays; `nir` and `red` must have identical shapes.
+/// Accepts arrays of any rank; `nir` and `red` must have broadcast-compatible shapes.
 #[pyfunction]
-pub fn evi2(py: Python<'_>, nir: &PyAny, red: &PyAny) -> PyResult<PyObject> {
-    if let Ok(nir_1d) = nir.extract::<PyReadonlyArray1<f64>>() {
-        let red_1d = red.extract::<PyReadonlyArray1<f64>>()?;
-        if nir_1d.shape() != red_1d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 1D EVI2 inputs: nir {:?}, red {:?}",
-                nir_1d.shape(),
-                red_1d.shape()
-            ))
-            .into());
-        }
-        evi2_1d(py, nir_1d, red_1d).map(|res| res.into_py(py))
-    } else if let Ok(nir_2d) = nir.extract::<PyReadonlyArray2<f64>>() {
-        let red_2d = red.extract::<PyReadonlyArray2<f64>>()?;
-        if nir_2d.shape() != red_2d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 2D EVI2 inputs: nir {:?}, red {:?}",
-                nir_2d.shape(),
-                red_2d.shape()
-            ))
-            .into());
-        }
-        evi2_2d(py, nir_2d, red_2d).map(|res| res.into_py(py))
-    } else if let Ok(nir_3d) = nir.extract::<PyReadonlyArray3<f64>>() {
-        let red_3d = red.extract::<PyReadonlyArray3<f64>>()?;
-        if nir_3d.shape() != red_3d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 3D EVI2 inputs: nir {:?}, red {:?}",
-                nir_3d.shape(),
-                red_3d.shape()
-            ))
-            .into());
-        }
-        evi2_3d(py, nir_3d, red_3d).map(|res| res.into_py(py))
-    } else if let Ok(nir_4d) = nir.extract::<PyReadonlyArray4<f64>>() {
-        let red_4d = red.extract::<PyReadonlyArray4<f64>>()?;
-        if nir_4d.shape() != red_4d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 4D EVI2 inputs: nir {:?}, red {:?}",
-                nir_4d.shape(),
-                red_4d.shape()
-            ))
-            .into());
-        }
-        evi2_4d(py, nir_4d, red_4d).map(|res| res.into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument(
-            "Input arrays must be either 1D, 2D, 3D, or 4D numpy arrays of type float64."
-                .to_string(),
-        )
-        .into())
-    }
-}
-
-fn evi2_1d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray1<f64>,
-    red: PyReadonlyArray1<f64>,
-) -> PyResult<&'py PyArray1<f64>> {
-    const G: f64 = 2.5;
-    const C1: f64 = 2.4;
-    const L: f64 = 1.0;
-
-    let nir = nir.as_array();
-    let red = red.as_array();
-
-    let mut result = Array1::<f64>::zeros(nir.len());
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let denom = nir_v + C1 * red_v + L;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                G * (nir_v - red_v) / denom
-            };
-        });
-    Ok(result.into_pyarray(py))
-}
-
-fn evi2_2d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray2<f64>,
-    red: PyReadonlyArray2<f64>,
-) -> PyResult<&'py PyArray2<f64>> {
-    const G: f64 = 2.5;
-    const C1: f64 = 2.4;
-    const L: f64 = 1.0;
-
-    let nir = nir.as_array();
-    let red = red.as_array();
-
-    let shape = nir.dim();
-    let mut result = Array2::<f64>::zeros(shape);
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let denom = nir_v + C1 * red_v + L;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                G * (nir_v - red_v) / denom
-            };
-        });
-    Ok(result.into_pyarray(py))
-}
-
-fn evi2_3d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray3<f64>,
-    red: PyReadonlyArray3<f64>,
-) -> PyResult<&'py PyArray3<f64>> {
-    const G: f64 = 2.5;
-    const C1: f64 = 2.4;
-    const L: f64 = 1.0;
-
-    let nir = nir.as_array();
-    let red = red.as_array();
-
-    let shape = nir.dim();
-    let mut result = ndarray::Array3::<f64>::zeros(shape);
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let denom = nir_v + C1 * red_v + L;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                G * (nir_v - red_v) / denom
-            };
-        });
-    Ok(result.into_pyarray(py))
-}
-
-fn evi2_4d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray4<f64>,
-    red: PyReadonlyArray4<f64>,
-) -> PyResult<&'py PyArray4<f64>> {
-    const G: f64 = 2.5;
-    const C1: f64 = 2.4;
-    const L: f64 = 1.0;
-
-    let nir = nir.as_array();
-    let red = red.as_array();
-
-    let shape = nir.dim();
-    let mut result = ndarray::Array4::<f64>::zeros(shape);
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let denom = nir_v + C1 * red_v + L;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                G * (nir_v - red_v) / denom
-            };
-        });
-    Ok(result.into_pyarray(py))
+#[pyo3(signature = (nir, red, scale=None, offset=None))]
+pub fn evi2(
+    py: Python<'_>,
+    nir: &PyAny,
+    red: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map2(
+        py,
+        "EVI2",
+        nir,
+        red,
+        Scaling::from_args(scale, offset)?,
+        evi2_px,
+    )
 }
 
 /// Compute Optimized Soil Adjusted Vegetation Index (OSAVI).
@@ -1209,171 +628,24 @@ fn evi2_4d<'py>(
 /// OSAVI is similar to SAVI but uses a fixed L=0.16, optimized for
 /// environments with moderate vegetation cover.
 ///
-/// Dispatches for 1D–4D float64 numpy arrays; `nir` and `red` must have identical shapes.
+/// Accepts arrays of any rank; `nir` and `red` must have broadcast-compatible shapes.
 #[pyfunction]
-pub fn osavi(py: Python<'_>, nir: &PyAny, red: &PyAny) -> PyResult<PyObject> {
-    if let Ok(nir_1d) = nir.extract::<PyReadonlyArray1<f64>>() {
-        let red_1d = red.extract::<PyReadonlyArray1<f64>>()?;
-        if nir_1d.shape() != red_1d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 1D OSAVI inputs: nir {:?}, red {:?}",
-                nir_1d.shape(),
-                red_1d.shape()
-            ))
-            .into());
-        }
-        osavi_1d(py, nir_1d, red_1d).map(|res| res.into_py(py))
-    } else if let Ok(nir_2d) = nir.extract::<PyReadonlyArray2<f64>>() {
-        let red_2d = red.extract::<PyReadonlyArray2<f64>>()?;
-        if nir_2d.shape() != red_2d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 2D OSAVI inputs: nir {:?}, red {:?}",
-                nir_2d.shape(),
-                red_2d.shape()
-            ))
-            .into());
-        }
-        osavi_2d(py, nir_2d, red_2d).map(|res| res.into_py(py))
-    } else if let Ok(nir_3d) = nir.extract::<PyReadonlyArray3<f64>>() {
-        let red_3d = red.extract::<PyReadonlyArray3<f64>>()?;
-        if nir_3d.shape() != red_3d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 3D OSAVI inputs: nir {:?}, red {:?}",
-                nir_3d.shape(),
-                red_3d.shape()
-            ))
-            .into());
-        }
-        osavi_3d(py, nir_3d, red_3d).map(|res| res.into_py(py))
-    } else if let Ok(nir_4d) = nir.extract::<PyReadonlyArray4<f64>>() {
-        let red_4d = red.extract::<PyReadonlyArray4<f64>>()?;
-        if nir_4d.shape() != red_4d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 4D OSAVI inputs: nir {:?}, red {:?}",
-                nir_4d.shape(),
-                red_4d.shape()
-            ))
-            .into());
-        }
-        osavi_4d(py, nir_4d, red_4d).map(|res| res.into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument(
-            "Input arrays must be either 1D, 2D, 3D, or 4D numpy arrays of type float64."
-                .to_string(),
-        )
-        .into())
-    }
-}
-
-fn osavi_1d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray1<f64>,
-    red: PyReadonlyArray1<f64>,
-) -> PyResult<&'py PyArray1<f64>> {
-    const L: f64 = 0.16;
-
-    let nir = nir.as_array();
-    let red = red.as_array();
-
-    let mut result = Array1::<f64>::zeros(nir.len());
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let denom = nir_v + red_v + L;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - red_v) / denom
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn osavi_2d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray2<f64>,
-    red: PyReadonlyArray2<f64>,
-) -> PyResult<&'py PyArray2<f64>> {
-    const L: f64 = 0.16;
-
-    let nir = nir.as_array();
-    let red = red.as_array();
-
-    let shape = nir.dim();
-    let mut result = Array2::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let denom = nir_v + red_v + L;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - red_v) / denom
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn osavi_3d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray3<f64>,
-    red: PyReadonlyArray3<f64>,
-) -> PyResult<&'py PyArray3<f64>> {
-    const L: f64 = 0.16;
-
-    let nir = nir.as_array();
-    let red = red.as_array();
-
-    let shape = nir.dim();
-    let mut result = Array3::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let denom = nir_v + red_v + L;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - red_v) / denom
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn osavi_4d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray4<f64>,
-    red: PyReadonlyArray4<f64>,
-) -> PyResult<&'py PyArray4<f64>> {
-    const L: f64 = 0.16;
-
-    let nir = nir.as_array();
-    let red = red.as_array();
-
-    let shape = nir.dim();
-    let mut result = Array4::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let denom = nir_v + red_v + L;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - red_v) / denom
-            };
-        });
-
-    Ok(result.into_pyarray(py))
+#[pyo3(signature = (nir, red, scale=None, offset=None))]
+pub fn osavi(
+    py: Python<'_>,
+    nir: &PyAny,
+    red: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map2(
+        py,
+        "OSAVI",
+        nir,
+        red,
+        Scaling::from_args(scale, offset)?,
+        osavi_px,
+    )
 }
 
 /// Compute Modified Soil Adjusted Vegetation Index (MSAVI).
@@ -1384,167 +656,24 @@ fn osavi_4d<'py>(
 /// MSAVI is a modification of SAVI that avoids the need for the L parameter
 /// by using a self-adjustment mechanism.
 ///
-/// Dispatches for 1D–4D float64 numpy arrays; `nir` and `red` must have identical shapes.
+/// Accepts arrays of any rank; `nir` and `red` must have broadcast-compatible shapes.
 #[pyfunction]
-pub fn msavi(py: Python<'_>, nir: &PyAny, red: &PyAny) -> PyResult<PyObject> {
-    if let Ok(nir_1d) = nir.extract::<PyReadonlyArray1<f64>>() {
-        let red_1d = red.extract::<PyReadonlyArray1<f64>>()?;
-        if nir_1d.shape() != red_1d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 1D MSAVI inputs: nir {:?}, red {:?}",
-                nir_1d.shape(),
-                red_1d.shape()
-            ))
-            .into());
-        }
-        msavi_1d(py, nir_1d, red_1d).map(|res| res.into_py(py))
-    } else if let Ok(nir_2d) = nir.extract::<PyReadonlyArray2<f64>>() {
-        let red_2d = red.extract::<PyReadonlyArray2<f64>>()?;
-        if nir_2d.shape() != red_2d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 2D MSAVI inputs: nir {:?}, red {:?}",
-                nir_2d.shape(),
-                red_2d.shape()
-            ))
-            .into());
-        }
-        msavi_2d(py, nir_2d, red_2d).map(|res| res.into_py(py))
-    } else if let Ok(nir_3d) = nir.extract::<PyReadonlyArray3<f64>>() {
-        let red_3d = red.extract::<PyReadonlyArray3<f64>>()?;
-        if nir_3d.shape() != red_3d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 3D MSAVI inputs: nir {:?}, red {:?}",
-                nir_3d.shape(),
-                red_3d.shape()
-            ))
-            .into());
-        }
-        msavi_3d(py, nir_3d, red_3d).map(|res| res.into_py(py))
-    } else if let Ok(nir_4d) = nir.extract::<PyReadonlyArray4<f64>>() {
-        let red_4d = red.extract::<PyReadonlyArray4<f64>>()?;
-        if nir_4d.shape() != red_4d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 4D MSAVI inputs: nir {:?}, red {:?}",
-                nir_4d.shape(),
-                red_4d.shape()
-            ))
-            .into());
-        }
-        msavi_4d(py, nir_4d, red_4d).map(|res| res.into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument(
-            "Input arrays must be either 1D, 2D, 3D, or 4D numpy arrays of type float64."
-                .to_string(),
-        )
-        .into())
-    }
-}
-
-fn msavi_1d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray1<f64>,
-    red: PyReadonlyArray1<f64>,
-) -> PyResult<&'py PyArray1<f64>> {
-    let nir = nir.as_array();
-    let red = red.as_array();
-
-    let mut result = Array1::<f64>::zeros(nir.len());
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let term = 2.0 * nir_v + 1.0;
-            let discriminant = term * term - 8.0 * (nir_v - red_v);
-            *r = if discriminant < 0.0 {
-                0.0
-            } else {
-                (term - discriminant.sqrt()) / 2.0
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn msavi_2d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray2<f64>,
-    red: PyReadonlyArray2<f64>,
-) -> PyResult<&'py PyArray2<f64>> {
-    let nir = nir.as_array();
-    let red = red.as_array();
-
-    let shape = nir.dim();
-    let mut result = Array2::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let term = 2.0 * nir_v + 1.0;
-            let discriminant = term * term - 8.0 * (nir_v - red_v);
-            *r = if discriminant < 0.0 {
-                0.0
-            } else {
-                (term - discriminant.sqrt()) / 2.0
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn msavi_3d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray3<f64>,
-    red: PyReadonlyArray3<f64>,
-) -> PyResult<&'py PyArray3<f64>> {
-    let nir = nir.as_array();
-    let red = red.as_array();
-
-    let shape = nir.dim();
-    let mut result = Array3::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let term = 2.0 * nir_v + 1.0;
-            let discriminant = term * term - 8.0 * (nir_v - red_v);
-            *r = if discriminant < 0.0 {
-                0.0
-            } else {
-                (term - discriminant.sqrt()) / 2.0
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn msavi_4d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray4<f64>,
-    red: PyReadonlyArray4<f64>,
-) -> PyResult<&'py PyArray4<f64>> {
-    let nir = nir.as_array();
-    let red = red.as_array();
-
-    let shape = nir.dim();
-    let mut result = Array4::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&red)
-        .for_each(|r, &nir_v, &red_v| {
-            let term = 2.0 * nir_v + 1.0;
-            let discriminant = term * term - 8.0 * (nir_v - red_v);
-            *r = if discriminant < 0.0 {
-                0.0
-            } else {
-                (term - discriminant.sqrt()) / 2.0
-            };
-        });
-
-    Ok(result.into_pyarray(py))
+#[pyo3(signature = (nir, red, scale=None, offset=None))]
+pub fn msavi(
+    py: Python<'_>,
+    nir: &PyAny,
+    red: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map2(
+        py,
+        "MSAVI",
+        nir,
+        red,
+        Scaling::from_args(scale, offset)?,
+        msavi_px,
+    )
 }
 
 /// Compute Green Normalized Difference Vegetation Index (GNDVI).
@@ -1556,959 +685,1695 @@ fn msavi_4d<'py>(
 /// It is more sensitive to chlorophyll concentration and can be used
 /// to assess vegetation health and nitrogen content.
 ///
-/// Dispatches for 1D–4D float64 numpy arrays; `nir` and `green` must have identical shapes.
+/// Accepts arrays of any rank; `nir` and `green` must have broadcast-compatible shapes.
 #[pyfunction]
-pub fn gndvi(py: Python<'_>, nir: &PyAny, green: &PyAny) -> PyResult<PyObject> {
-    if let Ok(nir_1d) = nir.extract::<PyReadonlyArray1<f64>>() {
-        let green_1d = green.extract::<PyReadonlyArray1<f64>>()?;
-        if nir_1d.shape() != green_1d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 1D GNDVI inputs: nir {:?}, green {:?}",
-                nir_1d.shape(),
-                green_1d.shape()
-            ))
-            .into());
-        }
-        gndvi_1d(py, nir_1d, green_1d).map(|res| res.into_py(py))
-    } else if let Ok(nir_2d) = nir.extract::<PyReadonlyArray2<f64>>() {
-        let green_2d = green.extract::<PyReadonlyArray2<f64>>()?;
-        if nir_2d.shape() != green_2d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 2D GNDVI inputs: nir {:?}, green {:?}",
-                nir_2d.shape(),
-                green_2d.shape()
-            ))
-            .into());
-        }
-        gndvi_2d(py, nir_2d, green_2d).map(|res| res.into_py(py))
-    } else if let Ok(nir_3d) = nir.extract::<PyReadonlyArray3<f64>>() {
-        let green_3d = green.extract::<PyReadonlyArray3<f64>>()?;
-        if nir_3d.shape() != green_3d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 3D GNDVI inputs: nir {:?}, green {:?}",
-                nir_3d.shape(),
-                green_3d.shape()
-            ))
-            .into());
-        }
-        gndvi_3d(py, nir_3d, green_3d).map(|res| res.into_py(py))
-    } else if let Ok(nir_4d) = nir.extract::<PyReadonlyArray4<f64>>() {
-        let green_4d = green.extract::<PyReadonlyArray4<f64>>()?;
-        if nir_4d.shape() != green_4d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 4D GNDVI inputs: nir {:?}, green {:?}",
-                nir_4d.shape(),
-                green_4d.shape()
-            ))
-            .into());
-        }
-        gndvi_4d(py, nir_4d, green_4d).map(|res| res.into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument(
-            "Input arrays must be either 1D, 2D, 3D, or 4D numpy arrays of type float64."
-                .to_string(),
-        )
-        .into())
-    }
+#[pyo3(signature = (nir, green, scale=None, offset=None))]
+pub fn gndvi(
+    py: Python<'_>,
+    nir: &PyAny,
+    green: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map2(
+        py,
+        "GNDVI",
+        nir,
+        green,
+        Scaling::from_args(scale, offset)?,
+        normalized_difference_px,
+    )
 }
 
-fn gndvi_1d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray1<f64>,
-    green: PyReadonlyArray1<f64>,
-) -> PyResult<&'py PyArray1<f64>> {
-    let nir = nir.as_array();
-    let green = green.as_array();
-
-    let mut result = Array1::<f64>::zeros(nir.len());
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&green)
-        .for_each(|r, &nir_v, &green_v| {
-            let denom = nir_v + green_v;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - green_v) / denom
-            };
-        });
-
-    Ok(result.into_pyarray(py))
+/// Compute Normalized Difference Red Edge (NDRE).
+///
+/// Formula:
+/// NDRE = (NIR - RedEdge) / (NIR + RedEdge)
+///
+/// NDRE uses the red edge band which is sensitive to chlorophyll content
+/// and plant stress. It is useful for assessing vegetation health and
+/// detecting early signs of stress before visible symptoms appear.
+///
+/// Accepts arrays of any rank; `nir` and `rededge` must have broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (nir, rededge, scale=None, offset=None))]
+pub fn ndre(
+    py: Python<'_>,
+    nir: &PyAny,
+    rededge: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map2(
+        py,
+        "NDRE",
+        nir,
+        rededge,
+        Scaling::from_args(scale, offset)?,
+        normalized_difference_px,
+    )
 }
 
-fn gndvi_2d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray2<f64>,
-    green: PyReadonlyArray2<f64>,
-) -> PyResult<&'py PyArray2<f64>> {
-    let nir = nir.as_array();
-    let green = green.as_array();
-
-    let shape = nir.dim();
-    let mut result = Array2::<f64>::zeros(shape);
+/// Compute Normalized Difference Vegetation Index Red Edge (NDVIre).
+///
+/// Formula:
+/// NDVIre = (NIR - RedEdge) / (NIR - Red)
+///
+/// This variant of NDVI uses the red edge band in the denominator,
+/// making it more sensitive to vegetation stress.
+///
+/// Accepts arrays of any rank; `nir`, `rededge`, and `red` must have broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (nir, rededge, red, scale=None, offset=None))]
+pub fn ndvi_re2(
+    py: Python<'_>,
+    nir: &PyAny,
+    rededge: &PyAny,
+    red: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map3(
+        py,
+        "NDVIre2",
+        nir,
+        rededge,
+        red,
+        Scaling::from_args(scale, offset)?,
+        ndvi_re2_px,
+    )
+}
 
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&green)
-        .for_each(|r, &nir_v, &green_v| {
-            let denom = nir_v + green_v;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - green_v) / denom
-            };
-        });
+/// Compute Leaf Area Index (LAI) from Enhanced Vegetation Index.
+///
+/// Formula:
+/// LAI = 3.618 * EVI - 0.118
+///
+/// This is a MODIS-derived relationship between EVI and LAI.
+///
+/// Accepts arrays of any rank; `nir`, `red`, and `blue` must have broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (nir, red, blue, scale=None, offset=None))]
+pub fn lai(
+    py: Python<'_>,
+    nir: &PyAny,
+    red: &PyAny,
+    blue: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map3(
+        py,
+        "LAI",
+        nir,
+        red,
+        blue,
+        Scaling::from_args(scale, offset)?,
+        lai_px,
+    )
+}
 
-    Ok(result.into_pyarray(py))
+/// Compute Differenced Normalized Burn Ratio (dNBR).
+///
+/// Formula:
+/// dNBR = NBR_pre - NBR_post
+///      = (NIR_pre - SWIR2_pre)/(NIR_pre + SWIR2_pre) - (NIR_post - SWIR2_post)/(NIR_post + SWIR2_post)
+///
+/// Positive values indicate increased burn severity.
+///
+/// Accepts arrays of any rank with broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (pre_nir, pre_swir2, post_nir, post_swir2, scale=None, offset=None))]
+pub fn dnbr(
+    py: Python<'_>,
+    pre_nir: &PyAny,
+    pre_swir2: &PyAny,
+    post_nir: &PyAny,
+    post_swir2: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map4(
+        py,
+        "dNBR",
+        pre_nir,
+        pre_swir2,
+        post_nir,
+        post_swir2,
+        Scaling::from_args(scale, offset)?,
+        delta_nd_px,
+    )
 }
 
-fn gndvi_3d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray3<f64>,
-    green: PyReadonlyArray3<f64>,
-) -> PyResult<&'py PyArray3<f64>> {
-    let nir = nir.as_array();
-    let green = green.as_array();
+/// Compute Relative Burn Ratio (RBR).
+///
+/// Formula:
+/// RBR = (NBR_pre - NBR_post) / (NBR_pre + 1)
+///
+/// RBR normalizes dNBR by pre-fire NBR to account for varying baseline conditions.
+///
+/// Accepts arrays of any rank with broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (pre_nir, pre_swir2, post_nir, post_swir2, scale=None, offset=None))]
+pub fn rbr(
+    py: Python<'_>,
+    pre_nir: &PyAny,
+    pre_swir2: &PyAny,
+    post_nir: &PyAny,
+    post_swir2: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map4(
+        py,
+        "RBR",
+        pre_nir,
+        pre_swir2,
+        post_nir,
+        post_swir2,
+        Scaling::from_args(scale, offset)?,
+        rbr_px,
+    )
+}
 
-    let shape = nir.dim();
-    let mut result = Array3::<f64>::zeros(shape);
+/// Compute Chlorophyll Index Red Edge (CIre).
+///
+/// Formula:
+/// CIre = (NIR / RedEdge) - 1
+///
+/// CIre is sensitive to chlorophyll content and can be used to
+/// estimate leaf nitrogen concentration.
+///
+/// Accepts arrays of any rank; `nir` and `rededge` must have broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (nir, rededge, scale=None, offset=None))]
+pub fn ci_re(
+    py: Python<'_>,
+    nir: &PyAny,
+    rededge: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map2(
+        py,
+        "CIre",
+        nir,
+        rededge,
+        Scaling::from_args(scale, offset)?,
+        ratio_minus_one_px,
+    )
+}
 
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&green)
-        .for_each(|r, &nir_v, &green_v| {
-            let denom = nir_v + green_v;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - green_v) / denom
-            };
-        });
+/// Compute MERIS Terrestrial Chlorophyll Index (MTCI).
+///
+/// Formula:
+/// MTCI = (RedEdge - Red) / (Red - Green)
+///
+/// MTCI is designed for estimating chlorophyll content in vegetation
+/// using MERIS/ENVISAT bands, but works with equivalent bands from other sensors.
+///
+/// Accepts arrays of any rank; `rededge`, `red`, and `green` must have broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (rededge, red, green, scale=None, offset=None))]
+pub fn mtci(
+    py: Python<'_>,
+    rededge: &PyAny,
+    red: &PyAny,
+    green: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map3(
+        py,
+        "MTCI",
+        rededge,
+        red,
+        green,
+        Scaling::from_args(scale, offset)?,
+        mtci_px,
+    )
+}
 
-    Ok(result.into_pyarray(py))
+/// Compute Normalized Difference Built-up Index (NDBI).
+///
+/// Formula:
+/// NDBI = (SWIR1 - NIR) / (SWIR1 + NIR)
+///
+/// Positive values highlight built-up and bare surfaces.
+///
+/// Accepts arrays of any rank; `swir1` and `nir` must have broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (swir1, nir, scale=None, offset=None))]
+pub fn ndbi(
+    py: Python<'_>,
+    swir1: &PyAny,
+    nir: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    normalized_difference(py, swir1, nir, scale, offset)
 }
 
-fn gndvi_4d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray4<f64>,
-    green: PyReadonlyArray4<f64>,
-) -> PyResult<&'py PyArray4<f64>> {
-    let nir = nir.as_array();
-    let green = green.as_array();
+/// Compute Modified Normalized Difference Water Index (MNDWI).
+///
+/// Formula:
+/// MNDWI = (Green - SWIR1) / (Green + SWIR1)
+///
+/// Replacing NDWI's NIR with SWIR1 suppresses built-up noise over open water.
+///
+/// Accepts arrays of any rank; `green` and `swir1` must have broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (green, swir1, scale=None, offset=None))]
+pub fn mndwi(
+    py: Python<'_>,
+    green: &PyAny,
+    swir1: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    normalized_difference(py, green, swir1, scale, offset)
+}
 
-    let shape = nir.dim();
-    let mut result = Array4::<f64>::zeros(shape);
+/// Compute Automated Water Extraction Index, no-shadow variant (AWEInsh).
+///
+/// Formula:
+/// AWEInsh = 4 * (Green - SWIR1) - (0.25 * NIR + 2.75 * SWIR2)
+///
+/// Water is positive; intended for scenes without strong terrain or urban shadow.
+///
+/// Accepts arrays of any rank with broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (green, swir1, nir, swir2, scale=None, offset=None))]
+pub fn awei_nsh(
+    py: Python<'_>,
+    green: &PyAny,
+    swir1: &PyAny,
+    nir: &PyAny,
+    swir2: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map4(
+        py,
+        "AWEInsh",
+        green,
+        swir1,
+        nir,
+        swir2,
+        Scaling::from_args(scale, offset)?,
+        awei_nsh_px,
+    )
+}
 
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&green)
-        .for_each(|r, &nir_v, &green_v| {
-            let denom = nir_v + green_v;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - green_v) / denom
-            };
-        });
+/// Compute Automated Water Extraction Index, shadow variant (AWEIsh).
+///
+/// Formula:
+/// AWEIsh = Blue + 2.5 * Green - 1.5 * (NIR + SWIR1) - 0.25 * SWIR2
+///
+/// Separates water from shadow, which AWEInsh tends to classify as water.
+///
+/// Accepts arrays of any rank with broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (blue, green, nir, swir1, swir2, scale=None, offset=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn awei_sh(
+    py: Python<'_>,
+    blue: &PyAny,
+    green: &PyAny,
+    nir: &PyAny,
+    swir1: &PyAny,
+    swir2: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map5(
+        py,
+        "AWEIsh",
+        blue,
+        green,
+        nir,
+        swir1,
+        swir2,
+        Scaling::from_args(scale, offset)?,
+        awei_sh_px,
+    )
+}
 
-    Ok(result.into_pyarray(py))
+/// Compute Bare Soil Index (BSI).
+///
+/// Formula:
+/// BSI = ((SWIR1 + Red) - (NIR + Blue)) / ((SWIR1 + Red) + (NIR + Blue))
+///
+/// Accepts arrays of any rank with broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (swir1, red, nir, blue, scale=None, offset=None))]
+pub fn bsi(
+    py: Python<'_>,
+    swir1: &PyAny,
+    red: &PyAny,
+    nir: &PyAny,
+    blue: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map4(
+        py,
+        "BSI",
+        swir1,
+        red,
+        nir,
+        blue,
+        Scaling::from_args(scale, offset)?,
+        bsi_px,
+    )
 }
 
-/// Compute Normalized Difference Red Edge (NDRE).
+/// Compute kernel NDVI (kNDVI) with an RBF kernel.
 ///
 /// Formula:
-/// NDRE = (NIR - RedEdge) / (NIR + RedEdge)
+/// kNDVI = tanh(((NIR - Red) / (2 * sigma))^2)
 ///
-/// NDRE uses the red edge band which is sensitive to chlorophyll content
-/// and plant stress. It is useful for assessing vegetation health and
-/// detecting early signs of stress before visible symptoms appear.
+/// `sigma` is the kernel length scale in reflectance units. When omitted the
+/// per-pixel default sigma = 0.5 * (NIR + Red) is used, for which
+/// kNDVI = tanh(NDVI^2).
 ///
-/// Dispatches for 1D–4D float64 numpy arrays; `nir` and `rededge` must have identical shapes.
+/// Accepts arrays of any rank; `nir` and `red` must have broadcast-compatible shapes.
 #[pyfunction]
-pub fn ndre(py: Python<'_>, nir: &PyAny, rededge: &PyAny) -> PyResult<PyObject> {
-    if let Ok(nir_1d) = nir.extract::<PyReadonlyArray1<f64>>() {
-        let rededge_1d = rededge.extract::<PyReadonlyArray1<f64>>()?;
-        if nir_1d.shape() != rededge_1d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 1D NDRE inputs: nir {:?}, rededge {:?}",
-                nir_1d.shape(),
-                rededge_1d.shape()
-            ))
-            .into());
-        }
-        ndre_1d(py, nir_1d, rededge_1d).map(|res| res.into_py(py))
-    } else if let Ok(nir_2d) = nir.extract::<PyReadonlyArray2<f64>>() {
-        let rededge_2d = rededge.extract::<PyReadonlyArray2<f64>>()?;
-        if nir_2d.shape() != rededge_2d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 2D NDRE inputs: nir {:?}, rededge {:?}",
-                nir_2d.shape(),
-                rededge_2d.shape()
-            ))
-            .into());
-        }
-        ndre_2d(py, nir_2d, rededge_2d).map(|res| res.into_py(py))
-    } else if let Ok(nir_3d) = nir.extract::<PyReadonlyArray3<f64>>() {
-        let rededge_3d = rededge.extract::<PyReadonlyArray3<f64>>()?;
-        if nir_3d.shape() != rededge_3d.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 3D NDRE inputs: nir {:?}, rededge {:?}",
-                nir_3d.shape(),
-                rededge_3d.shape()
-            ))
-            .into());
-        }
-        ndre_3d(py, nir_3d, rededge_3d).map(|res| res.into_py(py))
-    } else if let Ok(nir_4d) = nir.extract::<PyReadonlyArray4<f64>>() {
-        let rededge_4d = rededge.extract::<PyReadonlyArray4<f64>>()?;
-        if nir_4d.shape() != rededge_4d.shape() {
+#[pyo3(signature = (nir, red, sigma=None, scale=None, offset=None))]
+pub fn kndvi(
+    py: Python<'_>,
+    nir: &PyAny,
+    red: &PyAny,
+    sigma: Option<f64>,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    if let Some(sigma) = sigma {
+        if !(sigma > 0.0 && sigma.is_finite()) {
             return Err(CoreError::InvalidArgument(format!(
-                "Shape mismatch for 4D NDRE inputs: nir {:?}, rededge {:?}",
-                nir_4d.shape(),
-                rededge_4d.shape()
+                "kNDVI sigma must be positive and finite, got {}",
+                sigma
             ))
             .into());
         }
-        ndre_4d(py, nir_4d, rededge_4d).map(|res| res.into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument(
-            "Input arrays must be either 1D, 2D, 3D, or 4D numpy arrays of type float64."
-                .to_string(),
-        )
-        .into())
     }
+    py_map2(
+        py,
+        "kNDVI",
+        nir,
+        red,
+        Scaling::from_args(scale, offset)?,
+        move |n, r| kndvi_px(n, r, sigma),
+    )
 }
 
-fn ndre_1d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray1<f64>,
-    rededge: PyReadonlyArray1<f64>,
-) -> PyResult<&'py PyArray1<f64>> {
-    let nir = nir.as_array();
-    let rededge = rededge.as_array();
-
-    let mut result = Array1::<f64>::zeros(nir.len());
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&rededge)
-        .for_each(|r, &nir_v, &rededge_v| {
-            let denom = nir_v + rededge_v;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - rededge_v) / denom
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn ndre_2d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray2<f64>,
-    rededge: PyReadonlyArray2<f64>,
-) -> PyResult<&'py PyArray2<f64>> {
-    let nir = nir.as_array();
-    let rededge = rededge.as_array();
-
-    let shape = nir.dim();
-    let mut result = Array2::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&rededge)
-        .for_each(|r, &nir_v, &rededge_v| {
-            let denom = nir_v + rededge_v;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - rededge_v) / denom
-            };
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn ndre_3d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray3<f64>,
-    rededge: PyReadonlyArray3<f64>,
-) -> PyResult<&'py PyArray3<f64>> {
-    let nir = nir.as_array();
-    let rededge = rededge.as_array();
-
-    let shape = nir.dim();
-    let mut result = Array3::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&rededge)
-        .for_each(|r, &nir_v, &rededge_v| {
-            let denom = nir_v + rededge_v;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - rededge_v) / denom
-            };
-        });
-
-    Ok(result.into_pyarray(py))
+/// Compute Near-Infrared Reflectance of Vegetation (NIRv).
+///
+/// Formula:
+/// NIRv = NDVI * NIR
+///
+/// A proxy for canopy photosynthesis that removes the soil contribution to NIR.
+///
+/// Accepts arrays of any rank; `nir` and `red` must have broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (nir, red, scale=None, offset=None))]
+pub fn nirv(
+    py: Python<'_>,
+    nir: &PyAny,
+    red: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map2(
+        py,
+        "NIRv",
+        nir,
+        red,
+        Scaling::from_args(scale, offset)?,
+        nirv_px,
+    )
 }
 
-fn ndre_4d<'py>(
-    py: Python<'py>,
-    nir: PyReadonlyArray4<f64>,
-    rededge: PyReadonlyArray4<f64>,
-) -> PyResult<&'py PyArray4<f64>> {
-    let nir = nir.as_array();
-    let rededge = rededge.as_array();
-
-    let shape = nir.dim();
-    let mut result = Array4::<f64>::zeros(shape);
-
-    Zip::from(&mut result)
-        .and(&nir)
-        .and(&rededge)
-        .for_each(|r, &nir_v, &rededge_v| {
-            let denom = nir_v + rededge_v;
-            *r = if denom.abs() < EPSILON {
-                0.0
-            } else {
-                (nir_v - rededge_v) / denom
-            };
-        });
-
-    Ok(result.into_pyarray(py))
+/// Compute Structure Insensitive Pigment Index (SIPI).
+///
+/// Formula:
+/// SIPI = (NIR - Blue) / (NIR - Red)
+///
+/// Tracks the carotenoid to chlorophyll a ratio.
+///
+/// Accepts arrays of any rank; `nir`, `blue`, and `red` must have broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (nir, blue, red, scale=None, offset=None))]
+pub fn sipi(
+    py: Python<'_>,
+    nir: &PyAny,
+    blue: &PyAny,
+    red: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map3(
+        py,
+        "SIPI",
+        nir,
+        blue,
+        red,
+        Scaling::from_args(scale, offset)?,
+        sipi_px,
+    )
 }
 
-/// Compute Normalized Difference Vegetation Index Red Edge (NDVIre).
+/// Compute Plant Senescence Reflectance Index (PSRI).
 ///
 /// Formula:
-/// NDVIre = (NIR - RedEdge) / (NIR - Red)
+/// PSRI = (Red - Blue) / NIR
 ///
-/// This variant of NDVI uses the red edge band in the denominator,
-/// making it more sensitive to vegetation stress.
+/// The original 750 nm denominator is taken from the NIR band so the index is
+/// available for sensors without a red-edge band. Rises with senescence.
 ///
-/// Dispatches for 1D–4D float64 numpy arrays; `nir`, `rededge`, and `red` must have identical shapes.
+/// Accepts arrays of any rank; `red`, `blue`, and `nir` must have broadcast-compatible shapes.
 #[pyfunction]
-pub fn ndvi_re2(py: Python<'_>, nir: &PyAny, rededge: &PyAny, red: &PyAny) -> PyResult<PyObject> {
-    if let Ok(nir_1d) = nir.extract::<PyReadonlyArray1<f64>>() {
-        let rededge_1d = rededge.extract::<PyReadonlyArray1<f64>>()?;
-        let red_1d = red.extract::<PyReadonlyArray1<f64>>()?;
-        if nir_1d.shape() != rededge_1d.shape() || nir_1d.shape() != red_1d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for NDVIre2 inputs".to_string()).into());
-        }
-        ndvi_re2_1d(py, nir_1d, rededge_1d, red_1d).map(|res| res.into_py(py))
-    } else if let Ok(nir_2d) = nir.extract::<PyReadonlyArray2<f64>>() {
-        let rededge_2d = rededge.extract::<PyReadonlyArray2<f64>>()?;
-        let red_2d = red.extract::<PyReadonlyArray2<f64>>()?;
-        if nir_2d.shape() != rededge_2d.shape() || nir_2d.shape() != red_2d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for NDVIre2 inputs".to_string()).into());
-        }
-        ndvi_re2_2d(py, nir_2d, rededge_2d, red_2d).map(|res| res.into_py(py))
-    } else if let Ok(nir_3d) = nir.extract::<PyReadonlyArray3<f64>>() {
-        let rededge_3d = rededge.extract::<PyReadonlyArray3<f64>>()?;
-        let red_3d = red.extract::<PyReadonlyArray3<f64>>()?;
-        if nir_3d.shape() != rededge_3d.shape() || nir_3d.shape() != red_3d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for NDVIre2 inputs".to_string()).into());
-        }
-        ndvi_re2_3d(py, nir_3d, rededge_3d, red_3d).map(|res| res.into_py(py))
-    } else if let Ok(nir_4d) = nir.extract::<PyReadonlyArray4<f64>>() {
-        let rededge_4d = rededge.extract::<PyReadonlyArray4<f64>>()?;
-        let red_4d = red.extract::<PyReadonlyArray4<f64>>()?;
-        if nir_4d.shape() != rededge_4d.shape() || nir_4d.shape() != red_4d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for NDVIre2 inputs".to_string()).into());
-        }
-        ndvi_re2_4d(py, nir_4d, rededge_4d, red_4d).map(|res| res.into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument("Input arrays must be either 1D, 2D, 3D, or 4D numpy arrays".to_string()).into())
-    }
-}
-
-fn ndvi_re2_1d<'py>(py: Python<'py>, nir: PyReadonlyArray1<f64>, rededge: PyReadonlyArray1<f64>, red: PyReadonlyArray1<f64>) -> PyResult<&'py PyArray1<f64>> {
-    let nir = nir.as_array();
-    let rededge = rededge.as_array();
-    let red = red.as_array();
-    let mut result = Array1::<f64>::zeros(nir.len());
-    Zip::from(&mut result).and(&nir).and(&rededge).and(&red).for_each(|r, &nir_v, &rededge_v, &red_v| {
-        let denom = nir_v - red_v;
-        *r = if denom.abs() < EPSILON { 0.0 } else { (nir_v - rededge_v) / denom };
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn ndvi_re2_2d<'py>(py: Python<'py>, nir: PyReadonlyArray2<f64>, rededge: PyReadonlyArray2<f64>, red: PyReadonlyArray2<f64>) -> PyResult<&'py PyArray2<f64>> {
-    let nir = nir.as_array();
-    let rededge = rededge.as_array();
-    let red = red.as_array();
-    let shape = nir.dim();
-    let mut result = Array2::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&nir).and(&rededge).and(&red).for_each(|r, &nir_v, &rededge_v, &red_v| {
-        let denom = nir_v - red_v;
-        *r = if denom.abs() < EPSILON { 0.0 } else { (nir_v - rededge_v) / denom };
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn ndvi_re2_3d<'py>(py: Python<'py>, nir: PyReadonlyArray3<f64>, rededge: PyReadonlyArray3<f64>, red: PyReadonlyArray3<f64>) -> PyResult<&'py PyArray3<f64>> {
-    let nir = nir.as_array();
-    let rededge = rededge.as_array();
-    let red = red.as_array();
-    let shape = nir.dim();
-    let mut result = Array3::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&nir).and(&rededge).and(&red).for_each(|r, &nir_v, &rededge_v, &red_v| {
-        let denom = nir_v - red_v;
-        *r = if denom.abs() < EPSILON { 0.0 } else { (nir_v - rededge_v) / denom };
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn ndvi_re2_4d<'py>(py: Python<'py>, nir: PyReadonlyArray4<f64>, rededge: PyReadonlyArray4<f64>, red: PyReadonlyArray4<f64>) -> PyResult<&'py PyArray4<f64>> {
-    let nir = nir.as_array();
-    let rededge = rededge.as_array();
-    let red = red.as_array();
-    let shape = nir.dim();
-    let mut result = Array4::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&nir).and(&rededge).and(&red).for_each(|r, &nir_v, &rededge_v, &red_v| {
-        let denom = nir_v - red_v;
-        *r = if denom.abs() < EPSILON { 0.0 } else { (nir_v - rededge_v) / denom };
-    });
-    Ok(result.into_pyarray(py))
+#[pyo3(signature = (red, blue, nir, scale=None, offset=None))]
+pub fn psri(
+    py: Python<'_>,
+    red: &PyAny,
+    blue: &PyAny,
+    nir: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map3(
+        py,
+        "PSRI",
+        red,
+        blue,
+        nir,
+        Scaling::from_args(scale, offset)?,
+        psri_px,
+    )
 }
 
-/// Compute Leaf Area Index (LAI) from Enhanced Vegetation Index.
+/// Compute Atmospherically Resistant Vegetation Index (ARVI).
 ///
 /// Formula:
-/// LAI = 3.618 * EVI - 0.118
+/// ARVI = (NIR - RB) / (NIR + RB), RB = 2 * Red - Blue
 ///
-/// This is a MODIS-derived relationship between EVI and LAI.
+/// The blue band corrects the red band for aerosol scattering (gamma = 1).
 ///
-/// Dispatches for 1D–4D float64 numpy arrays; `nir`, `red`, and `blue` must have identical shapes.
+/// Accepts arrays of any rank; `nir`, `red`, and `blue` must have broadcast-compatible shapes.
 #[pyfunction]
-pub fn lai(py: Python<'_>, nir: &PyAny, red: &PyAny, blue: &PyAny) -> PyResult<PyObject> {
-    if let Ok(nir_1d) = nir.extract::<PyReadonlyArray1<f64>>() {
-        let red_1d = red.extract::<PyReadonlyArray1<f64>>()?;
-        let blue_1d = blue.extract::<PyReadonlyArray1<f64>>()?;
-        if nir_1d.shape() != red_1d.shape() || nir_1d.shape() != blue_1d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for LAI inputs".to_string()).into());
-        }
-        lai_1d(py, nir_1d, red_1d, blue_1d).map(|res| res.into_py(py))
-    } else if let Ok(nir_2d) = nir.extract::<PyReadonlyArray2<f64>>() {
-        let red_2d = red.extract::<PyReadonlyArray2<f64>>()?;
-        let blue_2d = blue.extract::<PyReadonlyArray2<f64>>()?;
-        if nir_2d.shape() != red_2d.shape() || nir_2d.shape() != blue_2d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for LAI inputs".to_string()).into());
-        }
-        lai_2d(py, nir_2d, red_2d, blue_2d).map(|res| res.into_py(py))
-    } else if let Ok(nir_3d) = nir.extract::<PyReadonlyArray3<f64>>() {
-        let red_3d = red.extract::<PyReadonlyArray3<f64>>()?;
-        let blue_3d = blue.extract::<PyReadonlyArray3<f64>>()?;
-        if nir_3d.shape() != red_3d.shape() || nir_3d.shape() != blue_3d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for LAI inputs".to_string()).into());
-        }
-        lai_3d(py, nir_3d, red_3d, blue_3d).map(|res| res.into_py(py))
-    } else if let Ok(nir_4d) = nir.extract::<PyReadonlyArray4<f64>>() {
-        let red_4d = red.extract::<PyReadonlyArray4<f64>>()?;
-        let blue_4d = blue.extract::<PyReadonlyArray4<f64>>()?;
-        if nir_4d.shape() != red_4d.shape() || nir_4d.shape() != blue_4d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for LAI inputs".to_string()).into());
-        }
-        lai_4d(py, nir_4d, red_4d, blue_4d).map(|res| res.into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument("Input arrays must be either 1D, 2D, 3D, or 4D numpy arrays".to_string()).into())
-    }
+#[pyo3(signature = (nir, red, blue, scale=None, offset=None))]
+pub fn arvi(
+    py: Python<'_>,
+    nir: &PyAny,
+    red: &PyAny,
+    blue: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map3(
+        py,
+        "ARVI",
+        nir,
+        red,
+        blue,
+        Scaling::from_args(scale, offset)?,
+        arvi_px,
+    )
 }
 
-fn lai_1d<'py>(py: Python<'py>, nir: PyReadonlyArray1<f64>, red: PyReadonlyArray1<f64>, blue: PyReadonlyArray1<f64>) -> PyResult<&'py PyArray1<f64>> {
-    const G: f64 = 2.5;
-    const C1: f64 = 6.0;
-    const C2: f64 = 7.5;
-    const L: f64 = 1.0;
-    const A: f64 = 3.618;
-    const B: f64 = -0.118;
-    let nir = nir.as_array();
-    let red = red.as_array();
-    let blue = blue.as_array();
-    let mut result = Array1::<f64>::zeros(nir.len());
-    Zip::from(&mut result).and(&nir).and(&red).and(&blue).for_each(|r, &nir_v, &red_v, &blue_v| {
-        let denom = nir_v + C1 * red_v - C2 * blue_v + L;
-        let evi = if denom.abs() < EPSILON { 0.0 } else { G * (nir_v - red_v) / denom };
-        *r = A * evi + B;
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn lai_2d<'py>(py: Python<'py>, nir: PyReadonlyArray2<f64>, red: PyReadonlyArray2<f64>, blue: PyReadonlyArray2<f64>) -> PyResult<&'py PyArray2<f64>> {
-    const G: f64 = 2.5;
-    const C1: f64 = 6.0;
-    const C2: f64 = 7.5;
-    const L: f64 = 1.0;
-    const A: f64 = 3.618;
-    const B: f64 = -0.118;
-    let nir = nir.as_array();
-    let red = red.as_array();
-    let blue = blue.as_array();
-    let shape = nir.dim();
-    let mut result = Array2::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&nir).and(&red).and(&blue).for_each(|r, &nir_v, &red_v, &blue_v| {
-        let denom = nir_v + C1 * red_v - C2 * blue_v + L;
-        let evi = if denom.abs() < EPSILON { 0.0 } else { G * (nir_v - red_v) / denom };
-        *r = A * evi + B;
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn lai_3d<'py>(py: Python<'py>, nir: PyReadonlyArray3<f64>, red: PyReadonlyArray3<f64>, blue: PyReadonlyArray3<f64>) -> PyResult<&'py PyArray3<f64>> {
-    const G: f64 = 2.5;
-    const C1: f64 = 6.0;
-    const C2: f64 = 7.5;
-    const L: f64 = 1.0;
-    const A: f64 = 3.618;
-    const B: f64 = -0.118;
-    let nir = nir.as_array();
-    let red = red.as_array();
-    let blue = blue.as_array();
-    let shape = nir.dim();
-    let mut result = Array3::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&nir).and(&red).and(&blue).for_each(|r, &nir_v, &red_v, &blue_v| {
-        let denom = nir_v + C1 * red_v - C2 * blue_v + L;
-        let evi = if denom.abs() < EPSILON { 0.0 } else { G * (nir_v - red_v) / denom };
-        *r = A * evi + B;
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn lai_4d<'py>(py: Python<'py>, nir: PyReadonlyArray4<f64>, red: PyReadonlyArray4<f64>, blue: PyReadonlyArray4<f64>) -> PyResult<&'py PyArray4<f64>> {
-    const G: f64 = 2.5;
-    const C1: f64 = 6.0;
-    const C2: f64 = 7.5;
-    const L: f64 = 1.0;
-    const A: f64 = 3.618;
-    const B: f64 = -0.118;
-    let nir = nir.as_array();
-    let red = red.as_array();
-    let blue = blue.as_array();
-    let shape = nir.dim();
-    let mut result = Array4::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&nir).and(&red).and(&blue).for_each(|r, &nir_v, &red_v, &blue_v| {
-        let denom = nir_v + C1 * red_v - C2 * blue_v + L;
-        let evi = if denom.abs() < EPSILON { 0.0 } else { G * (nir_v - red_v) / denom };
-        *r = A * evi + B;
-    });
-    Ok(result.into_pyarray(py))
+/// Compute Visible Atmospherically Resistant Index (VARI).
+///
+/// Formula:
+/// VARI = (Green - Red) / (Green + Red - Blue)
+///
+/// Estimates vegetation fraction from visible bands only.
+///
+/// Accepts arrays of any rank; `green`, `red`, and `blue` must have broadcast-compatible shapes.
+#[pyfunction]
+#[pyo3(signature = (green, red, blue, scale=None, offset=None))]
+pub fn vari(
+    py: Python<'_>,
+    green: &PyAny,
+    red: &PyAny,
+    blue: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map3(
+        py,
+        "VARI",
+        green,
+        red,
+        blue,
+        Scaling::from_args(scale, offset)?,
+        vari_px,
+    )
 }
 
-/// Compute Differenced Normalized Burn Ratio (dNBR).
+/// Compute the TCARI/OSAVI chlorophyll ratio.
 ///
 /// Formula:
-/// dNBR = NBR_pre - NBR_post
-///      = (NIR_pre - SWIR2_pre)/(NIR_pre + SWIR2_pre) - (NIR_post - SWIR2_post)/(NIR_post + SWIR2_post)
+/// TCARI = 3 * ((RedEdge - Red) - 0.2 * (RedEdge - Green) * (RedEdge / Red))
+/// OSAVI = 1.16 * (NIR - Red) / (NIR + Red + 0.16)
+/// TCARI/OSAVI = TCARI / OSAVI
 ///
-/// Positive values indicate increased burn severity.
+/// Dividing by OSAVI reduces the sensitivity of TCARI to soil background
+/// and LAI, leaving mostly the chlorophyll signal.
 ///
-/// Dispatches for 1D–4D float64 numpy arrays.
+/// Accepts arrays of any rank with broadcast-compatible shapes.
 #[pyfunction]
-pub fn dnbr(py: Python<'_>, pre_nir: &PyAny, pre_swir2: &PyAny, post_nir: &PyAny, post_swir2: &PyAny) -> PyResult<PyObject> {
-    if let Ok(pre_nir_1d) = pre_nir.extract::<PyReadonlyArray1<f64>>() {
-        let pre_swir2_1d = pre_swir2.extract::<PyReadonlyArray1<f64>>()?;
-        let post_nir_1d = post_nir.extract::<PyReadonlyArray1<f64>>()?;
-        let post_swir2_1d = post_swir2.extract::<PyReadonlyArray1<f64>>()?;
-        if pre_nir_1d.shape() != pre_swir2_1d.shape() || pre_nir_1d.shape() != post_nir_1d.shape() || pre_nir_1d.shape() != post_swir2_1d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for dNBR inputs".to_string()).into());
-        }
-        dnbr_1d(py, pre_nir_1d, pre_swir2_1d, post_nir_1d, post_swir2_1d).map(|res| res.into_py(py))
-    } else if let Ok(pre_nir_2d) = pre_nir.extract::<PyReadonlyArray2<f64>>() {
-        let pre_swir2_2d = pre_swir2.extract::<PyReadonlyArray2<f64>>()?;
-        let post_nir_2d = post_nir.extract::<PyReadonlyArray2<f64>>()?;
-        let post_swir2_2d = post_swir2.extract::<PyReadonlyArray2<f64>>()?;
-        if pre_nir_2d.shape() != pre_swir2_2d.shape() || pre_nir_2d.shape() != post_nir_2d.shape() || pre_nir_2d.shape() != post_swir2_2d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for dNBR inputs".to_string()).into());
-        }
-        dnbr_2d(py, pre_nir_2d, pre_swir2_2d, post_nir_2d, post_swir2_2d).map(|res| res.into_py(py))
-    } else if let Ok(pre_nir_3d) = pre_nir.extract::<PyReadonlyArray3<f64>>() {
-        let pre_swir2_3d = pre_swir2.extract::<PyReadonlyArray3<f64>>()?;
-        let post_nir_3d = post_nir.extract::<PyReadonlyArray3<f64>>()?;
-        let post_swir2_3d = post_swir2.extract::<PyReadonlyArray3<f64>>()?;
-        if pre_nir_3d.shape() != pre_swir2_3d.shape() || pre_nir_3d.shape() != post_nir_3d.shape() || pre_nir_3d.shape() != post_swir2_3d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for dNBR inputs".to_string()).into());
-        }
-        dnbr_3d(py, pre_nir_3d, pre_swir2_3d, post_nir_3d, post_swir2_3d).map(|res| res.into_py(py))
-    } else if let Ok(pre_nir_4d) = pre_nir.extract::<PyReadonlyArray4<f64>>() {
-        let pre_swir2_4d = pre_swir2.extract::<PyReadonlyArray4<f64>>()?;
-        let post_nir_4d = post_nir.extract::<PyReadonlyArray4<f64>>()?;
-        let post_swir2_4d = post_swir2.extract::<PyReadonlyArray4<f64>>()?;
-        if pre_nir_4d.shape() != pre_swir2_4d.shape() || pre_nir_4d.shape() != post_nir_4d.shape() || pre_nir_4d.shape() != post_swir2_4d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for dNBR inputs".to_string()).into());
-        }
-        dnbr_4d(py, pre_nir_4d, pre_swir2_4d, post_nir_4d, post_swir2_4d).map(|res| res.into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument("Input arrays must be either 1D, 2D, 3D, or 4D numpy arrays".to_string()).into())
-    }
-}
-
-fn dnbr_1d<'py>(py: Python<'py>, pre_nir: PyReadonlyArray1<f64>, pre_swir2: PyReadonlyArray1<f64>, post_nir: PyReadonlyArray1<f64>, post_swir2: PyReadonlyArray1<f64>) -> PyResult<&'py PyArray1<f64>> {
-    let pre_nir = pre_nir.as_array();
-    let pre_swir2 = pre_swir2.as_array();
-    let post_nir = post_nir.as_array();
-    let post_swir2 = post_swir2.as_array();
-    let mut result = Array1::<f64>::zeros(pre_nir.len());
-    Zip::from(&mut result).and(&pre_nir).and(&pre_swir2).and(&post_nir).and(&post_swir2).for_each(|r, &pre_n, &pre_s, &post_n, &post_s| {
-        let pre_denom = pre_n + pre_s;
-        let post_denom = post_n + post_s;
-        let pre_nbr = if pre_denom.abs() < EPSILON { 0.0 } else { (pre_n - pre_s) / pre_denom };
-        let post_nbr = if post_denom.abs() < EPSILON { 0.0 } else { (post_n - post_s) / post_denom };
-        *r = pre_nbr - post_nbr;
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn dnbr_2d<'py>(py: Python<'py>, pre_nir: PyReadonlyArray2<f64>, pre_swir2: PyReadonlyArray2<f64>, post_nir: PyReadonlyArray2<f64>, post_swir2: PyReadonlyArray2<f64>) -> PyResult<&'py PyArray2<f64>> {
-    let pre_nir = pre_nir.as_array();
-    let pre_swir2 = pre_swir2.as_array();
-    let post_nir = post_nir.as_array();
-    let post_swir2 = post_swir2.as_array();
-    let shape = pre_nir.dim();
-    let mut result = Array2::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&pre_nir).and(&pre_swir2).and(&post_nir).and(&post_swir2).for_each(|r, &pre_n, &pre_s, &post_n, &post_s| {
-        let pre_denom = pre_n + pre_s;
-        let post_denom = post_n + post_s;
-        let pre_nbr = if pre_denom.abs() < EPSILON { 0.0 } else { (pre_n - pre_s) / pre_denom };
-        let post_nbr = if post_denom.abs() < EPSILON { 0.0 } else { (post_n - post_s) / post_denom };
-        *r = pre_nbr - post_nbr;
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn dnbr_3d<'py>(py: Python<'py>, pre_nir: PyReadonlyArray3<f64>, pre_swir2: PyReadonlyArray3<f64>, post_nir: PyReadonlyArray3<f64>, post_swir2: PyReadonlyArray3<f64>) -> PyResult<&'py PyArray3<f64>> {
-    let pre_nir = pre_nir.as_array();
-    let pre_swir2 = pre_swir2.as_array();
-    let post_nir = post_nir.as_array();
-    let post_swir2 = post_swir2.as_array();
-    let shape = pre_nir.dim();
-    let mut result = Array3::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&pre_nir).and(&pre_swir2).and(&post_nir).and(&post_swir2).for_each(|r, &pre_n, &pre_s, &post_n, &post_s| {
-        let pre_denom = pre_n + pre_s;
-        let post_denom = post_n + post_s;
-        let pre_nbr = if pre_denom.abs() < EPSILON { 0.0 } else { (pre_n - pre_s) / pre_denom };
-        let post_nbr = if post_denom.abs() < EPSILON { 0.0 } else { (post_n - post_s) / post_denom };
-        *r = pre_nbr - post_nbr;
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn dnbr_4d<'py>(py: Python<'py>, pre_nir: PyReadonlyArray4<f64>, pre_swir2: PyReadonlyArray4<f64>, post_nir: PyReadonlyArray4<f64>, post_swir2: PyReadonlyArray4<f64>) -> PyResult<&'py PyArray4<f64>> {
-    let pre_nir = pre_nir.as_array();
-    let pre_swir2 = pre_swir2.as_array();
-    let post_nir = post_nir.as_array();
-    let post_swir2 = post_swir2.as_array();
-    let shape = pre_nir.dim();
-    let mut result = Array4::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&pre_nir).and(&pre_swir2).and(&post_nir).and(&post_swir2).for_each(|r, &pre_n, &pre_s, &post_n, &post_s| {
-        let pre_denom = pre_n + pre_s;
-        let post_denom = post_n + post_s;
-        let pre_nbr = if pre_denom.abs() < EPSILON { 0.0 } else { (pre_n - pre_s) / pre_denom };
-        let post_nbr = if post_denom.abs() < EPSILON { 0.0 } else { (post_n - post_s) / post_denom };
-        *r = pre_nbr - post_nbr;
-    });
-    Ok(result.into_pyarray(py))
+#[pyo3(signature = (rededge, red, green, nir, scale=None, offset=None))]
+pub fn tcari_osavi(
+    py: Python<'_>,
+    rededge: &PyAny,
+    red: &PyAny,
+    green: &PyAny,
+    nir: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    py_map4(
+        py,
+        "TCARI/OSAVI",
+        rededge,
+        red,
+        green,
+        nir,
+        Scaling::from_args(scale, offset)?,
+        tcari_osavi_px,
+    )
 }
 
-/// Compute Relative Burn Ratio (RBR).
+/// Compute Normalized Difference Chlorophyll Index (NDCI).
 ///
 /// Formula:
-/// RBR = (NBR_pre - NBR_post) / (NBR_pre + 1)
+/// NDCI = (RedEdge - Red) / (RedEdge + Red)
 ///
-/// RBR normalizes dNBR by pre-fire NBR to account for varying baseline conditions.
+/// Estimates chlorophyll-a in turbid, productive waters.
 ///
-/// Dispatches for 1D–4D float64 numpy arrays.
+/// Accepts arrays of any rank; `rededge` and `red` must have broadcast-compatible shapes.
 #[pyfunction]
-pub fn rbr(py: Python<'_>, pre_nir: &PyAny, pre_swir2: &PyAny, post_nir: &PyAny, post_swir2: &PyAny) -> PyResult<PyObject> {
-    if let Ok(pre_nir_1d) = pre_nir.extract::<PyReadonlyArray1<f64>>() {
-        let pre_swir2_1d = pre_swir2.extract::<PyReadonlyArray1<f64>>()?;
-        let post_nir_1d = post_nir.extract::<PyReadonlyArray1<f64>>()?;
-        let post_swir2_1d = post_swir2.extract::<PyReadonlyArray1<f64>>()?;
-        if pre_nir_1d.shape() != pre_swir2_1d.shape() || pre_nir_1d.shape() != post_nir_1d.shape() || pre_nir_1d.shape() != post_swir2_1d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for RBR inputs".to_string()).into());
+#[pyo3(signature = (rededge, red, scale=None, offset=None))]
+pub fn ndci(
+    py: Python<'_>,
+    rededge: &PyAny,
+    red: &PyAny,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    normalized_difference(py, rededge, red, scale, offset)
+}
+
+//
+// Change vector analysis.
+//
+
+/// Number of histogram bins used by the automatic magnitude thresholds.
+const THRESHOLD_BINS: usize = 256;
+
+/// How the direction of a change vector is reported.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CvaDirection {
+    /// Degrees: the polar angle `atan2(d1, d0)` in `[0, 360)` for two bands,
+    /// otherwise the angle to the `(1, ..., 1)` diagonal in `[0, 180]`.
+    Angle,
+    /// `1 + sum(2^b for bands b that increased)`, so `1..=2^n_bands`.
+    Sector,
+}
+
+impl CvaDirection {
+    pub fn parse(direction: &str) -> Result<Self, CoreError> {
+        match direction.to_ascii_lowercase().as_str() {
+            "angle" => Ok(CvaDirection::Angle),
+            "sector" => Ok(CvaDirection::Sector),
+            _ => Err(CoreError::InvalidArgument(format!(
+                "Unknown change direction '{}'; expected 'angle' or 'sector'",
+                direction
+            ))),
         }
-        rbr_1d(py, pre_nir_1d, pre_swir2_1d, post_nir_1d, post_swir2_1d).map(|res| res.into_py(py))
-    } else if let Ok(pre_nir_2d) = pre_nir.extract::<PyReadonlyArray2<f64>>() {
-        let pre_swir2_2d = pre_swir2.extract::<PyReadonlyArray2<f64>>()?;
-        let post_nir_2d = post_nir.extract::<PyReadonlyArray2<f64>>()?;
-        let post_swir2_2d = post_swir2.extract::<PyReadonlyArray2<f64>>()?;
-        if pre_nir_2d.shape() != pre_swir2_2d.shape() || pre_nir_2d.shape() != post_nir_2d.shape() || pre_nir_2d.shape() != post_swir2_2d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for RBR inputs".to_string()).into());
+    }
+}
+
+/// Change magnitude threshold: fixed, or picked from the magnitude histogram.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ChangeThreshold {
+    Value(f64),
+    /// Maximum between-class variance (Otsu 1979).
+    Otsu,
+    /// Minimum error thresholding (Kittler & Illingworth 1986).
+    Kittler,
+}
+
+impl ChangeThreshold {
+    pub fn extract(obj: &PyAny) -> PyResult<Self> {
+        if let Ok(v) = obj.extract::<f64>() {
+            if v.is_nan() {
+                return Err(CoreError::InvalidArgument(
+                    "Change threshold must be a number, got NaN".to_string(),
+                )
+                .into());
+            }
+            return Ok(ChangeThreshold::Value(v));
         }
-        rbr_2d(py, pre_nir_2d, pre_swir2_2d, post_nir_2d, post_swir2_2d).map(|res| res.into_py(py))
-    } else if let Ok(pre_nir_3d) = pre_nir.extract::<PyReadonlyArray3<f64>>() {
-        let pre_swir2_3d = pre_swir2.extract::<PyReadonlyArray3<f64>>()?;
-        let post_nir_3d = post_nir.extract::<PyReadonlyArray3<f64>>()?;
-        let post_swir2_3d = post_swir2.extract::<PyReadonlyArray3<f64>>()?;
-        if pre_nir_3d.shape() != pre_swir2_3d.shape() || pre_nir_3d.shape() != post_nir_3d.shape() || pre_nir_3d.shape() != post_swir2_3d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for RBR inputs".to_string()).into());
+        let name: &str = obj.extract().map_err(|_| {
+            CoreError::InvalidArgument(
+                "threshold must be a number, 'otsu' or 'kittler'".to_string(),
+            )
+        })?;
+        match name.to_ascii_lowercase().as_str() {
+            "otsu" => Ok(ChangeThreshold::Otsu),
+            "kittler" | "kittler-illingworth" => Ok(ChangeThreshold::Kittler),
+            _ => Err(CoreError::InvalidArgument(format!(
+                "Unknown threshold method '{}'; expected 'otsu' or 'kittler'",
+                name
+            ))
+            .into()),
         }
-        rbr_3d(py, pre_nir_3d, pre_swir2_3d, post_nir_3d, post_swir2_3d).map(|res| res.into_py(py))
-    } else if let Ok(pre_nir_4d) = pre_nir.extract::<PyReadonlyArray4<f64>>() {
-        let pre_swir2_4d = pre_swir2.extract::<PyReadonlyArray4<f64>>()?;
-        let post_nir_4d = post_nir.extract::<PyReadonlyArray4<f64>>()?;
-        let post_swir2_4d = post_swir2.extract::<PyReadonlyArray4<f64>>()?;
-        if pre_nir_4d.shape() != pre_swir2_4d.shape() || pre_nir_4d.shape() != post_nir_4d.shape() || pre_nir_4d.shape() != post_swir2_4d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for RBR inputs".to_string()).into());
+    }
+
+    /// Resolve the threshold for the finite values in `magnitude`.
+    pub fn resolve<R: Real>(self, magnitude: &ArrayD<R>) -> Result<f64, CoreError> {
+        let method = match self {
+            ChangeThreshold::Value(v) => return Ok(v),
+            m => m,
+        };
+        let (lo, hi) = magnitude
+            .par_iter()
+            .map(|v| v.to_f64())
+            .filter(|v| v.is_finite())
+            .fold(
+                || (f64::INFINITY, f64::NEG_INFINITY),
+                |(lo, hi), v| (lo.min(v), hi.max(v)),
+            )
+            .reduce(
+                || (f64::INFINITY, f64::NEG_INFINITY),
+                |a, b| (a.0.min(b.0), a.1.max(b.1)),
+            );
+        if lo > hi {
+            return Err(CoreError::NotEnoughData(
+                "Automatic change threshold needs at least one finite magnitude".to_string(),
+            ));
+        }
+        if lo == hi {
+            return Ok(hi);
+        }
+        let width = (hi - lo) / THRESHOLD_BINS as f64;
+        let hist = magnitude
+            .par_iter()
+            .map(|v| v.to_f64())
+            .filter(|v| v.is_finite())
+            .fold(
+                || vec![0u64; THRESHOLD_BINS],
+                |mut h, v| {
+                    h[(((v - lo) / width) as usize).min(THRESHOLD_BINS - 1)] += 1;
+                    h
+                },
+            )
+            .reduce(
+                || vec![0u64; THRESHOLD_BINS],
+                |mut a, b| {
+                    a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+                    a
+                },
+            );
+        let bin = match method {
+            ChangeThreshold::Otsu => otsu_bin(&hist),
+            _ => kittler_bin(&hist),
+        };
+        // Pixels in bins `0..=bin` are unchanged.
+        Ok(lo + width * (bin + 1) as f64)
+    }
+}
+
+/// Per-split class weights, means and variances of a histogram: for each
+/// `t`, the statistics of bins `0..=t` and `t+1..`.
+fn histogram_splits(hist: &[u64]) -> Vec<[(f64, f64, f64); 2]> {
+    let total: f64 = hist.iter().map(|&c| c as f64).sum();
+    let (s0, s1, s2) = hist
+        .iter()
+        .enumerate()
+        .fold((0.0, 0.0, 0.0), |acc, (i, &c)| {
+            let (c, i) = (c as f64, i as f64);
+            (acc.0 + c, acc.1 + c * i, acc.2 + c * i * i)
+        });
+    let stats = |n: f64, m1: f64, m2: f64| {
+        if n == 0.0 {
+            return (0.0, 0.0, 0.0);
+        }
+        let mean = m1 / n;
+        (n / total, mean, (m2 / n - mean * mean).max(0.0))
+    };
+    let mut splits = Vec::with_capacity(hist.len() - 1);
+    let (mut n, mut m1, mut m2) = (0.0, 0.0, 0.0);
+    for (i, &c) in hist[..hist.len() - 1].iter().enumerate() {
+        let (c, i) = (c as f64, i as f64);
+        n += c;
+        m1 += c * i;
+        m2 += c * i * i;
+        splits.push([stats(n, m1, m2), stats(s0 - n, s1 - m1, s2 - m2)]);
+    }
+    splits
+}
+
+/// Histogram bin maximising the between-class variance.
+fn otsu_bin(hist: &[u64]) -> usize {
+    histogram_splits(hist)
+        .iter()
+        .map(|[(w0, m0, _), (w1, m1, _)]| w0 * w1 * (m0 - m1) * (m0 - m1))
+        .enumerate()
+        .fold((0, f64::NEG_INFINITY), |best, (t, v)| {
+            if v > best.1 {
+                (t, v)
+            } else {
+                best
+            }
+        })
+        .0
+}
+
+/// Histogram bin minimising the Kittler-Illingworth criterion
+/// `P0 ln(s0 / P0) + P1 ln(s1 / P1)` over splits with two non-degenerate
+/// classes; falls back to Otsu when there is none.
+fn kittler_bin(hist: &[u64]) -> usize {
+    histogram_splits(hist)
+        .iter()
+        .enumerate()
+        .filter(|(_, [(w0, _, v0), (w1, _, v1)])| *w0 > 0.0 && *w1 > 0.0 && *v0 > 0.0 && *v1 > 0.0)
+        .map(|(t, [(w0, _, v0), (w1, _, v1)])| {
+            (t, w0 * (v0.sqrt() / w0).ln() + w1 * (v1.sqrt() / w1).ln())
+        })
+        .fold(None, |best: Option<(usize, f64)>, (t, j)| match best {
+            Some((_, b)) if b <= j => best,
+            _ => Some((t, j)),
+        })
+        .map_or_else(|| otsu_bin(hist), |(t, _)| t)
+}
+
+/// Scaled change vector `post - pre` of one pixel in `buf`; returns its
+/// length, or `None` if any band is not finite.
+fn change_vector<A: Sample>(
+    pre: ArrayView1<A>,
+    post: ArrayView1<A>,
+    scaling: Scaling,
+    buf: &mut Vec<f64>,
+) -> Option<f64> {
+    buf.clear();
+    for (&a, &b) in pre.iter().zip(post.iter()) {
+        let d = scaling.apply(b.to_f64()) - scaling.apply(a.to_f64());
+        if !d.is_finite() {
+            return None;
+        }
+        buf.push(d);
+    }
+    Some(buf.iter().map(|d| d * d).sum::<f64>().sqrt())
+}
+
+/// Direction layer of a change vector analysis.
+pub(crate) enum DirectionMap<R> {
+    Angle(ArrayD<R>),
+    Sector(ArrayD<i32>),
+}
+
+/// Change magnitude and direction.
+pub(crate) type ChangeVectors<R> = (ArrayD<R>, DirectionMap<R>);
+
+/// Change vector analysis of two `(band, ...)` cubes of the same shape.
+/// Returns the per-pixel magnitude and direction; pixels with a non-finite
+/// band get NaN magnitude and angle, and sector 0.
+pub(crate) fn change_vectors<A: Sample>(
+    pre: ArrayViewD<A>,
+    post: ArrayViewD<A>,
+    direction: CvaDirection,
+    scaling: Scaling,
+) -> Result<ChangeVectors<A::Out>, CoreError> {
+    if pre.shape() != post.shape() {
+        return Err(CoreError::InvalidArgument(format!(
+            "Pre and post cubes must have the same shape, got {:?} and {:?}",
+            pre.shape(),
+            post.shape()
+        )));
+    }
+    let bands = pre.shape().first().copied().unwrap_or(0);
+    if bands < 2 {
+        return Err(CoreError::InvalidArgument(format!(
+            "Change vector analysis needs a (band, ...) cube with at least 2 bands, got shape {:?}",
+            pre.shape()
+        )));
+    }
+    if direction == CvaDirection::Sector && bands > 30 {
+        return Err(CoreError::InvalidArgument(format!(
+            "Sector codes support at most 30 bands, got {}",
+            bands
+        )));
+    }
+    let pixels = IxDyn(&pre.shape()[1..]);
+    let nan = A::Out::from_f64(f64::NAN);
+    let mut magnitude = ArrayD::from_elem(pixels.clone(), nan);
+    let direction = match direction {
+        CvaDirection::Angle => {
+            let mut angle = ArrayD::from_elem(pixels, nan);
+            let diagonal = (bands as f64).sqrt();
+            Zip::from(&mut magnitude)
+                .and(&mut angle)
+                .and(pre.lanes(Axis(0)))
+                .and(post.lanes(Axis(0)))
+                .par_for_each(|m, a, pre, post| {
+                    let mut d = Vec::with_capacity(bands);
+                    let Some(len) = change_vector(pre, post, scaling, &mut d) else {
+                        return;
+                    };
+                    *m = A::Out::from_f64(len);
+                    let deg = if bands == 2 {
+                        d[1].atan2(d[0]).to_degrees().rem_euclid(360.0)
+                    } else if len > 0.0 {
+                        let cos = d.iter().sum::<f64>() / (diagonal * len);
+                        cos.clamp(-1.0, 1.0).acos().to_degrees()
+                    } else {
+                        0.0
+                    };
+                    *a = A::Out::from_f64(deg);
+                });
+            DirectionMap::Angle(angle)
+        }
+        CvaDirection::Sector => {
+            let mut sector = ArrayD::zeros(pixels);
+            Zip::from(&mut magnitude)
+                .and(&mut sector)
+                .and(pre.lanes(Axis(0)))
+                .and(post.lanes(Axis(0)))
+                .par_for_each(|m, s, pre, post| {
+                    let mut d = Vec::with_capacity(bands);
+                    let Some(len) = change_vector(pre, post, scaling, &mut d) else {
+                        return;
+                    };
+                    *m = A::Out::from_f64(len);
+                    *s = 1 + d
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, &v)| v > 0.0)
+                        .map(|(b, _)| 1i32 << b)
+                        .sum::<i32>();
+                });
+            DirectionMap::Sector(sector)
         }
-        rbr_4d(py, pre_nir_4d, pre_swir2_4d, post_nir_4d, post_swir2_4d).map(|res| res.into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument("Input arrays must be either 1D, 2D, 3D, or 4D numpy arrays".to_string()).into())
-    }
-}
-
-fn rbr_1d<'py>(py: Python<'py>, pre_nir: PyReadonlyArray1<f64>, pre_swir2: PyReadonlyArray1<f64>, post_nir: PyReadonlyArray1<f64>, post_swir2: PyReadonlyArray1<f64>) -> PyResult<&'py PyArray1<f64>> {
-    let pre_nir = pre_nir.as_array();
-    let pre_swir2 = pre_swir2.as_array();
-    let post_nir = post_nir.as_array();
-    let post_swir2 = post_swir2.as_array();
-    let mut result = Array1::<f64>::zeros(pre_nir.len());
-    Zip::from(&mut result).and(&pre_nir).and(&pre_swir2).and(&post_nir).and(&post_swir2).for_each(|r, &pre_n, &pre_s, &post_n, &post_s| {
-        let pre_denom = pre_n + pre_s;
-        let post_denom = post_n + post_s;
-        let pre_nbr = if pre_denom.abs() < EPSILON { 0.0 } else { (pre_n - pre_s) / pre_denom };
-        let post_nbr = if post_denom.abs() < EPSILON { 0.0 } else { (post_n - post_s) / post_denom };
-        let denom = pre_nbr + 1.0;
-        *r = if denom.abs() < EPSILON { 0.0 } else { (pre_nbr - post_nbr) / denom };
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn rbr_2d<'py>(py: Python<'py>, pre_nir: PyReadonlyArray2<f64>, pre_swir2: PyReadonlyArray2<f64>, post_nir: PyReadonlyArray2<f64>, post_swir2: PyReadonlyArray2<f64>) -> PyResult<&'py PyArray2<f64>> {
-    let pre_nir = pre_nir.as_array();
-    let pre_swir2 = pre_swir2.as_array();
-    let post_nir = post_nir.as_array();
-    let post_swir2 = post_swir2.as_array();
-    let shape = pre_nir.dim();
-    let mut result = Array2::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&pre_nir).and(&pre_swir2).and(&post_nir).and(&post_swir2).for_each(|r, &pre_n, &pre_s, &post_n, &post_s| {
-        let pre_denom = pre_n + pre_s;
-        let post_denom = post_n + post_s;
-        let pre_nbr = if pre_denom.abs() < EPSILON { 0.0 } else { (pre_n - pre_s) / pre_denom };
-        let post_nbr = if post_denom.abs() < EPSILON { 0.0 } else { (post_n - post_s) / post_denom };
-        let denom = pre_nbr + 1.0;
-        *r = if denom.abs() < EPSILON { 0.0 } else { (pre_nbr - post_nbr) / denom };
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn rbr_3d<'py>(py: Python<'py>, pre_nir: PyReadonlyArray3<f64>, pre_swir2: PyReadonlyArray3<f64>, post_nir: PyReadonlyArray3<f64>, post_swir2: PyReadonlyArray3<f64>) -> PyResult<&'py PyArray3<f64>> {
-    let pre_nir = pre_nir.as_array();
-    let pre_swir2 = pre_swir2.as_array();
-    let post_nir = post_nir.as_array();
-    let post_swir2 = post_swir2.as_array();
-    let shape = pre_nir.dim();
-    let mut result = Array3::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&pre_nir).and(&pre_swir2).and(&post_nir).and(&post_swir2).for_each(|r, &pre_n, &pre_s, &post_n, &post_s| {
-        let pre_denom = pre_n + pre_s;
-        let post_denom = post_n + post_s;
-        let pre_nbr = if pre_denom.abs() < EPSILON { 0.0 } else { (pre_n - pre_s) / pre_denom };
-        let post_nbr = if post_denom.abs() < EPSILON { 0.0 } else { (post_n - post_s) / post_denom };
-        let denom = pre_nbr + 1.0;
-        *r = if denom.abs() < EPSILON { 0.0 } else { (pre_nbr - post_nbr) / denom };
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn rbr_4d<'py>(py: Python<'py>, pre_nir: PyReadonlyArray4<f64>, pre_swir2: PyReadonlyArray4<f64>, post_nir: PyReadonlyArray4<f64>, post_swir2: PyReadonlyArray4<f64>) -> PyResult<&'py PyArray4<f64>> {
-    let pre_nir = pre_nir.as_array();
-    let pre_swir2 = pre_swir2.as_array();
-    let post_nir = post_nir.as_array();
-    let post_swir2 = post_swir2.as_array();
-    let shape = pre_nir.dim();
-    let mut result = Array4::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&pre_nir).and(&pre_swir2).and(&post_nir).and(&post_swir2).for_each(|r, &pre_n, &pre_s, &post_n, &post_s| {
-        let pre_denom = pre_n + pre_s;
-        let post_denom = post_n + post_s;
-        let pre_nbr = if pre_denom.abs() < EPSILON { 0.0 } else { (pre_n - pre_s) / pre_denom };
-        let post_nbr = if post_denom.abs() < EPSILON { 0.0 } else { (post_n - post_s) / post_denom };
-        let denom = pre_nbr + 1.0;
-        *r = if denom.abs() < EPSILON { 0.0 } else { (pre_nbr - post_nbr) / denom };
-    });
-    Ok(result.into_pyarray(py))
+    };
+    Ok((magnitude, direction))
 }
 
-/// Compute Chlorophyll Index Red Edge (CIre).
+/// Change vector analysis (CVA) for bi-temporal, multi-band change detection.
 ///
-/// Formula:
-/// CIre = (NIR / RedEdge) - 1
+/// Each pixel's change vector is `post - pre` across the bands. Its length is
+/// the change magnitude; its direction is reported either as an angle
+/// (degrees; the polar angle `atan2(d1, d0)` in `[0, 360)` for two bands,
+/// otherwise the angle to the `(1, ..., 1)` diagonal in `[0, 180]`, where 0
+/// means every band increased equally) or as a sector code
+/// `1 + sum(2^b for bands b that increased)`.
 ///
-/// CIre is sensitive to chlorophyll content and can be used to
-/// estimate leaf nitrogen concentration.
+/// # Arguments
+/// * `pre`, `post` - `(band, y, x)` cubes (any rank with bands first) of the
+///   same shape and dtype
+/// * `direction` - `"angle"` (default) or `"sector"`
+/// * `threshold` - Optional magnitude threshold: a number, `"otsu"` or
+///   `"kittler"` (Kittler-Illingworth minimum error), the latter two chosen
+///   from a 256-bin histogram of the finite magnitudes
+/// * `scale`, `offset` - DN conversion applied to both cubes
 ///
-/// Dispatches for 1D–4D float64 numpy arrays; `nir` and `rededge` must have identical shapes.
+/// # Returns
+/// `(magnitude, direction, change, threshold)`: the `(y, x)` magnitude, the
+/// `(y, x)` angle (float) or sector code (int32, 0 for NaN pixels), and, when
+/// `threshold` is given, the boolean `magnitude > threshold` mask and the
+/// threshold used (otherwise `None`).
 #[pyfunction]
-pub fn ci_re(py: Python<'_>, nir: &PyAny, rededge: &PyAny) -> PyResult<PyObject> {
-    if let Ok(nir_1d) = nir.extract::<PyReadonlyArray1<f64>>() {
-        let rededge_1d = rededge.extract::<PyReadonlyArray1<f64>>()?;
-        if nir_1d.shape() != rededge_1d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for CIre inputs".to_string()).into());
-        }
-        ci_re_1d(py, nir_1d, rededge_1d).map(|res| res.into_py(py))
-    } else if let Ok(nir_2d) = nir.extract::<PyReadonlyArray2<f64>>() {
-        let rededge_2d = rededge.extract::<PyReadonlyArray2<f64>>()?;
-        if nir_2d.shape() != rededge_2d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for CIre inputs".to_string()).into());
-        }
-        ci_re_2d(py, nir_2d, rededge_2d).map(|res| res.into_py(py))
-    } else if let Ok(nir_3d) = nir.extract::<PyReadonlyArray3<f64>>() {
-        let rededge_3d = rededge.extract::<PyReadonlyArray3<f64>>()?;
-        if nir_3d.shape() != rededge_3d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for CIre inputs".to_string()).into());
-        }
-        ci_re_3d(py, nir_3d, rededge_3d).map(|res| res.into_py(py))
-    } else if let Ok(nir_4d) = nir.extract::<PyReadonlyArray4<f64>>() {
-        let rededge_4d = rededge.extract::<PyReadonlyArray4<f64>>()?;
-        if nir_4d.shape() != rededge_4d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for CIre inputs".to_string()).into());
+#[pyo3(signature = (pre, post, direction="angle", threshold=None, scale=None, offset=None))]
+pub fn change_vector_analysis(
+    py: Python<'_>,
+    pre: &PyAny,
+    post: &PyAny,
+    direction: &str,
+    threshold: Option<&PyAny>,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<(PyObject, PyObject, PyObject, Option<f64>)> {
+    let direction = CvaDirection::parse(direction)?;
+    let threshold = threshold.map(ChangeThreshold::extract).transpose()?;
+    let scaling = Scaling::from_args(scale, offset)?;
+
+    fn finish<R: Real + numpy::Element>(
+        py: Python<'_>,
+        magnitude: ArrayD<R>,
+        direction: DirectionMap<R>,
+        threshold: Option<ChangeThreshold>,
+    ) -> PyResult<(PyObject, PyObject, PyObject, Option<f64>)> {
+        let (change, value) = match threshold {
+            Some(t) => {
+                let value = py.allow_threads(|| t.resolve(&magnitude))?;
+                let change = magnitude.mapv(|m| m.to_f64() > value);
+                (kernels::into_py(py, change), Some(value))
+            }
+            None => (py.None(), None),
+        };
+        let direction = match direction {
+            DirectionMap::Angle(a) => kernels::into_py(py, a),
+            DirectionMap::Sector(s) => kernels::into_py(py, s),
+        };
+        Ok((kernels::into_py(py, magnitude), direction, change, value))
+    }
+
+    let operands = Operand::extract_common(&[pre, post])?;
+    kernels::dispatch!(all operands, |views| {
+        let (a, b) = (views[0].clone(), views[1].clone());
+        let (m, d) = py.allow_threads(|| change_vectors(a, b, direction, scaling))?;
+        finish(py, m, d, threshold)
+    })
+}
+
+//
+// Index registry.
+//
+
+/// Input band roles. Role names match the parameter names of the
+/// single-index functions and the keys accepted by `compute_indices`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BandRole {
+    /// Generic first operand of `normalized_difference`.
+    A,
+    /// Generic second operand of `normalized_difference`.
+    B,
+    Blue,
+    Green,
+    Red,
+    RedEdge,
+    Nir,
+    Swir1,
+    Swir2,
+    PreNir,
+    PreRed,
+    PreSwir2,
+    PostNir,
+    PostRed,
+    PostSwir2,
+}
+
+impl BandRole {
+    pub const ALL: [BandRole; 15] = [
+        BandRole::A,
+        BandRole::B,
+        BandRole::Blue,
+        BandRole::Green,
+        BandRole::Red,
+        BandRole::RedEdge,
+        BandRole::Nir,
+        BandRole::Swir1,
+        BandRole::Swir2,
+        BandRole::PreNir,
+        BandRole::PreRed,
+        BandRole::PreSwir2,
+        BandRole::PostNir,
+        BandRole::PostRed,
+        BandRole::PostSwir2,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BandRole::A => "a",
+            BandRole::B => "b",
+            BandRole::Blue => "blue",
+            BandRole::Green => "green",
+            BandRole::Red => "red",
+            BandRole::RedEdge => "rededge",
+            BandRole::Nir => "nir",
+            BandRole::Swir1 => "swir1",
+            BandRole::Swir2 => "swir2",
+            BandRole::PreNir => "pre_nir",
+            BandRole::PreRed => "pre_red",
+            BandRole::PreSwir2 => "pre_swir2",
+            BandRole::PostNir => "post_nir",
+            BandRole::PostRed => "post_red",
+            BandRole::PostSwir2 => "post_swir2",
         }
-        ci_re_4d(py, nir_4d, rededge_4d).map(|res| res.into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument("Input arrays must be either 1D, 2D, 3D, or 4D numpy arrays".to_string()).into())
+    }
+
+    /// Parse a role name (case-insensitive).
+    pub fn parse(name: &str) -> Result<Self, CoreError> {
+        let lower = name.to_ascii_lowercase();
+        BandRole::ALL
+            .into_iter()
+            .find(|role| role.as_str() == lower)
+            .ok_or_else(|| CoreError::UnknownBandRole {
+                role: name.to_string(),
+                valid: BandRole::ALL.iter().map(|r| r.as_str()).collect(),
+            })
     }
 }
 
-fn ci_re_1d<'py>(py: Python<'py>, nir: PyReadonlyArray1<f64>, rededge: PyReadonlyArray1<f64>) -> PyResult<&'py PyArray1<f64>> {
-    let nir = nir.as_array();
-    let rededge = rededge.as_array();
-    let mut result = Array1::<f64>::zeros(nir.len());
-    Zip::from(&mut result).and(&nir).and(&rededge).for_each(|r, &nir_v, &rededge_v| {
-        *r = if rededge_v.abs() < EPSILON { 0.0 } else { nir_v / rededge_v - 1.0 };
-    });
-    Ok(result.into_pyarray(py))
+const UNBOUNDED: (f64, f64) = (f64::NEG_INFINITY, f64::INFINITY);
+
+/// Registry entry for one index in this module.
+pub(crate) struct SpectralIndex {
+    pub name: &'static str,
+    pub long_name: &'static str,
+    pub formula: &'static str,
+    /// Required bands, in the argument order of the single-index function.
+    pub bands: &'static [BandRole],
+    /// Expected output range for valid surface reflectance; infinite bounds
+    /// where the formula is unbounded. Values outside usually flag bad input.
+    pub valid_range: (f64, f64),
+    /// Literature reference; empty when none is recorded.
+    pub reference: &'static str,
+    /// Per-pixel formula over `bands`, shared with the single-index function.
+    pub compute: fn(&[f64]) -> f64,
+}
+
+/// Every index in this module. `compute_indices`, `index_registry` (and, on
+/// the Python side, the CLI and docs) are driven from this table; SAVI uses
+/// the default L = 0.5.
+pub(crate) const INDEX_REGISTRY: &[SpectralIndex] = &[
+    SpectralIndex {
+        name: "normalized_difference",
+        long_name: "Normalized Difference",
+        formula: "(A - B) / (A + B)",
+        bands: &[BandRole::A, BandRole::B],
+        valid_range: (-1.0, 1.0),
+        reference: "",
+        compute: |b| normalized_difference_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "ndvi",
+        long_name: "Normalized Difference Vegetation Index",
+        formula: "(NIR - Red) / (NIR + Red)",
+        bands: &[BandRole::Nir, BandRole::Red],
+        valid_range: (-1.0, 1.0),
+        reference: "Rouse, J. W., Haas, R. H., Schell, J. A. & Deering, D. W. (1974). \
+                    Monitoring vegetation systems in the Great Plains with ERTS. NASA SP-351, 309-317.",
+        compute: |b| normalized_difference_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "ndwi",
+        long_name: "Normalized Difference Water Index",
+        formula: "(Green - NIR) / (Green + NIR)",
+        bands: &[BandRole::Green, BandRole::Nir],
+        valid_range: (-1.0, 1.0),
+        reference: "McFeeters, S. K. (1996). The use of the Normalized Difference Water Index \
+                    (NDWI) in the delineation of open water features. Int. J. Remote Sens. \
+                    17(7), 1425-1432.",
+        compute: |b| normalized_difference_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "ndsi",
+        long_name: "Normalized Difference Snow Index",
+        formula: "(Green - SWIR1) / (Green + SWIR1)",
+        bands: &[BandRole::Green, BandRole::Swir1],
+        valid_range: (-1.0, 1.0),
+        reference: "Hall, D. K., Riggs, G. A. & Salomonson, V. V. (1995). Development of methods \
+                    for mapping global snow cover using MODIS data. Remote Sens. Environ. 54(2), \
+                    127-140.",
+        compute: |b| normalized_difference_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "nbr",
+        long_name: "Normalized Burn Ratio",
+        formula: "(NIR - SWIR2) / (NIR + SWIR2)",
+        bands: &[BandRole::Nir, BandRole::Swir2],
+        valid_range: (-1.0, 1.0),
+        reference: KEY_BENSON_2006,
+        compute: |b| normalized_difference_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "ndmi",
+        long_name: "Normalized Difference Moisture Index",
+        formula: "(NIR - SWIR1) / (NIR + SWIR1)",
+        bands: &[BandRole::Nir, BandRole::Swir1],
+        valid_range: (-1.0, 1.0),
+        reference: "Gao, B.-C. (1996). NDWI - A normalized difference water index for remote \
+                    sensing of vegetation liquid water from space. Remote Sens. Environ. 58(3), \
+                    257-266.",
+        compute: |b| normalized_difference_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "nbr2",
+        long_name: "Normalized Burn Ratio 2",
+        formula: "(SWIR1 - SWIR2) / (SWIR1 + SWIR2)",
+        bands: &[BandRole::Swir1, BandRole::Swir2],
+        valid_range: (-1.0, 1.0),
+        reference: "USGS (2017). Landsat Surface Reflectance-Derived Spectral Indices \
+                    Product Guide.",
+        compute: |b| normalized_difference_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "gndvi",
+        long_name: "Green Normalized Difference Vegetation Index",
+        formula: "(NIR - Green) / (NIR + Green)",
+        bands: &[BandRole::Nir, BandRole::Green],
+        valid_range: (-1.0, 1.0),
+        reference: "Gitelson, A. A., Kaufman, Y. J. & Merzlyak, M. N. (1996). Use of a green \
+                    channel in remote sensing of global vegetation from EOS-MODIS. Remote Sens. \
+                    Environ. 58(3), 289-298.",
+        compute: |b| normalized_difference_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "ndre",
+        long_name: "Normalized Difference Red Edge",
+        formula: "(NIR - RedEdge) / (NIR + RedEdge)",
+        bands: &[BandRole::Nir, BandRole::RedEdge],
+        valid_range: (-1.0, 1.0),
+        reference: "Gitelson, A. & Merzlyak, M. N. (1994). Spectral reflectance changes \
+                    associated with autumn senescence of Aesculus hippocastanum L. and Acer \
+                    platanoides L. leaves. J. Plant Physiol. 143(3), 286-292.",
+        compute: |b| normalized_difference_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "gci",
+        long_name: "Green Chlorophyll Index",
+        formula: "NIR / Green - 1",
+        bands: &[BandRole::Nir, BandRole::Green],
+        valid_range: (-1.0, f64::INFINITY),
+        reference: GITELSON_2003,
+        compute: |b| ratio_minus_one_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "ci_re",
+        long_name: "Chlorophyll Index Red Edge",
+        formula: "NIR / RedEdge - 1",
+        bands: &[BandRole::Nir, BandRole::RedEdge],
+        valid_range: (-1.0, f64::INFINITY),
+        reference: GITELSON_2003,
+        compute: |b| ratio_minus_one_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "savi",
+        long_name: "Soil Adjusted Vegetation Index",
+        formula: "(NIR - Red) / (NIR + Red + L) * (1 + L), L = 0.5",
+        bands: &[BandRole::Nir, BandRole::Red],
+        valid_range: (-1.5, 1.5),
+        reference: "Huete, A. R. (1988). A soil-adjusted vegetation index (SAVI). Remote Sens. \
+                    Environ. 25(3), 295-309.",
+        compute: |b| savi_px(b[0], b[1], 0.5),
+    },
+    SpectralIndex {
+        name: "osavi",
+        long_name: "Optimized Soil Adjusted Vegetation Index",
+        formula: "(NIR - Red) / (NIR + Red + 0.16)",
+        bands: &[BandRole::Nir, BandRole::Red],
+        valid_range: (-1.0, 1.0),
+        reference: "Rondeaux, G., Steven, M. & Baret, F. (1996). Optimization of soil-adjusted \
+                    vegetation indices. Remote Sens. Environ. 55(2), 95-107.",
+        compute: |b| osavi_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "msavi",
+        long_name: "Modified Soil Adjusted Vegetation Index",
+        formula: "((2*NIR + 1) - sqrt((2*NIR + 1)^2 - 8*(NIR - Red))) / 2",
+        bands: &[BandRole::Nir, BandRole::Red],
+        valid_range: (-1.0, 1.0),
+        reference: "Qi, J., Chehbouni, A., Huete, A. R., Kerr, Y. H. & Sorooshian, S. (1994). \
+                    A modified soil adjusted vegetation index. Remote Sens. Environ. 48(2), \
+                    119-126.",
+        compute: |b| msavi_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "evi",
+        long_name: "Enhanced Vegetation Index",
+        formula: "2.5 * (NIR - Red) / (NIR + 6*Red - 7.5*Blue + 1)",
+        bands: &[BandRole::Nir, BandRole::Red, BandRole::Blue],
+        valid_range: (-1.0, 1.0),
+        reference: HUETE_2002,
+        compute: |b| evi_px(b[0], b[1], b[2]),
+    },
+    SpectralIndex {
+        name: "evi2",
+        long_name: "Two-band Enhanced Vegetation Index",
+        formula: "2.5 * (NIR - Red) / (NIR + 2.4*Red + 1)",
+        bands: &[BandRole::Nir, BandRole::Red],
+        valid_range: (-1.0, 1.0),
+        reference: "Jiang, Z., Huete, A. R., Didan, K. & Miura, T. (2008). Development of a \
+                    two-band enhanced vegetation index without a blue band. Remote Sens. \
+                    Environ. 112(10), 3833-3845.",
+        compute: |b| evi2_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "ndvi_re2",
+        long_name: "NDVI Red Edge",
+        formula: "(NIR - RedEdge) / (NIR - Red)",
+        bands: &[BandRole::Nir, BandRole::RedEdge, BandRole::Red],
+        valid_range: UNBOUNDED,
+        reference: "",
+        compute: |b| ndvi_re2_px(b[0], b[1], b[2]),
+    },
+    SpectralIndex {
+        name: "lai",
+        long_name: "Leaf Area Index (EVI-based)",
+        formula: "3.618 * EVI - 0.118",
+        bands: &[BandRole::Nir, BandRole::Red, BandRole::Blue],
+        valid_range: (0.0, 3.5),
+        reference: "Boegh, E. et al. (2002). Airborne multispectral data for quantifying leaf \
+                    area index, nitrogen concentration, and photosynthetic efficiency in \
+                    agriculture. Remote Sens. Environ. 81(2-3), 179-193.",
+        compute: |b| lai_px(b[0], b[1], b[2]),
+    },
+    SpectralIndex {
+        name: "mtci",
+        long_name: "MERIS Terrestrial Chlorophyll Index",
+        formula: "(RedEdge - Red) / (Red - Green)",
+        bands: &[BandRole::RedEdge, BandRole::Red, BandRole::Green],
+        valid_range: UNBOUNDED,
+        reference: "Dash, J. & Curran, P. J. (2004). The MERIS terrestrial chlorophyll index. \
+                    Int. J. Remote Sens. 25(23), 5403-5413.",
+        compute: |b| mtci_px(b[0], b[1], b[2]),
+    },
+    SpectralIndex {
+        name: "ndbi",
+        long_name: "Normalized Difference Built-up Index",
+        formula: "(SWIR1 - NIR) / (SWIR1 + NIR)",
+        bands: &[BandRole::Swir1, BandRole::Nir],
+        valid_range: (-1.0, 1.0),
+        reference: "Zha, Y., Gao, J. & Ni, S. (2003). Use of normalized difference built-up \
+                    index in automatically mapping urban areas from TM imagery. Int. J. Remote \
+                    Sens. 24(3), 583-594.",
+        compute: |b| normalized_difference_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "mndwi",
+        long_name: "Modified Normalized Difference Water Index",
+        formula: "(Green - SWIR1) / (Green + SWIR1)",
+        bands: &[BandRole::Green, BandRole::Swir1],
+        valid_range: (-1.0, 1.0),
+        reference: "Xu, H. (2006). Modification of normalised difference water index (NDWI) to \
+                    enhance open water features in remotely sensed imagery. Int. J. Remote Sens. \
+                    27(14), 3025-3033.",
+        compute: |b| normalized_difference_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "awei_nsh",
+        long_name: "Automated Water Extraction Index (no shadow)",
+        formula: "4 * (Green - SWIR1) - (0.25*NIR + 2.75*SWIR2)",
+        bands: &[BandRole::Green, BandRole::Swir1, BandRole::Nir, BandRole::Swir2],
+        valid_range: UNBOUNDED,
+        reference: FEYISA_2014,
+        compute: |b| awei_nsh_px(b[0], b[1], b[2], b[3]),
+    },
+    SpectralIndex {
+        name: "awei_sh",
+        long_name: "Automated Water Extraction Index (shadow)",
+        formula: "Blue + 2.5*Green - 1.5*(NIR + SWIR1) - 0.25*SWIR2",
+        bands: &[
+            BandRole::Blue,
+            BandRole::Green,
+            BandRole::Nir,
+            BandRole::Swir1,
+            BandRole::Swir2,
+        ],
+        valid_range: UNBOUNDED,
+        reference: FEYISA_2014,
+        compute: |b| awei_sh_px(b[0], b[1], b[2], b[3], b[4]),
+    },
+    SpectralIndex {
+        name: "bsi",
+        long_name: "Bare Soil Index",
+        formula: "((SWIR1 + Red) - (NIR + Blue)) / ((SWIR1 + Red) + (NIR + Blue))",
+        bands: &[BandRole::Swir1, BandRole::Red, BandRole::Nir, BandRole::Blue],
+        valid_range: (-1.0, 1.0),
+        reference: "Rikimaru, A., Roy, P. S. & Miyatake, S. (2002). Tropical forest cover \
+                    density mapping. Trop. Ecol. 43(1), 39-47.",
+        compute: |b| bsi_px(b[0], b[1], b[2], b[3]),
+    },
+    SpectralIndex {
+        name: "kndvi",
+        long_name: "Kernel NDVI",
+        formula: "tanh(((NIR - Red) / (2*sigma))^2), sigma = 0.5 * (NIR + Red)",
+        bands: &[BandRole::Nir, BandRole::Red],
+        valid_range: (0.0, 1.0),
+        reference: "Camps-Valls, G. et al. (2021). A unified vegetation index for quantifying \
+                    the terrestrial biosphere. Sci. Adv. 7(9), eabc7447.",
+        compute: |b| kndvi_px(b[0], b[1], None),
+    },
+    SpectralIndex {
+        name: "nirv",
+        long_name: "Near-Infrared Reflectance of Vegetation",
+        formula: "NDVI * NIR",
+        bands: &[BandRole::Nir, BandRole::Red],
+        valid_range: (-1.0, 1.0),
+        reference: "Badgley, G., Field, C. B. & Berry, J. A. (2017). Canopy near-infrared \
+                    reflectance and terrestrial photosynthesis. Sci. Adv. 3(3), e1602244.",
+        compute: |b| nirv_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "sipi",
+        long_name: "Structure Insensitive Pigment Index",
+        formula: "(NIR - Blue) / (NIR - Red)",
+        bands: &[BandRole::Nir, BandRole::Blue, BandRole::Red],
+        valid_range: UNBOUNDED,
+        reference: "Penuelas, J., Baret, F. & Filella, I. (1995). Semi-empirical indices to \
+                    assess carotenoids/chlorophyll a ratio from leaf spectral reflectance. \
+                    Photosynthetica 31(2), 221-230.",
+        compute: |b| sipi_px(b[0], b[1], b[2]),
+    },
+    SpectralIndex {
+        name: "psri",
+        long_name: "Plant Senescence Reflectance Index",
+        formula: "(Red - Blue) / NIR",
+        bands: &[BandRole::Red, BandRole::Blue, BandRole::Nir],
+        valid_range: UNBOUNDED,
+        reference: "Merzlyak, M. N., Gitelson, A. A., Chivkunova, O. B. & Rakitin, V. Y. (1999). \
+                    Non-destructive optical detection of pigment changes during leaf senescence \
+                    and fruit ripening. Physiol. Plant. 106(1), 135-141.",
+        compute: |b| psri_px(b[0], b[1], b[2]),
+    },
+    SpectralIndex {
+        name: "arvi",
+        long_name: "Atmospherically Resistant Vegetation Index",
+        formula: "(NIR - (2*Red - Blue)) / (NIR + (2*Red - Blue))",
+        bands: &[BandRole::Nir, BandRole::Red, BandRole::Blue],
+        valid_range: (-1.0, 1.0),
+        reference: "Kaufman, Y. J. & Tanre, D. (1992). Atmospherically resistant vegetation \
+                    index (ARVI) for EOS-MODIS. IEEE Trans. Geosci. Remote Sens. 30(2), 261-270.",
+        compute: |b| arvi_px(b[0], b[1], b[2]),
+    },
+    SpectralIndex {
+        name: "vari",
+        long_name: "Visible Atmospherically Resistant Index",
+        formula: "(Green - Red) / (Green + Red - Blue)",
+        bands: &[BandRole::Green, BandRole::Red, BandRole::Blue],
+        valid_range: UNBOUNDED,
+        reference: "Gitelson, A. A., Kaufman, Y. J., Stark, R. & Rundquist, D. (2002). Novel \
+                    algorithms for remote estimation of vegetation fraction. Remote Sens. \
+                    Environ. 80(1), 76-87.",
+        compute: |b| vari_px(b[0], b[1], b[2]),
+    },
+    SpectralIndex {
+        name: "tcari_osavi",
+        long_name: "TCARI / OSAVI",
+        formula: "3*((RedEdge - Red) - 0.2*(RedEdge - Green)*(RedEdge/Red)) \
+                  / (1.16*(NIR - Red) / (NIR + Red + 0.16))",
+        bands: &[BandRole::RedEdge, BandRole::Red, BandRole::Green, BandRole::Nir],
+        valid_range: UNBOUNDED,
+        reference: "Haboudane, D., Miller, J. R., Tremblay, N., Zarco-Tejada, P. J. & Dextraze, \
+                    L. (2002). Integrated narrow-band vegetation indices for prediction of crop \
+                    chlorophyll content for application to precision agriculture. Remote Sens. \
+                    Environ. 81(2-3), 416-426.",
+        compute: |b| tcari_osavi_px(b[0], b[1], b[2], b[3]),
+    },
+    SpectralIndex {
+        name: "ndci",
+        long_name: "Normalized Difference Chlorophyll Index",
+        formula: "(RedEdge - Red) / (RedEdge + Red)",
+        bands: &[BandRole::RedEdge, BandRole::Red],
+        valid_range: (-1.0, 1.0),
+        reference: "Mishra, S. & Mishra, D. R. (2012). Normalized difference chlorophyll index: \
+                    A novel model for remote estimation of chlorophyll-a concentration in turbid \
+                    productive waters. Remote Sens. Environ. 117, 394-406.",
+        compute: |b| normalized_difference_px(b[0], b[1]),
+    },
+    SpectralIndex {
+        name: "delta_ndvi",
+        long_name: "Delta NDVI",
+        formula: "NDVI(pre) - NDVI(post)",
+        bands: &[
+            BandRole::PreNir,
+            BandRole::PreRed,
+            BandRole::PostNir,
+            BandRole::PostRed,
+        ],
+        valid_range: (-2.0, 2.0),
+        reference: "Lyon, J. G., Yuan, D., Lunetta, R. S. & Elvidge, C. D. (1998). A change \
+                    detection experiment using vegetation indices. Photogramm. Eng. Remote \
+                    Sens. 64(2), 143-150.",
+        compute: |b| delta_nd_px(b[0], b[1], b[2], b[3]),
+    },
+    SpectralIndex {
+        name: "delta_nbr",
+        long_name: "Delta NBR",
+        formula: "NBR(pre) - NBR(post)",
+        bands: PRE_POST_NBR_BANDS,
+        valid_range: (-2.0, 2.0),
+        reference: KEY_BENSON_2006,
+        compute: |b| delta_nd_px(b[0], b[1], b[2], b[3]),
+    },
+    SpectralIndex {
+        name: "dnbr",
+        long_name: "Differenced Normalized Burn Ratio",
+        formula: "NBR(pre) - NBR(post)",
+        bands: PRE_POST_NBR_BANDS,
+        valid_range: (-2.0, 2.0),
+        reference: KEY_BENSON_2006,
+        compute: |b| delta_nd_px(b[0], b[1], b[2], b[3]),
+    },
+    SpectralIndex {
+        name: "rbr",
+        long_name: "Relativized Burn Ratio",
+        formula: "(NBR(pre) - NBR(post)) / (NBR(pre) + 1)",
+        bands: PRE_POST_NBR_BANDS,
+        valid_range: UNBOUNDED,
+        reference: "Parks, S. A., Dillon, G. K. & Miller, C. (2014). A new metric for \
+                    quantifying burn severity: the relativized burn ratio. Remote Sens. 6(3), \
+                    1827-1844.",
+        compute: |b| rbr_px(b[0], b[1], b[2], b[3]),
+    },
+];
+
+const PRE_POST_NBR_BANDS: &[BandRole] = &[
+    BandRole::PreNir,
+    BandRole::PreSwir2,
+    BandRole::PostNir,
+    BandRole::PostSwir2,
+];
+
+const KEY_BENSON_2006: &str = "Key, C. H. & Benson, N. C. (2006). Landscape Assessment (LA). \
+                               USDA Forest Service Gen. Tech. Rep. RMRS-GTR-164-CD.";
+
+const GITELSON_2003: &str = "Gitelson, A. A., Gritz, Y. & Merzlyak, M. N. (2003). Relationships \
+                             between leaf chlorophyll content and spectral reflectance and \
+                             algorithms for non-destructive chlorophyll assessment in higher \
+                             plant leaves. J. Plant Physiol. 160(3), 271-282.";
+
+const FEYISA_2014: &str = "Feyisa, G. L., Meilby, H., Fensholt, R. & Proud, S. R. (2014). \
+                           Automated Water Extraction Index: A new technique for surface water \
+                           mapping using Landsat imagery. Remote Sens. Environ. 140, 23-35.";
+
+const HUETE_2002: &str = "Huete, A. et al. (2002). Overview of the radiometric and biophysical \
+                          performance of the MODIS vegetation indices. Remote Sens. Environ. \
+                          83(1-2), 195-213.";
+
+/// Look up a registry entry by name (case-insensitive).
+pub(crate) fn lookup_index(name: &str) -> Result<&'static SpectralIndex, CoreError> {
+    let lower = name.to_ascii_lowercase();
+    INDEX_REGISTRY
+        .iter()
+        .find(|i| i.name == lower)
+        .ok_or_else(|| {
+            let supported: Vec<&str> = INDEX_REGISTRY.iter().map(|i| i.name).collect();
+            CoreError::InvalidArgument(format!(
+                "Unknown index '{}'; supported: {:?}",
+                name, supported
+            ))
+        })
 }
 
-fn ci_re_2d<'py>(py: Python<'py>, nir: PyReadonlyArray2<f64>, rededge: PyReadonlyArray2<f64>) -> PyResult<&'py PyArray2<f64>> {
-    let nir = nir.as_array();
-    let rededge = rededge.as_array();
-    let shape = nir.dim();
-    let mut result = Array2::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&nir).and(&rededge).for_each(|r, &nir_v, &rededge_v| {
-        *r = if rededge_v.abs() < EPSILON { 0.0 } else { nir_v / rededge_v - 1.0 };
-    });
-    Ok(result.into_pyarray(py))
+/// Describe every registered index as a list of dicts with keys `name`,
+/// `long_name`, `formula`, `bands`, `valid_range` and `reference`, in
+/// registry order.
+#[pyfunction]
+pub fn index_registry(py: Python<'_>) -> PyResult<Vec<PyObject>> {
+    INDEX_REGISTRY
+        .iter()
+        .map(|index| {
+            let dict = PyDict::new(py);
+            dict.set_item("name", index.name)?;
+            dict.set_item("long_name", index.long_name)?;
+            dict.set_item("formula", index.formula)?;
+            let bands: Vec<&str> = index.bands.iter().map(|b| b.as_str()).collect();
+            dict.set_item("bands", bands)?;
+            dict.set_item("valid_range", index.valid_range)?;
+            dict.set_item("reference", index.reference)?;
+            Ok(dict.to_object(py))
+        })
+        .collect()
+}
+
+/// All band role names, in canonical order.
+#[pyfunction]
+pub fn band_roles() -> Vec<&'static str> {
+    BandRole::ALL.iter().map(|r| r.as_str()).collect()
 }
 
-fn ci_re_3d<'py>(py: Python<'py>, nir: PyReadonlyArray3<f64>, rededge: PyReadonlyArray3<f64>) -> PyResult<&'py PyArray3<f64>> {
-    let nir = nir.as_array();
-    let rededge = rededge.as_array();
-    let shape = nir.dim();
-    let mut result = Array3::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&nir).and(&rededge).for_each(|r, &nir_v, &rededge_v| {
-        *r = if rededge_v.abs() < EPSILON { 0.0 } else { nir_v / rededge_v - 1.0 };
-    });
-    Ok(result.into_pyarray(py))
+//
+// Fused multi-index evaluation.
+//
+
+/// Widest band list of any registry entry (AWEIsh).
+const MAX_INDEX_BANDS: usize = 5;
+
+/// Pixels per parallel work item in `FusedPlan::evaluate`.
+const FUSED_BLOCK: usize = 4096;
+
+/// Resolved `compute_indices` request: the union of required bands and, per
+/// requested index, the positions of its arguments within that union.
+pub(crate) struct FusedPlan {
+    bands: Vec<BandRole>,
+    entries: Vec<(&'static SpectralIndex, Vec<usize>)>,
 }
 
-fn ci_re_4d<'py>(py: Python<'py>, nir: PyReadonlyArray4<f64>, rededge: PyReadonlyArray4<f64>) -> PyResult<&'py PyArray4<f64>> {
-    let nir = nir.as_array();
-    let rededge = rededge.as_array();
-    let shape = nir.dim();
-    let mut result = Array4::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&nir).and(&rededge).for_each(|r, &nir_v, &rededge_v| {
-        *r = if rededge_v.abs() < EPSILON { 0.0 } else { nir_v / rededge_v - 1.0 };
-    });
-    Ok(result.into_pyarray(py))
+impl FusedPlan {
+    /// Resolve index names (case-insensitive) against `INDEX_REGISTRY`.
+    pub fn new(names: &[String]) -> Result<Self, CoreError> {
+        if names.is_empty() {
+            return Err(CoreError::InvalidArgument(
+                "compute_indices requires at least one index name".to_string(),
+            ));
+        }
+        let mut bands: Vec<BandRole> = Vec::new();
+        let mut entries = Vec::with_capacity(names.len());
+        for name in names {
+            let index = lookup_index(name)?;
+            let slots = index
+                .bands
+                .iter()
+                .map(|&role| match bands.iter().position(|&b| b == role) {
+                    Some(slot) => slot,
+                    None => {
+                        bands.push(role);
+                        bands.len() - 1
+                    }
+                })
+                .collect();
+            entries.push((index, slots));
+        }
+        Ok(FusedPlan { bands, entries })
+    }
+
+    /// Bands to pass to `evaluate`, in order.
+    pub fn bands(&self) -> &[BandRole] {
+        &self.bands
+    }
+
+    /// Evaluate every requested index over `bands` (ordered as `self.bands()`),
+    /// reading each pixel's band values once. Returns `(index, ...)` with the
+    /// broadcast shape of the bands as trailing axes.
+    pub fn evaluate<A: Sample>(
+        &self,
+        bands: &[ArrayViewD<A>],
+        scaling: Scaling,
+    ) -> Result<ArrayD<A::Out>, CoreError> {
+        let shapes: Vec<&[usize]> = bands.iter().map(|b| b.shape()).collect();
+        let shape = kernels::broadcast_shape_for("compute_indices", &shapes)?;
+
+        // Contiguous, broadcast bands; only strided or stretched bands are copied.
+        let flat: Vec<CowArray<A, IxDyn>> = bands
+            .iter()
+            .map(|b| {
+                let view = b.broadcast(IxDyn(&shape)).expect("shape was validated");
+                if view.is_standard_layout() {
+                    CowArray::from(view)
+                } else {
+                    CowArray::from(view.to_owned())
+                }
+            })
+            .collect();
+        let slices: Vec<&[A]> = flat
+            .iter()
+            .map(|b| b.as_slice().expect("standard layout"))
+            .collect();
+
+        let pixels: usize = shape.iter().product();
+        let mut out = Array2::from_elem((self.entries.len(), pixels), A::Out::from_f64(0.0));
+        out.axis_chunks_iter_mut(Axis(1), FUSED_BLOCK)
+            .into_par_iter()
+            .enumerate()
+            .for_each(|(block, mut chunk)| {
+                let start = block * FUSED_BLOCK;
+                let mut px = vec![0.0; slices.len()];
+                let mut args = [0.0; MAX_INDEX_BANDS];
+                for j in 0..chunk.ncols() {
+                    for (v, band) in px.iter_mut().zip(&slices) {
+                        *v = scaling.apply(band[start + j].to_f64());
+                    }
+                    for (k, (index, slots)) in self.entries.iter().enumerate() {
+                        for (arg, &slot) in args.iter_mut().zip(slots) {
+                            *arg = px[slot];
+                        }
+                        chunk[[k, j]] = Real::from_f64((index.compute)(&args[..slots.len()]));
+                    }
+                }
+            });
+
+        let mut out_shape = vec![self.entries.len()];
+        out_shape.extend_from_slice(&shape);
+        Ok(out
+            .into_shape(IxDyn(&out_shape))
+            .expect("standard layout output"))
+    }
 }
 
-/// Compute MERIS Terrestrial Chlorophyll Index (MTCI).
+/// Compute several single-scene indices in one pass.
 ///
-/// Formula:
-/// MTCI = (RedEdge - Red) / (Red - Green)
+/// Each pixel's bands are read once and every requested index is written into
+/// a stacked `(index, ...)` output. The formulas and EPSILON handling are
+/// those of the single-index functions, so each output plane matches e.g.
+/// `ndvi(nir, red)` exactly.
 ///
-/// MTCI is designed for estimating chlorophyll content in vegetation
-/// using MERIS/ENVISAT bands, but works with equivalent bands from other sensors.
+/// # Arguments
+/// * `bands` - Mapping of band role (see `band_roles`; case-insensitive) to
+///   array. Bands may have any rank and are broadcast NumPy-style; bands not
+///   needed by the requested indices are ignored. A key that is not a band
+///   role raises `UnknownBandRoleError`.
+/// * `names` - Index names (case-insensitive), see `index_registry`.
+/// * `scale`, `offset` - Optional DN conversion applied to every band
+///
+/// # Returns
+/// Array of shape `(len(names), *broadcast_shape)`; float32 for float32 /
+/// uint16 bands, float64 otherwise.
 ///
-/// Dispatches for 1D–4D float64 numpy arrays; `rededge`, `red`, and `green` must have identical shapes.
+/// # Example (from Python)
+/// ```python
+/// from eo_processor import compute_indices
+///
+/// stack = compute_indices({"nir": nir, "red": red, "green": green}, ["ndvi", "ndwi"])
+/// ndvi, ndwi = stack
+/// ```
 #[pyfunction]
-pub fn mtci(py: Python<'_>, rededge: &PyAny, red: &PyAny, green: &PyAny) -> PyResult<PyObject> {
-    if let Ok(rededge_1d) = rededge.extract::<PyReadonlyArray1<f64>>() {
-        let red_1d = red.extract::<PyReadonlyArray1<f64>>()?;
-        let green_1d = green.extract::<PyReadonlyArray1<f64>>()?;
-        if rededge_1d.shape() != red_1d.shape() || rededge_1d.shape() != green_1d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for MTCI inputs".to_string()).into());
-        }
-        mtci_1d(py, rededge_1d, red_1d, green_1d).map(|res| res.into_py(py))
-    } else if let Ok(rededge_2d) = rededge.extract::<PyReadonlyArray2<f64>>() {
-        let red_2d = red.extract::<PyReadonlyArray2<f64>>()?;
-        let green_2d = green.extract::<PyReadonlyArray2<f64>>()?;
-        if rededge_2d.shape() != red_2d.shape() || rededge_2d.shape() != green_2d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for MTCI inputs".to_string()).into());
-        }
-        mtci_2d(py, rededge_2d, red_2d, green_2d).map(|res| res.into_py(py))
-    } else if let Ok(rededge_3d) = rededge.extract::<PyReadonlyArray3<f64>>() {
-        let red_3d = red.extract::<PyReadonlyArray3<f64>>()?;
-        let green_3d = green.extract::<PyReadonlyArray3<f64>>()?;
-        if rededge_3d.shape() != red_3d.shape() || rededge_3d.shape() != green_3d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for MTCI inputs".to_string()).into());
-        }
-        mtci_3d(py, rededge_3d, red_3d, green_3d).map(|res| res.into_py(py))
-    } else if let Ok(rededge_4d) = rededge.extract::<PyReadonlyArray4<f64>>() {
-        let red_4d = red.extract::<PyReadonlyArray4<f64>>()?;
-        let green_4d = green.extract::<PyReadonlyArray4<f64>>()?;
-        if rededge_4d.shape() != red_4d.shape() || rededge_4d.shape() != green_4d.shape() {
-            return Err(CoreError::InvalidArgument("Shape mismatch for MTCI inputs".to_string()).into());
+#[pyo3(signature = (bands, names, scale=None, offset=None))]
+pub fn compute_indices(
+    py: Python<'_>,
+    bands: &PyDict,
+    names: Vec<String>,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    let plan = FusedPlan::new(&names)?;
+    let scaling = Scaling::from_args(scale, offset)?;
+
+    let mut provided: Vec<(BandRole, &PyAny)> = Vec::with_capacity(bands.len());
+    for (key, value) in bands.iter() {
+        let role = BandRole::parse(key.extract()?)?;
+        if provided.iter().any(|(r, _)| *r == role) {
+            return Err(CoreError::InvalidArgument(format!(
+                "compute_indices got band '{}' more than once",
+                role.as_str()
+            ))
+            .into());
         }
-        mtci_4d(py, rededge_4d, red_4d, green_4d).map(|res| res.into_py(py))
-    } else {
-        Err(CoreError::InvalidArgument("Input arrays must be either 1D, 2D, 3D, or 4D numpy arrays".to_string()).into())
-    }
-}
-
-fn mtci_1d<'py>(py: Python<'py>, rededge: PyReadonlyArray1<f64>, red: PyReadonlyArray1<f64>, green: PyReadonlyArray1<f64>) -> PyResult<&'py PyArray1<f64>> {
-    let rededge = rededge.as_array();
-    let red = red.as_array();
-    let green = green.as_array();
-    let mut result = Array1::<f64>::zeros(rededge.len());
-    Zip::from(&mut result).and(&rededge).and(&red).and(&green).for_each(|r, &redge_v, &red_v, &green_v| {
-        let denom = red_v - green_v;
-        *r = if denom.abs() < EPSILON { 0.0 } else { (redge_v - red_v) / denom };
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn mtci_2d<'py>(py: Python<'py>, rededge: PyReadonlyArray2<f64>, red: PyReadonlyArray2<f64>, green: PyReadonlyArray2<f64>) -> PyResult<&'py PyArray2<f64>> {
-    let rededge = rededge.as_array();
-    let red = red.as_array();
-    let green = green.as_array();
-    let shape = rededge.dim();
-    let mut result = Array2::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&rededge).and(&red).and(&green).for_each(|r, &redge_v, &red_v, &green_v| {
-        let denom = red_v - green_v;
-        *r = if denom.abs() < EPSILON { 0.0 } else { (redge_v - red_v) / denom };
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn mtci_3d<'py>(py: Python<'py>, rededge: PyReadonlyArray3<f64>, red: PyReadonlyArray3<f64>, green: PyReadonlyArray3<f64>) -> PyResult<&'py PyArray3<f64>> {
-    let rededge = rededge.as_array();
-    let red = red.as_array();
-    let green = green.as_array();
-    let shape = rededge.dim();
-    let mut result = Array3::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&rededge).and(&red).and(&green).for_each(|r, &redge_v, &red_v, &green_v| {
-        let denom = red_v - green_v;
-        *r = if denom.abs() < EPSILON { 0.0 } else { (redge_v - red_v) / denom };
-    });
-    Ok(result.into_pyarray(py))
-}
-
-fn mtci_4d<'py>(py: Python<'py>, rededge: PyReadonlyArray4<f64>, red: PyReadonlyArray4<f64>, green: PyReadonlyArray4<f64>) -> PyResult<&'py PyArray4<f64>> {
-    let rededge = rededge.as_array();
-    let red = red.as_array();
-    let green = green.as_array();
-    let shape = rededge.dim();
-    let mut result = Array4::<f64>::zeros(shape);
-    Zip::from(&mut result).and(&rededge).and(&red).and(&green).for_each(|r, &redge_v, &red_v, &green_v| {
-        let denom = red_v - green_v;
-        *r = if denom.abs() < EPSILON { 0.0 } else { (redge_v - red_v) / denom };
-    });
-    Ok(result.into_pyarray(py))
+        provided.push((role, value));
+    }
+
+    let mut objs: Vec<&PyAny> = Vec::with_capacity(plan.bands().len());
+    for &role in plan.bands() {
+        let (_, obj) = provided.iter().find(|(r, _)| *r == role).ok_or_else(|| {
+            let mut names: Vec<&str> = provided.iter().map(|(r, _)| r.as_str()).collect();
+            names.sort();
+            CoreError::InvalidArgument(format!(
+                "compute_indices requires band '{}'; provided bands: {:?}",
+                role.as_str(),
+                names
+            ))
+        })?;
+        objs.push(*obj);
+    }
+
+    let operands = Operand::extract_common(&objs)?;
+    Ok(kernels::dispatch!(all operands, |views| kernels::into_py(
+        py,
+        py.allow_threads(|| plan.evaluate(&views, scaling))?
+    )))
 }
 
 #[cfg(test)]
@@ -2522,7 +2387,7 @@ mod savi_nbr_tests {
         Python::with_gil(|py| {
             let nir = PyArray1::from_vec(py, vec![0.7_f64, 0.6_f64]);
             let red = PyArray1::from_vec(py, vec![0.2_f64, 0.3_f64]);
-            let out = savi(py, nir, red, 0.5).expect("SAVI 1D failed");
+            let out = savi(py, nir, red, 0.5, None, None).expect("SAVI 1D failed");
             let arr = out
                 .as_ref(py)
                 .downcast::<PyArray1<f64>>()
@@ -2556,7 +2421,7 @@ mod savi_nbr_tests {
             let red_vals = vec![vec![0.2_f64, 0.3_f64], vec![0.1_f64, 0.2_f64]];
             let nir = PyArray2::from_vec2(py, &nir_vals).unwrap();
             let red = PyArray2::from_vec2(py, &red_vals).unwrap();
-            let out = savi(py, nir, red, 0.5).expect("SAVI 2D failed");
+            let out = savi(py, nir, red, 0.5, None, None).expect("SAVI 2D failed");
             let arr = out
                 .as_ref(py)
                 .downcast::<PyArray2<f64>>()
@@ -2584,8 +2449,8 @@ mod savi_nbr_tests {
     fn test_savi_shape_mismatch() {
         Python::with_gil(|py| {
             let nir = PyArray1::from_vec(py, vec![0.7_f64, 0.6_f64]);
-            let red = PyArray1::from_vec(py, vec![0.2_f64]);
-            let err = savi(py, nir, red, 0.5).unwrap_err();
+            let red = PyArray1::from_vec(py, vec![0.2_f64, 0.3_f64, 0.4_f64]);
+            let err = savi(py, nir, red, 0.5, None, None).unwrap_err();
             assert!(err.to_string().contains("Shape mismatch"));
         });
     }
@@ -2596,7 +2461,7 @@ mod savi_nbr_tests {
         Python::with_gil(|py| {
             let nir = PyArray1::from_vec(py, vec![-0.25_f64]);
             let red = PyArray1::from_vec(py, vec![-0.25_f64]);
-            let out = savi(py, nir, red, 0.5).unwrap();
+            let out = savi(py, nir, red, 0.5, None, None).unwrap();
             let arr = out
                 .as_ref(py)
                 .downcast::<PyArray1<f64>>()
@@ -2611,7 +2476,7 @@ mod savi_nbr_tests {
         Python::with_gil(|py| {
             let nir = PyArray1::from_vec(py, vec![0.8_f64, 0.6_f64]);
             let swir2 = PyArray1::from_vec(py, vec![0.3_f64, 0.2_f64]);
-            let out = nbr(py, nir, swir2).unwrap();
+            let out = nbr(py, nir, swir2, None, None).unwrap();
             let arr = out
                 .as_ref(py)
                 .downcast::<PyArray1<f64>>()
@@ -2638,7 +2503,7 @@ mod savi_nbr_tests {
             let swir_vals = vec![vec![0.3_f64, 0.25_f64], vec![0.2_f64, 0.15_f64]];
             let nir = PyArray2::from_vec2(py, &nir_vals).unwrap();
             let swir2 = PyArray2::from_vec2(py, &swir_vals).unwrap();
-            let out = nbr(py, nir, swir2).unwrap();
+            let out = nbr(py, nir, swir2, None, None).unwrap();
             let arr = out
                 .as_ref(py)
                 .downcast::<PyArray2<f64>>()
@@ -2665,8 +2530,8 @@ mod savi_nbr_tests {
     fn test_nbr_shape_mismatch() {
         Python::with_gil(|py| {
             let nir = PyArray1::from_vec(py, vec![0.8_f64, 0.7_f64]);
-            let swir2 = PyArray1::from_vec(py, vec![0.3_f64]);
-            let err = nbr(py, nir, swir2).unwrap_err();
+            let swir2 = PyArray1::from_vec(py, vec![0.3_f64, 0.2_f64, 0.1_f64]);
+            let err = nbr(py, nir, swir2, None, None).unwrap_err();
             assert!(err.to_string().contains("Shape mismatch"));
         });
     }
@@ -2675,6 +2540,8 @@ mod savi_nbr_tests {
 mod tests {
     use super::*;
     use approx::assert_relative_eq;
+    use ndarray::{Array1, Array2, ArrayD, IxDyn};
+    use numpy::{IntoPyArray, PyArray1, PyArray2, PyArrayDyn};
 
     #[test]
     fn test_normalized_difference_basic() {
@@ -2687,7 +2554,8 @@ mod tests {
             let a_py = a.clone().into_pyarray(py);
             let b_py = b.clone().into_pyarray(py);
 
-            let result = normalized_difference_1d(py, a_py.readonly(), b_py.readonly()).unwrap();
+            let result_obj = normalized_difference(py, a_py, b_py, None, None).unwrap();
+            let result: &PyArray1<f64> = result_obj.extract(py).unwrap();
 
             let result_readonly = result.readonly();
             let result_array = result_readonly.as_array();
@@ -2712,7 +2580,8 @@ mod tests {
             let a_py = a.clone().into_pyarray(py);
             let b_py = b.clone().into_pyarray(py);
 
-            let result = normalized_difference_1d(py, a_py.readonly(), b_py.readonly()).unwrap();
+            let result_obj = normalized_difference(py, a_py, b_py, None, None).unwrap();
+            let result: &PyArray1<f64> = result_obj.extract(py).unwrap();
 
             let result_readonly = result.readonly();
             let result_array = result_readonly.as_array();
@@ -2737,7 +2606,8 @@ mod tests {
             let a_py = a.clone().into_pyarray(py);
             let b_py = b.clone().into_pyarray(py);
 
-            let result = normalized_difference_2d(py, a_py.readonly(), b_py.readonly()).unwrap();
+            let result_obj = normalized_difference(py, a_py, b_py, None, None).unwrap();
+            let result: &PyArray2<f64> = result_obj.extract(py).unwrap();
 
             let result_readonly = result.readonly();
             let result_array = result_readonly.as_array();
@@ -2759,7 +2629,7 @@ mod tests {
         Python::with_gil(|py| {
             let nir_py = nir.clone().into_pyarray(py);
             let red_py = red.clone().into_pyarray(py);
-            let ndvi_obj = ndvi(py, nir_py, red_py).unwrap();
+            let ndvi_obj = ndvi(py, nir_py, red_py, None, None).unwrap();
             let ndvi_arr: &PyArray1<f64> = ndvi_obj.extract(py).unwrap();
             let ndvi_read = ndvi_arr.readonly();
             let ndvi_vals = ndvi_read.as_array();
@@ -2777,7 +2647,7 @@ mod tests {
         Python::with_gil(|py| {
             let green_py = green.clone().into_pyarray(py);
             let nir_py = nir.clone().into_pyarray(py);
-            let ndwi_obj = ndwi(py, green_py, nir_py).unwrap();
+            let ndwi_obj = ndwi(py, green_py, nir_py, None, None).unwrap();
             let ndwi_arr: &PyArray1<f64> = ndwi_obj.extract(py).unwrap();
             let ndwi_read = ndwi_arr.readonly();
             let ndwi_vals = ndwi_read.as_array();
@@ -2805,7 +2675,8 @@ mod tests {
             let nir_py = nir.clone().into_pyarray(py);
             let red_py = red.clone().into_pyarray(py);
             let blue_py = blue.clone().into_pyarray(py);
-            let evi_obj = enhanced_vegetation_index(py, nir_py, red_py, blue_py).unwrap();
+            let evi_obj =
+                enhanced_vegetation_index(py, nir_py, red_py, blue_py, None, None).unwrap();
             let evi_arr: &PyArray1<f64> = evi_obj.extract(py).unwrap();
             let evi_read = evi_arr.readonly();
             let evi_vals = evi_read.as_array();
@@ -2830,7 +2701,8 @@ mod tests {
             let nir_py = nir.clone().into_pyarray(py);
             let red_py = red.clone().into_pyarray(py);
             let blue_py = blue.clone().into_pyarray(py);
-            let evi_obj = enhanced_vegetation_index(py, nir_py, red_py, blue_py).unwrap();
+            let evi_obj =
+                enhanced_vegetation_index(py, nir_py, red_py, blue_py, None, None).unwrap();
             let evi_arr: &PyArray2<f64> = evi_obj.extract(py).unwrap();
             let evi_read = evi_arr.readonly();
             let evi_vals = evi_read.as_array();
@@ -2845,4 +2717,460 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn test_normalized_difference_5d() {
+        let a = ArrayD::from_shape_fn(IxDyn(&[2, 2, 2, 2, 3]), |ix| 0.1 + ix[4] as f64 * 0.2);
+        let b = ArrayD::from_elem(IxDyn(&[2, 2, 2, 2, 3]), 0.1);
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let out = normalized_difference(
+                py,
+                a.clone().into_pyarray(py),
+                b.into_pyarray(py),
+                None,
+                None,
+            )
+            .unwrap();
+            let arr: &PyArrayDyn<f64> = out.extract(py).unwrap();
+            let vals = arr.to_owned_array();
+            assert_eq!(vals.shape(), &[2, 2, 2, 2, 3]);
+            for (&o, &av) in vals.iter().zip(a.iter()) {
+                assert_relative_eq!(o, (av - 0.1) / (av + 0.1), epsilon = 1e-12);
+            }
+        });
+    }
+
+    #[test]
+    fn test_evi_broadcasts_size_one_axes() {
+        // (time=2, y=1, x=3) NIR against a per-scene (2, 1, 1) red/blue constant.
+        let nir =
+            ArrayD::from_shape_vec(IxDyn(&[2, 1, 3]), vec![0.5, 0.6, 0.7, 0.4, 0.5, 0.6]).unwrap();
+        let red = ArrayD::from_shape_vec(IxDyn(&[2, 1, 1]), vec![0.2, 0.1]).unwrap();
+        let blue = ArrayD::from_elem(IxDyn(&[1]), 0.05);
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let out = enhanced_vegetation_index(
+                py,
+                nir.clone().into_pyarray(py),
+                red.into_pyarray(py),
+                blue.into_pyarray(py),
+                None,
+                None,
+            )
+            .unwrap();
+            let arr: &PyArrayDyn<f64> = out.extract(py).unwrap();
+            let vals = arr.to_owned_array();
+            assert_eq!(vals.shape(), &[2, 1, 3]);
+            assert_relative_eq!(vals[[1, 0, 2]], evi_px(0.6, 0.1, 0.05), epsilon = 1e-15);
+        });
+    }
+
+    #[test]
+    fn test_ndvi_float32_in_float32_out() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let nir = PyArray1::from_vec(py, vec![0.8_f32, 0.7, 0.6]);
+            let red = PyArray1::from_vec(py, vec![0.2_f32, 0.1, 0.3]);
+            let out = ndvi(py, nir, red, None, None).unwrap();
+            let arr: &PyArrayDyn<f32> = out.extract(py).unwrap();
+            let vals = arr.to_owned_array();
+            for (&o, (&n, &r)) in vals
+                .iter()
+                .zip([0.8, 0.7, 0.6].iter().zip(&[0.2, 0.1, 0.3]))
+            {
+                assert_relative_eq!(o as f64, normalized_difference_px(n, r), epsilon = 1e-6);
+            }
+        });
+    }
+
+    #[test]
+    fn test_ndvi_uint16_dn_with_scale_offset() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            // Sentinel-2 L2A baseline 04.00: reflectance = DN / 10000 - 0.1
+            let nir = PyArray1::from_vec(py, vec![9000_u16, 4000]);
+            let red = PyArray1::from_vec(py, vec![3000_u16, 2000]);
+            let out = ndvi(py, nir, red, Some(1e-4), Some(-0.1)).unwrap();
+            let arr: &PyArrayDyn<f32> = out.extract(py).unwrap();
+            let vals = arr.to_owned_array();
+            assert_relative_eq!(vals[[0]] as f64, (0.8 - 0.2) / (0.8 + 0.2), epsilon = 1e-6);
+            assert_relative_eq!(vals[[1]] as f64, (0.3 - 0.1) / (0.3 + 0.1), epsilon = 1e-6);
+        });
+    }
+}
+
+#[cfg(test)]
+mod catalogue_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use ndarray::{array, Array1};
+    use numpy::{IntoPyArray, PyArray2, PyArrayDyn};
+
+    #[test]
+    fn test_water_and_soil_formulas() {
+        let (blue, green, red, nir, swir1, swir2) = (0.04, 0.08, 0.06, 0.30, 0.20, 0.12);
+        assert_relative_eq!(
+            awei_nsh_px(green, swir1, nir, swir2),
+            4.0 * (0.08 - 0.20) - (0.25 * 0.30 + 2.75 * 0.12),
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            awei_sh_px(blue, green, nir, swir1, swir2),
+            0.04 + 2.5 * 0.08 - 1.5 * (0.30 + 0.20) - 0.25 * 0.12,
+            epsilon = 1e-12
+        );
+        assert_relative_eq!(
+            bsi_px(swir1, red, nir, blue),
+            (0.26 - 0.34) / (0.26 + 0.34),
+            epsilon = 1e-12
+        );
+        assert_eq!(bsi_px(0.0, 0.0, 0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_kndvi_sigma() {
+        let (nir, red) = (0.45, 0.08);
+        let ndvi = normalized_difference_px(nir, red);
+        assert_relative_eq!(
+            kndvi_px(nir, red, None),
+            (ndvi * ndvi).tanh(),
+            epsilon = 1e-15
+        );
+        // The default sigma is 0.5 * (NIR + Red).
+        assert_relative_eq!(
+            kndvi_px(nir, red, Some(0.5 * (nir + red))),
+            kndvi_px(nir, red, None),
+            epsilon = 1e-12
+        );
+        assert!(kndvi_px(nir, red, Some(0.1)) > kndvi_px(nir, red, Some(1.0)));
+        assert_eq!(kndvi_px(0.0, 0.0, None), 0.0);
+    }
+
+    #[test]
+    fn test_pigment_formulas_guard_denominators() {
+        assert_eq!(sipi_px(0.3, 0.05, 0.3), 0.0);
+        assert_eq!(psri_px(0.1, 0.05, 0.0), 0.0);
+        assert_eq!(vari_px(0.1, 0.1, 0.2), 0.0);
+        assert_eq!(tcari_osavi_px(0.2, 0.0, 0.1, 0.4), 0.0);
+        assert_eq!(tcari_osavi_px(0.2, 0.1, 0.1, 0.1), 0.0);
+        assert_relative_eq!(psri_px(0.1, 0.05, 0.4), 0.125, epsilon = 1e-12);
+        assert_relative_eq!(
+            arvi_px(0.4, 0.1, 0.05),
+            normalized_difference_px(0.4, 0.15),
+            epsilon = 1e-15
+        );
+        let tcari = 3.0 * ((0.2 - 0.05) - 0.2 * (0.2 - 0.08) * (0.2 / 0.05));
+        let osavi = 1.16 * (0.4 - 0.05) / (0.4 + 0.05 + 0.16);
+        assert_relative_eq!(
+            tcari_osavi_px(0.2, 0.05, 0.08, 0.4),
+            tcari / osavi,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_awei_sh_broadcasts_five_bands() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let blue = array![[0.04f32, 0.05], [0.06, 0.07]].into_pyarray(py);
+            let green = Array1::from_vec(vec![0.08f32, 0.09]).into_pyarray(py);
+            let nir = array![[0.3f32], [0.2]].into_pyarray(py);
+            let swir1 = array![[0.2f32, 0.1], [0.15, 0.05]].into_pyarray(py);
+            let swir2 = array![[0.1f32]].into_pyarray(py);
+            let out = awei_sh(py, blue, green, nir, swir1, swir2, None, None).unwrap();
+            let out: &PyArray2<f32> = out.extract(py).unwrap();
+            let out = out.readonly();
+            let expected = awei_sh_px(0.07, 0.09, 0.2, 0.05, 0.1);
+            assert_relative_eq!(out.as_array()[[1, 1]] as f64, expected, epsilon = 1e-6);
+        });
+    }
+
+    #[test]
+    fn test_kndvi_rejects_bad_sigma() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let nir = Array1::from_vec(vec![0.4]).into_dyn().into_pyarray(py);
+            let red = Array1::from_vec(vec![0.1]).into_dyn().into_pyarray(py);
+            assert!(kndvi(py, nir, red, Some(0.0), None, None).is_err());
+            let out = kndvi(py, nir, red, Some(0.15), None, None).unwrap();
+            let out: &PyArrayDyn<f64> = out.extract(py).unwrap();
+            let expected = (1.0f64).tanh();
+            assert_relative_eq!(out.readonly().as_array()[0], expected, epsilon = 1e-12);
+        });
+    }
+
+    #[test]
+    fn test_catalogue_registered() {
+        for name in [
+            "ndbi",
+            "mndwi",
+            "awei_nsh",
+            "awei_sh",
+            "bsi",
+            "kndvi",
+            "nirv",
+            "sipi",
+            "psri",
+            "arvi",
+            "vari",
+            "tcari_osavi",
+            "ndci",
+        ] {
+            assert!(lookup_index(name).is_ok(), "{name} missing from registry");
+        }
+        let p = FusedPlan::new(&["awei_sh".to_string(), "ndbi".to_string()]).unwrap();
+        assert_eq!(p.bands().len(), 5);
+    }
+}
+
+#[cfg(test)]
+mod fused_tests {
+    use super::*;
+    use ndarray::{array, ArrayD};
+
+    fn plan(names: &[&str]) -> FusedPlan {
+        FusedPlan::new(&names.iter().map(|n| n.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn test_plan_shares_bands() {
+        let p = plan(&["NDVI", "evi", "ndwi"]);
+        assert_eq!(
+            p.bands(),
+            &[
+                BandRole::Nir,
+                BandRole::Red,
+                BandRole::Blue,
+                BandRole::Green
+            ]
+        );
+        assert!(FusedPlan::new(&["foo".to_string()]).is_err());
+        assert!(FusedPlan::new(&[]).is_err());
+    }
+
+    #[test]
+    fn test_fused_matches_single_index_formulas() {
+        let nir = array![[0.8, 0.0, 0.45], [0.3, 0.62, 0.5]].into_dyn();
+        let red = array![[0.2, 0.0, 0.5], [0.31, 0.1, 0.05]].into_dyn();
+        let blue = array![[0.05]].into_dyn();
+        let p = plan(&["ndvi", "evi", "savi", "msavi"]);
+        let out: ArrayD<f64> = p
+            .evaluate(&[nir.view(), red.view(), blue.view()], Scaling::IDENTITY)
+            .unwrap();
+        assert_eq!(out.shape(), &[4, 2, 3]);
+        for ((ix, &n), &r) in nir.indexed_iter().zip(red.iter()) {
+            let (y, x) = (ix[0], ix[1]);
+            // Bit-for-bit equality with the single-index kernels.
+            assert_eq!(out[[0, y, x]], normalized_difference_px(n, r));
+            assert_eq!(out[[1, y, x]], evi_px(n, r, 0.05));
+            assert_eq!(out[[2, y, x]], savi_px(n, r, 0.5));
+            assert_eq!(out[[3, y, x]], msavi_px(n, r));
+        }
+    }
+
+    #[test]
+    fn test_fused_blocks_and_native_dtypes() {
+        let len = FUSED_BLOCK * 2 + 17;
+        let nir = ArrayD::from_shape_fn(IxDyn(&[len]), |ix| 5000 + (ix[0] % 3000) as u16);
+        let swir2 = ArrayD::from_shape_fn(IxDyn(&[len]), |ix| 1000 + (ix[0] % 700) as u16);
+        let scaling = Scaling::from_args(Some(1e-4), Some(-0.1)).unwrap();
+        let out: ArrayD<f32> = plan(&["nbr"])
+            .evaluate(&[nir.view(), swir2.view()], scaling)
+            .unwrap();
+        for i in [0, FUSED_BLOCK - 1, FUSED_BLOCK, len - 1] {
+            let expected = normalized_difference_px(
+                scaling.apply(nir[[i]] as f64),
+                scaling.apply(swir2[[i]] as f64),
+            );
+            assert_eq!(out[[0, i]], expected as f32);
+        }
+    }
+
+    #[test]
+    fn test_fused_shape_mismatch() {
+        let a = ArrayD::<f64>::zeros(IxDyn(&[2]));
+        let b = ArrayD::<f64>::zeros(IxDyn(&[3]));
+        let err = plan(&["ndvi"])
+            .evaluate(&[a.view(), b.view()], Scaling::IDENTITY)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Shape mismatch for compute_indices"));
+    }
+}
+
+#[cfg(test)]
+mod registry_tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_entries_are_consistent() {
+        for (i, index) in INDEX_REGISTRY.iter().enumerate() {
+            assert_eq!(index.name, index.name.to_ascii_lowercase());
+            assert!(
+                INDEX_REGISTRY[..i]
+                    .iter()
+                    .all(|other| other.name != index.name),
+                "duplicate registry entry {}",
+                index.name
+            );
+            assert!(!index.bands.is_empty() && index.bands.len() <= MAX_INDEX_BANDS);
+            assert!(index.valid_range.0 < index.valid_range.1, "{}", index.name);
+            assert!(!index.formula.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_registry_compute_matches_kernels() {
+        let b = [0.52, 0.11, 0.07, 0.31];
+        let get = |name: &str| lookup_index(name).unwrap().compute;
+        assert_eq!(get("NDVI")(&b[..2]), normalized_difference_px(b[0], b[1]));
+        assert_eq!(get("savi")(&b[..2]), savi_px(b[0], b[1], 0.5));
+        assert_eq!(get("mtci")(&b[..3]), mtci_px(b[0], b[1], b[2]));
+        assert_eq!(get("rbr")(&b), rbr_px(b[0], b[1], b[2], b[3]));
+        assert_eq!(get("dnbr")(&b), get("delta_nbr")(&b));
+        let ndvi = get("ndvi")(&b[..2]);
+        let range = lookup_index("ndvi").unwrap().valid_range;
+        assert!(range.0 <= ndvi && ndvi <= range.1);
+        let err = lookup_index("foo").err().expect("unknown index");
+        assert!(err.to_string().contains("Unknown index 'foo'"));
+    }
+
+    #[test]
+    fn test_band_role_parse() {
+        assert_eq!(BandRole::parse("NIR").unwrap(), BandRole::Nir);
+        assert_eq!(BandRole::parse("post_swir2").unwrap(), BandRole::PostSwir2);
+        for role in BandRole::ALL {
+            assert_eq!(BandRole::parse(role.as_str()).unwrap(), role);
+        }
+        match BandRole::parse("nri").unwrap_err() {
+            CoreError::UnknownBandRole { role, valid } => {
+                assert_eq!(role, "nri");
+                assert_eq!(valid.len(), BandRole::ALL.len());
+                assert!(valid.contains(&"rededge"));
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod cva_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use ndarray::{array, Array1, Array3};
+
+    fn pair() -> (Array3<f64>, Array3<f64>) {
+        let pre = Array3::from_elem((2, 1, 4), 0.2);
+        let mut post = pre.clone();
+        post[[0, 0, 0]] = 0.5; // band 0 up
+        post[[1, 0, 1]] = 0.6; // band 1 up
+        post[[0, 0, 2]] = -0.1; // band 0 down
+        post[[1, 0, 3]] = f64::NAN;
+        (pre, post)
+    }
+
+    #[test]
+    fn test_two_band_magnitude_and_angle() {
+        let (pre, post) = pair();
+        let (mag, dir) = change_vectors(
+            pre.view().into_dyn(),
+            post.view().into_dyn(),
+            CvaDirection::Angle,
+            Scaling::IDENTITY,
+        )
+        .unwrap();
+        let DirectionMap::Angle(angle) = dir else {
+            panic!("expected angles")
+        };
+        assert_relative_eq!(mag[[0, 0]], 0.3, epsilon = 1e-12);
+        assert_relative_eq!(mag[[0, 1]], 0.4, epsilon = 1e-12);
+        assert_relative_eq!(angle[[0, 0]], 0.0, epsilon = 1e-9);
+        assert_relative_eq!(angle[[0, 1]], 90.0, epsilon = 1e-9);
+        assert_relative_eq!(angle[[0, 2]], 180.0, epsilon = 1e-9);
+        assert!(mag[[0, 3]].is_nan() && angle[[0, 3]].is_nan());
+    }
+
+    #[test]
+    fn test_diagonal_angle_and_sectors() {
+        let pre = Array3::<f64>::zeros((3, 1, 2));
+        let mut post = pre.clone();
+        post.slice_mut(ndarray::s![.., 0, 0]).fill(0.1);
+        post[[2, 0, 1]] = -0.1;
+        let (_, dir) = change_vectors(
+            pre.view().into_dyn(),
+            post.view().into_dyn(),
+            CvaDirection::Angle,
+            Scaling::IDENTITY,
+        )
+        .unwrap();
+        let DirectionMap::Angle(angle) = dir else {
+            panic!("expected angles")
+        };
+        assert_relative_eq!(angle[[0, 0]], 0.0, epsilon = 1e-6);
+        let expected = (-1.0 / 3f64.sqrt()).acos().to_degrees();
+        assert_relative_eq!(angle[[0, 1]], expected, epsilon = 1e-9);
+
+        let (pre, post) = pair();
+        let (_, dir) = change_vectors(
+            pre.view().into_dyn(),
+            post.view().into_dyn(),
+            CvaDirection::Sector,
+            Scaling::IDENTITY,
+        )
+        .unwrap();
+        let DirectionMap::Sector(sector) = dir else {
+            panic!("expected sectors")
+        };
+        assert_eq!(sector, array![[2, 3, 1, 0]].into_dyn());
+    }
+
+    #[test]
+    fn test_automatic_thresholds_split_bimodal_magnitudes() {
+        // 900 small and 100 large magnitudes.
+        let mag: Array1<f64> = (0..1000)
+            .map(|i| {
+                let jitter = (i % 10) as f64 * 0.01;
+                if i < 900 {
+                    0.05 + jitter
+                } else {
+                    1.0 + jitter
+                }
+            })
+            .collect();
+        let mag = mag.into_dyn();
+        for method in [ChangeThreshold::Otsu, ChangeThreshold::Kittler] {
+            let t = method.resolve(&mag).unwrap();
+            assert!(t > 0.14 && t < 1.0, "{:?} gave {}", method, t);
+        }
+        assert_eq!(ChangeThreshold::Value(0.3).resolve(&mag).unwrap(), 0.3);
+        let empty = ArrayD::from_elem(IxDyn(&[3]), f64::NAN);
+        assert!(matches!(
+            ChangeThreshold::Otsu.resolve(&empty),
+            Err(CoreError::NotEnoughData(_))
+        ));
+    }
+
+    #[test]
+    fn test_shape_validation() {
+        let (pre, _) = pair();
+        let other = Array3::<f64>::zeros((2, 1, 3));
+        assert!(change_vectors(
+            pre.view().into_dyn(),
+            other.view().into_dyn(),
+            CvaDirection::Angle,
+            Scaling::IDENTITY
+        )
+        .is_err());
+        let single = Array3::<f64>::zeros((1, 1, 3));
+        assert!(change_vectors(
+            single.view().into_dyn(),
+            single.view().into_dyn(),
+            CvaDirection::Angle,
+            Scaling::IDENTITY
+        )
+        .is_err());
+        assert!(CvaDirection::parse("polar").is_err());
+    }
 }
@@ -0,0 +1,721 @@
+//! Shared element-wise kernel layer.
+//!
+//! Every spectral index in `indices.rs` and every mask in `masking.rs` is a
+//! per-pixel function of one or more co-registered arrays. This module owns
+//! the plumbing around those functions so each caller only supplies the
+//! scalar formula:
+//!
+//! - Inputs are viewed as `ArrayViewD<T>` for any [`Sample`] type, so any rank
+//!   is accepted (including `(time, band, y, x, ensemble)` stacks) and float32
+//!   or uint16 rasters are read in place instead of being upcast to float64.
+//!   Formulas are always evaluated in f64; the result is stored as
+//!   [`Sample::Out`] (f64 for f64 input, f32 for f32 and u16 input).
+//! - Operands are broadcast NumPy-style: shapes are right-aligned and axes of
+//!   length 1 (or missing leading axes) stretch to match the other operands.
+//! - Evaluation runs through `Zip::par_for_each` on the rayon pool.
+//! - The PyO3 helpers (`Operand`, `py_map1`..`py_map5`)
+//!   borrow float64/float32/uint16 arrays natively, coerce any other numeric
+//!   dtype to float64, apply an optional DN [`Scaling`], release the GIL for
+//!   the computation and hand back a NumPy array, which keeps the
+//!   `#[pyfunction]` wrappers one-liners.
+
+use crate::CoreError;
+use ndarray::{ArrayD, ArrayView1, ArrayViewD, Axis, IxDyn, Zip};
+use numpy::{Element, IntoPyArray, PyReadonlyArrayDyn};
+use pyo3::prelude::*;
+
+/// Element types the kernels read natively.
+pub trait Sample: Copy + Send + Sync + 'static {
+    /// Floating type of results computed from this sample type.
+    type Out: Real;
+
+    fn to_f64(self) -> f64;
+}
+
+/// Floating result types.
+pub trait Real: Sample<Out = Self> {
+    fn from_f64(v: f64) -> Self;
+}
+
+impl Sample for f64 {
+    type Out = f64;
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl Real for f64 {
+    #[inline]
+    fn from_f64(v: f64) -> Self {
+        v
+    }
+}
+
+impl Sample for f32 {
+    type Out = f32;
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Real for f32 {
+    #[inline]
+    fn from_f64(v: f64) -> Self {
+        v as f32
+    }
+}
+
+/// Integer digital numbers (e.g. Sentinel-2 / Landsat L2 reflectance).
+/// Results are float32, which holds scaled reflectance with room to spare.
+impl Sample for u16 {
+    type Out = f32;
+
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+/// Linear DN-to-physical conversion, `value * scale + offset`, applied to
+/// every input sample before a formula is evaluated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scaling {
+    pub scale: f64,
+    pub offset: f64,
+}
+
+impl Default for Scaling {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Scaling {
+    pub const IDENTITY: Scaling = Scaling {
+        scale: 1.0,
+        offset: 0.0,
+    };
+
+    /// Build from optional Python arguments (`None` means 1.0 / 0.0).
+    pub fn from_args(scale: Option<f64>, offset: Option<f64>) -> Result<Self, CoreError> {
+        let scaling = Scaling {
+            scale: scale.unwrap_or(1.0),
+            offset: offset.unwrap_or(0.0),
+        };
+        if !scaling.scale.is_finite() || !scaling.offset.is_finite() {
+            return Err(CoreError::InvalidArgument(format!(
+                "scale and offset must be finite, got scale={}, offset={}",
+                scaling.scale, scaling.offset
+            )));
+        }
+        Ok(scaling)
+    }
+
+    /// Apply the conversion. The identity is a no-op so unscaled results
+    /// stay bit-identical.
+    #[inline]
+    pub fn apply(self, v: f64) -> f64 {
+        if self == Self::IDENTITY {
+            v
+        } else {
+            v * self.scale + self.offset
+        }
+    }
+}
+
+/// Compute the NumPy broadcast shape of `shapes`, or `None` if they are
+/// incompatible.
+pub fn broadcast_shape(shapes: &[&[usize]]) -> Option<Vec<usize>> {
+    let ndim = shapes.iter().map(|s| s.len()).max().unwrap_or(0);
+    let mut out = vec![1usize; ndim];
+    for shape in shapes {
+        let offset = ndim - shape.len();
+        for (axis, &len) in shape.iter().enumerate() {
+            let slot = &mut out[offset + axis];
+            if *slot == 1 {
+                *slot = len;
+            } else if len != 1 && len != *slot {
+                return None;
+            }
+        }
+    }
+    Some(out)
+}
+
+/// Like [`broadcast_shape`], but reports incompatible shapes as a
+/// `CoreError::InvalidArgument` naming the operation (`label`).
+pub fn broadcast_shape_for(label: &str, shapes: &[&[usize]]) -> Result<Vec<usize>, CoreError> {
+    broadcast_shape(shapes).ok_or_else(|| {
+        CoreError::InvalidArgument(format!(
+            "Shape mismatch for {} inputs: shapes {:?} cannot be broadcast together",
+            label, shapes
+        ))
+    })
+}
+
+/// Resolve a possibly negative `band_axis` against a cube of `ndim`
+/// dimensions.
+pub fn resolve_band_axis(band_axis: isize, ndim: usize) -> Result<usize, CoreError> {
+    let axis = if band_axis < 0 {
+        band_axis + ndim as isize
+    } else {
+        band_axis
+    };
+    if axis < 0 || axis >= ndim as isize {
+        return Err(CoreError::InvalidArgument(format!(
+            "band_axis {} is out of range for a {}-D cube",
+            band_axis, ndim
+        )));
+    }
+    Ok(axis as usize)
+}
+
+fn stretch<'a, 'b, T>(view: &'b ArrayViewD<'a, T>, shape: &[usize]) -> ArrayViewD<'b, T> {
+    view.broadcast(IxDyn(shape))
+        .expect("shape was validated by broadcast_shape")
+}
+
+fn output<R: Real>(shape: &[usize]) -> ArrayD<R> {
+    ArrayD::from_elem(IxDyn(shape), R::from_f64(0.0))
+}
+
+/// Apply `f` to every element of `a`.
+pub fn map1<A, F>(a: ArrayViewD<A>, f: F) -> ArrayD<A::Out>
+where
+    A: Sample,
+    F: Fn(f64) -> f64 + Sync + Send,
+{
+    let mut out = output::<A::Out>(a.shape());
+    Zip::from(&mut out)
+        .and(&a)
+        .par_for_each(|r, &a| *r = Real::from_f64(f(a.to_f64())));
+    out
+}
+
+/// Apply `f` element-wise over `a` and `b` after broadcasting.
+/// The result type follows the first operand.
+pub fn map2<A, B, F>(
+    label: &str,
+    a: ArrayViewD<A>,
+    b: ArrayViewD<B>,
+    f: F,
+) -> Result<ArrayD<A::Out>, CoreError>
+where
+    A: Sample,
+    B: Sample,
+    F: Fn(f64, f64) -> f64 + Sync + Send,
+{
+    let shape = broadcast_shape_for(label, &[a.shape(), b.shape()])?;
+    let (a, b) = (stretch(&a, &shape), stretch(&b, &shape));
+    let mut out = output::<A::Out>(&shape);
+    Zip::from(&mut out)
+        .and(&a)
+        .and(&b)
+        .par_for_each(|r, &a, &b| *r = Real::from_f64(f(a.to_f64(), b.to_f64())));
+    Ok(out)
+}
+
+/// Apply `f` element-wise over `a`, `b` and `c` after broadcasting.
+pub fn map3<A, B, C, F>(
+    label: &str,
+    a: ArrayViewD<A>,
+    b: ArrayViewD<B>,
+    c: ArrayViewD<C>,
+    f: F,
+) -> Result<ArrayD<A::Out>, CoreError>
+where
+    A: Sample,
+    B: Sample,
+    C: Sample,
+    F: Fn(f64, f64, f64) -> f64 + Sync + Send,
+{
+    let shape = broadcast_shape_for(label, &[a.shape(), b.shape(), c.shape()])?;
+    let (a, b, c) = (
+        stretch(&a, &shape),
+        stretch(&b, &shape),
+        stretch(&c, &shape),
+    );
+    let mut out = output::<A::Out>(&shape);
+    Zip::from(&mut out)
+        .and(&a)
+        .and(&b)
+        .and(&c)
+        .par_for_each(|r, &a, &b, &c| *r = Real::from_f64(f(a.to_f64(), b.to_f64(), c.to_f64())));
+    Ok(out)
+}
+
+/// Apply `f` element-wise over four operands after broadcasting.
+pub fn map4<A, B, C, D, F>(
+    label: &str,
+    a: ArrayViewD<A>,
+    b: ArrayViewD<B>,
+    c: ArrayViewD<C>,
+    d: ArrayViewD<D>,
+    f: F,
+) -> Result<ArrayD<A::Out>, CoreError>
+where
+    A: Sample,
+    B: Sample,
+    C: Sample,
+    D: Sample,
+    F: Fn(f64, f64, f64, f64) -> f64 + Sync + Send,
+{
+    let shape = broadcast_shape_for(label, &[a.shape(), b.shape(), c.shape(), d.shape()])?;
+    let (a, b, c, d) = (
+        stretch(&a, &shape),
+        stretch(&b, &shape),
+        stretch(&c, &shape),
+        stretch(&d, &shape),
+    );
+    let mut out = output::<A::Out>(&shape);
+    Zip::from(&mut out)
+        .and(&a)
+        .and(&b)
+        .and(&c)
+        .and(&d)
+        .par_for_each(|r, &a, &b, &c, &d| {
+            *r = Real::from_f64(f(a.to_f64(), b.to_f64(), c.to_f64(), d.to_f64()))
+        });
+    Ok(out)
+}
+
+/// Apply `f` element-wise over five operands after broadcasting.
+#[allow(clippy::too_many_arguments)]
+pub fn map5<A, B, C, D, E, F>(
+    label: &str,
+    a: ArrayViewD<A>,
+    b: ArrayViewD<B>,
+    c: ArrayViewD<C>,
+    d: ArrayViewD<D>,
+    e: ArrayViewD<E>,
+    f: F,
+) -> Result<ArrayD<A::Out>, CoreError>
+where
+    A: Sample,
+    B: Sample,
+    C: Sample,
+    D: Sample,
+    E: Sample,
+    F: Fn(f64, f64, f64, f64, f64) -> f64 + Sync + Send,
+{
+    let shape = broadcast_shape_for(
+        label,
+        &[a.shape(), b.shape(), c.shape(), d.shape(), e.shape()],
+    )?;
+    let (a, b, c, d, e) = (
+        stretch(&a, &shape),
+        stretch(&b, &shape),
+        stretch(&c, &shape),
+        stretch(&d, &shape),
+        stretch(&e, &shape),
+    );
+    let mut out = output::<A::Out>(&shape);
+    Zip::from(&mut out)
+        .and(&a)
+        .and(&b)
+        .and(&c)
+        .and(&d)
+        .and(&e)
+        .par_for_each(|r, &a, &b, &c, &d, &e| {
+            *r = Real::from_f64(f(
+                a.to_f64(),
+                b.to_f64(),
+                c.to_f64(),
+                d.to_f64(),
+                e.to_f64(),
+            ))
+        });
+    Ok(out)
+}
+
+/// Reduce the leading (time) axis: `f` receives each `(t,)` series and the
+/// result has the input shape minus axis 0.
+pub fn reduce_time<A, F>(a: ArrayViewD<A>, f: F) -> Result<ArrayD<A::Out>, CoreError>
+where
+    A: Sample,
+    F: Fn(ArrayView1<A>) -> f64 + Sync + Send,
+{
+    if a.ndim() == 0 {
+        return Err(CoreError::InvalidArgument(
+            "Expected an array with a leading time axis, got a 0-d array".to_string(),
+        ));
+    }
+    let mut out = output::<A::Out>(&a.shape()[1..]);
+    Zip::from(&mut out)
+        .and(a.lanes(Axis(0)))
+        .par_for_each(|r, series| *r = Real::from_f64(f(series)));
+    Ok(out)
+}
+
+//
+// PyO3 glue.
+//
+
+/// Coerce a Python object to a readonly float64 NumPy array of any rank.
+/// Tries direct extraction; on failure, attempts `.astype("float64")`.
+pub fn coerce_dyn<'py>(obj: &'py PyAny) -> PyResult<PyReadonlyArrayDyn<'py, f64>> {
+    astype(obj, "float64")
+}
+
+fn astype<'py, T: Element>(obj: &'py PyAny, dtype: &str) -> PyResult<PyReadonlyArrayDyn<'py, T>> {
+    if let Ok(arr) = obj.extract::<PyReadonlyArrayDyn<T>>() {
+        return Ok(arr);
+    }
+    obj.call_method1("astype", (dtype,))
+        .and_then(|coerced| coerced.extract::<PyReadonlyArrayDyn<T>>())
+        .map_err(|_| {
+            CoreError::InvalidArgument(format!(
+                "Expected a numeric NumPy array, got {}",
+                obj.get_type().name().unwrap_or("unknown type")
+            ))
+            .into()
+        })
+}
+
+/// A NumPy input borrowed in one of the dtypes the kernels read natively.
+pub enum Operand<'py> {
+    F64(PyReadonlyArrayDyn<'py, f64>),
+    F32(PyReadonlyArrayDyn<'py, f32>),
+    U16(PyReadonlyArrayDyn<'py, u16>),
+}
+
+impl<'py> Operand<'py> {
+    /// Borrow float64, float32 and uint16 arrays without copying; any other
+    /// numeric dtype is coerced to float64.
+    pub fn extract(obj: &'py PyAny) -> PyResult<Self> {
+        if let Ok(arr) = obj.extract::<PyReadonlyArrayDyn<f64>>() {
+            Ok(Operand::F64(arr))
+        } else if let Ok(arr) = obj.extract::<PyReadonlyArrayDyn<f32>>() {
+            Ok(Operand::F32(arr))
+        } else if let Ok(arr) = obj.extract::<PyReadonlyArrayDyn<u16>>() {
+            Ok(Operand::U16(arr))
+        } else {
+            coerce_dyn(obj).map(Operand::F64)
+        }
+    }
+
+    /// Borrow as a float64 view, if that is the native dtype.
+    pub fn as_f64(&self) -> Option<ArrayViewD<'_, f64>> {
+        match self {
+            Operand::F64(arr) => Some(arr.as_array()),
+            _ => None,
+        }
+    }
+
+    /// Borrow as a float32 view, if that is the native dtype.
+    pub fn as_f32(&self) -> Option<ArrayViewD<'_, f32>> {
+        match self {
+            Operand::F32(arr) => Some(arr.as_array()),
+            _ => None,
+        }
+    }
+
+    /// Borrow as a uint16 view, if that is the native dtype.
+    pub fn as_u16(&self) -> Option<ArrayViewD<'_, u16>> {
+        match self {
+            Operand::U16(arr) => Some(arr.as_array()),
+            _ => None,
+        }
+    }
+
+    /// Extract several inputs as a single native dtype. Inputs of differing
+    /// dtypes are promoted (with a copy): to float32 when every input is
+    /// float32 or uint16, to float64 otherwise.
+    pub fn extract_common(objs: &[&'py PyAny]) -> PyResult<Vec<Self>> {
+        let ops = objs
+            .iter()
+            .map(|obj| Operand::extract(obj))
+            .collect::<PyResult<Vec<_>>>()?;
+        let first = std::mem::discriminant(&ops[0]);
+        if ops.iter().all(|op| std::mem::discriminant(op) == first) {
+            return Ok(ops);
+        }
+        let to_f32 = !ops.iter().any(|op| matches!(op, Operand::F64(_)));
+        objs.iter()
+            .zip(ops)
+            .map(|(obj, op)| match (to_f32, op) {
+                (true, Operand::F32(arr)) => Ok(Operand::F32(arr)),
+                (true, _) => astype(obj, "float32").map(Operand::F32),
+                (false, Operand::F64(arr)) => Ok(Operand::F64(arr)),
+                (false, _) => coerce_dyn(obj).map(Operand::F64),
+            })
+            .collect()
+    }
+}
+
+/// Evaluate `$body` for the native dtype of an [`Operand`], with `$view`
+/// bound to its array view, so generic kernels are instantiated for f64, f32
+/// and u16 without spelling out the match. The `all` form binds `$views` to
+/// a `Vec` of views of the operands in `$ops`, which share one dtype as
+/// returned by [`Operand::extract_common`].
+macro_rules! dispatch {
+    (all $ops:ident, |$views:ident| $body:expr) => {
+        match &$ops[0] {
+            $crate::kernels::Operand::F64(_) => {
+                let $views: Vec<_> = $ops.iter().map(|op| op.as_f64().unwrap()).collect();
+                $body
+            }
+            $crate::kernels::Operand::F32(_) => {
+                let $views: Vec<_> = $ops.iter().map(|op| op.as_f32().unwrap()).collect();
+                $body
+            }
+            $crate::kernels::Operand::U16(_) => {
+                let $views: Vec<_> = $ops.iter().map(|op| op.as_u16().unwrap()).collect();
+                $body
+            }
+        }
+    };
+    ($op:expr, |$view:ident| $body:expr) => {
+        match $op {
+            $crate::kernels::Operand::F64(arr) => {
+                let $view = arr.as_array();
+                $body
+            }
+            $crate::kernels::Operand::F32(arr) => {
+                let $view = arr.as_array();
+                $body
+            }
+            $crate::kernels::Operand::U16(arr) => {
+                let $view = arr.as_array();
+                $body
+            }
+        }
+    };
+}
+pub(crate) use dispatch;
+
+/// Hand an owned result back to Python as a NumPy array.
+pub fn into_py<R: Element>(py: Python<'_>, out: ArrayD<R>) -> PyObject {
+    out.into_pyarray(py).into_py(py)
+}
+
+/// PyO3 entry point for [`map1`]. Masks compare raw codes, so no scaling
+/// is applied here.
+pub fn py_map1<F>(py: Python<'_>, a: &PyAny, f: F) -> PyResult<PyObject>
+where
+    F: Fn(f64) -> f64 + Sync + Send,
+{
+    Ok(dispatch!(Operand::extract(a)?, |a| into_py(
+        py,
+        py.allow_threads(|| map1(a, f))
+    )))
+}
+
+/// PyO3 entry point for [`map2`].
+pub fn py_map2<F>(
+    py: Python<'_>,
+    label: &str,
+    a: &PyAny,
+    b: &PyAny,
+    scaling: Scaling,
+    f: F,
+) -> PyResult<PyObject>
+where
+    F: Fn(f64, f64) -> f64 + Sync + Send,
+{
+    let f = move |a, b| f(scaling.apply(a), scaling.apply(b));
+    let ops = Operand::extract_common(&[a, b])?;
+    Ok(dispatch!(all ops, |v| into_py(
+        py,
+        py.allow_threads(|| map2(label, v[0].clone(), v[1].clone(), f))?
+    )))
+}
+
+/// PyO3 entry point for [`map3`].
+pub fn py_map3<F>(
+    py: Python<'_>,
+    label: &str,
+    a: &PyAny,
+    b: &PyAny,
+    c: &PyAny,
+    scaling: Scaling,
+    f: F,
+) -> PyResult<PyObject>
+where
+    F: Fn(f64, f64, f64) -> f64 + Sync + Send,
+{
+    let f = move |a, b, c| f(scaling.apply(a), scaling.apply(b), scaling.apply(c));
+    let ops = Operand::extract_common(&[a, b, c])?;
+    Ok(dispatch!(all ops, |v| into_py(
+        py,
+        py.allow_threads(|| map3(label, v[0].clone(), v[1].clone(), v[2].clone(), f))?
+    )))
+}
+
+/// PyO3 entry point for [`map4`].
+#[allow(clippy::too_many_arguments)]
+pub fn py_map4<F>(
+    py: Python<'_>,
+    label: &str,
+    a: &PyAny,
+    b: &PyAny,
+    c: &PyAny,
+    d: &PyAny,
+    scaling: Scaling,
+    f: F,
+) -> PyResult<PyObject>
+where
+    F: Fn(f64, f64, f64, f64) -> f64 + Sync + Send,
+{
+    let s = scaling;
+    let f = move |a, b, c, d| f(s.apply(a), s.apply(b), s.apply(c), s.apply(d));
+    let ops = Operand::extract_common(&[a, b, c, d])?;
+    Ok(dispatch!(all ops, |v| into_py(
+        py,
+        py.allow_threads(|| map4(label, v[0].clone(), v[1].clone(), v[2].clone(), v[3].clone(), f))?
+    )))
+}
+
+/// PyO3 entry point for [`map5`].
+#[allow(clippy::too_many_arguments)]
+pub fn py_map5<F>(
+    py: Python<'_>,
+    label: &str,
+    a: &PyAny,
+    b: &PyAny,
+    c: &PyAny,
+    d: &PyAny,
+    e: &PyAny,
+    scaling: Scaling,
+    f: F,
+) -> PyResult<PyObject>
+where
+    F: Fn(f64, f64, f64, f64, f64) -> f64 + Sync + Send,
+{
+    let s = scaling;
+    let f = move |a, b, c, d, e| f(s.apply(a), s.apply(b), s.apply(c), s.apply(d), s.apply(e));
+    let ops = Operand::extract_common(&[a, b, c, d, e])?;
+    Ok(dispatch!(all ops, |v| into_py(
+        py,
+        py.allow_threads(|| map5(label, v[0].clone(), v[1].clone(), v[2].clone(), v[3].clone(), v[4].clone(), f))?
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::{array, Array4};
+
+    #[test]
+    fn test_broadcast_shape_rules() {
+        assert_eq!(broadcast_shape(&[&[3, 4], &[3, 4]]), Some(vec![3, 4]));
+        assert_eq!(broadcast_shape(&[&[3, 1], &[1, 4]]), Some(vec![3, 4]));
+        assert_eq!(broadcast_shape(&[&[2, 3, 4], &[4]]), Some(vec![2, 3, 4]));
+        assert_eq!(broadcast_shape(&[&[0], &[1]]), Some(vec![0]));
+        assert_eq!(broadcast_shape(&[&[2], &[3]]), None);
+        assert_eq!(broadcast_shape(&[&[2, 3], &[3, 2]]), None);
+    }
+
+    #[test]
+    fn test_map2_matches_per_rank_loop() {
+        // Regression guard: the dynamic-rank path must reproduce the former
+        // fixed-rank Zip loops bit-for-bit.
+        let a = Array4::from_shape_fn((2, 3, 4, 5), |(t, b, y, x)| {
+            (t * 60 + b * 20 + y * 5 + x) as f64 * 0.01
+        });
+        let b = a.mapv(|v| 1.0 - v);
+        let nd = |x: f64, y: f64| {
+            let denom = x + y;
+            if denom.abs() < 1e-10 {
+                0.0
+            } else {
+                (x - y) / denom
+            }
+        };
+        let mut expected = Array4::<f64>::zeros(a.dim());
+        Zip::from(&mut expected)
+            .and(&a)
+            .and(&b)
+            .for_each(|r, &x, &y| *r = nd(x, y));
+        let out = map2("test", a.view().into_dyn(), b.view().into_dyn(), nd).unwrap();
+        assert_eq!(out, expected.into_dyn());
+    }
+
+    #[test]
+    fn test_map2_broadcasts_size_one_axes() {
+        let a = array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]].into_dyn();
+        let b = array![[10.0], [20.0]].into_dyn();
+        let out = map2("test", a.view(), b.view(), |x, y| x + y).unwrap();
+        assert_eq!(
+            out,
+            array![[11.0, 12.0, 13.0], [24.0, 25.0, 26.0]].into_dyn()
+        );
+    }
+
+    #[test]
+    fn test_map_high_rank_and_strided() {
+        let a = ArrayD::from_shape_fn(IxDyn(&[2, 2, 3, 4, 5]), |ix| ix[4] as f64);
+        let t = a.view().reversed_axes();
+        let out = map1(t.view(), |v| v * 2.0);
+        assert_eq!(out.shape(), t.shape());
+        assert!(out.iter().zip(t.iter()).all(|(&o, &v)| o == v * 2.0));
+
+        let c = ArrayD::from_elem(IxDyn(&[1, 1, 1, 1, 5]), 1.0);
+        let out = map3("test", a.view(), c.view(), c.view(), |x, y, z| x + y + z).unwrap();
+        assert_eq!(out.shape(), a.shape());
+    }
+
+    #[test]
+    fn test_shape_mismatch_error() {
+        let a = ArrayD::<f64>::zeros(IxDyn(&[2]));
+        let b = ArrayD::<f64>::zeros(IxDyn(&[3]));
+        let err = map4(
+            "dNBR",
+            a.view(),
+            b.view(),
+            a.view(),
+            a.view(),
+            |w, _, _, _| w,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Shape mismatch for dNBR inputs"));
+    }
+
+    #[test]
+    fn test_native_f32_and_u16() {
+        let nd = |x: f64, y: f64| (x - y) / (x + y);
+        let a64 = array![0.8, 0.6, 0.3].into_dyn();
+        let b64 = array![0.2, 0.3, 0.3].into_dyn();
+        let expected: ArrayD<f64> = map2("test", a64.view(), b64.view(), nd).unwrap();
+
+        let (a32, b32) = (a64.mapv(|v| v as f32), b64.mapv(|v| v as f32));
+        let out: ArrayD<f32> = map2("test", a32.view(), b32.view(), nd).unwrap();
+        for (&o, &e) in out.iter().zip(expected.iter()) {
+            assert!((o as f64 - e).abs() < 1e-6);
+        }
+
+        let dn_a = array![8000u16, 6000, 3000].into_dyn();
+        let dn_b = array![2000u16, 3000, 3000].into_dyn();
+        let s = Scaling::from_args(Some(1e-4), None).unwrap();
+        let out: ArrayD<f32> = map2("test", dn_a.view(), dn_b.view(), |x, y| {
+            nd(s.apply(x), s.apply(y))
+        })
+        .unwrap();
+        for (&o, &e) in out.iter().zip(expected.iter()) {
+            assert!((o as f64 - e).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_scaling() {
+        assert_eq!(Scaling::from_args(None, None).unwrap(), Scaling::IDENTITY);
+        let s = Scaling::from_args(Some(0.0000275), Some(-0.2)).unwrap();
+        assert!((s.apply(10000.0) - 0.075).abs() < 1e-12);
+        assert!(Scaling::from_args(Some(f64::NAN), None).is_err());
+        // Identity must not perturb values (e.g. -0.0 stays -0.0).
+        assert!(Scaling::IDENTITY.apply(-0.0).is_sign_negative());
+    }
+
+    #[test]
+    fn test_reduce_time() {
+        let a = ArrayD::from_shape_fn(IxDyn(&[3, 2, 2]), |ix| (ix[0] + 1) as f32);
+        let out = reduce_time(a.view(), |series| series.iter().map(|&v| v as f64).sum()).unwrap();
+        assert_eq!(out.shape(), &[2, 2]);
+        assert!(out.iter().all(|&v| v == 6.0f32));
+        assert!(reduce_time(ArrayD::<f64>::zeros(IxDyn(&[])).view(), |_| 0.0).is_err());
+    }
+}
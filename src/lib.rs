@@ -1,12 +1,18 @@
+pub mod bandmath;
 pub mod classification;
 pub mod indices;
+pub mod kernels;
 pub mod masking;
 pub mod morphology;
 pub mod processes;
+pub mod sensors;
+pub mod similarity;
 pub mod spatial;
 pub mod temporal;
 pub mod texture;
+pub mod transforms;
 pub mod trends;
+pub mod unmixing;
 pub mod workflows;
 pub mod zonal;
 
@@ -22,14 +28,37 @@ pub enum CoreError {
     ComputationError(String),
     #[error("Not enough data: {0}")]
     NotEnoughData(String),
+    #[error("Unknown band role '{role}'; valid roles: {valid:?}")]
+    UnknownBandRole {
+        role: String,
+        valid: Vec<&'static str>,
+    },
 }
 
+pyo3::create_exception!(
+    _core,
+    UnknownBandRoleError,
+    PyValueError,
+    "Raised for a band name that is not a known band role; carries `role` and `valid_roles`."
+);
+
 impl From<CoreError> for PyErr {
     fn from(err: CoreError) -> PyErr {
         match err {
             CoreError::InvalidArgument(msg) => PyValueError::new_err(msg),
             CoreError::ComputationError(msg) => PyValueError::new_err(msg),
             CoreError::NotEnoughData(msg) => PyValueError::new_err(msg),
+            CoreError::UnknownBandRole {
+                ref role,
+                ref valid,
+            } => Python::with_gil(|py| {
+                let py_err = UnknownBandRoleError::new_err(err.to_string());
+                let value = py_err.value(py);
+                // Attribute assignment on a fresh exception instance cannot fail.
+                let _ = value.setattr("role", role);
+                let _ = value.setattr("valid_roles", valid.clone());
+                py_err
+            }),
         }
     }
 }
@@ -39,7 +68,7 @@ impl From<CoreError> for PyErr {
 /// This module provides Rust-accelerated functions for common EO computations
 /// that can be used with XArray/Dask workflows to bypass Python's GIL.
 #[pymodule]
-fn _core(_py: Python, m: &PyModule) -> PyResult<()> {
+fn _core(py: Python, m: &PyModule) -> PyResult<()> {
     // --- Spectral Indices ---
     m.add_function(wrap_pyfunction!(indices::normalized_difference, m)?)?;
     m.add_function(wrap_pyfunction!(indices::ndvi, m)?)?;
@@ -56,8 +85,22 @@ fn _core(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(indices::lai, m)?)?;
     m.add_function(wrap_pyfunction!(indices::dnbr, m)?)?;
     m.add_function(wrap_pyfunction!(indices::rbr, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::change_vector_analysis, m)?)?;
     m.add_function(wrap_pyfunction!(indices::ci_re, m)?)?;
     m.add_function(wrap_pyfunction!(indices::mtci, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::ndbi, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::mndwi, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::awei_nsh, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::awei_sh, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::bsi, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::kndvi, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::nirv, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::sipi, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::psri, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::arvi, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::vari, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::tcari_osavi, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::ndci, m)?)?;
     m.add_function(wrap_pyfunction!(indices::nbr, m)?)?;
     // Additional spectral indices
     m.add_function(wrap_pyfunction!(indices::ndmi, m)?)?;
@@ -66,15 +109,36 @@ fn _core(_py: Python, m: &PyModule) -> PyResult<()> {
     // --- Change Detection Indices ---
     m.add_function(wrap_pyfunction!(indices::delta_ndvi, m)?)?;
     m.add_function(wrap_pyfunction!(indices::delta_nbr, m)?)?;
+    // --- Fused Multi-Index ---
+    m.add_function(wrap_pyfunction!(indices::compute_indices, m)?)?;
+    // --- Index Registry ---
+    m.add_function(wrap_pyfunction!(indices::index_registry, m)?)?;
+    m.add_function(wrap_pyfunction!(indices::band_roles, m)?)?;
+    m.add(
+        "UnknownBandRoleError",
+        py.get_type::<UnknownBandRoleError>(),
+    )?;
+    // --- Sensor Presets ---
+    m.add_function(wrap_pyfunction!(sensors::sensor_profiles, m)?)?;
+    m.add_function(wrap_pyfunction!(sensors::sensor_indices, m)?)?;
+    // --- Band Transforms ---
+    m.add_function(wrap_pyfunction!(transforms::tasseled_cap, m)?)?;
+    m.add_function(wrap_pyfunction!(transforms::pca_transform, m)?)?;
+    m.add_function(wrap_pyfunction!(transforms::mnf_transform, m)?)?;
+    m.add_function(wrap_pyfunction!(unmixing::linear_unmixing, m)?)?;
+    m.add_function(wrap_pyfunction!(similarity::spectral_similarity, m)?)?;
+    // --- Band Math ---
+    m.add_function(wrap_pyfunction!(bandmath::band_math, m)?)?;
 
     // --- Spatial Distance & Aggregation Functions ---
     m.add_function(wrap_pyfunction!(spatial::euclidean_distance, m)?)?;
     m.add_function(wrap_pyfunction!(spatial::manhattan_distance, m)?)?;
     m.add_function(wrap_pyfunction!(spatial::chebyshev_distance, m)?)?;
     m.add_function(wrap_pyfunction!(spatial::minkowski_distance, m)?)?;
-    m.add_function(wrap_pyfunction!(spatial::median, m)?)?;
+    m.add_function(wrap_pyfunction!(spatial::distance_transform, m)?)?;
 
     // --- Temporal Functions ---
+    m.add_function(wrap_pyfunction!(temporal::median, m)?)?;
     m.add_function(wrap_pyfunction!(temporal::composite_mean, m)?)?;
     m.add_function(wrap_pyfunction!(temporal::composite_std, m)?)?;
     m.add_function(wrap_pyfunction!(temporal::temporal_sum, m)?)?;
@@ -86,6 +150,11 @@ fn _core(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(masking::mask_in_range, m)?)?;
     m.add_function(wrap_pyfunction!(masking::mask_scl, m)?)?;
     m.add_function(wrap_pyfunction!(masking::mask_with_scl, m)?)?;
+    m.add_function(wrap_pyfunction!(masking::qa_mask, m)?)?;
+    m.add_function(wrap_pyfunction!(masking::mask_with_qa, m)?)?;
+    m.add_function(wrap_pyfunction!(masking::buffer_mask, m)?)?;
+    m.add_function(wrap_pyfunction!(masking::cloud_shadow_mask, m)?)?;
+    m.add_function(wrap_pyfunction!(masking::scene_cloud_stats, m)?)?;
     // --- Advanced Processes ---
     m.add_function(wrap_pyfunction!(processes::moving_average_temporal, m)?)?;
     m.add_function(wrap_pyfunction!(
@@ -108,10 +177,25 @@ fn _core(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(morphology::binary_erosion, m)?)?;
     m.add_function(wrap_pyfunction!(morphology::binary_opening, m)?)?;
     m.add_function(wrap_pyfunction!(morphology::binary_closing, m)?)?;
+    m.add_function(wrap_pyfunction!(morphology::structuring_element, m)?)?;
+    m.add_function(wrap_pyfunction!(morphology::grey_erosion, m)?)?;
+    m.add_function(wrap_pyfunction!(morphology::grey_dilation, m)?)?;
+    m.add_function(wrap_pyfunction!(morphology::grey_opening, m)?)?;
+    m.add_function(wrap_pyfunction!(morphology::grey_closing, m)?)?;
+    m.add_function(wrap_pyfunction!(morphology::white_tophat, m)?)?;
+    m.add_function(wrap_pyfunction!(morphology::black_tophat, m)?)?;
+    m.add_function(wrap_pyfunction!(morphology::morphological_gradient, m)?)?;
+    m.add_class::<morphology::ComponentProps>()?;
+    m.add_function(wrap_pyfunction!(morphology::label_components, m)?)?;
+    m.add_function(wrap_pyfunction!(morphology::sieve, m)?)?;
+    m.add_function(wrap_pyfunction!(morphology::fill_holes, m)?)?;
+    m.add_function(wrap_pyfunction!(morphology::majority_filter, m)?)?;
 
     // --- Workflows ---
     m.add_function(wrap_pyfunction!(workflows::bfast_monitor, m)?)?;
     m.add_function(wrap_pyfunction!(workflows::complex_classification, m)?)?;
+    m.add_function(wrap_pyfunction!(workflows::burn_severity, m)?)?;
+    m.add_function(wrap_pyfunction!(workflows::fmask, m)?)?;
 
     // --- Texture ---
     m.add_function(wrap_pyfunction!(texture::haralick_features_py, m)?)?;
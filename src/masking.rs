@@ -1,14 +1,15 @@
 //! Masking utilities for EO workflows.
 //!
-//! This module introduces a generic `mask_vals` function supporting 1D, 2D, 3D, and 4D
-//! NumPy arrays (float64) with optional value-based masking and NaN replacement.
+//! This module introduces a generic `mask_vals` function supporting NumPy arrays of
+//! any rank with optional value-based masking and NaN replacement.
 //!
 //! Design Goals:
-//! - Accept any numeric NumPy dtype (coerced to float64 internally).
+//! - Accept any numeric NumPy dtype: float64 / float32 / uint16 are read natively
+//!   (float32 and uint16 produce float32 output), anything else is coerced to float64.
 //! - Allow masking of specific coded values (e.g., Sentinel-2 SCL classes, fill values).
 //! - Allow replacing existing NaNs with a user-specified numeric value.
 //! - Preserve input shape and dimensionality.
-//! - No `unsafe` code; clear explicit dispatch.
+//! - No `unsafe` code; rank dispatch is delegated to the shared `kernels` layer.
 //!
 //! To expose these functions to Python you MUST add:
 //!     `pub mod masking;`
@@ -54,13 +55,13 @@
 //!   to `nan_to` AFTER the value masking step.
 //!
 //! Performance Notes:
-//! - Implements straightforward element-wise loops. For extremely large arrays, a future
-//!   optimization may leverage rayon + owned buffers. Current approach keeps code minimal
-//!   and safe without extra allocations (besides coercion).
+//! - Each mask is a scalar predicate evaluated through `kernels::map1`/`map2`, which
+//!   runs element-wise over the rayon pool into a single owned output buffer
+//!   (no extra allocations besides coercion).
 //!
 //! Edge Cases:
 //! - Empty `values` list → no value masking (only possible NaN replacement).
-//! - If input dtype is already float64, no coercion cost.
+//! - float64, float32 and uint16 inputs incur no coercion cost.
 //! - For integer-coded masks, exact equality is used (no tolerance).
 //!
 //! Future Extensions:
@@ -68,80 +69,38 @@
 //! - Boolean mask input (separate function) for advanced workflows.
 //! - Composable predicates (greater-than / less-than) integrated with value sets.
 
-use numpy::{IntoPyArray, PyReadonlyArray1, PyReadonlyArray2, PyReadonlyArray3, PyReadonlyArray4};
+use ndarray::{Array1, Array2, Array3, ArrayD, ArrayView2, ArrayView3, ArrayViewD, Axis, Ix2, Zip};
+use numpy::{Element, PyArrayDyn, PyReadonlyArray2, PyReadonlyArray3, PyReadonlyArrayDyn};
 
+use crate::kernels::{self, coerce_dyn, py_map1, Operand, Real, Sample, Scaling};
+use crate::morphology::squared_distance_transform;
+use crate::sensors::{lookup_sensor, SensorProfile};
 use crate::CoreError;
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 
-/// Attempt to coerce an arbitrary Python object to a readonly 1D float64 NumPy array.
-fn coerce_1d<'py>(obj: &'py PyAny) -> PyResult<PyReadonlyArray1<'py, f64>> {
-    if let Ok(arr) = obj.extract::<PyReadonlyArray1<f64>>() {
-        return Ok(arr);
-    }
-    let coerced = obj.call_method1("astype", ("float64",))?;
-    coerced.extract::<PyReadonlyArray1<f64>>()
-}
-
-/// Attempt to coerce an arbitrary Python object to a readonly 2D float64 NumPy array.
-fn coerce_2d<'py>(obj: &'py PyAny) -> PyResult<PyReadonlyArray2<'py, f64>> {
-    if let Ok(arr) = obj.extract::<PyReadonlyArray2<f64>>() {
-        return Ok(arr);
-    }
-    let coerced = obj.call_method1("astype", ("float64",))?;
-    coerced.extract::<PyReadonlyArray2<f64>>()
-}
-
-/// Attempt to coerce an arbitrary Python object to a readonly 3D float64 NumPy array.
-fn coerce_3d<'py>(obj: &'py PyAny) -> PyResult<PyReadonlyArray3<'py, f64>> {
-    if let Ok(arr) = obj.extract::<PyReadonlyArray3<f64>>() {
-        return Ok(arr);
-    }
-    let coerced = obj.call_method1("astype", ("float64",))?;
-    coerced.extract::<PyReadonlyArray3<f64>>()
-}
-
-/// Attempt to coerce an arbitrary Python object to a readonly 4D float64 NumPy array.
-fn coerce_4d<'py>(obj: &'py PyAny) -> PyResult<PyReadonlyArray4<'py, f64>> {
-    if let Ok(arr) = obj.extract::<PyReadonlyArray4<f64>>() {
-        return Ok(arr);
-    }
-    let coerced = obj.call_method1("astype", ("float64",))?;
-    coerced.extract::<PyReadonlyArray4<f64>>()
-}
-
-/// Internal helper: apply masking logic to a slice of values (in place).
-fn apply_mask_slice(
-    values: &mut [f64],
-    mask_vals: Option<&[f64]>,
-    fill_value: f64,
-    nan_to: Option<f64>,
-) {
-    for v in values.iter_mut() {
-        let mut is_masked = false;
-
-        if let Some(mask_list) = mask_vals {
-            if mask_list.contains(v) {
-                is_masked = true;
-                *v = fill_value;
-            }
-        }
+/// Internal helper: masking logic for a single value.
+fn mask_value(v: f64, mask_vals: &[f64], fill_value: f64, nan_to: Option<f64>) -> f64 {
+    let is_masked = mask_vals.contains(&v);
+    let mut out = if is_masked { fill_value } else { v };
 
-        // If the original value was NaN or became NaN, and user wants replacement.
-        if let Some(nan_replacement) = nan_to {
-            if v.is_nan() || is_masked {
-                *v = nan_replacement;
-            }
+    // If the original value was NaN or became NaN, and user wants replacement.
+    if let Some(nan_replacement) = nan_to {
+        if out.is_nan() || is_masked {
+            out = nan_replacement;
         }
     }
+    out
 }
 
 /// Generic value masking across dimensions.
-/// - `arr`: Input NumPy array (1D, 2D, 3D, or 4D), any numeric dtype.
+/// - `arr`: Input NumPy array (any rank), any numeric dtype.
 /// - `values`: Optional list of numeric codes to replace (exact equality).
 /// - `fill_value`: Value to assign to masked codes (default NaN if None passed).
 /// - `nan_to`: Optional replacement for existing NaNs (including those produced by masking).
 ///
-/// Returns a new float64 NumPy array of identical shape.
+/// Returns a new float NumPy array of identical shape (float32 for float32 /
+/// uint16 input, float64 otherwise).
 ///
 /// Examples:
 ///     mask_vals(arr, Some(vec![0.0, 255.0]), None, None)        // mask 0 & 255 -> NaN
@@ -158,66 +117,12 @@ pub fn mask_vals(
     nan_to: Option<f64>,
 ) -> PyResult<PyObject> {
     let fill = fill_value.unwrap_or(f64::NAN);
-    let mask_list: Option<Vec<f64>> = values;
-
-    // Dispatch by dimension
-    if let Ok(a1) = coerce_1d(arr) {
-        let mut out = a1.as_array().to_owned();
-        apply_mask_slice(
-            out.as_slice_mut().unwrap(),
-            mask_list.as_deref(),
-            fill,
-            nan_to,
-        );
-        return Ok(out.into_pyarray(py).into_py(py));
-    } else if let Ok(a2) = coerce_2d(arr) {
-        let mut out = a2.as_array().to_owned();
-        for mut row in out.rows_mut() {
-            apply_mask_slice(
-                row.as_slice_mut().unwrap(),
-                mask_list.as_deref(),
-                fill,
-                nan_to,
-            );
-        }
-        return Ok(out.into_pyarray(py).into_py(py));
-    } else if let Ok(a3) = coerce_3d(arr) {
-        let mut out = a3.as_array().to_owned();
-        for mut plane in out.outer_iter_mut() {
-            for mut row in plane.rows_mut() {
-                apply_mask_slice(
-                    row.as_slice_mut().unwrap(),
-                    mask_list.as_deref(),
-                    fill,
-                    nan_to,
-                );
-            }
-        }
-        return Ok(out.into_pyarray(py).into_py(py));
-    } else if let Ok(a4) = coerce_4d(arr) {
-        let mut out = a4.as_array().to_owned();
-        for mut block in out.outer_iter_mut() {
-            for mut plane in block.outer_iter_mut() {
-                for mut row in plane.rows_mut() {
-                    apply_mask_slice(
-                        row.as_slice_mut().unwrap(),
-                        mask_list.as_deref(),
-                        fill,
-                        nan_to,
-                    );
-                }
-            }
-        }
-        return Ok(out.into_pyarray(py).into_py(py));
-    }
+    let mask_list = values.unwrap_or_default();
 
-    Err(
-        CoreError::InvalidArgument("Expected a 1D, 2D, 3D, or 4D numeric NumPy array.".to_string())
-            .into(),
-    )
+    py_map1(py, arr, move |v| mask_value(v, &mask_list, fill, nan_to))
 }
 
-/// Replace NaNs with a specified numeric value across 1D–4D arrays.
+/// Replace NaNs with a specified numeric value across arrays of any rank.
 /// More explicit alias to `mask_vals(arr, None, None, Some(value))`.
 ///
 /// Example:
@@ -228,36 +133,15 @@ pub fn replace_nans(py: Python<'_>, arr: &PyAny, value: f64) -> PyResult<PyObjec
     mask_vals(py, arr, None, None, Some(value))
 }
 
-/// Internal helper for range masking.
-fn apply_mask_out_range_slice(
-    values: &mut [f64],
-    min: Option<f64>,
-    max: Option<f64>,
-    fill_value: f64,
-) {
-    for v in values.iter_mut() {
-        let mut is_masked = false;
-        if let Some(min_val) = min {
-            if *v < min_val {
-                is_masked = true;
-            }
-        }
-        if let Some(max_val) = max {
-            if *v > max_val {
-                is_masked = true;
-            }
-        }
-
-        if is_masked {
-            *v = fill_value;
-        }
-    }
+/// Internal helper: true if `v` falls outside the optional [min, max] bounds.
+fn is_out_of_range(v: f64, min: Option<f64>, max: Option<f64>) -> bool {
+    matches!(min, Some(min_val) if v < min_val) || matches!(max, Some(max_val) if v > max_val)
 }
 
 /// Mask values outside a specified numeric range [min, max].
 ///
 /// Parameters:
-/// - `arr`: Input NumPy array (1D–4D), any numeric dtype.
+/// - `arr`: Input NumPy array (any rank), any numeric dtype.
 /// - `min`: Optional minimum valid value (inclusive).
 /// - `max`: Optional maximum valid value (inclusive).
 /// - `fill_value`: Value to assign to masked codes (default NaN).
@@ -274,41 +158,13 @@ pub fn mask_out_range(
 ) -> PyResult<PyObject> {
     let fill = fill_value.unwrap_or(f64::NAN);
 
-    // Dispatch by dimension
-    if let Ok(a1) = coerce_1d(arr) {
-        let mut out = a1.as_array().to_owned();
-        apply_mask_out_range_slice(out.as_slice_mut().unwrap(), min, max, fill);
-        return Ok(out.into_pyarray(py).into_py(py));
-    } else if let Ok(a2) = coerce_2d(arr) {
-        let mut out = a2.as_array().to_owned();
-        for mut row in out.rows_mut() {
-            apply_mask_out_range_slice(row.as_slice_mut().unwrap(), min, max, fill);
-        }
-        return Ok(out.into_pyarray(py).into_py(py));
-    } else if let Ok(a3) = coerce_3d(arr) {
-        let mut out = a3.as_array().to_owned();
-        for mut plane in out.outer_iter_mut() {
-            for mut row in plane.rows_mut() {
-                apply_mask_out_range_slice(row.as_slice_mut().unwrap(), min, max, fill);
-            }
-        }
-        return Ok(out.into_pyarray(py).into_py(py));
-    } else if let Ok(a4) = coerce_4d(arr) {
-        let mut out = a4.as_array().to_owned();
-        for mut block in out.outer_iter_mut() {
-            for mut plane in block.outer_iter_mut() {
-                for mut row in plane.rows_mut() {
-                    apply_mask_out_range_slice(row.as_slice_mut().unwrap(), min, max, fill);
-                }
-            }
+    py_map1(py, arr, move |v| {
+        if is_out_of_range(v, min, max) {
+            fill
+        } else {
+            v
         }
-        return Ok(out.into_pyarray(py).into_py(py));
-    }
-
-    Err(
-        CoreError::InvalidArgument("Expected a 1D, 2D, 3D, or 4D numeric NumPy array.".to_string())
-            .into(),
-    )
+    })
 }
 
 /// Convenience wrapper to mask a list of common invalid sentinel values.
@@ -332,34 +188,15 @@ pub fn mask_invalid(
     mask_vals(py, arr, Some(invalid_values), fill_value, None)
 }
 
-/// Internal helper for in-range masking.
-fn apply_mask_in_range_slice(
-    values: &mut [f64],
-    min: Option<f64>,
-    max: Option<f64>,
-    fill_value: f64,
-) {
-    for v in values.iter_mut() {
-        let mut is_masked = false;
-        if let Some(min_val) = min {
-            if *v >= min_val {
-                if let Some(max_val) = max {
-                    if *v <= max_val {
-                        is_masked = true;
-                    }
-                } else {
-                    is_masked = true; // No max, so anything >= min is masked
-                }
-            }
-        } else if let Some(max_val) = max {
-            if *v <= max_val {
-                is_masked = true; // No min, so anything <= max is masked
-            }
-        }
-
-        if is_masked {
-            *v = fill_value;
-        }
+/// Internal helper: true if `v` falls inside the [min, max] range.
+/// A missing bound leaves that side of the range open; with neither bound
+/// nothing is considered inside.
+fn is_in_range(v: f64, min: Option<f64>, max: Option<f64>) -> bool {
+    match (min, max) {
+        (Some(min_val), Some(max_val)) => v >= min_val && v <= max_val,
+        (Some(min_val), None) => v >= min_val, // No max, so anything >= min is masked
+        (None, Some(max_val)) => v <= max_val, // No min, so anything <= max is masked
+        (None, None) => false,
     }
 }
 
@@ -375,72 +212,172 @@ pub fn mask_in_range(
 ) -> PyResult<PyObject> {
     let fill = fill_value.unwrap_or(f64::NAN);
 
-    if let Ok(a1) = coerce_1d(arr) {
-        let mut out = a1.as_array().to_owned();
-        apply_mask_in_range_slice(out.as_slice_mut().unwrap(), min, max, fill);
-        return Ok(out.into_pyarray(py).into_py(py));
-    } else if let Ok(a2) = coerce_2d(arr) {
-        let mut out = a2.as_array().to_owned();
-        for mut row in out.rows_mut() {
-            apply_mask_in_range_slice(row.as_slice_mut().unwrap(), min, max, fill);
-        }
-        return Ok(out.into_pyarray(py).into_py(py));
-    } else if let Ok(a3) = coerce_3d(arr) {
-        let mut out = a3.as_array().to_owned();
-        for mut plane in out.outer_iter_mut() {
-            for mut row in plane.rows_mut() {
-                apply_mask_in_range_slice(row.as_slice_mut().unwrap(), min, max, fill);
+    py_map1(
+        py,
+        arr,
+        move |v| if is_in_range(v, min, max) { fill } else { v },
+    )
+}
+
+/// Set membership of class codes: a 256-entry table for the integer codes
+/// 0..=255 (every SCL class), with a list fallback for any other code.
+pub(crate) struct CodeSet {
+    table: [bool; 256],
+    other: Vec<f64>,
+}
+
+impl CodeSet {
+    pub fn new(codes: &[f64]) -> Self {
+        let mut table = [false; 256];
+        let mut other = Vec::new();
+        for &c in codes {
+            match Self::slot(c) {
+                Some(i) => table[i] = true,
+                None => other.push(c),
             }
         }
-        return Ok(out.into_pyarray(py).into_py(py));
-    } else if let Ok(a4) = coerce_4d(arr) {
-        let mut out = a4.as_array().to_owned();
-        for mut block in out.outer_iter_mut() {
-            for mut plane in block.outer_iter_mut() {
-                for mut row in plane.rows_mut() {
-                    apply_mask_in_range_slice(row.as_slice_mut().unwrap(), min, max, fill);
-                }
-            }
+        CodeSet { table, other }
+    }
+
+    #[inline]
+    fn slot(v: f64) -> Option<usize> {
+        (v.fract() == 0.0 && (0.0..=255.0).contains(&v)).then_some(v as usize)
+    }
+
+    #[inline]
+    pub fn contains(&self, v: f64) -> bool {
+        match Self::slot(v) {
+            Some(i) => self.table[i],
+            None => self.other.contains(&v),
         }
-        return Ok(out.into_pyarray(py).into_py(py));
     }
 
-    Err(
-        CoreError::InvalidArgument("Expected a 1D, 2D, 3D, or 4D numeric NumPy array.".to_string())
-            .into(),
-    )
+    #[inline]
+    pub fn contains_u8(&self, v: u8) -> bool {
+        self.table[v as usize]
+    }
+}
+
+/// What `mask_scl` returns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SclOutput {
+    /// SCL codes as floats with dropped classes set to the fill value.
+    Values,
+    /// Valid mask, true where the class is kept.
+    Bool,
+    /// Valid mask as 1 (kept) / 0.
+    Uint8,
+}
+
+impl SclOutput {
+    fn parse(output: &str) -> Result<Self, CoreError> {
+        match output {
+            "values" => Ok(SclOutput::Values),
+            "bool" => Ok(SclOutput::Bool),
+            "uint8" => Ok(SclOutput::Uint8),
+            _ => Err(CoreError::InvalidArgument(format!(
+                "Unknown mask_scl output '{}'; expected 'values', 'bool' or 'uint8'",
+                output
+            ))),
+        }
+    }
+}
+
+/// Valid mask of an SCL array: `on` where `keep` holds, `off` elsewhere.
+fn scl_valid_mask<A, T, F>(scl: ArrayViewD<A>, keep: F, on: T, off: T) -> ArrayD<T>
+where
+    A: Copy + Sync,
+    T: Copy + Send + Sync,
+    F: Fn(A) -> bool + Sync,
+{
+    let mut out = ArrayD::from_elem(scl.raw_dim(), off);
+    Zip::from(&mut out).and(&scl).par_for_each(|o, &v| {
+        if keep(v) {
+            *o = on;
+        }
+    });
+    out
 }
 
-/// Mask a Sentinel-2 Scene Classification Layer (SCL) array.
+fn scl_output<A, F>(
+    py: Python<'_>,
+    scl: ArrayViewD<A>,
+    keep: F,
+    output: SclOutput,
+    fill: f64,
+) -> PyObject
+where
+    A: Copy + Sync + Into<f64>,
+    F: Fn(A) -> bool + Send + Sync,
+{
+    match output {
+        SclOutput::Bool => kernels::into_py(
+            py,
+            py.allow_threads(|| scl_valid_mask(scl, keep, true, false)),
+        ),
+        SclOutput::Uint8 => {
+            kernels::into_py(py, py.allow_threads(|| scl_valid_mask(scl, keep, 1u8, 0u8)))
+        }
+        SclOutput::Values => {
+            let out = py.allow_threads(|| {
+                let mut out = ArrayD::from_elem(scl.raw_dim(), fill);
+                Zip::from(&mut out).and(&scl).par_for_each(|o, &v| {
+                    if keep(v) {
+                        *o = v.into();
+                    }
+                });
+                out
+            });
+            kernels::into_py(py, out)
+        }
+    }
+}
+
+/// Mask a Sentinel-2 Scene Classification Layer (SCL) array of any rank.
+///
+/// Class membership is a 256-entry table lookup, and uint8 SCL is read
+/// without conversion.
+///
+/// Parameters:
+/// - `scl`: SCL array (any rank), any numeric dtype.
+/// - `keep_codes`: Classes to keep (default vegetation 4, not vegetated 5,
+///   water 6, unclassified 7 and snow 11).
+/// - `fill_value`: Value for dropped classes in `"values"` output (default NaN).
+/// - `output`: `"values"` (default) returns the SCL as floats with dropped
+///   classes filled; `"bool"` / `"uint8"` return a compact valid mask (true /
+///   1 where kept) that can be reused across bands.
 #[pyfunction]
-#[pyo3(signature = (scl, keep_codes=None, fill_value=None))]
+#[pyo3(signature = (scl, keep_codes=None, fill_value=None, output="values"))]
 pub fn mask_scl(
     py: Python<'_>,
     scl: &PyAny,
     keep_codes: Option<Vec<f64>>,
     fill_value: Option<f64>,
+    output: &str,
 ) -> PyResult<PyObject> {
     // Default S2 codes to KEEP: vegetation (4,5), water (6), bare soil (7), snow (11)
     let default_keep = vec![4.0, 5.0, 6.0, 7.0, 11.0];
-    let codes_to_keep = keep_codes.unwrap_or(default_keep);
-
-    let arr_float = if let Ok(a) = scl.extract::<PyReadonlyArray1<f64>>() {
-        a.to_owned_array()
-    } else {
-        scl.call_method1("astype", ("float64",))?
-            .extract::<PyReadonlyArray1<f64>>()?
-            .to_owned_array()
-    };
+    let codes = CodeSet::new(&keep_codes.unwrap_or(default_keep));
+    let fill = fill_value.unwrap_or(f64::NAN);
+    let output = SclOutput::parse(output)?;
 
-    let mut out = arr_float.to_owned();
-    for v in out.iter_mut() {
-        if !codes_to_keep.contains(v) {
-            *v = fill_value.unwrap_or(f64::NAN);
-        }
+    if let Ok(scl) = scl.downcast::<PyArrayDyn<u8>>() {
+        let scl = scl.readonly();
+        return Ok(scl_output(
+            py,
+            scl.as_array(),
+            |v: u8| codes.contains_u8(v),
+            output,
+            fill,
+        ));
     }
-
-    let result_array = out.into_dyn();
-    Ok(result_array.into_pyarray(py).into_py(py))
+    if output == SclOutput::Values {
+        return py_map1(py, scl, move |v| if codes.contains(v) { v } else { fill });
+    }
+    let keep = |v: f64| codes.contains(v);
+    Ok(kernels::dispatch!(Operand::extract(scl)?, |a| {
+        scl_output(py, a, |v| keep(v.to_f64()), output, fill)
+    }))
 }
 
 /// Apply SCL-based masking to a data array.
@@ -450,132 +387,968 @@ pub fn mask_scl(
 /// this function applies the mask to actual data (e.g., spectral bands).
 ///
 /// Supported array shapes:
-/// - 2D data (y, x) with 2D SCL (y, x)
-/// - 3D data (time, y, x) with 3D SCL (time, y, x)
+/// - data and SCL of any rank whose shapes broadcast NumPy-style, e.g.
+///   2D data (y, x) with 2D SCL (y, x) or 3D data (time, y, x) with 3D SCL (time, y, x)
 /// - 4D data (time, band, y, x) with 3D SCL (time, y, x) - SCL broadcast across bands
+/// - any data with SCL lacking only the band axis, when `band_axis` is given
+///
+/// With `sensor` and no `scl`, `data` is a band-indexed cube of that sensor and
+/// the SCL is read from its SCL band (located through `band_names`); the SCL
+/// band itself is returned unmasked.
 ///
 /// Parameters
 /// ----------
 /// data : numpy.ndarray
-///     The data array to mask (2D, 3D, or 4D).
-/// scl : numpy.ndarray
-///     The SCL array (2D or 3D). For 4D data, SCL should be 3D (time, y, x).
+///     The data array to mask.
+/// scl : numpy.ndarray, optional
+///     The SCL array. For 4D data, SCL may be 3D (time, y, x). Required
+///     unless `sensor` is given.
 /// mask_codes : sequence of float, optional
 ///     SCL codes to mask (set to fill_value). Defaults to clouds/shadows/etc:
 ///     [0, 1, 2, 3, 8, 9, 10] (no data, saturated, dark, shadow, cloud med/high, cirrus).
 /// fill_value : float, optional
 ///     Value to assign to masked pixels. Defaults to NaN.
+/// sensor : str, optional
+///     Sensor preset (see `sensor_profiles`); must have an SCL band.
+/// band_axis : int, optional
+///     Band axis of `data` (negative counts from the end). Defaults to 0 for
+///     a sensor cube.
+/// band_names : sequence of str, optional
+///     Band names along `band_axis` of a sensor cube, including the SCL band.
 ///
 /// Returns
 /// -------
 /// numpy.ndarray
 ///     Data array with masked pixels replaced by fill_value.
 #[pyfunction]
-#[pyo3(signature = (data, scl, mask_codes=None, fill_value=None))]
+#[pyo3(signature = (
+    data,
+    scl=None,
+    mask_codes=None,
+    fill_value=None,
+    sensor=None,
+    band_axis=None,
+    band_names=None
+))]
+#[allow(clippy::too_many_arguments)]
 pub fn mask_with_scl(
     py: Python<'_>,
     data: &PyAny,
-    scl: &PyAny,
+    scl: Option<&PyAny>,
     mask_codes: Option<Vec<f64>>,
     fill_value: Option<f64>,
+    sensor: Option<&str>,
+    band_axis: Option<isize>,
+    band_names: Option<Vec<String>>,
 ) -> PyResult<PyObject> {
     // Default SCL codes to MASK (remove): no data, saturated, dark, shadow, cloud med/high, cirrus
     let default_mask = vec![0.0, 1.0, 2.0, 3.0, 8.0, 9.0, 10.0];
     let codes_to_mask = mask_codes.unwrap_or(default_mask);
     let fill = fill_value.unwrap_or(f64::NAN);
 
-    // Try 2D data with 2D SCL
-    if let (Ok(data_2d), Ok(scl_2d)) = (coerce_2d(data), coerce_2d(scl)) {
-        let data_arr = data_2d.as_array();
-        let scl_arr = scl_2d.as_array();
+    let profile = sensor.map(lookup_sensor).transpose()?;
+    if let Some(profile) = profile {
+        profile.require_scl()?;
+    }
+    let scl_arr = scl.map(coerce_dyn).transpose()?;
+    let source = match (&scl_arr, profile) {
+        (Some(scl), _) => SclSource::Array(scl.as_array(), band_axis),
+        (None, Some(profile)) => SclSource::Band {
+            profile,
+            band_axis: band_axis.unwrap_or(0),
+            band_names: band_names.as_deref(),
+        },
+        (None, None) => {
+            return Err(CoreError::InvalidArgument(
+                "mask_with_scl requires `scl`, or a `sensor` whose cube holds the SCL band"
+                    .to_string(),
+            )
+            .into())
+        }
+    };
+    kernels::dispatch!(Operand::extract(data)?, |data| {
+        apply_scl(py, data, source, &codes_to_mask, fill)
+    })
+}
+
+/// Where `mask_with_scl` reads the SCL from.
+enum SclSource<'a> {
+    /// Separate SCL array, broadcast across the data's band axis (if given)
+    /// when it has one dimension fewer than the data.
+    Array(ArrayViewD<'a, f64>, Option<isize>),
+    /// SCL band of a sensor cube.
+    Band {
+        profile: &'static SensorProfile,
+        band_axis: isize,
+        band_names: Option<&'a [String]>,
+    },
+}
 
-        if data_arr.shape() != scl_arr.shape() {
-            return Err(CoreError::InvalidArgument(format!(
-                "Data shape {:?} does not match SCL shape {:?}",
-                data_arr.shape(),
-                scl_arr.shape()
-            ))
-            .into());
+fn apply_scl<A>(
+    py: Python<'_>,
+    data: ArrayViewD<A>,
+    source: SclSource,
+    codes_to_mask: &[f64],
+    fill: f64,
+) -> PyResult<PyObject>
+where
+    A: Sample,
+    A::Out: Element,
+{
+    let codes = CodeSet::new(codes_to_mask);
+    let mask = |d: f64, s: f64| if codes.contains(s) { fill } else { d };
+
+    let (scl, band_axis) = match source {
+        SclSource::Array(scl, band_axis) => (scl, band_axis),
+        SclSource::Band {
+            profile,
+            band_axis,
+            band_names,
+        } => {
+            let axis = profile.band_axis(data.shape(), band_axis, band_names)?;
+            let index = profile.scl_position(band_names)?;
+            let scl_band = data.index_axis(Axis(axis), index);
+            let scl = scl_band.mapv(|v| v.to_f64()).insert_axis(Axis(axis));
+            let mut out =
+                py.allow_threads(|| kernels::map2("mask_with_scl", data.view(), scl.view(), mask))?;
+            // Pass the SCL band through unmasked.
+            out.index_axis_mut(Axis(axis), index)
+                .zip_mut_with(&scl_band, |o, &v| *o = Real::from_f64(v.to_f64()));
+            return Ok(kernels::into_py(py, out));
         }
+    };
 
-        let mut out = data_arr.to_owned();
-        ndarray::Zip::from(&mut out)
-            .and(&scl_arr)
-            .for_each(|d, &s| {
-                if codes_to_mask.contains(&s) {
-                    *d = fill;
-                }
-            });
+    apply_layer(py, data, scl, band_axis, "SCL", |s| codes.contains(s), fill)
+}
 
-        return Ok(out.into_pyarray(py).into_py(py));
+/// Replace `data` with `fill` where `masked(layer)` holds. `layer` is
+/// broadcast against `data`, across the band axis (if given) when it has one
+/// dimension fewer, and across axis 1 of 4-D data when it is 3-D. `name`
+/// labels the layer in error messages.
+fn apply_layer<A, F>(
+    py: Python<'_>,
+    data: ArrayViewD<A>,
+    mut layer: ArrayViewD<f64>,
+    band_axis: Option<isize>,
+    name: &str,
+    masked: F,
+    fill: f64,
+) -> PyResult<PyObject>
+where
+    A: Sample,
+    A::Out: Element,
+    F: Fn(f64) -> bool + Sync + Send,
+{
+    let band_broadcast = match band_axis {
+        Some(requested) if layer.ndim() + 1 == data.ndim() => {
+            Some(kernels::resolve_band_axis(requested, data.ndim())?)
+        }
+        Some(_) => None,
+        // (time, band, y, x) data with a (time, y, x) layer: broadcast it across bands.
+        None if data.ndim() == 4 && layer.ndim() == 3 => Some(1),
+        None => None,
+    };
+    if let Some(axis) = band_broadcast {
+        layer.insert_axis_inplace(Axis(axis));
+    }
+    if kernels::broadcast_shape(&[data.shape(), layer.shape()]).is_none() {
+        let msg = if let Some(axis) = band_broadcast {
+            layer.index_axis_inplace(Axis(axis), 0);
+            format!(
+                "Data shape {:?} does not align with {} shape {:?}",
+                data.shape(),
+                name,
+                layer.shape()
+            )
+        } else {
+            format!(
+                "Data shape {:?} does not match {} shape {:?}",
+                data.shape(),
+                name,
+                layer.shape()
+            )
+        };
+        return Err(CoreError::InvalidArgument(msg).into());
     }
 
-    // Try 3D data with 3D SCL (time, y, x)
-    if let (Ok(data_3d), Ok(scl_3d)) = (coerce_3d(data), coerce_3d(scl)) {
-        let data_arr = data_3d.as_array();
-        let scl_arr = scl_3d.as_array();
+    let out = py.allow_threads(|| {
+        kernels::map2(name, data, layer, |d, v| if masked(v) { fill } else { d })
+    })?;
+    Ok(kernels::into_py(py, out))
+}
+
+/// A bit field of a QA band: `width` bits starting at `bit`. A pixel is
+/// flagged when the field holds one of `values`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct QaFlag {
+    pub bit: u32,
+    pub width: u32,
+    pub values: Vec<u16>,
+}
 
-        if data_arr.shape() != scl_arr.shape() {
+impl QaFlag {
+    pub fn new(bit: u32, width: u32, values: Vec<u16>) -> Result<Self, CoreError> {
+        if width == 0 || bit + width > 16 {
             return Err(CoreError::InvalidArgument(format!(
-                "Data shape {:?} does not match SCL shape {:?}",
-                data_arr.shape(),
-                scl_arr.shape()
-            ))
-            .into());
+                "QA flag at bit {} with width {} does not fit in 16 bits",
+                bit, width
+            )));
         }
+        if let Some(v) = values.iter().find(|&&v| u32::from(v) >= 1 << width) {
+            return Err(CoreError::InvalidArgument(format!(
+                "QA flag value {} does not fit in {} bit(s)",
+                v, width
+            )));
+        }
+        Ok(QaFlag { bit, width, values })
+    }
 
-        let mut out = data_arr.to_owned();
-        ndarray::Zip::from(&mut out)
-            .and(&scl_arr)
-            .for_each(|d, &s| {
-                if codes_to_mask.contains(&s) {
-                    *d = fill;
-                }
-            });
+    #[inline]
+    fn field(&self, qa: u16) -> u16 {
+        (qa >> self.bit) & ((1u32 << self.width) - 1) as u16
+    }
+}
+
+/// Named bit field of a Landsat Collection 2 QA band.
+pub(crate) struct QaPreset {
+    pub name: &'static str,
+    pub bit: u32,
+    pub width: u32,
+    /// Field values flagged when the preset is requested by name alone.
+    pub values: &'static [u16],
+    /// Whether the flag is part of the band's default mask.
+    pub default: bool,
+}
+
+impl QaPreset {
+    /// Single-bit flag, set when the condition holds.
+    const fn bit(name: &'static str, bit: u32, default: bool) -> Self {
+        QaPreset {
+            name,
+            bit,
+            width: 1,
+            values: &[1],
+            default,
+        }
+    }
+
+    /// Two-bit confidence level: 0 none, 1 low, 2 medium, 3 high. By name,
+    /// medium and high are flagged.
+    const fn confidence(name: &'static str, bit: u32) -> Self {
+        QaPreset {
+            name,
+            bit,
+            width: 2,
+            values: &[2, 3],
+            default: false,
+        }
+    }
+}
+
+/// Landsat Collection 2 Level-1/Level-2 `QA_PIXEL` bits. Cirrus (bit 2) is
+/// only set by Landsat 8/9 and never by TM/ETM+.
+pub(crate) const QA_PIXEL_FLAGS: &[QaPreset] = &[
+    QaPreset::bit("fill", 0, true),
+    QaPreset::bit("dilated_cloud", 1, true),
+    QaPreset::bit("cirrus", 2, true),
+    QaPreset::bit("cloud", 3, true),
+    QaPreset::bit("cloud_shadow", 4, true),
+    QaPreset::bit("snow", 5, true),
+    QaPreset::bit("clear", 6, false),
+    QaPreset::bit("water", 7, false),
+    QaPreset::confidence("cloud_confidence", 8),
+    QaPreset::confidence("cloud_shadow_confidence", 10),
+    QaPreset::confidence("snow_confidence", 12),
+    QaPreset::confidence("cirrus_confidence", 14),
+];
+
+/// Landsat Collection 2 `QA_RADSAT` bits: per-band radiometric saturation,
+/// plus dropped pixels (TM/ETM+) and terrain occlusion (OLI).
+pub(crate) const QA_RADSAT_FLAGS: &[QaPreset] = &[
+    QaPreset::bit("b1_saturated", 0, true),
+    QaPreset::bit("b2_saturated", 1, true),
+    QaPreset::bit("b3_saturated", 2, true),
+    QaPreset::bit("b4_saturated", 3, true),
+    QaPreset::bit("b5_saturated", 4, true),
+    QaPreset::bit("b6_saturated", 5, true),
+    QaPreset::bit("b7_saturated", 6, true),
+    QaPreset::bit("b9_saturated", 8, true),
+    QaPreset::bit("dropped_pixel", 9, true),
+    QaPreset::bit("terrain_occlusion", 11, true),
+];
 
-        return Ok(out.into_pyarray(py).into_py(py));
+/// Preset table of a QA band name.
+pub(crate) fn qa_presets(qa_band: &str) -> Result<&'static [QaPreset], CoreError> {
+    match qa_band.to_ascii_lowercase().as_str() {
+        "qa_pixel" => Ok(QA_PIXEL_FLAGS),
+        "qa_radsat" => Ok(QA_RADSAT_FLAGS),
+        _ => Err(CoreError::InvalidArgument(format!(
+            "Unknown QA band '{}'; expected 'qa_pixel' or 'qa_radsat'",
+            qa_band
+        ))),
     }
+}
 
-    // Try 4D data (time, band, y, x) with 3D SCL (time, y, x)
-    // SCL is broadcast across all bands
-    if let (Ok(data_4d), Ok(scl_3d)) = (coerce_4d(data), coerce_3d(scl)) {
-        let data_arr = data_4d.as_array();
-        let scl_arr = scl_3d.as_array();
-        let (t, b, h, w) = data_arr.dim();
-        let (scl_t, scl_h, scl_w) = scl_arr.dim();
+fn qa_preset(presets: &[QaPreset], name: &str) -> Result<QaFlag, CoreError> {
+    let preset = presets.iter().find(|p| p.name == name).ok_or_else(|| {
+        let names: Vec<&str> = presets.iter().map(|p| p.name).collect();
+        CoreError::InvalidArgument(format!(
+            "Unknown QA flag '{}'; available flags: {:?}",
+            name, names
+        ))
+    })?;
+    QaFlag::new(preset.bit, preset.width, preset.values.to_vec())
+}
 
-        if t != scl_t || h != scl_h || w != scl_w {
+fn default_qa_flags(presets: &[QaPreset]) -> Vec<QaFlag> {
+    presets
+        .iter()
+        .filter(|p| p.default)
+        .map(|p| QaFlag {
+            bit: p.bit,
+            width: p.width,
+            values: p.values.to_vec(),
+        })
+        .collect()
+}
+
+/// Resolve the `flags` argument of the QA functions. Each entry is a preset
+/// name, a `(name, values)` pair overriding the preset's values, or a custom
+/// `(bit, width, values)` triple; `values` may be a single int. `None` selects
+/// the band's default flags.
+fn extract_qa_flags(qa_band: &str, flags: Option<&PyAny>) -> PyResult<Vec<QaFlag>> {
+    let presets = qa_presets(qa_band)?;
+    let Some(flags) = flags else {
+        return Ok(default_qa_flags(presets));
+    };
+    let values = |obj: &PyAny| -> PyResult<Vec<u16>> {
+        obj.extract::<u16>()
+            .map(|v| vec![v])
+            .or_else(|_| obj.extract::<Vec<u16>>())
+    };
+    let mut out = Vec::new();
+    for item in flags.iter()? {
+        let item = item?;
+        let flag = if let Ok(name) = item.extract::<&str>() {
+            qa_preset(presets, name)?
+        } else if let Ok((name, v)) = item.extract::<(&str, &PyAny)>() {
+            let preset = qa_preset(presets, name)?;
+            QaFlag::new(preset.bit, preset.width, values(v)?)?
+        } else if let Ok((bit, width, v)) = item.extract::<(u32, u32, &PyAny)>() {
+            QaFlag::new(bit, width, values(v)?)?
+        } else {
             return Err(CoreError::InvalidArgument(format!(
-                "Data shape ({}, {}, {}, {}) does not align with SCL shape ({}, {}, {})",
-                t, b, h, w, scl_t, scl_h, scl_w
+                "QA flags must be names, (name, values) or (bit, width, values), got {}",
+                item
             ))
             .into());
+        };
+        out.push(flag);
+    }
+    Ok(out)
+}
+
+/// Lookup table over all 16-bit QA words: true where any flag fires.
+pub(crate) struct QaDecoder {
+    lut: Vec<bool>,
+}
+
+impl QaDecoder {
+    pub fn new(flags: &[QaFlag]) -> Self {
+        let lut = (0..=u16::MAX)
+            .map(|qa| flags.iter().any(|f| f.values.contains(&f.field(qa))))
+            .collect();
+        QaDecoder { lut }
+    }
+
+    #[inline]
+    pub fn flagged(&self, qa: u16) -> bool {
+        self.lut[qa as usize]
+    }
+
+    /// Boolean mask of `qa`, true where a flag fires.
+    pub fn mask(&self, qa: ArrayViewD<u16>) -> ArrayD<bool> {
+        let mut out = ArrayD::from_elem(qa.raw_dim(), false);
+        Zip::from(&mut out)
+            .and(&qa)
+            .par_for_each(|o, &v| *o = self.flagged(v));
+        out
+    }
+}
+
+/// Decode a Landsat Collection 2 QA band (or any bit-packed QA layer) into a
+/// boolean mask.
+///
+/// Parameters
+/// ----------
+/// qa : numpy.ndarray (uint16)
+///     QA band of any rank.
+/// flags : sequence, optional
+///     Flags to test. Each entry is a preset name (see below), a
+///     `(name, values)` pair, or a custom `(bit, width, values)` triple; a
+///     pixel is flagged when any field holds one of its values. Defaults to
+///     fill, dilated cloud, cirrus, cloud, cloud shadow and snow for
+///     `QA_PIXEL`, and every flag for `QA_RADSAT`.
+/// qa_band : str
+///     `"qa_pixel"` (default) or `"qa_radsat"`, selecting the preset names:
+///     `QA_PIXEL`: fill, dilated_cloud, cirrus, cloud, cloud_shadow, snow,
+///     clear, water and the two-bit cloud/cloud_shadow/snow/cirrus
+///     `_confidence` levels (0 none, 1 low, 2 medium, 3 high; medium and high
+///     by name). `QA_RADSAT`: b1..b7_saturated, b9_saturated, dropped_pixel,
+///     terrain_occlusion.
+///
+/// Returns
+/// -------
+/// numpy.ndarray (bool)
+///     True where the pixel is flagged.
+#[pyfunction]
+#[pyo3(signature = (qa, flags=None, qa_band="qa_pixel"))]
+pub fn qa_mask(
+    py: Python<'_>,
+    qa: PyReadonlyArrayDyn<u16>,
+    flags: Option<&PyAny>,
+    qa_band: &str,
+) -> PyResult<PyObject> {
+    let decoder = QaDecoder::new(&extract_qa_flags(qa_band, flags)?);
+    let qa = qa.as_array();
+    let out = py.allow_threads(|| decoder.mask(qa));
+    Ok(kernels::into_py(py, out))
+}
+
+/// Apply a QA bit-flag mask to a data array.
+///
+/// Pixels whose QA word fires any of `flags` (see `qa_mask`) are replaced
+/// by `fill_value`. The QA array broadcasts against `data` like the SCL in
+/// `mask_with_scl`: same shape, (time, y, x) QA for (time, band, y, x) data,
+/// or QA lacking only the band axis when `band_axis` is given.
+///
+/// Returns a float array shaped like `data` (float32 for float32 / uint16
+/// data, float64 otherwise).
+#[pyfunction]
+#[pyo3(signature = (data, qa, flags=None, qa_band="qa_pixel", fill_value=None, band_axis=None))]
+pub fn mask_with_qa(
+    py: Python<'_>,
+    data: &PyAny,
+    qa: PyReadonlyArrayDyn<u16>,
+    flags: Option<&PyAny>,
+    qa_band: &str,
+    fill_value: Option<f64>,
+    band_axis: Option<isize>,
+) -> PyResult<PyObject> {
+    let decoder = QaDecoder::new(&extract_qa_flags(qa_band, flags)?);
+    let fill = fill_value.unwrap_or(f64::NAN);
+    let qa = qa.as_array();
+    let layer = py.allow_threads(|| decoder.mask(qa).mapv(|m| if m { 1.0 } else { 0.0 }));
+    let masked = |v: f64| v != 0.0;
+    kernels::dispatch!(Operand::extract(data)?, |d| {
+        apply_layer(py, d, layer.view(), band_axis, "QA", masked, fill)
+    })
+}
+
+/// Pixels of a class mask that a buffer grows from: one class code, or any
+/// non-zero value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum BufferSource {
+    Any,
+    Code(u8),
+}
+
+impl BufferSource {
+    #[inline]
+    fn matches(self, v: u8) -> bool {
+        match self {
+            BufferSource::Any => v != 0,
+            BufferSource::Code(c) => v == c,
         }
+    }
+}
 
-        let mut out = data_arr.to_owned();
-
-        // Iterate over time and spatial dimensions, applying mask across all bands
-        for ti in 0..t {
-            for yi in 0..h {
-                for xi in 0..w {
-                    let scl_val = scl_arr[[ti, yi, xi]];
-                    if codes_to_mask.contains(&scl_val) {
-                        for bi in 0..b {
-                            out[[ti, bi, yi, xi]] = fill;
-                        }
-                    }
-                }
-            }
+/// Buffer one `(y, x)` class mask: a pixel is set when it lies within the
+/// source's distance of a source pixel, for any of `sources`. `spacing` is
+/// the `[dy, dx]` pixel size in the distance unit.
+pub(crate) fn buffer_plane(
+    mask: ArrayView2<u8>,
+    sources: &[(BufferSource, f64)],
+    spacing: [f64; 2],
+) -> Array2<bool> {
+    let mut out = Array2::from_elem(mask.raw_dim(), false);
+    for &(source, distance) in sources {
+        let features = mask.mapv(|v| source.matches(v));
+        if !features.iter().any(|&f| f) {
+            continue;
         }
+        if distance <= 0.0 {
+            Zip::from(&mut out).and(&features).for_each(|o, &f| *o |= f);
+            continue;
+        }
+        // Tolerate rounding in the squared distances of exact multiples.
+        let limit = distance * distance * (1.0 + 1e-12);
+        let dist = squared_distance_transform(features.view(), spacing);
+        Zip::from(&mut out)
+            .and(&dist)
+            .for_each(|o, &d| *o |= d <= limit);
+    }
+    out
+}
 
-        return Ok(out.into_pyarray(py).into_py(py));
+/// Buffer a `(y, x)` mask or a `(..., y, x)` stack of masks; planes are
+/// buffered in parallel.
+pub(crate) fn buffer_stack(
+    mask: ArrayViewD<u8>,
+    sources: &[(BufferSource, f64)],
+    spacing: [f64; 2],
+) -> Result<ArrayD<bool>, CoreError> {
+    let ndim = mask.ndim();
+    if ndim < 2 {
+        return Err(CoreError::InvalidArgument(format!(
+            "buffer_mask expects a (y, x) mask or a (..., y, x) stack, got {}-D input",
+            ndim
+        )));
+    }
+    if spacing.iter().any(|s| !(s.is_finite() && *s > 0.0)) {
+        return Err(CoreError::InvalidArgument(format!(
+            "pixel_size must be positive, got {:?}",
+            spacing
+        )));
+    }
+    if let Some((_, d)) = sources.iter().find(|(_, d)| !d.is_finite()) {
+        return Err(CoreError::InvalidArgument(format!(
+            "Buffer distances must be finite, got {}",
+            d
+        )));
     }
+    let (ny, nx) = (mask.shape()[ndim - 2], mask.shape()[ndim - 1]);
+    let planes = mask.len() / (ny * nx).max(1);
+    let stack = mask.as_standard_layout();
+    let stack = stack
+        .view()
+        .into_shape((planes, ny, nx))
+        .map_err(|e| CoreError::ComputationError(e.to_string()))?;
+    let mut out = Array3::from_elem((planes, ny, nx), false);
+    Zip::from(out.outer_iter_mut())
+        .and(stack.outer_iter())
+        .par_for_each(|mut o, m| o.assign(&buffer_plane(m, sources, spacing)));
+    out.into_shape(mask.raw_dim())
+        .map_err(|e| CoreError::ComputationError(e.to_string()))
+}
 
-    Err(CoreError::InvalidArgument(
-        "mask_with_scl requires: 2D data + 2D SCL, 3D data + 3D SCL, or 4D data + 3D SCL."
-            .to_string(),
-    )
-    .into())
+/// Buffer a cloud (or any class) mask by a metric distance.
+///
+/// Every pixel within `distance_m` (Euclidean, measured between pixel
+/// centres with an exact distance transform) of a masked pixel is set, so
+/// the buffer is a true disk rather than the square of `binary_dilation`.
+///
+/// Parameters
+/// ----------
+/// mask : numpy.ndarray (uint8)
+///     `(y, x)` mask or `(time, y, x)` stack (any leading dims). With a
+///     scalar distance, non-zero pixels are buffered; with a mapping, the
+///     mask holds class codes (e.g. SCL).
+/// distance_m : float or dict[int, float]
+///     Buffer distance, or per-class distances such as `{9: 300, 3: 150}`.
+///     Classes not in the mapping are ignored.
+/// pixel_size : float or (float, float)
+///     Pixel size in the same unit, or `(dy, dx)` for non-square pixels.
+///
+/// Returns
+/// -------
+/// numpy.ndarray (bool)
+///     Buffered mask shaped like `mask`; planes of a stack are buffered in
+///     parallel.
+#[pyfunction]
+#[pyo3(signature = (mask, distance_m, pixel_size))]
+pub fn buffer_mask(
+    py: Python<'_>,
+    mask: PyReadonlyArrayDyn<u8>,
+    distance_m: &PyAny,
+    pixel_size: &PyAny,
+) -> PyResult<PyObject> {
+    let sources = match distance_m.extract::<f64>() {
+        Ok(d) => vec![(BufferSource::Any, d)],
+        Err(_) => {
+            let per_class: std::collections::BTreeMap<u8, f64> =
+                distance_m.extract().map_err(|_| {
+                    CoreError::InvalidArgument(
+                        "distance_m must be a number or a mapping of class code to distance"
+                            .to_string(),
+                    )
+                })?;
+            per_class
+                .into_iter()
+                .map(|(c, d)| (BufferSource::Code(c), d))
+                .collect()
+        }
+    };
+    let spacing = match pixel_size.extract::<f64>() {
+        Ok(s) => [s, s],
+        Err(_) => {
+            let (dy, dx): (f64, f64) = pixel_size.extract()?;
+            [dy, dx]
+        }
+    };
+    let mask = mask.as_array();
+    let out = py.allow_threads(|| buffer_stack(mask, &sources, spacing))?;
+    Ok(kernels::into_py(py, out))
+}
+
+/// Pixel offsets `(dy, dx)` of a cloud's shadow for cloud heights in
+/// `heights` (same unit as `pixel_size`). The shadow falls away from the sun
+/// (azimuth clockwise from north, rows increasing southwards) at
+/// `height * tan(zenith)`; heights are sampled so consecutive offsets move
+/// at most one pixel.
+pub(crate) fn shadow_offsets(
+    sun_azimuth: f64,
+    sun_zenith: f64,
+    pixel_size: f64,
+    heights: (f64, f64),
+) -> Result<Vec<(isize, isize)>, CoreError> {
+    if !(0.0..90.0).contains(&sun_zenith) {
+        return Err(CoreError::InvalidArgument(format!(
+            "sun_zenith must be in [0, 90) degrees, got {}",
+            sun_zenith
+        )));
+    }
+    if !(pixel_size.is_finite() && pixel_size > 0.0) {
+        return Err(CoreError::InvalidArgument(format!(
+            "pixel_size must be positive, got {}",
+            pixel_size
+        )));
+    }
+    let (low, high) = heights;
+    if !(low.is_finite() && high.is_finite() && 0.0 <= low && low <= high) {
+        return Err(CoreError::InvalidArgument(format!(
+            "cloud_heights must satisfy 0 <= min <= max, got ({}, {})",
+            low, high
+        )));
+    }
+    let (az, tan_z) = (sun_azimuth.to_radians(), sun_zenith.to_radians().tan());
+    // Displacement per unit height, in pixels.
+    let (ky, kx) = (
+        tan_z * az.cos() / pixel_size,
+        -tan_z * az.sin() / pixel_size,
+    );
+    let per_height = ky.abs().max(kx.abs());
+    let steps = if per_height > 0.0 {
+        ((high - low) * per_height).ceil() as usize
+    } else {
+        0
+    };
+    let mut offsets: Vec<(isize, isize)> = (0..=steps)
+        .map(|i| {
+            let h = if steps == 0 {
+                low
+            } else {
+                low + (high - low) * i as f64 / steps as f64
+            };
+            ((h * ky).round() as isize, (h * kx).round() as isize)
+        })
+        .collect();
+    offsets.dedup();
+    Ok(offsets)
+}
+
+/// Shadow mask of a `(y, x)` scene: pixels hit by the cloud projection at
+/// any of `offsets` that are dark in NIR (and SWIR, if given) and not cloud
+/// themselves. Band values are scaled before the thresholds apply.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn project_cloud_shadows<A: Sample>(
+    cloud: ArrayView2<bool>,
+    nir: ArrayView2<A>,
+    swir: Option<ArrayView2<A>>,
+    offsets: &[(isize, isize)],
+    nir_threshold: f64,
+    swir_threshold: f64,
+    scaling: Scaling,
+) -> Result<Array2<bool>, CoreError> {
+    let dim = cloud.dim();
+    if nir.dim() != dim || swir.is_some_and(|s| s.dim() != dim) {
+        return Err(CoreError::InvalidArgument(format!(
+            "Cloud mask {:?}, NIR {:?} and SWIR {:?} must have the same shape",
+            dim,
+            nir.dim(),
+            swir.map(|s| s.dim())
+        )));
+    }
+    let (rows, cols) = (dim.0 as isize, dim.1 as isize);
+    let dark = |v: A, t: f64| scaling.apply(v.to_f64()) < t;
+    let mut out = Array2::from_elem(dim, false);
+    Zip::indexed(&mut out).par_for_each(|(r, c), o| {
+        if cloud[[r, c]]
+            || !dark(nir[[r, c]], nir_threshold)
+            || swir.is_some_and(|s| !dark(s[[r, c]], swir_threshold))
+        {
+            return;
+        }
+        *o = offsets.iter().any(|&(dy, dx)| {
+            let (sr, sc) = (r as isize - dy, c as isize - dx);
+            (0..rows).contains(&sr) && (0..cols).contains(&sc) && cloud[[sr as usize, sc as usize]]
+        });
+    });
+    Ok(out)
+}
+
+/// Cloud shadow mask from scene geometry.
+///
+/// The cloud mask is projected away from the sun for every candidate cloud
+/// height in `cloud_heights`, and projected pixels that are dark in NIR (and
+/// in SWIR, if given) are flagged as shadow. Cloud pixels are never shadow.
+///
+/// Parameters
+/// ----------
+/// cloud : numpy.ndarray (bool)
+///     `(y, x)` cloud mask.
+/// nir : numpy.ndarray
+///     `(y, x)` NIR band.
+/// sun_azimuth, sun_zenith : float
+///     Solar angles in degrees (azimuth clockwise from north, image rows
+///     pointing south).
+/// pixel_size : float
+///     Pixel size, in the unit of `cloud_heights` (metres by default).
+/// swir : numpy.ndarray, optional
+///     `(y, x)` SWIR1 band for a second darkness test.
+/// cloud_heights : (float, float)
+///     Range of cloud base heights to project (default 200-12000 m).
+/// nir_threshold, swir_threshold : float
+///     Reflectance below which a pixel counts as dark (defaults 0.15 / 0.10).
+/// scale, offset : float, optional
+///     DN conversion applied to the bands.
+///
+/// Returns
+/// -------
+/// numpy.ndarray (bool)
+///     Shadow mask.
+#[pyfunction]
+#[pyo3(signature = (
+    cloud,
+    nir,
+    sun_azimuth,
+    sun_zenith,
+    pixel_size,
+    swir=None,
+    cloud_heights=(200.0, 12000.0),
+    nir_threshold=0.15,
+    swir_threshold=0.10,
+    scale=None,
+    offset=None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn cloud_shadow_mask(
+    py: Python<'_>,
+    cloud: PyReadonlyArray2<bool>,
+    nir: &PyAny,
+    sun_azimuth: f64,
+    sun_zenith: f64,
+    pixel_size: f64,
+    swir: Option<&PyAny>,
+    cloud_heights: (f64, f64),
+    nir_threshold: f64,
+    swir_threshold: f64,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    let offsets = shadow_offsets(sun_azimuth, sun_zenith, pixel_size, cloud_heights)?;
+    let scaling = Scaling::from_args(scale, offset)?;
+    let cloud = cloud.as_array();
+    let objs: Vec<&PyAny> = std::iter::once(nir).chain(swir).collect();
+    let operands = Operand::extract_common(&objs)?;
+    fn as_2d<A>(band: ArrayViewD<'_, A>) -> Result<ArrayView2<'_, A>, CoreError> {
+        band.into_dimensionality::<Ix2>().map_err(|_| {
+            CoreError::InvalidArgument("cloud_shadow_mask expects 2-D (y, x) bands".to_string())
+        })
+    }
+    let out = kernels::dispatch!(all operands, |views| {
+        let mut views = views.into_iter().map(as_2d);
+        let nir = views.next().unwrap()?;
+        let swir = views.next().transpose()?;
+        py.allow_threads(|| {
+            project_cloud_shadows(
+                cloud,
+                nir,
+                swir,
+                &offsets,
+                nir_threshold,
+                swir_threshold,
+                scaling,
+            )
+        })?
+    });
+    Ok(kernels::into_py(py, out.into_dyn()))
+}
+
+/// Scene classes counted by `scene_cloud_stats`, in output order.
+pub(crate) const SCENE_CLASSES: [&str; 5] = ["clear", "cloud", "shadow", "snow", "nodata"];
+const CLEAR: u8 = 0;
+const CLOUD: u8 = 1;
+const SHADOW: u8 = 2;
+const SNOW: u8 = 3;
+const NODATA: u8 = 4;
+
+/// Scene class (index into `SCENE_CLASSES`) of every 16-bit SCL or QA code.
+pub(crate) struct SceneClassifier {
+    lut: Vec<u8>,
+}
+
+impl SceneClassifier {
+    /// Sentinel-2 SCL: no data / saturated (0, 1) and codes above 11 are
+    /// nodata, dark area and cloud shadow (2, 3) shadow, medium/high cloud
+    /// probability and cirrus (8-10) cloud, 11 snow, the rest clear.
+    pub fn scl() -> Self {
+        let lut = (0..=u16::MAX)
+            .map(|code| match code {
+                0 | 1 => NODATA,
+                2 | 3 => SHADOW,
+                4..=7 => CLEAR,
+                8..=10 => CLOUD,
+                11 => SNOW,
+                _ => NODATA,
+            })
+            .collect();
+        SceneClassifier { lut }
+    }
+
+    /// Landsat Collection 2 `QA_PIXEL`: fill is nodata, dilated cloud,
+    /// cirrus and cloud are cloud, then cloud shadow, then snow.
+    pub fn qa_pixel() -> Result<Self, CoreError> {
+        let rules = [
+            (NODATA, "fill"),
+            (CLOUD, "dilated_cloud"),
+            (CLOUD, "cirrus"),
+            (CLOUD, "cloud"),
+            (SHADOW, "cloud_shadow"),
+            (SNOW, "snow"),
+        ]
+        .into_iter()
+        .map(|(class, name)| Ok((class, qa_preset(QA_PIXEL_FLAGS, name)?)))
+        .collect::<Result<Vec<_>, CoreError>>()?;
+        let lut = (0..=u16::MAX)
+            .map(|qa| {
+                rules
+                    .iter()
+                    .find(|(_, f)| f.values.contains(&f.field(qa)))
+                    .map_or(CLEAR, |(class, _)| *class)
+            })
+            .collect();
+        Ok(SceneClassifier { lut })
+    }
+
+    pub fn parse(source: &str) -> Result<Self, CoreError> {
+        match source.to_ascii_lowercase().as_str() {
+            "scl" => Ok(Self::scl()),
+            "qa_pixel" => Self::qa_pixel(),
+            _ => Err(CoreError::InvalidArgument(format!(
+                "Unknown scene_cloud_stats source '{}'; expected 'scl' or 'qa_pixel'",
+                source
+            ))),
+        }
+    }
+
+    #[inline]
+    pub fn class(&self, code: u16) -> u8 {
+        self.lut[code as usize]
+    }
+}
+
+/// Per-timestep class fractions and per-pixel clear counts of a stack.
+pub(crate) struct SceneStats {
+    /// `(time, class)` fraction of AOI pixels, classes as in `SCENE_CLASSES`.
+    pub fractions: Array2<f64>,
+    /// Clear observations per pixel (0 outside the AOI).
+    pub clear_count: Array2<u32>,
+    /// Number of AOI pixels per timestep.
+    pub aoi_pixels: usize,
+}
+
+/// Classify a `(time, y, x)` SCL/QA stack in a single parallel pass over
+/// pixels, counting classes per timestep inside `aoi` and clear observations
+/// per pixel.
+pub(crate) fn scene_stats(
+    layer: ArrayView3<u16>,
+    aoi: Option<ArrayView2<bool>>,
+    classifier: &SceneClassifier,
+) -> Result<SceneStats, CoreError> {
+    let (times, ny, nx) = layer.dim();
+    let everywhere = Array2::from_elem((ny, nx), true);
+    let aoi = aoi.as_ref().map_or(everywhere.view(), |a| a.view());
+    if aoi.dim() != (ny, nx) {
+        return Err(CoreError::InvalidArgument(format!(
+            "AOI mask {:?} must match the (y, x) shape {:?} of the stack",
+            aoi.dim(),
+            (ny, nx)
+        )));
+    }
+    let mut clear_count = Array2::<u32>::zeros((ny, nx));
+    let counts = Zip::from(&mut clear_count)
+        .and(layer.lanes(Axis(0)))
+        .and(&aoi)
+        .par_fold(
+            || Array2::<u64>::zeros((times, SCENE_CLASSES.len())),
+            |mut counts, n, series, &inside| {
+                if inside {
+                    for (t, &code) in series.iter().enumerate() {
+                        let class = classifier.class(code);
+                        counts[[t, class as usize]] += 1;
+                        *n += u32::from(class == CLEAR);
+                    }
+                }
+                counts
+            },
+            |a, b| a + b,
+        );
+    let aoi_pixels = aoi.iter().filter(|&&v| v).count();
+    let fractions = counts.mapv(|c| c as f64 / aoi_pixels as f64);
+    Ok(SceneStats {
+        fractions,
+        clear_count,
+        aoi_pixels,
+    })
+}
+
+/// Per-scene cloud cover and valid-pixel statistics for scene selection.
+///
+/// Every pixel of a `(time, y, x)` SCL or QA stack is classified as clear,
+/// cloud, shadow, snow or nodata in one parallel pass.
+///
+/// Parameters
+/// ----------
+/// layer : numpy.ndarray (uint16)
+///     `(time, y, x)` Sentinel-2 SCL or Landsat C2 `QA_PIXEL` stack.
+/// source : str
+///     `"scl"` (default): 0, 1 and unknown codes are nodata, 2-3 shadow,
+///     8-10 cloud, 11 snow and 4-7 clear. `"qa_pixel"`: fill is nodata,
+///     dilated cloud / cirrus / cloud are cloud, then cloud shadow and snow;
+///     anything else is clear.
+/// aoi : numpy.ndarray (bool), optional
+///     `(y, x)` area of interest; only pixels inside it are counted.
+///
+/// Returns
+/// -------
+/// dict
+///     `clear`, `cloud`, `shadow`, `snow`, `nodata`: `(time,)` fractions of
+///     AOI pixels (summing to 1; NaN for an empty AOI), `clear_count`:
+///     `(y, x)` uint32 number of clear observations, and `aoi_pixels`.
+#[pyfunction]
+#[pyo3(signature = (layer, source="scl", aoi=None))]
+pub fn scene_cloud_stats(
+    py: Python<'_>,
+    layer: PyReadonlyArray3<u16>,
+    source: &str,
+    aoi: Option<PyReadonlyArray2<bool>>,
+) -> PyResult<PyObject> {
+    let classifier = SceneClassifier::parse(source)?;
+    let layer = layer.as_array();
+    let aoi = aoi.as_ref().map(|a| a.as_array());
+    let stats = py.allow_threads(|| scene_stats(layer, aoi, &classifier))?;
+    let dict = PyDict::new(py);
+    for (name, column) in SCENE_CLASSES.iter().zip(stats.fractions.columns()) {
+        let column: Array1<f64> = column.to_owned();
+        dict.set_item(*name, kernels::into_py(py, column.into_dyn()))?;
+    }
+    dict.set_item(
+        "clear_count",
+        kernels::into_py(py, stats.clear_count.into_dyn()),
+    )?;
+    dict.set_item("aoi_pixels", stats.aoi_pixels)?;
+    Ok(dict.to_object(py))
 }
 
 #[cfg(test)]
@@ -659,4 +1432,256 @@ mod tests {
             assert_eq!(result[[1, 1]], 5.0);
         });
     }
+
+    #[test]
+    fn test_mask_with_scl_sensor_cube() {
+        Python::with_gil(|py| {
+            // (band, y, x) cube: B04, SCL, B08; SCL 9 (cloud high) at [0, 0].
+            let cube = ndarray::Array3::from_shape_fn((3, 2, 2), |(b, y, x)| match b {
+                1 if y == 0 && x == 0 => 9_u16,
+                1 => 4,
+                _ => 1000 + b as u16,
+            });
+            let cube = numpy::PyArray3::from_owned_array(py, cube);
+            let names = vec!["B04".to_string(), "SCL".to_string(), "B08".to_string()];
+            let out = mask_with_scl(
+                py,
+                cube,
+                None,
+                None,
+                None,
+                Some("sentinel2"),
+                None,
+                Some(names),
+            )
+            .unwrap();
+            let out = out
+                .extract::<&numpy::PyArrayDyn<f32>>(py)
+                .unwrap()
+                .to_owned_array();
+            assert!(out[[0, 0, 0]].is_nan() && out[[2, 0, 0]].is_nan());
+            assert_eq!(out[[0, 1, 1]], 1000.0);
+            // The SCL band is passed through.
+            assert_eq!(out[[1, 0, 0]], 9.0);
+
+            let err = mask_with_scl(py, cube, None, None, None, Some("landsat8"), None, None)
+                .unwrap_err();
+            assert!(err.to_string().contains("no scene classification"));
+        });
+    }
+
+    #[test]
+    fn test_buffer_is_a_metric_disk() {
+        let mut mask = ndarray::Array2::<u8>::zeros((11, 11));
+        mask[[5, 5]] = 9;
+        let sources = [(BufferSource::Any, 30.0)];
+        let out = buffer_plane(mask.view(), &sources, [10.0, 10.0]);
+        // Radius 3 pixels: 29 pixels, (3, 0) inside, (2, 3) outside.
+        assert_eq!(out.iter().filter(|&&b| b).count(), 29);
+        assert!(out[[2, 5]] && out[[5, 8]] && out[[3, 3]]);
+        assert!(!out[[3, 2]] && !out[[1, 5]]);
+
+        // Anisotropic pixels: 3 rows but 1.5 columns of 20 m.
+        let out = buffer_plane(mask.view(), &sources, [10.0, 20.0]);
+        assert!(out[[2, 5]] && out[[5, 6]] && !out[[5, 7]]);
+    }
+
+    #[test]
+    fn test_buffer_per_class_over_stack() {
+        let mut stack = ndarray::Array3::<u8>::from_elem((2, 9, 9), 4);
+        stack[[0, 4, 4]] = 9; // cloud
+        stack[[1, 0, 0]] = 3; // shadow
+        let sources = [(BufferSource::Code(9), 20.0), (BufferSource::Code(3), 10.0)];
+        let out = buffer_stack(stack.view().into_dyn(), &sources, [10.0, 10.0]).unwrap();
+        assert_eq!(out.shape(), &[2, 9, 9]);
+        let counts: Vec<usize> = out
+            .outer_iter()
+            .map(|p| p.iter().filter(|&&b| b).count())
+            .collect();
+        assert_eq!(counts, vec![13, 3]);
+        assert!(buffer_stack(stack.view().into_dyn(), &sources, [0.0, 10.0]).is_err());
+        let flat = ndarray::Array1::<u8>::zeros(4);
+        assert!(buffer_stack(flat.view().into_dyn(), &sources, [10.0, 10.0]).is_err());
+    }
+
+    #[test]
+    fn test_shadow_offsets_point_away_from_sun() {
+        // Sun in the south-east: shadows fall to the north-west (up-left).
+        let offsets = shadow_offsets(135.0, 45.0, 100.0, (100.0, 300.0)).unwrap();
+        assert!(offsets.iter().all(|&(dy, dx)| dy <= 0 && dx <= 0));
+        assert_eq!(offsets.first(), Some(&(-1, -1)));
+        assert_eq!(offsets.last(), Some(&(-2, -2)));
+        // Consecutive offsets move at most one pixel.
+        assert!(offsets
+            .windows(2)
+            .all(|w| (w[0].0 - w[1].0).abs() <= 1 && (w[0].1 - w[1].1).abs() <= 1));
+        // Overhead sun: the shadow sits under the cloud.
+        assert_eq!(
+            shadow_offsets(0.0, 0.0, 30.0, (0.0, 5000.0)).unwrap(),
+            [(0, 0)]
+        );
+        assert!(shadow_offsets(0.0, 90.0, 30.0, (0.0, 1.0)).is_err());
+        assert!(shadow_offsets(0.0, 30.0, 30.0, (500.0, 100.0)).is_err());
+    }
+
+    #[test]
+    fn test_project_cloud_shadows_needs_dark_pixels() {
+        let mut cloud = ndarray::Array2::from_elem((8, 8), false);
+        cloud[[5, 5]] = true;
+        cloud[[5, 6]] = true;
+        // Shadow 3 px west (sun in the east).
+        let offsets = shadow_offsets(90.0, 45.0, 10.0, (30.0, 30.0)).unwrap();
+        assert_eq!(offsets, [(0, -3)]);
+        let mut nir = ndarray::Array2::from_elem((8, 8), 3000_u16);
+        nir[[5, 2]] = 500;
+        nir[[5, 3]] = 500;
+        nir[[1, 1]] = 500; // dark but not under the projection
+        let out = project_cloud_shadows(
+            cloud.view(),
+            nir.view(),
+            None,
+            &offsets,
+            0.15,
+            0.1,
+            Scaling::from_args(Some(1e-4), None).unwrap(),
+        )
+        .unwrap();
+        let hits: Vec<(usize, usize)> = out
+            .indexed_iter()
+            .filter(|(_, &v)| v)
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(hits, vec![(5, 2), (5, 3)]);
+
+        // A bright SWIR rules the pixel out.
+        let mut swir = ndarray::Array2::from_elem((8, 8), 300_u16);
+        swir[[5, 3]] = 2500;
+        let out = project_cloud_shadows(
+            cloud.view(),
+            nir.view(),
+            Some(swir.view()),
+            &offsets,
+            0.15,
+            0.1,
+            Scaling::from_args(Some(1e-4), None).unwrap(),
+        )
+        .unwrap();
+        assert!(out[[5, 2]] && !out[[5, 3]]);
+    }
+
+    #[test]
+    fn test_scene_classifiers() {
+        let scl = SceneClassifier::scl();
+        let classes: Vec<u8> = (0..13).map(|c| scl.class(c)).collect();
+        assert_eq!(classes, [4, 4, 2, 2, 0, 0, 0, 0, 1, 1, 1, 3, 4]);
+
+        let qa = SceneClassifier::qa_pixel().unwrap();
+        assert_eq!(qa.class(21824), CLEAR); // Landsat 8 clear land
+        assert_eq!(qa.class(1), NODATA);
+        assert_eq!(qa.class(1 << 3 | 1 << 4), CLOUD);
+        assert_eq!(qa.class(1 << 4 | 1 << 5), SHADOW);
+        assert_eq!(qa.class(1 << 5), SNOW);
+        assert!(SceneClassifier::parse("qa_radsat").is_err());
+    }
+
+    #[test]
+    fn test_scene_stats_fractions_and_counts() {
+        // t0: all clear; t1: one cloud, one shadow, one nodata, one clear.
+        let layer =
+            ndarray::Array3::from_shape_vec((2, 2, 2), vec![4, 5, 6, 7, 9, 3, 0, 4]).unwrap();
+        let stats = scene_stats(layer.view(), None, &SceneClassifier::scl()).unwrap();
+        assert_eq!(stats.aoi_pixels, 4);
+        assert_eq!(stats.fractions.row(0).to_vec(), [1.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(
+            stats.fractions.row(1).to_vec(),
+            [0.25, 0.25, 0.25, 0.0, 0.25]
+        );
+        assert_eq!(stats.clear_count, ndarray::array![[1, 1], [1, 2]]);
+
+        let aoi = ndarray::array![[true, false], [false, false]];
+        let stats = scene_stats(layer.view(), Some(aoi.view()), &SceneClassifier::scl()).unwrap();
+        assert_eq!(stats.aoi_pixels, 1);
+        assert_eq!(stats.fractions.row(1).to_vec(), [0.0, 1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(stats.clear_count, ndarray::array![[1, 0], [0, 0]]);
+
+        let wrong = ndarray::Array2::from_elem((3, 2), true);
+        assert!(scene_stats(layer.view(), Some(wrong.view()), &SceneClassifier::scl()).is_err());
+    }
+
+    #[test]
+    fn test_code_set_lookup() {
+        let codes = CodeSet::new(&[4.0, 11.0, 300.0, -1.0]);
+        assert!(codes.contains(4.0) && codes.contains(11.0) && codes.contains_u8(4));
+        assert!(codes.contains(300.0) && codes.contains(-1.0));
+        assert!(!codes.contains(5.0) && !codes.contains(4.5) && !codes.contains(f64::NAN));
+        assert!(!codes.contains_u8(255));
+    }
+
+    #[test]
+    fn test_scl_valid_mask_3d() {
+        let codes = CodeSet::new(&[4.0, 5.0]);
+        let scl = ndarray::Array3::from_shape_fn((2, 2, 2), |(t, y, x)| (3 + t + y + x) as u8);
+        let mask = scl_valid_mask(scl.view().into_dyn(), |v| codes.contains_u8(v), 1u8, 0);
+        assert_eq!(mask.shape(), &[2, 2, 2]);
+        assert_eq!(
+            mask.iter().copied().collect::<Vec<_>>(),
+            vec![0, 1, 1, 1, 1, 1, 1, 0]
+        );
+    }
+
+    fn decoder(qa_band: &str, flags: &[(&str, Option<Vec<u16>>)]) -> QaDecoder {
+        let presets = qa_presets(qa_band).unwrap();
+        let flags: Vec<QaFlag> = if flags.is_empty() {
+            default_qa_flags(presets)
+        } else {
+            flags
+                .iter()
+                .map(|(name, values)| {
+                    let flag = qa_preset(presets, name).unwrap();
+                    match values {
+                        Some(v) => QaFlag::new(flag.bit, flag.width, v.clone()).unwrap(),
+                        None => flag,
+                    }
+                })
+                .collect()
+        };
+        QaDecoder::new(&flags)
+    }
+
+    #[test]
+    fn test_qa_pixel_defaults_and_confidence() {
+        // Landsat C2 clear land, clear water, high-confidence cloud and fill.
+        let words = [21824_u16, 21952, 22280, 1];
+        let hits = |d: &QaDecoder| words.iter().map(|&w| d.flagged(w)).collect::<Vec<_>>();
+        assert_eq!(hits(&decoder("qa_pixel", &[])), [false, false, true, true]);
+        assert_eq!(
+            hits(&decoder("qa_pixel", &[("water", None)])),
+            [false, true, false, false]
+        );
+        // Low cloud confidence (bits 8-9 == 1) everywhere except the cloud.
+        assert_eq!(
+            hits(&decoder("QA_PIXEL", &[("cloud_confidence", Some(vec![1]))])),
+            [true, true, false, false]
+        );
+        let custom = QaDecoder::new(&[QaFlag::new(8, 2, vec![3]).unwrap()]);
+        assert_eq!(hits(&custom), [false, false, true, false]);
+
+        let qa = ndarray::array![[21824_u16, 22280], [1, 21952]].into_dyn();
+        let mask = decoder("qa_pixel", &[]).mask(qa.view());
+        assert_eq!(
+            mask,
+            ndarray::array![[false, true], [true, false]].into_dyn()
+        );
+    }
+
+    #[test]
+    fn test_qa_flag_validation() {
+        assert!(QaFlag::new(15, 2, vec![1]).is_err());
+        assert!(QaFlag::new(0, 1, vec![2]).is_err());
+        assert!(qa_presets("qa_aerosol").is_err());
+        let err = qa_preset(QA_RADSAT_FLAGS, "cloud").unwrap_err();
+        assert!(err.to_string().contains("b1_saturated"));
+        let radsat = decoder("qa_radsat", &[]);
+        assert!(radsat.flagged(1 << 4) && !radsat.flagged(1 << 7) && !radsat.flagged(0));
+    }
 }
@@ -1,141 +1,1552 @@
-use ndarray::{Array2, ArrayView2};
+use crate::kernels::{self, Operand, Real, Sample};
+use crate::CoreError;
+use ndarray::{Array2, ArrayView2, ArrayViewD, Axis, Ix2, Zip};
 use numpy::{PyReadonlyArray2, ToPyArray};
 use pyo3::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Structuring element: the pixel offsets `(dy, dx)` it covers, relative to
+/// its origin at `(rows / 2, cols / 2)`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Footprint {
+    offsets: Vec<(isize, isize)>,
+}
+
+impl Footprint {
+    /// Footprint from a boolean mask, centred on `(rows / 2, cols / 2)`.
+    pub fn from_mask(mask: ArrayView2<bool>) -> Result<Self, CoreError> {
+        let (cy, cx) = (mask.nrows() as isize / 2, mask.ncols() as isize / 2);
+        let offsets: Vec<(isize, isize)> = mask
+            .indexed_iter()
+            .filter(|(_, &on)| on)
+            .map(|((r, c), _)| (r as isize - cy, c as isize - cx))
+            .collect();
+        if offsets.is_empty() {
+            return Err(CoreError::InvalidArgument(
+                "Footprint must contain at least one true pixel".to_string(),
+            ));
+        }
+        Ok(Footprint { offsets })
+    }
+
+    /// Named footprint spanning `size` pixels (radius `size / 2`):
+    /// `"square"`, `"disk"` (Euclidean), `"diamond"` (city-block), `"cross"`
+    /// (plus sign) or `"line"` through the centre at `angle` degrees
+    /// counter-clockwise from the x axis.
+    pub fn named(shape: &str, size: usize, angle: f64) -> Result<Self, CoreError> {
+        Footprint::from_mask(structuring_element_mask(shape, size, angle)?.view())
+    }
+
+    pub fn square(size: usize) -> Self {
+        let r = (size / 2) as isize;
+        let offsets = (-r..=r)
+            .flat_map(|dy| (-r..=r).map(move |dx| (dy, dx)))
+            .collect();
+        Footprint { offsets }
+    }
+
+    /// `binary_*` footprint argument: `None` is a `kernel_size` square, a
+    /// string names a shape of `kernel_size`, anything else is a boolean
+    /// mask.
+    pub fn extract(kernel_size: usize, footprint: Option<&PyAny>) -> PyResult<Self> {
+        match footprint {
+            None => Ok(Footprint::square(kernel_size)),
+            Some(obj) => match obj.extract::<&str>() {
+                Ok(shape) => Ok(Footprint::named(shape, kernel_size, 0.0)?),
+                Err(_) => {
+                    let mask: PyReadonlyArray2<bool> = obj.extract()?;
+                    Ok(Footprint::from_mask(mask.as_array())?)
+                }
+            },
+        }
+    }
+
+    pub fn offsets(&self) -> &[(isize, isize)] {
+        &self.offsets
+    }
+
+    /// Row and column offset ranges `((y0, y1), (x0, x1))` when the footprint
+    /// fills its bounding box.
+    pub fn rectangle(&self) -> Option<((isize, isize), (isize, isize))> {
+        let ys = self.offsets.iter().map(|o| o.0);
+        let xs = self.offsets.iter().map(|o| o.1);
+        let (y0, y1) = (ys.clone().min()?, ys.max()?);
+        let (x0, x1) = (xs.clone().min()?, xs.max()?);
+        let area = ((y1 - y0 + 1) * (x1 - x0 + 1)) as usize;
+        (area == self.offsets.len()).then_some(((y0, y1), (x0, x1)))
+    }
+}
+
+/// Boolean mask of a named structuring element (see `Footprint::named`).
+fn structuring_element_mask(
+    shape: &str,
+    size: usize,
+    angle: f64,
+) -> Result<Array2<bool>, CoreError> {
+    let r = (size / 2) as isize;
+    let n = (2 * r + 1) as usize;
+    let within = |f: &dyn Fn(isize, isize) -> bool| {
+        Array2::from_shape_fn((n, n), |(y, x)| f(y as isize - r, x as isize - r))
+    };
+    match shape {
+        "square" => Ok(Array2::from_elem((n, n), true)),
+        "disk" => Ok(within(&|dy, dx| dy * dy + dx * dx <= r * r)),
+        "diamond" => Ok(within(&|dy, dx| dy.abs() + dx.abs() <= r)),
+        "cross" => Ok(within(&|dy, dx| dy == 0 || dx == 0)),
+        "line" => {
+            if !angle.is_finite() {
+                return Err(CoreError::InvalidArgument(format!(
+                    "Line angle must be finite, got {}",
+                    angle
+                )));
+            }
+            // Step along the dominant axis so the line is 8-connected.
+            let (sin, cos) = angle.to_radians().sin_cos();
+            let scale = sin.abs().max(cos.abs());
+            let mut mask = Array2::from_elem((n, n), false);
+            for t in -r..=r {
+                let dy = (-(t as f64) * sin / scale).round() as isize;
+                let dx = ((t as f64) * cos / scale).round() as isize;
+                mask[[(dy + r) as usize, (dx + r) as usize]] = true;
+            }
+            Ok(mask)
+        }
+        _ => Err(CoreError::InvalidArgument(format!(
+            "Unknown footprint '{}'; expected 'square', 'disk', 'diamond', 'cross' or 'line'",
+            shape
+        ))),
+    }
+}
+
+/// Build a structuring element for the binary morphology functions.
+///
+/// # Arguments
+/// * `shape` - `"square"`, `"disk"`, `"diamond"`, `"cross"` or `"line"`.
+/// * `size` - Width in pixels (radius `size // 2`).
+/// * `angle` - Line orientation in degrees counter-clockwise from the x axis.
+///
+/// # Returns
+/// `(size, size)` boolean footprint (odd sizes; even sizes round up).
+#[pyfunction]
+#[pyo3(signature = (shape, size=3, angle=0.0))]
+pub fn structuring_element(
+    py: Python<'_>,
+    shape: &str,
+    size: usize,
+    angle: f64,
+) -> PyResult<PyObject> {
+    Ok(structuring_element_mask(shape, size, angle)?
+        .to_pyarray(py)
+        .into())
+}
 
 /// Perform binary dilation on a 2D boolean/int array.
 ///
 /// # Arguments
 /// * `input` - 2D input array (treated as boolean: >0 is True).
 /// * `kernel_size` - Size of the square structuring element (default 3).
+/// * `footprint` - Optional footprint name (`"disk"`, `"diamond"`, `"cross"`,
+///   `"line"`, `"square"`) sized by `kernel_size`, or a 2D boolean mask.
 ///
 /// # Returns
 /// Dilated 2D array (uint8: 0 or 1).
 #[pyfunction]
-#[pyo3(signature = (input, kernel_size=3))]
+#[pyo3(signature = (input, kernel_size=3, footprint=None))]
 pub fn binary_dilation(
     py: Python<'_>,
     input: PyReadonlyArray2<u8>,
     kernel_size: usize,
+    footprint: Option<&PyAny>,
 ) -> PyResult<PyObject> {
+    let footprint = Footprint::extract(kernel_size, footprint)?;
     let input_arr = input.as_array();
-    let dilated = dilation_impl(input_arr, kernel_size);
+    let dilated = dilation_impl(input_arr, &footprint);
     Ok(dilated.to_pyarray(py).into())
 }
 
 /// Perform binary erosion on a 2D boolean/int array.
 #[pyfunction]
-#[pyo3(signature = (input, kernel_size=3))]
+#[pyo3(signature = (input, kernel_size=3, footprint=None))]
 pub fn binary_erosion(
     py: Python<'_>,
     input: PyReadonlyArray2<u8>,
     kernel_size: usize,
+    footprint: Option<&PyAny>,
 ) -> PyResult<PyObject> {
+    let footprint = Footprint::extract(kernel_size, footprint)?;
     let input_arr = input.as_array();
-    let eroded = erosion_impl(input_arr, kernel_size);
+    let eroded = erosion_impl(input_arr, &footprint);
     Ok(eroded.to_pyarray(py).into())
 }
 
 /// Perform binary opening (erosion followed by dilation).
 #[pyfunction]
-#[pyo3(signature = (input, kernel_size=3))]
+#[pyo3(signature = (input, kernel_size=3, footprint=None))]
 pub fn binary_opening(
     py: Python<'_>,
     input: PyReadonlyArray2<u8>,
     kernel_size: usize,
+    footprint: Option<&PyAny>,
 ) -> PyResult<PyObject> {
     // We can't easily compose PyReadonlyArray2 without converting back and forth or refactoring logic.
     // Refactoring logic to pure Rust functions is better.
 
+    let footprint = Footprint::extract(kernel_size, footprint)?;
     let input_arr = input.as_array();
-    let eroded = erosion_impl(input_arr, kernel_size);
-    let dilated = dilation_impl(eroded.view(), kernel_size);
+    let eroded = erosion_impl(input_arr, &footprint);
+    let dilated = dilation_impl(eroded.view(), &footprint);
 
     Ok(dilated.to_pyarray(py).into())
 }
 
 /// Perform binary closing (dilation followed by erosion).
 #[pyfunction]
-#[pyo3(signature = (input, kernel_size=3))]
+#[pyo3(signature = (input, kernel_size=3, footprint=None))]
 pub fn binary_closing(
     py: Python<'_>,
     input: PyReadonlyArray2<u8>,
     kernel_size: usize,
+    footprint: Option<&PyAny>,
 ) -> PyResult<PyObject> {
+    let footprint = Footprint::extract(kernel_size, footprint)?;
     let input_arr = input.as_array();
-    let dilated = dilation_impl(input_arr, kernel_size);
-    let eroded = erosion_impl(dilated.view(), kernel_size);
+    let dilated = dilation_impl(input_arr, &footprint);
+    let eroded = erosion_impl(dilated.view(), &footprint);
 
     Ok(eroded.to_pyarray(py).into())
 }
 
-// Pure Rust implementations for composition
-fn dilation_impl(input: ArrayView2<u8>, kernel_size: usize) -> Array2<u8> {
+// Pure Rust implementations for composition. Dilation sets `p` when
+// `input[p - o]` is set for any footprint offset `o`; erosion keeps `p` when
+// `input[p + o]` is set for every offset, with pixels outside the image
+// treated as unset. Rectangular footprints take the separable van Herk /
+// Gil-Werman path, whose cost per pixel does not depend on the kernel size.
+pub(crate) fn dilation_impl(input: ArrayView2<u8>, footprint: &Footprint) -> Array2<u8> {
+    match footprint.rectangle() {
+        Some(((y0, y1), (x0, x1))) => {
+            let binary = input.mapv(|v| u8::from(v > 0));
+            rect_extremum(binary.view(), (-y1, -y0), (-x1, -x0), 0, u8::max)
+        }
+        None => dilation_scan(input, footprint),
+    }
+}
+
+pub(crate) fn erosion_impl(input: ArrayView2<u8>, footprint: &Footprint) -> Array2<u8> {
+    match footprint.rectangle() {
+        Some((rows, cols)) => {
+            let binary = input.mapv(|v| u8::from(v > 0));
+            rect_extremum(binary.view(), rows, cols, 0, u8::min)
+        }
+        None => erosion_scan(input, footprint),
+    }
+}
+
+fn dilation_scan(input: ArrayView2<u8>, footprint: &Footprint) -> Array2<u8> {
     let (rows, cols) = input.dim();
-    let radius = (kernel_size / 2) as isize;
+    let offsets = footprint.offsets();
     let mut out_vec = vec![0u8; rows * cols];
 
     use rayon::prelude::*;
     out_vec
-        .par_chunks_mut(cols)
+        .par_chunks_mut(cols.max(1))
         .enumerate()
         .for_each(|(r, row_slice)| {
             for (c, out) in row_slice.iter_mut().enumerate().take(cols) {
-                let mut hit = false;
-                'kernel: for kr in -radius..=radius {
-                    for kc in -radius..=radius {
-                        let nr = r as isize + kr;
-                        let nc = c as isize + kc;
-                        if nr >= 0
-                            && nr < rows as isize
-                            && nc >= 0
-                            && nc < cols as isize
-                            && input[[nr as usize, nc as usize]] > 0
-                        {
-                            hit = true;
-                            break 'kernel;
-                        }
-                    }
-                }
+                let hit = offsets.iter().any(|&(dy, dx)| {
+                    let nr = r as isize - dy;
+                    let nc = c as isize - dx;
+                    nr >= 0
+                        && nr < rows as isize
+                        && nc >= 0
+                        && nc < cols as isize
+                        && input[[nr as usize, nc as usize]] > 0
+                });
                 *out = if hit { 1 } else { 0 };
             }
         });
     Array2::from_shape_vec((rows, cols), out_vec).unwrap()
 }
 
-fn erosion_impl(input: ArrayView2<u8>, kernel_size: usize) -> Array2<u8> {
+fn erosion_scan(input: ArrayView2<u8>, footprint: &Footprint) -> Array2<u8> {
     let (rows, cols) = input.dim();
-    let radius = (kernel_size / 2) as isize;
+    let offsets = footprint.offsets();
     let mut out_vec = vec![0u8; rows * cols];
 
     use rayon::prelude::*;
     out_vec
-        .par_chunks_mut(cols)
+        .par_chunks_mut(cols.max(1))
         .enumerate()
         .for_each(|(r, row_slice)| {
             for (c, out) in row_slice.iter_mut().enumerate().take(cols) {
-                if input[[r, c]] == 0 {
-                    *out = 0;
-                    continue;
-                }
-                let mut all_hit = true;
-                'kernel: for kr in -radius..=radius {
-                    for kc in -radius..=radius {
-                        let nr = r as isize + kr;
-                        let nc = c as isize + kc;
-
-                        if nr < 0
-                            || nr >= rows as isize
-                            || nc < 0
-                            || nc >= cols as isize
-                            || input[[nr as usize, nc as usize]] == 0
-                        {
-                            all_hit = false;
-                            break 'kernel;
-                        }
-                    }
-                }
+                let all_hit = offsets.iter().all(|&(dy, dx)| {
+                    let nr = r as isize + dy;
+                    let nc = c as isize + dx;
+                    nr >= 0
+                        && nr < rows as isize
+                        && nc >= 0
+                        && nc < cols as isize
+                        && input[[nr as usize, nc as usize]] > 0
+                });
                 *out = if all_hit { 1 } else { 0 };
             }
         });
     Array2::from_shape_vec((rows, cols), out_vec).unwrap()
 }
+
+/// Running extremum over the rectangle `[p + rows.0, p + rows.1] x
+/// [p + cols.0, p + cols.1]` of every pixel `p`, as a row pass followed by a
+/// column pass; pixels outside the image read as `pad`.
+pub(crate) fn rect_extremum<T, F>(
+    input: ArrayView2<T>,
+    rows: (isize, isize),
+    cols: (isize, isize),
+    pad: T,
+    op: F,
+) -> Array2<T>
+where
+    T: Copy + Send + Sync,
+    F: Fn(T, T) -> T + Sync,
+{
+    let mut out = input.to_owned();
+    for (axis, (lo, hi)) in [(1, cols), (0, rows)] {
+        Zip::from(out.lanes_mut(Axis(axis))).par_for_each(|mut lane| {
+            let line: Vec<T> = lane.to_vec();
+            let mut result = line.clone();
+            running_extremum(&line, lo, hi, pad, &op, &mut result);
+            lane.iter_mut().zip(result).for_each(|(o, v)| *o = v);
+        });
+    }
+    out
+}
+
+/// van Herk / Gil-Werman running extremum: `out[p] = op` over
+/// `line[p + lo ..= p + hi]` (out-of-range samples read as `pad`) with three
+/// `op` calls per sample whatever the window length.
+pub(crate) fn running_extremum<T, F>(line: &[T], lo: isize, hi: isize, pad: T, op: F, out: &mut [T])
+where
+    T: Copy,
+    F: Fn(T, T) -> T,
+{
+    let n = line.len() as isize;
+    let w = (hi - lo + 1) as usize;
+    // Samples lo ..= n - 1 + hi, padded outside the line.
+    let ext: Vec<T> = (lo..n + hi)
+        .map(|i| {
+            if (0..n).contains(&i) {
+                line[i as usize]
+            } else {
+                pad
+            }
+        })
+        .collect();
+    // Block-wise prefix (g) and suffix (h) extrema over blocks of length w.
+    let mut g = ext.clone();
+    let mut h = ext.clone();
+    for start in (0..ext.len()).step_by(w) {
+        let end = (start + w).min(ext.len());
+        for i in start + 1..end {
+            g[i] = op(g[i - 1], ext[i]);
+        }
+        for i in (start..end - 1).rev() {
+            h[i] = op(h[i + 1], ext[i]);
+        }
+    }
+    for (p, o) in out.iter_mut().enumerate() {
+        *o = op(h[p], g[p + w - 1]);
+    }
+}
+
+/// Extremum over the footprint, `out[p] = op` over `input[p + sign * o]`,
+/// for footprints that are not rectangles; pixels outside read as `pad`.
+fn window_extremum<T, F>(
+    input: ArrayView2<T>,
+    offsets: &[(isize, isize)],
+    sign: isize,
+    pad: T,
+    op: F,
+) -> Array2<T>
+where
+    T: Copy + Send + Sync,
+    F: Fn(T, T) -> T + Sync,
+{
+    let (rows, cols) = (input.nrows() as isize, input.ncols() as isize);
+    let mut out = Array2::from_elem(input.raw_dim(), pad);
+    Zip::indexed(&mut out).par_for_each(|(r, c), o| {
+        *o = offsets.iter().fold(pad, |acc, &(dy, dx)| {
+            let (nr, nc) = (r as isize + sign * dy, c as isize + sign * dx);
+            if (0..rows).contains(&nr) && (0..cols).contains(&nc) {
+                op(acc, input[[nr as usize, nc as usize]])
+            } else {
+                acc
+            }
+        });
+    });
+    out
+}
+
+fn max_of<T: PartialOrd>(a: T, b: T) -> T {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
+
+fn min_of<T: PartialOrd>(a: T, b: T) -> T {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+/// Grayscale morphological operation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum GreyOp {
+    Erosion,
+    Dilation,
+    Opening,
+    Closing,
+    /// `f - opening(f)`: bright features smaller than the footprint.
+    WhiteTophat,
+    /// `closing(f) - f`: dark features smaller than the footprint.
+    BlackTophat,
+    /// `dilation(f) - erosion(f)`.
+    Gradient,
+}
+
+impl GreyOp {
+    pub fn apply<T>(self, input: ArrayView2<T>, footprint: &Footprint) -> Array2<T>
+    where
+        T: Real + PartialOrd + std::ops::Sub<Output = T>,
+    {
+        let difference = |a: &Array2<T>, b: &Array2<T>| {
+            let mut out = a.clone();
+            Zip::from(&mut out).and(b).par_for_each(|o, &v| *o = *o - v);
+            out
+        };
+        match self {
+            GreyOp::Erosion => grey_extremum(input, footprint, false),
+            GreyOp::Dilation => grey_extremum(input, footprint, true),
+            GreyOp::Opening => {
+                let eroded = grey_extremum(input, footprint, false);
+                grey_extremum(eroded.view(), footprint, true)
+            }
+            GreyOp::Closing => {
+                let dilated = grey_extremum(input, footprint, true);
+                grey_extremum(dilated.view(), footprint, false)
+            }
+            GreyOp::WhiteTophat => {
+                difference(&input.to_owned(), &GreyOp::Opening.apply(input, footprint))
+            }
+            GreyOp::BlackTophat => {
+                difference(&GreyOp::Closing.apply(input, footprint), &input.to_owned())
+            }
+            GreyOp::Gradient => difference(
+                &grey_extremum(input, footprint, true),
+                &grey_extremum(input, footprint, false),
+            ),
+        }
+    }
+}
+
+/// Grayscale dilation (`dilate`) or erosion. NaN pixels are ignored in the
+/// windows and stay NaN; a window without valid pixels gives NaN. Pixels
+/// outside the image are ignored.
+fn grey_extremum<T>(input: ArrayView2<T>, footprint: &Footprint, dilate: bool) -> Array2<T>
+where
+    T: Real + PartialOrd,
+{
+    let (pad, op): (T, fn(T, T) -> T) = if dilate {
+        (T::from_f64(f64::NEG_INFINITY), max_of)
+    } else {
+        (T::from_f64(f64::INFINITY), min_of)
+    };
+    let filled = input.mapv(|v| if v.to_f64().is_nan() { pad } else { v });
+    let mut out = footprint_extremum(filled.view(), footprint, dilate, pad, op);
+    // A window without valid pixels yields `pad`, but so does one whose
+    // extremum is a genuine infinity, so look for valid pixels separately.
+    let valid = input.mapv(|v| u8::from(!v.to_f64().is_nan()));
+    let any_valid = footprint_extremum(valid.view(), footprint, dilate, 0, u8::max);
+    let nan = T::from_f64(f64::NAN);
+    Zip::from(&mut out)
+        .and(&valid)
+        .and(&any_valid)
+        .par_for_each(|o, &v, &a| {
+            if v == 0 || a == 0 {
+                *o = nan;
+            }
+        });
+    out
+}
+
+/// `op` over the footprint around each pixel, reflected for dilation.
+fn footprint_extremum<T, F>(
+    input: ArrayView2<T>,
+    footprint: &Footprint,
+    dilate: bool,
+    pad: T,
+    op: F,
+) -> Array2<T>
+where
+    T: Copy + Send + Sync,
+    F: Fn(T, T) -> T + Sync,
+{
+    match (footprint.rectangle(), dilate) {
+        (Some(((y0, y1), (x0, x1))), true) => rect_extremum(input, (-y1, -y0), (-x1, -x0), pad, op),
+        (Some((rows, cols)), false) => rect_extremum(input, rows, cols, pad, op),
+        (None, _) => window_extremum(
+            input,
+            footprint.offsets(),
+            if dilate { -1 } else { 1 },
+            pad,
+            op,
+        ),
+    }
+}
+
+fn grey_py(
+    py: Python<'_>,
+    input: &PyAny,
+    kernel_size: usize,
+    footprint: Option<&PyAny>,
+    op: GreyOp,
+) -> PyResult<PyObject> {
+    let footprint = Footprint::extract(kernel_size, footprint)?;
+    fn run<A: Sample>(
+        py: Python<'_>,
+        input: ArrayViewD<A>,
+        footprint: &Footprint,
+        op: GreyOp,
+    ) -> PyResult<PyObject>
+    where
+        A::Out: PartialOrd + std::ops::Sub<Output = A::Out> + numpy::Element,
+    {
+        let input = input.into_dimensionality::<Ix2>().map_err(|_| {
+            CoreError::InvalidArgument("Grayscale morphology expects a 2D array".to_string())
+        })?;
+        let out = py.allow_threads(|| {
+            let values = input.mapv(|v| A::Out::from_f64(v.to_f64()));
+            op.apply(values.view(), footprint)
+        });
+        Ok(kernels::into_py(py, out.into_dyn()))
+    }
+    kernels::dispatch!(Operand::extract(input)?, |a| run(py, a, &footprint, op))
+}
+
+/// Grayscale erosion: minimum over the footprint.
+///
+/// # Arguments
+/// * `input` - 2D raster (float64 stays float64; float32 and uint16 give
+///   float32). NaN pixels are ignored in the windows and stay NaN; pixels
+///   outside the image are ignored.
+/// * `kernel_size` - Size of the square structuring element (default 3).
+/// * `footprint` - Optional footprint name or 2D boolean mask, as for
+///   `binary_erosion`.
+#[pyfunction]
+#[pyo3(signature = (input, kernel_size=3, footprint=None))]
+pub fn grey_erosion(
+    py: Python<'_>,
+    input: &PyAny,
+    kernel_size: usize,
+    footprint: Option<&PyAny>,
+) -> PyResult<PyObject> {
+    grey_py(py, input, kernel_size, footprint, GreyOp::Erosion)
+}
+
+/// Grayscale dilation: maximum over the (reflected) footprint.
+#[pyfunction]
+#[pyo3(signature = (input, kernel_size=3, footprint=None))]
+pub fn grey_dilation(
+    py: Python<'_>,
+    input: &PyAny,
+    kernel_size: usize,
+    footprint: Option<&PyAny>,
+) -> PyResult<PyObject> {
+    grey_py(py, input, kernel_size, footprint, GreyOp::Dilation)
+}
+
+/// Grayscale opening (erosion followed by dilation).
+#[pyfunction]
+#[pyo3(signature = (input, kernel_size=3, footprint=None))]
+pub fn grey_opening(
+    py: Python<'_>,
+    input: &PyAny,
+    kernel_size: usize,
+    footprint: Option<&PyAny>,
+) -> PyResult<PyObject> {
+    grey_py(py, input, kernel_size, footprint, GreyOp::Opening)
+}
+
+/// Grayscale closing (dilation followed by erosion).
+#[pyfunction]
+#[pyo3(signature = (input, kernel_size=3, footprint=None))]
+pub fn grey_closing(
+    py: Python<'_>,
+    input: &PyAny,
+    kernel_size: usize,
+    footprint: Option<&PyAny>,
+) -> PyResult<PyObject> {
+    grey_py(py, input, kernel_size, footprint, GreyOp::Closing)
+}
+
+/// White top-hat `input - grey_opening(input)`: bright features smaller than
+/// the footprint.
+#[pyfunction]
+#[pyo3(signature = (input, kernel_size=3, footprint=None))]
+pub fn white_tophat(
+    py: Python<'_>,
+    input: &PyAny,
+    kernel_size: usize,
+    footprint: Option<&PyAny>,
+) -> PyResult<PyObject> {
+    grey_py(py, input, kernel_size, footprint, GreyOp::WhiteTophat)
+}
+
+/// Black top-hat `grey_closing(input) - input`: dark features smaller than
+/// the footprint.
+#[pyfunction]
+#[pyo3(signature = (input, kernel_size=3, footprint=None))]
+pub fn black_tophat(
+    py: Python<'_>,
+    input: &PyAny,
+    kernel_size: usize,
+    footprint: Option<&PyAny>,
+) -> PyResult<PyObject> {
+    grey_py(py, input, kernel_size, footprint, GreyOp::BlackTophat)
+}
+
+/// Morphological gradient `grey_dilation(input) - grey_erosion(input)`.
+#[pyfunction]
+#[pyo3(signature = (input, kernel_size=3, footprint=None))]
+pub fn morphological_gradient(
+    py: Python<'_>,
+    input: &PyAny,
+    kernel_size: usize,
+    footprint: Option<&PyAny>,
+) -> PyResult<PyObject> {
+    grey_py(py, input, kernel_size, footprint, GreyOp::Gradient)
+}
+
+/// Pixel connectivity of a region.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Connectivity {
+    /// Edge neighbours only.
+    Four,
+    /// Edge and corner neighbours.
+    Eight,
+}
+
+impl Connectivity {
+    pub fn from_int(connectivity: u8) -> Result<Self, CoreError> {
+        match connectivity {
+            4 => Ok(Connectivity::Four),
+            8 => Ok(Connectivity::Eight),
+            _ => Err(CoreError::InvalidArgument(format!(
+                "connectivity must be 4 or 8, got {}",
+                connectivity
+            ))),
+        }
+    }
+
+    /// Neighbours visited before a pixel in raster order.
+    fn preceding(self) -> &'static [(isize, isize)] {
+        match self {
+            Connectivity::Four => &[(-1, 0), (0, -1)],
+            Connectivity::Eight => &[(-1, -1), (-1, 0), (-1, 1), (0, -1)],
+        }
+    }
+
+    /// All neighbour offsets.
+    pub fn neighbours(self) -> &'static [(isize, isize)] {
+        match self {
+            Connectivity::Four => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Connectivity::Eight => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+        }
+    }
+}
+
+/// Union-find root of `i`, halving paths on the way.
+fn find_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Label connected regions of equal value with a two-pass union-find scan.
+/// Pixels equal to `background` get label 0; regions are numbered from 1 in
+/// raster order of their first pixel. Returns the labels and region count.
+pub(crate) fn label_regions<T: Copy + PartialEq>(
+    grid: ArrayView2<T>,
+    connectivity: Connectivity,
+    background: Option<T>,
+) -> (Array2<i64>, usize) {
+    let (rows, cols) = (grid.nrows() as isize, grid.ncols() as isize);
+    let mut provisional = Array2::<usize>::zeros(grid.raw_dim());
+    // parent[0] is the background.
+    let mut parent: Vec<usize> = vec![0];
+    for ((r, c), &v) in grid.indexed_iter() {
+        if background == Some(v) {
+            continue;
+        }
+        let mut label = 0;
+        for &(dy, dx) in connectivity.preceding() {
+            let (nr, nc) = (r as isize + dy, c as isize + dx);
+            if !(0..rows).contains(&nr) || !(0..cols).contains(&nc) {
+                continue;
+            }
+            let (nr, nc) = (nr as usize, nc as usize);
+            if grid[[nr, nc]] != v {
+                continue;
+            }
+            let other = find_root(&mut parent, provisional[[nr, nc]]);
+            if label == 0 {
+                label = other;
+            } else if other != label {
+                let (lo, hi) = (label.min(other), label.max(other));
+                parent[hi] = lo;
+                label = lo;
+            }
+        }
+        if label == 0 {
+            label = parent.len();
+            parent.push(label);
+        }
+        provisional[[r, c]] = label;
+    }
+    // Roots are the smallest provisional label of each region, so numbering
+    // roots in order keeps the raster order of first pixels.
+    let mut compact = vec![0i64; parent.len()];
+    let mut count = 0;
+    for i in 1..parent.len() {
+        let root = find_root(&mut parent, i);
+        if root == i {
+            count += 1;
+            compact[i] = count as i64;
+        } else {
+            compact[i] = compact[root];
+        }
+    }
+    (provisional.mapv(|l| compact[l]), count)
+}
+
+/// Properties of one connected component.
+#[pyclass]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComponentProps {
+    #[pyo3(get)]
+    pub label: i64,
+    /// Number of pixels.
+    #[pyo3(get)]
+    pub area: usize,
+    /// `(min_row, min_col, max_row, max_col)`, max exclusive.
+    #[pyo3(get)]
+    pub bbox: (usize, usize, usize, usize),
+    /// Mean `(row, col)` of the pixels.
+    #[pyo3(get)]
+    pub centroid: (f64, f64),
+    /// Number of pixel edges shared with other labels or the image border.
+    #[pyo3(get)]
+    pub perimeter: usize,
+    /// Mean of the value raster over the component's non-NaN pixels.
+    #[pyo3(get)]
+    pub mean: Option<f64>,
+}
+
+/// Per-label properties of `labels` (1..=count), with the mean of `values`
+/// when given.
+pub(crate) fn component_props(
+    labels: ArrayView2<i64>,
+    count: usize,
+    values: Option<ArrayView2<f64>>,
+) -> Vec<ComponentProps> {
+    let (rows, cols) = labels.dim();
+    let mut props: Vec<ComponentProps> = (1..=count as i64)
+        .map(|label| ComponentProps {
+            label,
+            area: 0,
+            bbox: (usize::MAX, usize::MAX, 0, 0),
+            centroid: (0.0, 0.0),
+            perimeter: 0,
+            mean: values.map(|_| 0.0),
+        })
+        .collect();
+    let mut valid = vec![0usize; count];
+    for ((r, c), &label) in labels.indexed_iter() {
+        if label <= 0 {
+            continue;
+        }
+        let i = (label - 1) as usize;
+        let p = &mut props[i];
+        p.area += 1;
+        p.bbox = (
+            p.bbox.0.min(r),
+            p.bbox.1.min(c),
+            p.bbox.2.max(r + 1),
+            p.bbox.3.max(c + 1),
+        );
+        p.centroid.0 += r as f64;
+        p.centroid.1 += c as f64;
+        p.perimeter += Connectivity::Four
+            .neighbours()
+            .iter()
+            .filter(|&&(dy, dx)| {
+                let (nr, nc) = (r as isize + dy, c as isize + dx);
+                nr < 0
+                    || nc < 0
+                    || nr >= rows as isize
+                    || nc >= cols as isize
+                    || labels[[nr as usize, nc as usize]] != label
+            })
+            .count();
+        if let (Some(values), Some(sum)) = (&values, &mut p.mean) {
+            let v = values[[r, c]];
+            if !v.is_nan() {
+                *sum += v;
+                valid[i] += 1;
+            }
+        }
+    }
+    for (p, &n) in props.iter_mut().zip(&valid) {
+        p.centroid = (p.centroid.0 / p.area as f64, p.centroid.1 / p.area as f64);
+        p.mean = p
+            .mean
+            .map(|sum| if n > 0 { sum / n as f64 } else { f64::NAN });
+    }
+    props
+}
+
+/// Label connected components of a binary mask.
+///
+/// # Arguments
+/// * `mask` - 2D boolean mask (true is foreground).
+/// * `connectivity` - 4 (edge neighbours) or 8 (default, also corners).
+/// * `values` - Optional float64 raster of the same shape; each component
+///   gets the mean of its non-NaN values.
+///
+/// # Returns
+/// `(labels, props)`: int64 labels (0 background, components numbered from
+/// 1 in raster order), usable as `zonal_stats` zones, and a dict mapping
+/// each label to its `ComponentProps` (area, bbox, centroid, perimeter,
+/// mean).
+#[pyfunction]
+#[pyo3(signature = (mask, connectivity=8, values=None))]
+pub fn label_components(
+    py: Python<'_>,
+    mask: PyReadonlyArray2<bool>,
+    connectivity: u8,
+    values: Option<PyReadonlyArray2<f64>>,
+) -> PyResult<(PyObject, HashMap<i64, ComponentProps>)> {
+    let connectivity = Connectivity::from_int(connectivity)?;
+    let mask = mask.as_array();
+    let values = values.as_ref().map(|v| v.as_array());
+    if let Some(v) = &values {
+        if v.dim() != mask.dim() {
+            return Err(CoreError::InvalidArgument(format!(
+                "values {:?} must match the mask shape {:?}",
+                v.dim(),
+                mask.dim()
+            ))
+            .into());
+        }
+    }
+    let (labels, props) = py.allow_threads(|| {
+        let (labels, count) = label_regions(mask, connectivity, Some(false));
+        let props = component_props(labels.view(), count, values);
+        (labels, props)
+    });
+    let props = props.into_iter().map(|p| (p.label, p)).collect();
+    Ok((labels.to_pyarray(py).into(), props))
+}
+
+// --- Post-classification cleanup ---
+
+/// Merge regions of fewer than `threshold` pixels into their largest
+/// neighbouring region, as GDAL's sieve does. Regions are merged smallest
+/// first, so a merged region counts with its grown size. `nodata` pixels are
+/// neither sieved nor used as neighbours.
+pub(crate) fn sieve_impl(
+    classes: ArrayView2<i64>,
+    threshold: usize,
+    connectivity: Connectivity,
+    nodata: Option<i64>,
+) -> Array2<i64> {
+    let (labels, count) = label_regions(classes, connectivity, None);
+    let (rows, cols) = labels.dim();
+    let mut area = vec![0usize; count + 1];
+    let mut class = vec![0i64; count + 1];
+    let mut adjacent: Vec<HashSet<usize>> = vec![HashSet::new(); count + 1];
+    for ((r, c), &label) in labels.indexed_iter() {
+        let label = label as usize;
+        area[label] += 1;
+        class[label] = classes[[r, c]];
+        for &(dy, dx) in connectivity.neighbours() {
+            let (nr, nc) = (r as isize + dy, c as isize + dx);
+            if nr < 0 || nc < 0 || nr >= rows as isize || nc >= cols as isize {
+                continue;
+            }
+            let other = labels[[nr as usize, nc as usize]] as usize;
+            if other != label {
+                adjacent[label].insert(other);
+            }
+        }
+    }
+    let is_nodata = |l: usize| nodata == Some(class[l]);
+
+    let mut order: Vec<usize> = (1..=count)
+        .filter(|&l| area[l] < threshold && !is_nodata(l))
+        .collect();
+    order.sort_by_key(|&l| (area[l], l));
+    let mut parent: Vec<usize> = (0..=count).collect();
+    for region in order {
+        let root = find_root(&mut parent, region);
+        if area[root] >= threshold {
+            continue;
+        }
+        let neighbours: Vec<usize> = adjacent[root].iter().copied().collect();
+        let mut target: Option<usize> = None;
+        for n in neighbours {
+            let n = find_root(&mut parent, n);
+            if n == root || is_nodata(n) {
+                continue;
+            }
+            if target
+                .is_none_or(|t| (area[n], std::cmp::Reverse(n)) > (area[t], std::cmp::Reverse(t)))
+            {
+                target = Some(n);
+            }
+        }
+        if let Some(target) = target {
+            parent[root] = target;
+            area[target] += area[root];
+            let merged = std::mem::take(&mut adjacent[root]);
+            adjacent[target].extend(merged);
+        }
+    }
+    labels.mapv(|l| class[find_root(&mut parent, l as usize)])
+}
+
+/// Fill holes: unset regions (under `connectivity`) that do not touch the
+/// image border.
+pub(crate) fn fill_holes_impl(input: ArrayView2<u8>, connectivity: Connectivity) -> Array2<u8> {
+    let set = input.mapv(|v| v > 0);
+    let (labels, count) = label_regions(set.view(), connectivity, Some(true));
+    let (rows, cols) = labels.dim();
+    let mut open = vec![false; count + 1];
+    for ((r, c), &label) in labels.indexed_iter() {
+        if r == 0 || c == 0 || r + 1 == rows || c + 1 == cols {
+            open[label as usize] = true;
+        }
+    }
+    labels.mapv(|l| u8::from(l == 0 || !open[l as usize]))
+}
+
+/// Most frequent class over the footprint around each pixel. Ties keep the
+/// centre class when it is among the most frequent and otherwise take the
+/// smallest class. `nodata` pixels are not counted and stay `nodata`.
+pub(crate) fn majority_impl(
+    classes: ArrayView2<i64>,
+    footprint: &Footprint,
+    nodata: Option<i64>,
+) -> Array2<i64> {
+    let (rows, cols) = classes.dim();
+    let mut out = Array2::<i64>::zeros((rows, cols));
+    Zip::indexed(&mut out).par_for_each(|(r, c), o| {
+        let centre = classes[[r, c]];
+        if nodata == Some(centre) {
+            *o = centre;
+            return;
+        }
+        let mut counts: Vec<(i64, usize)> = Vec::new();
+        for &(dy, dx) in footprint.offsets() {
+            let (nr, nc) = (r as isize + dy, c as isize + dx);
+            if nr < 0 || nc < 0 || nr >= rows as isize || nc >= cols as isize {
+                continue;
+            }
+            let v = classes[[nr as usize, nc as usize]];
+            if nodata == Some(v) {
+                continue;
+            }
+            match counts.iter_mut().find(|(k, _)| *k == v) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((v, 1)),
+            }
+        }
+        let best = counts.iter().map(|&(_, n)| n).max().unwrap_or(0);
+        let centre_count = counts
+            .iter()
+            .find(|(k, _)| *k == centre)
+            .map_or(0, |&(_, n)| n);
+        *o = if centre_count == best {
+            centre
+        } else {
+            counts
+                .iter()
+                .filter(|&&(_, n)| n == best)
+                .map(|&(k, _)| k)
+                .min()
+                .unwrap_or(centre)
+        };
+    });
+    out
+}
+
+/// Remove regions smaller than `threshold` pixels from a class map by
+/// merging each into its largest neighbouring region (GDAL-style sieve).
+///
+/// # Arguments
+/// * `classes` - 2D int64 class map.
+/// * `threshold` - Minimum region size in pixels; smaller regions are merged.
+/// * `connectivity` - 4 (default) or 8.
+/// * `nodata` - Optional class that is left untouched and never merged into.
+///
+/// # Returns
+/// Sieved int64 class map.
+#[pyfunction]
+#[pyo3(signature = (classes, threshold, connectivity=4, nodata=None))]
+pub fn sieve(
+    py: Python<'_>,
+    classes: PyReadonlyArray2<i64>,
+    threshold: usize,
+    connectivity: u8,
+    nodata: Option<i64>,
+) -> PyResult<PyObject> {
+    let connectivity = Connectivity::from_int(connectivity)?;
+    let classes = classes.as_array();
+    let out = py.allow_threads(|| sieve_impl(classes, threshold, connectivity, nodata));
+    Ok(out.to_pyarray(py).into())
+}
+
+/// Fill holes in a binary mask: unset regions not connected to the image
+/// border become set.
+///
+/// # Arguments
+/// * `input` - 2D input array (treated as boolean: >0 is True).
+/// * `connectivity` - Connectivity of the background regions, 4 (default)
+///   or 8.
+///
+/// # Returns
+/// Filled 2D array (uint8: 0 or 1).
+#[pyfunction]
+#[pyo3(signature = (input, connectivity=4))]
+pub fn fill_holes(
+    py: Python<'_>,
+    input: PyReadonlyArray2<u8>,
+    connectivity: u8,
+) -> PyResult<PyObject> {
+    let connectivity = Connectivity::from_int(connectivity)?;
+    let input = input.as_array();
+    let out = py.allow_threads(|| fill_holes_impl(input, connectivity));
+    Ok(out.to_pyarray(py).into())
+}
+
+/// Majority (mode) filter for class maps.
+///
+/// # Arguments
+/// * `classes` - 2D int64 class map.
+/// * `kernel_size` - Size of the square window (default 3).
+/// * `footprint` - Optional footprint name or 2D boolean mask, as for the
+///   binary operations.
+/// * `nodata` - Optional class that is not counted and stays unchanged.
+///
+/// # Returns
+/// Filtered int64 class map. Ties keep the centre class if it is among the
+/// most frequent, otherwise the smallest tied class.
+#[pyfunction]
+#[pyo3(signature = (classes, kernel_size=3, footprint=None, nodata=None))]
+pub fn majority_filter(
+    py: Python<'_>,
+    classes: PyReadonlyArray2<i64>,
+    kernel_size: usize,
+    footprint: Option<&PyAny>,
+    nodata: Option<i64>,
+) -> PyResult<PyObject> {
+    let footprint = Footprint::extract(kernel_size, footprint)?;
+    let classes = classes.as_array();
+    let out = py.allow_threads(|| majority_impl(classes, &footprint, nodata));
+    Ok(out.to_pyarray(py).into())
+}
+
+/// Exact squared Euclidean distance from every pixel to the nearest `true`
+/// pixel of `features`, with pixel spacing `[dy, dx]`. Pixels are
+/// `f64::INFINITY` when there are no features.
+pub(crate) fn squared_distance_transform(
+    features: ArrayView2<bool>,
+    spacing: [f64; 2],
+) -> Array2<f64> {
+    feature_transform(features, spacing).squared
+}
+
+/// Squared distances to the nearest feature pixel and that pixel's
+/// `(row, col)` index, `-1` where there are no features.
+pub(crate) struct FeatureTransform {
+    pub squared: Array2<f64>,
+    pub rows: Array2<i64>,
+    pub cols: Array2<i64>,
+}
+
+/// Exact Euclidean feature transform (Felzenszwalb & Huttenlocher 2012: a
+/// 1-D lower-envelope pass down each column, then along each row). The
+/// column pass records the nearest feature row of every pixel; the row pass
+/// picks the winning column, whose recorded row completes the index.
+pub(crate) fn feature_transform(features: ArrayView2<bool>, spacing: [f64; 2]) -> FeatureTransform {
+    let mut squared = features.mapv(|f| if f { 0.0 } else { f64::INFINITY });
+    let mut rows = Array2::<i64>::from_elem(features.raw_dim(), -1);
+    let mut cols = Array2::<i64>::from_elem(features.raw_dim(), -1);
+    Zip::from(squared.lanes_mut(Axis(0)))
+        .and(rows.lanes_mut(Axis(0)))
+        .par_for_each(|mut lane, mut nearest| {
+            let mut f: Vec<f64> = lane.to_vec();
+            let mut v = vec![-1; f.len()];
+            lower_envelope(&mut f, spacing[0], &mut v);
+            lane.iter_mut().zip(f).for_each(|(o, d)| *o = d);
+            nearest.iter_mut().zip(v).for_each(|(o, q)| *o = q);
+        });
+    Zip::from(squared.lanes_mut(Axis(1)))
+        .and(rows.lanes_mut(Axis(1)))
+        .and(cols.lanes_mut(Axis(1)))
+        .par_for_each(|mut lane, mut row_index, mut col_index| {
+            let mut f: Vec<f64> = lane.to_vec();
+            let mut v = vec![-1; f.len()];
+            lower_envelope(&mut f, spacing[1], &mut v);
+            let column_rows = row_index.to_vec();
+            lane.iter_mut().zip(f).for_each(|(o, d)| *o = d);
+            for ((r, c), q) in row_index.iter_mut().zip(col_index.iter_mut()).zip(v) {
+                *c = q;
+                *r = if q < 0 { -1 } else { column_rows[q as usize] };
+            }
+        });
+    FeatureTransform {
+        squared,
+        rows,
+        cols,
+    }
+}
+
+/// In-place 1-D squared distance transform `d(p) = min_q (s(p - q))^2 + f(q)`
+/// over samples `s` apart; `nearest[p]` receives the minimising `q`, and is
+/// left untouched when `f` has no finite samples.
+fn lower_envelope(f: &mut [f64], s: f64, nearest: &mut [i64]) {
+    let n = f.len();
+    let sites: Vec<usize> = (0..n).filter(|&q| f[q].is_finite()).collect();
+    if sites.is_empty() {
+        return;
+    }
+    // Parabola vertices (indices) and the boundaries between them.
+    let mut v: Vec<usize> = Vec::with_capacity(sites.len());
+    let mut z: Vec<f64> = Vec::with_capacity(sites.len() + 1);
+    let cross = |f: &[f64], a: usize, b: usize| {
+        let (xa, xb) = (a as f64 * s, b as f64 * s);
+        ((f[b] + xb * xb) - (f[a] + xa * xa)) / (2.0 * (xb - xa))
+    };
+    for &q in &sites {
+        while let Some(&last) = v.last() {
+            let x = cross(f, last, q);
+            if v.len() > 1 && x <= z[v.len() - 1] {
+                v.pop();
+                z.pop();
+            } else {
+                z.push(x);
+                break;
+            }
+        }
+        if v.is_empty() {
+            z.clear();
+            z.push(f64::NEG_INFINITY);
+        }
+        v.push(q);
+    }
+    z.push(f64::INFINITY);
+    let g: Vec<f64> = v.iter().map(|&q| f[q]).collect();
+    let mut k = 0;
+    for (p, (out, near)) in f.iter_mut().zip(nearest.iter_mut()).enumerate() {
+        let x = p as f64 * s;
+        while z[k + 1] < x {
+            k += 1;
+        }
+        let dx = x - v[k] as f64 * s;
+        *out = dx * dx + g[k];
+        *near = v[k] as i64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(features: &Array2<bool>, spacing: [f64; 2]) -> Array2<f64> {
+        let sites: Vec<(usize, usize)> = features
+            .indexed_iter()
+            .filter(|(_, &f)| f)
+            .map(|(i, _)| i)
+            .collect();
+        Array2::from_shape_fn(features.dim(), |(y, x)| {
+            sites
+                .iter()
+                .map(|&(sy, sx)| {
+                    let dy = (y as f64 - sy as f64) * spacing[0];
+                    let dx = (x as f64 - sx as f64) * spacing[1];
+                    dy * dy + dx * dx
+                })
+                .fold(f64::INFINITY, f64::min)
+        })
+    }
+
+    fn mask(rows: &[&str]) -> Array2<bool> {
+        Array2::from_shape_fn((rows.len(), rows[0].len()), |(y, x)| {
+            rows[y].as_bytes()[x] == b'#'
+        })
+    }
+
+    #[test]
+    fn test_named_footprints() {
+        assert_eq!(
+            structuring_element_mask("disk", 7, 0.0).unwrap(),
+            mask(&["...#...", ".#####.", ".#####.", "#######", ".#####.", ".#####.", "...#...",])
+        );
+        assert_eq!(
+            structuring_element_mask("diamond", 5, 0.0).unwrap(),
+            mask(&["..#..", ".###.", "#####", ".###.", "..#.."])
+        );
+        assert_eq!(
+            structuring_element_mask("cross", 3, 0.0).unwrap(),
+            mask(&[".#.", "###", ".#."])
+        );
+        assert_eq!(
+            structuring_element_mask("line", 5, 45.0).unwrap(),
+            mask(&["....#", "...#.", "..#..", ".#...", "#...."])
+        );
+        assert_eq!(
+            structuring_element_mask("line", 3, 90.0).unwrap(),
+            mask(&[".#.", ".#.", ".#."])
+        );
+        assert_eq!(
+            Footprint::named("square", 3, 0.0).unwrap(),
+            Footprint::square(3)
+        );
+        assert!(structuring_element_mask("hexagon", 3, 0.0).is_err());
+        assert!(Footprint::from_mask(Array2::from_elem((3, 3), false).view()).is_err());
+    }
+
+    #[test]
+    fn test_footprint_dilation_and_erosion() {
+        let mut input = Array2::<u8>::zeros((7, 7));
+        input[[3, 3]] = 1;
+        let disk = Footprint::named("disk", 5, 0.0).unwrap();
+        let dilated = dilation_impl(input.view(), &disk);
+        let expected = structuring_element_mask("disk", 5, 0.0).unwrap();
+        assert_eq!(
+            dilated.slice(ndarray::s![1..6, 1..6]).mapv(|v| v > 0),
+            expected
+        );
+        assert_eq!(dilated.iter().filter(|&&v| v > 0).count(), 13);
+        // Eroding the disk by itself recovers the centre pixel.
+        assert_eq!(erosion_impl(dilated.view(), &disk), input);
+
+        // Asymmetric footprint: a one-pixel shift to the right.
+        let shift = Footprint::from_mask(mask(&["..#"]).view()).unwrap();
+        let shifted = dilation_impl(input.view(), &shift);
+        assert_eq!(shifted[[3, 4]], 1);
+        assert_eq!(shifted.sum(), 1);
+        assert_eq!(erosion_impl(shifted.view(), &shift), input);
+    }
+
+    #[test]
+    fn test_running_extremum_matches_window_scan() {
+        let line: Vec<u8> = (0..23).map(|i| ((i * 37 + 11) % 17) as u8).collect();
+        for (lo, hi) in [(0, 0), (-1, 1), (-4, 2), (3, 9), (-30, 30)] {
+            let mut out = vec![0; line.len()];
+            running_extremum(&line, lo, hi, 0, u8::max, &mut out);
+            for (p, &o) in out.iter().enumerate() {
+                let expected = (p as isize + lo..=p as isize + hi)
+                    .filter_map(|i| line.get(usize::try_from(i).ok()?).copied())
+                    .fold(0, u8::max);
+                assert_eq!(o, expected, "window ({}, {}) at {}", lo, hi, p);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rectangle_fast_path_matches_scan() {
+        let input = Array2::from_shape_fn((19, 23), |(y, x)| u8::from((y * 5 + x * 3) % 7 < 3) * 2);
+        let footprints = [
+            Footprint::square(1),
+            Footprint::square(3),
+            Footprint::square(9),
+            Footprint::named("line", 7, 0.0).unwrap(),
+            Footprint::named("line", 5, 90.0).unwrap(),
+            Footprint::from_mask(Array2::from_elem((2, 5), true).view()).unwrap(),
+            Footprint::from_mask(mask(&["...###"]).view()).unwrap(),
+        ];
+        for footprint in &footprints {
+            assert!(footprint.rectangle().is_some());
+            assert_eq!(
+                dilation_impl(input.view(), footprint),
+                dilation_scan(input.view(), footprint)
+            );
+            assert_eq!(
+                erosion_impl(input.view(), footprint),
+                erosion_scan(input.view(), footprint)
+            );
+        }
+        assert!(Footprint::named("disk", 5, 0.0)
+            .unwrap()
+            .rectangle()
+            .is_none());
+    }
+
+    /// Brute-force grayscale dilation/erosion ignoring NaN and the border.
+    fn grey_brute(input: &Array2<f64>, footprint: &Footprint, dilate: bool) -> Array2<f64> {
+        let (rows, cols) = (input.nrows() as isize, input.ncols() as isize);
+        let sign = if dilate { -1 } else { 1 };
+        Array2::from_shape_fn(input.dim(), |(r, c)| {
+            if input[[r, c]].is_nan() {
+                return f64::NAN;
+            }
+            let values: Vec<f64> = footprint
+                .offsets()
+                .iter()
+                .map(|&(dy, dx)| (r as isize + sign * dy, c as isize + sign * dx))
+                .filter(|(y, x)| (0..rows).contains(y) && (0..cols).contains(x))
+                .map(|(y, x)| input[[y as usize, x as usize]])
+                .filter(|v| !v.is_nan())
+                .collect();
+            let pick = if dilate { f64::max } else { f64::min };
+            values.into_iter().reduce(pick).unwrap_or(f64::NAN)
+        })
+    }
+
+    fn assert_same(a: &Array2<f64>, b: &Array2<f64>) {
+        for (x, y) in a.iter().zip(b) {
+            assert!(x == y || (x.is_nan() && y.is_nan()), "{} != {}", x, y);
+        }
+    }
+
+    #[test]
+    fn test_grey_extremum_matches_brute_force() {
+        let mut input = Array2::from_shape_fn((11, 13), |(y, x)| ((y * 7 + x * 5) % 11) as f64);
+        input[[4, 6]] = f64::NAN;
+        input[[0, 0]] = f64::NAN;
+        // Infinite plateaus: windows inside them have a genuinely infinite
+        // extremum, which must not be mistaken for an all-NaN window.
+        input
+            .slice_mut(ndarray::s![6.., ..5])
+            .fill(f64::NEG_INFINITY);
+        input.slice_mut(ndarray::s![..5, 8..]).fill(f64::INFINITY);
+        let footprints = [
+            Footprint::square(3),
+            Footprint::named("disk", 5, 0.0).unwrap(),
+            Footprint::named("line", 5, 30.0).unwrap(),
+            Footprint::from_mask(mask(&["##.", "..."]).view()).unwrap(),
+        ];
+        for footprint in &footprints {
+            for dilate in [true, false] {
+                let fast = grey_extremum(input.view(), footprint, dilate);
+                assert_same(&fast, &grey_brute(&input, footprint, dilate));
+            }
+        }
+        let square = Footprint::square(3);
+        assert_eq!(
+            grey_extremum(input.view(), &square, true)[[8, 2]],
+            f64::NEG_INFINITY
+        );
+        assert_eq!(
+            grey_extremum(input.view(), &square, false)[[2, 10]],
+            f64::INFINITY
+        );
+    }
+
+    #[test]
+    fn test_tophat_and_gradient() {
+        // A two-pixel bright spot and a one-pixel dark pit on a ramp.
+        let mut input = Array2::from_shape_fn((9, 9), |(_, x)| x as f64);
+        input[[2, 2]] += 10.0;
+        input[[2, 3]] += 10.0;
+        input[[6, 6]] -= 5.0;
+        let fp = Footprint::square(3);
+        let white = GreyOp::WhiteTophat.apply(input.view(), &fp);
+        assert!(white[[2, 2]] >= 10.0 && white[[2, 3]] >= 10.0);
+        assert_eq!(white[[6, 6]], 0.0);
+        let black = GreyOp::BlackTophat.apply(input.view(), &fp);
+        assert!(black[[6, 6]] >= 5.0);
+        assert_eq!(black[[2, 2]], 0.0);
+        assert!(white.iter().chain(black.iter()).all(|&v| v >= 0.0));
+
+        let gradient = GreyOp::Gradient.apply(input.view(), &fp);
+        assert_eq!(gradient[[4, 4]], 2.0); // ramp slope 1 over a 3-pixel window
+        let single = GreyOp::Gradient.apply(input.mapv(|v| v as f32).view(), &fp);
+        assert_eq!(single[[4, 4]], 2.0_f32);
+    }
+
+    #[test]
+    fn test_label_regions_connectivity() {
+        let grid = mask(&["##..#", "#..#.", "..##.", "#...."]);
+        let (four, n4) = label_regions(grid.view(), Connectivity::Four, Some(false));
+        assert_eq!(n4, 4);
+        assert_eq!(
+            four,
+            ndarray::array![
+                [1, 1, 0, 0, 2],
+                [1, 0, 0, 3, 0],
+                [0, 0, 3, 3, 0],
+                [4, 0, 0, 0, 0]
+            ]
+        );
+        let (eight, n8) = label_regions(grid.view(), Connectivity::Eight, Some(false));
+        assert_eq!(n8, 3);
+        assert_eq!(
+            eight,
+            ndarray::array![
+                [1, 1, 0, 0, 2],
+                [1, 0, 0, 2, 0],
+                [0, 0, 2, 2, 0],
+                [3, 0, 0, 0, 0]
+            ]
+        );
+
+        // A U shape merges two provisional labels.
+        let u = mask(&["#.#", "#.#", "###"]);
+        let (labels, n) = label_regions(u.view(), Connectivity::Four, Some(false));
+        assert_eq!(n, 1);
+        assert!(labels.iter().zip(u.iter()).all(|(&l, &m)| (l == 1) == m));
+
+        // Without a background every class region is labelled.
+        let classes = ndarray::array![[1u8, 1, 2], [3, 1, 2]];
+        let (labels, n) = label_regions(classes.view(), Connectivity::Four, None);
+        assert_eq!(n, 3);
+        assert_eq!(labels, ndarray::array![[1, 1, 2], [3, 1, 2]]);
+    }
+
+    #[test]
+    fn test_component_props() {
+        let grid = mask(&["##...", "##...", "....#"]);
+        let (labels, n) = label_regions(grid.view(), Connectivity::Eight, Some(false));
+        let mut values = Array2::from_elem((3, 5), 1.0);
+        values[[0, 0]] = 5.0;
+        values[[1, 1]] = f64::NAN;
+        let props = component_props(labels.view(), n, Some(values.view()));
+        assert_eq!(props.len(), 2);
+        let square = props[0];
+        assert_eq!(square.area, 4);
+        assert_eq!(square.bbox, (0, 0, 2, 2));
+        assert_eq!(square.centroid, (0.5, 0.5));
+        assert_eq!(square.perimeter, 8);
+        assert_eq!(square.mean, Some(7.0 / 3.0));
+        let dot = props[1];
+        assert_eq!((dot.label, dot.area, dot.bbox), (2, 1, (2, 4, 3, 5)));
+        assert_eq!(dot.perimeter, 4);
+        assert!(component_props(labels.view(), n, None)[0].mean.is_none());
+    }
+
+    #[test]
+    fn test_sieve_merges_into_largest_neighbour() {
+        let classes = ndarray::array![
+            [1i64, 1, 2, 2, 2],
+            [1, 3, 1, 2, 2],
+            [1, 1, 1, 2, 2],
+            [4, 4, 2, 2, 2],
+        ];
+        // The single 3 is enclosed by 1s; the pair of 4s touches the 1s
+        // (8 px after absorbing the 3) and the 2s (10 px) and joins the 2s.
+        let out = sieve_impl(classes.view(), 3, Connectivity::Four, None);
+        assert_eq!(
+            out,
+            ndarray::array![
+                [1, 1, 2, 2, 2],
+                [1, 1, 1, 2, 2],
+                [1, 1, 1, 2, 2],
+                [2, 2, 2, 2, 2],
+            ]
+        );
+        // Nodata regions are kept and never absorb their neighbours.
+        let out = sieve_impl(classes.view(), 3, Connectivity::Four, Some(1));
+        assert_eq!(out[[1, 1]], 3);
+        assert_eq!(out[[3, 0]], 2);
+        // A threshold of 0 or 1 changes nothing.
+        assert_eq!(
+            sieve_impl(classes.view(), 1, Connectivity::Four, None),
+            classes
+        );
+    }
+
+    #[test]
+    fn test_fill_holes() {
+        let input = mask(&["#####", "#..##", "#####", "#...#", "##.##"]).mapv(u8::from);
+        let filled = fill_holes_impl(input.view(), Connectivity::Four);
+        assert_eq!(
+            filled,
+            mask(&["#####", "#####", "#####", "#...#", "##.##"]).mapv(u8::from)
+        );
+        // A diagonal gap closes the ring only under 4-connectivity.
+        let ring = mask(&["###.", "#.#.", "##..", "...."]).mapv(u8::from);
+        assert_eq!(fill_holes_impl(ring.view(), Connectivity::Four)[[1, 1]], 1);
+        assert_eq!(fill_holes_impl(ring.view(), Connectivity::Eight)[[1, 1]], 0);
+    }
+
+    #[test]
+    fn test_majority_filter() {
+        let classes = ndarray::array![[1i64, 1, 1], [1, 2, 1], [3, 3, 0]];
+        let out = majority_impl(classes.view(), &Footprint::square(3), None);
+        // Ties keep the centre class.
+        assert_eq!(out, ndarray::array![[1, 1, 1], [1, 1, 1], [3, 3, 0]]);
+
+        // Without nodata 0 and 1 tie and the smaller class wins; skipping
+        // nodata leaves 1 as the majority.
+        let classes = ndarray::array![[0i64, 0, 1], [0, 2, 1], [0, 1, 1]];
+        let out = majority_impl(classes.view(), &Footprint::square(3), None);
+        assert_eq!(out[[1, 1]], 0);
+        let out = majority_impl(classes.view(), &Footprint::square(3), Some(0));
+        assert_eq!(out[[1, 1]], 1);
+        assert_eq!(out[[0, 0]], 0);
+    }
+
+    #[test]
+    fn test_distance_transform_matches_brute_force() {
+        // Deterministic scatter of features on an irregular grid.
+        let features = Array2::from_shape_fn((13, 17), |(y, x)| (y * 7 + x * 11) % 23 == 0);
+        for spacing in [[1.0, 1.0], [10.0, 10.0], [30.0, 20.0]] {
+            let fast = squared_distance_transform(features.view(), spacing);
+            let slow = brute_force(&features, spacing);
+            for (a, b) in fast.iter().zip(slow.iter()) {
+                assert!((a - b).abs() <= 1e-9 * b.max(1.0), "{} != {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_distance_transform_without_features() {
+        let features = Array2::from_elem((3, 4), false);
+        let dist = squared_distance_transform(features.view(), [1.0, 1.0]);
+        assert!(dist.iter().all(|d| d.is_infinite()));
+    }
+
+    #[test]
+    fn test_feature_transform_indices_point_at_nearest_feature() {
+        let features = Array2::from_shape_fn((13, 17), |(y, x)| (y * 7 + x * 11) % 23 == 0);
+        for spacing in [[1.0, 1.0], [30.0, 10.0], [5.0, 20.0]] {
+            let ft = feature_transform(features.view(), spacing);
+            let slow = brute_force(&features, spacing);
+            for ((y, x), &d) in slow.indexed_iter() {
+                let (r, c) = (ft.rows[[y, x]], ft.cols[[y, x]]);
+                assert!(features[[r as usize, c as usize]]);
+                let dy = (y as f64 - r as f64) * spacing[0];
+                let dx = (x as f64 - c as f64) * spacing[1];
+                assert!((dy * dy + dx * dx - d).abs() <= 1e-9 * d.max(1.0));
+                assert!((ft.squared[[y, x]] - d).abs() <= 1e-9 * d.max(1.0));
+            }
+        }
+        let empty = feature_transform(Array2::from_elem((2, 3), false).view(), [1.0, 1.0]);
+        assert!(empty.rows.iter().chain(empty.cols.iter()).all(|&i| i == -1));
+    }
+}
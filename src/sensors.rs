@@ -0,0 +1,466 @@
+//! Sensor band presets.
+//!
+//! A `SensorProfile` maps a sensor's band names to the band roles of the index
+//! registry and carries the sensor's DN-to-reflectance scaling, so callers can
+//! pass a band-indexed cube plus a sensor name instead of wiring up `nir`,
+//! `red`, ... and the scale factors themselves.
+
+use crate::indices::{BandRole, FusedPlan};
+use crate::kernels::{self, Operand, Sample, Scaling};
+use crate::CoreError;
+use ndarray::{ArrayD, ArrayViewD, Axis};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+/// One band of a sensor product.
+pub(crate) struct SensorBand {
+    pub name: &'static str,
+    /// Band role used by the index registry; `None` for bands no index reads.
+    pub role: Option<BandRole>,
+}
+
+const fn band(name: &'static str, role: BandRole) -> SensorBand {
+    SensorBand {
+        name,
+        role: Some(role),
+    }
+}
+
+const fn other(name: &'static str) -> SensorBand {
+    SensorBand { name, role: None }
+}
+
+/// Band layout and reflectance scaling of one sensor product.
+pub(crate) struct SensorProfile {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Bands in canonical cube order, used when no `band_names` are given.
+    pub bands: &'static [SensorBand],
+    /// DN to surface reflectance: `DN * scale + offset`.
+    pub scaling: Scaling,
+    /// Scene classification band, if the product has one.
+    pub scl_band: Option<&'static str>,
+}
+
+/// Sentinel-2 L2A bands (B10 is not delivered at L2A). The red-edge role maps
+/// to B05.
+const SENTINEL2_BANDS: &[SensorBand] = &[
+    other("B01"),
+    band("B02", BandRole::Blue),
+    band("B03", BandRole::Green),
+    band("B04", BandRole::Red),
+    band("B05", BandRole::RedEdge),
+    other("B06"),
+    other("B07"),
+    band("B08", BandRole::Nir),
+    other("B8A"),
+    other("B09"),
+    band("B11", BandRole::Swir1),
+    band("B12", BandRole::Swir2),
+];
+
+/// Landsat 8/9 OLI Collection 2 Level-2 surface reflectance bands.
+const LANDSAT_OLI_BANDS: &[SensorBand] = &[
+    other("SR_B1"),
+    band("SR_B2", BandRole::Blue),
+    band("SR_B3", BandRole::Green),
+    band("SR_B4", BandRole::Red),
+    band("SR_B5", BandRole::Nir),
+    band("SR_B6", BandRole::Swir1),
+    band("SR_B7", BandRole::Swir2),
+];
+
+/// Landsat 4-5 TM and Landsat 7 ETM+ Collection 2 Level-2 surface reflectance
+/// bands (the thermal band 6 is delivered as `ST_B6`).
+const LANDSAT_TM_BANDS: &[SensorBand] = &[
+    band("SR_B1", BandRole::Blue),
+    band("SR_B2", BandRole::Green),
+    band("SR_B3", BandRole::Red),
+    band("SR_B4", BandRole::Nir),
+    band("SR_B5", BandRole::Swir1),
+    band("SR_B7", BandRole::Swir2),
+];
+
+/// Landsat Collection 2 Level-2 surface reflectance scaling.
+const LANDSAT_C2_SCALING: Scaling = Scaling {
+    scale: 0.0000275,
+    offset: -0.2,
+};
+
+/// Supported sensors.
+pub(crate) const SENSOR_PROFILES: &[SensorProfile] = &[
+    SensorProfile {
+        name: "sentinel2",
+        description: "Sentinel-2 MSI L2A, processing baseline 04.00 or later \
+                      (BOA_ADD_OFFSET = -1000)",
+        bands: SENTINEL2_BANDS,
+        scaling: Scaling {
+            scale: 1e-4,
+            offset: -0.1,
+        },
+        scl_band: Some("SCL"),
+    },
+    SensorProfile {
+        name: "sentinel2_legacy",
+        description: "Sentinel-2 MSI L2A, processing baseline before 04.00 \
+                      (no BOA_ADD_OFFSET)",
+        bands: SENTINEL2_BANDS,
+        scaling: Scaling {
+            scale: 1e-4,
+            offset: 0.0,
+        },
+        scl_band: Some("SCL"),
+    },
+    SensorProfile {
+        name: "landsat5",
+        description: "Landsat 4-5 TM Collection 2 Level-2 surface reflectance",
+        bands: LANDSAT_TM_BANDS,
+        scaling: LANDSAT_C2_SCALING,
+        scl_band: None,
+    },
+    SensorProfile {
+        name: "landsat7",
+        description: "Landsat 7 ETM+ Collection 2 Level-2 surface reflectance",
+        bands: LANDSAT_TM_BANDS,
+        scaling: LANDSAT_C2_SCALING,
+        scl_band: None,
+    },
+    SensorProfile {
+        name: "landsat8",
+        description: "Landsat 8 OLI Collection 2 Level-2 surface reflectance",
+        bands: LANDSAT_OLI_BANDS,
+        scaling: LANDSAT_C2_SCALING,
+        scl_band: None,
+    },
+    SensorProfile {
+        name: "landsat9",
+        description: "Landsat 9 OLI-2 Collection 2 Level-2 surface reflectance",
+        bands: LANDSAT_OLI_BANDS,
+        scaling: LANDSAT_C2_SCALING,
+        scl_band: None,
+    },
+    SensorProfile {
+        name: "modis",
+        description: "MODIS MOD09/MYD09 surface reflectance (bands 1-7)",
+        bands: &[
+            band("sur_refl_b01", BandRole::Red),
+            band("sur_refl_b02", BandRole::Nir),
+            band("sur_refl_b03", BandRole::Blue),
+            band("sur_refl_b04", BandRole::Green),
+            other("sur_refl_b05"),
+            band("sur_refl_b06", BandRole::Swir1),
+            band("sur_refl_b07", BandRole::Swir2),
+        ],
+        scaling: Scaling {
+            scale: 1e-4,
+            offset: 0.0,
+        },
+        scl_band: None,
+    },
+];
+
+/// Look up a sensor profile by name (case-insensitive).
+pub(crate) fn lookup_sensor(name: &str) -> Result<&'static SensorProfile, CoreError> {
+    let lower = name.to_ascii_lowercase();
+    SENSOR_PROFILES
+        .iter()
+        .find(|p| p.name == lower)
+        .ok_or_else(|| {
+            let supported: Vec<&str> = SENSOR_PROFILES.iter().map(|p| p.name).collect();
+            CoreError::InvalidArgument(format!(
+                "Unknown sensor '{}'; supported: {:?}",
+                name, supported
+            ))
+        })
+}
+
+impl SensorProfile {
+    /// Resolve `band_axis` (negative counts from the end) for a cube of
+    /// `shape` and check its length against `band_names` or the canonical
+    /// band list.
+    pub fn band_axis(
+        &self,
+        shape: &[usize],
+        band_axis: isize,
+        band_names: Option<&[String]>,
+    ) -> Result<usize, CoreError> {
+        let axis = kernels::resolve_band_axis(band_axis, shape.len())?;
+        let expected = band_names.map_or(self.bands.len(), |names| names.len());
+        if shape[axis] != expected {
+            let names: Vec<&str> = match band_names {
+                Some(names) => names.iter().map(String::as_str).collect(),
+                None => self.bands.iter().map(|b| b.name).collect(),
+            };
+            return Err(CoreError::InvalidArgument(format!(
+                "Cube has {} bands along axis {} but sensor '{}' expects {}: {:?}",
+                shape[axis], axis, self.name, expected, names
+            )));
+        }
+        Ok(axis)
+    }
+
+    /// Index of `band` along the band axis: its position in `band_names`
+    /// (case-insensitive) if given, else in the canonical band list.
+    fn position(&self, band: &str, band_names: Option<&[String]>) -> Option<usize> {
+        match band_names {
+            Some(names) => names.iter().position(|n| n.eq_ignore_ascii_case(band)),
+            None => self.bands.iter().position(|b| b.name == band),
+        }
+    }
+
+    /// Band-axis index of the band playing `role`.
+    pub fn role_position(
+        &self,
+        role: BandRole,
+        band_names: Option<&[String]>,
+    ) -> Result<usize, CoreError> {
+        let band = self
+            .bands
+            .iter()
+            .find(|b| b.role == Some(role))
+            .ok_or_else(|| {
+                CoreError::InvalidArgument(format!(
+                    "Sensor '{}' has no '{}' band",
+                    self.name,
+                    role.as_str()
+                ))
+            })?;
+        self.position(band.name, band_names).ok_or_else(|| {
+            CoreError::InvalidArgument(format!(
+                "Sensor '{}' band '{}' ({}) not found in band_names: {:?}",
+                self.name,
+                band.name,
+                role.as_str(),
+                band_names.unwrap_or_default()
+            ))
+        })
+    }
+
+    /// Name of the scene classification band, or an error for sensors
+    /// without one.
+    pub fn require_scl(&self) -> Result<&'static str, CoreError> {
+        self.scl_band.ok_or_else(|| {
+            CoreError::InvalidArgument(format!(
+                "Sensor '{}' has no scene classification (SCL) band",
+                self.name
+            ))
+        })
+    }
+
+    /// Band-axis index of the scene classification band.
+    pub fn scl_position(&self, band_names: Option<&[String]>) -> Result<usize, CoreError> {
+        let scl = self.require_scl()?;
+        self.position(scl, band_names).ok_or_else(|| {
+            CoreError::InvalidArgument(format!(
+                "SCL band '{}' not found in band_names {:?}; list the cube's bands \
+                 including '{}'",
+                scl,
+                band_names.unwrap_or_default(),
+                scl
+            ))
+        })
+    }
+}
+
+/// Describe every sensor preset as a list of dicts with keys `name`,
+/// `description`, `bands` (canonical cube order), `roles` (band role to band
+/// name), `scale`, `offset` and `scl_band`.
+#[pyfunction]
+pub fn sensor_profiles(py: Python<'_>) -> PyResult<Vec<PyObject>> {
+    SENSOR_PROFILES
+        .iter()
+        .map(|profile| {
+            let dict = PyDict::new(py);
+            dict.set_item("name", profile.name)?;
+            dict.set_item("description", profile.description)?;
+            let bands: Vec<&str> = profile.bands.iter().map(|b| b.name).collect();
+            dict.set_item("bands", bands)?;
+            let roles = PyDict::new(py);
+            for b in profile.bands {
+                if let Some(role) = b.role {
+                    roles.set_item(role.as_str(), b.name)?;
+                }
+            }
+            dict.set_item("roles", roles)?;
+            dict.set_item("scale", profile.scaling.scale)?;
+            dict.set_item("offset", profile.scaling.offset)?;
+            dict.set_item("scl_band", profile.scl_band)?;
+            Ok(dict.to_object(py))
+        })
+        .collect()
+}
+
+/// Compute indices from a band-indexed cube of a known sensor.
+///
+/// The bands each index needs are resolved through the sensor profile and the
+/// sensor's reflectance scaling is applied, so e.g. a Sentinel-2 L2A uint16
+/// cube can be passed as delivered. Results match `compute_indices` on the
+/// equivalent reflectance bands.
+///
+/// # Arguments
+/// * `cube` - Array with one axis holding the sensor's bands
+/// * `sensor` - Sensor name, see `sensor_profiles`
+/// * `names` - Index names (case-insensitive), see `index_registry`
+/// * `band_axis` - Axis of `cube` holding the bands (default 0; negative
+///   values count from the end)
+/// * `band_names` - Band names along `band_axis`; defaults to the sensor's
+///   canonical band order
+/// * `scale`, `offset` - Override the sensor's reflectance scaling
+///
+/// # Returns
+/// Array of shape `(len(names), *cube_shape_without_band_axis)`; float32 for
+/// float32 / uint16 cubes, float64 otherwise.
+#[pyfunction]
+#[pyo3(signature = (cube, sensor, names, band_axis=0, band_names=None, scale=None, offset=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn sensor_indices(
+    py: Python<'_>,
+    cube: &PyAny,
+    sensor: &str,
+    names: Vec<String>,
+    band_axis: isize,
+    band_names: Option<Vec<String>>,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    let profile = lookup_sensor(sensor)?;
+    let plan = FusedPlan::new(&names)?;
+    let scaling = if scale.is_some() || offset.is_some() {
+        Scaling::from_args(scale, offset)?
+    } else {
+        profile.scaling
+    };
+    let band_names = band_names.as_deref();
+    Ok(kernels::dispatch!(Operand::extract(cube)?, |cube| {
+        let out = py
+            .allow_threads(|| cube_indices(cube, profile, &plan, band_axis, band_names, scaling))?;
+        kernels::into_py(py, out)
+    }))
+}
+
+pub(crate) fn cube_indices<A: Sample>(
+    cube: ArrayViewD<A>,
+    profile: &SensorProfile,
+    plan: &FusedPlan,
+    band_axis: isize,
+    band_names: Option<&[String]>,
+    scaling: Scaling,
+) -> Result<ArrayD<A::Out>, CoreError> {
+    let axis = profile.band_axis(cube.shape(), band_axis, band_names)?;
+    let views = plan
+        .bands()
+        .iter()
+        .map(|&role| {
+            let index = profile.role_position(role, band_names)?;
+            Ok(cube.index_axis(Axis(axis), index))
+        })
+        .collect::<Result<Vec<_>, CoreError>>()?;
+    plan.evaluate(&views, scaling)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indices::{evi_px, normalized_difference_px};
+    use ndarray::{Array3, IxDyn};
+
+    fn plan(names: &[&str]) -> FusedPlan {
+        FusedPlan::new(&names.iter().map(|n| n.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn test_profiles_resolve_roles() {
+        let s2 = lookup_sensor("Sentinel2").unwrap();
+        assert_eq!(s2.role_position(BandRole::Nir, None).unwrap(), 7);
+        assert_eq!(s2.role_position(BandRole::Swir2, None).unwrap(), 11);
+        let l8 = lookup_sensor("landsat8").unwrap();
+        assert_eq!(l8.scaling, LANDSAT_C2_SCALING);
+        assert_eq!(l8.role_position(BandRole::Nir, None).unwrap(), 4);
+        let err = l8.role_position(BandRole::RedEdge, None).unwrap_err();
+        assert!(err.to_string().contains("no 'rededge' band"));
+        assert!(lookup_sensor("spot").is_err());
+        for profile in SENSOR_PROFILES {
+            assert!(profile.role_position(BandRole::Nir, None).is_ok());
+            assert!(profile.role_position(BandRole::Red, None).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_cube_indices_sentinel2_dn() {
+        // (band, y, x) uint16 cube in canonical order.
+        let cube = Array3::from_shape_fn((12, 2, 3), |(b, y, x)| {
+            1000 + (b * 500 + y * 70 + x * 13) as u16
+        })
+        .into_dyn();
+        let s2 = lookup_sensor("sentinel2").unwrap();
+        let out: ArrayD<f32> = cube_indices(
+            cube.view(),
+            s2,
+            &plan(&["ndvi", "evi"]),
+            0,
+            None,
+            s2.scaling,
+        )
+        .unwrap();
+        assert_eq!(out.shape(), &[2, 2, 3]);
+        let refl = |b: usize, y: usize, x: usize| cube[[b, y, x]] as f64 * 1e-4 - 0.1;
+        for y in 0..2 {
+            for x in 0..3 {
+                let ndvi = normalized_difference_px(refl(7, y, x), refl(3, y, x));
+                assert_eq!(out[[0, y, x]], ndvi as f32);
+                let evi = evi_px(refl(7, y, x), refl(3, y, x), refl(1, y, x));
+                assert_eq!(out[[1, y, x]], evi as f32);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cube_indices_band_names_and_axis() {
+        // (y, x, band) cube holding only the bands NDVI needs, plus SCL.
+        let names: Vec<String> = ["SCL", "b04", "B08"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let cube = ArrayD::from_shape_fn(IxDyn(&[2, 2, 3]), |ix| match ix[2] {
+            0 => 4.0,
+            1 => 0.1 + ix[0] as f64 * 0.1,
+            _ => 0.6 + ix[1] as f64 * 0.1,
+        });
+        let s2 = lookup_sensor("sentinel2").unwrap();
+        let out: ArrayD<f64> = cube_indices(
+            cube.view(),
+            s2,
+            &plan(&["ndvi"]),
+            -1,
+            Some(&names),
+            Scaling::IDENTITY,
+        )
+        .unwrap();
+        assert_eq!(out.shape(), &[1, 2, 2]);
+        assert_eq!(out[[0, 1, 0]], normalized_difference_px(0.6, 0.2));
+        assert_eq!(s2.scl_position(Some(&names)).unwrap(), 0);
+        assert!(s2.scl_position(None).is_err());
+
+        let err = cube_indices(
+            cube.view(),
+            s2,
+            &plan(&["nbr"]),
+            -1,
+            Some(&names),
+            Scaling::IDENTITY,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("'B12' (swir2) not found"));
+        let err = cube_indices(
+            cube.view(),
+            s2,
+            &plan(&["ndvi"]),
+            0,
+            None,
+            Scaling::IDENTITY,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Cube has 2 bands along axis 0"));
+        assert!(s2.band_axis(cube.shape(), 3, None).is_err());
+    }
+}
@@ -0,0 +1,397 @@
+//! Per-pixel spectral similarity.
+//!
+//! Compares each pixel's band vector of a `(band, ...)` cube against a
+//! library of reference spectra and assigns the best-matching reference.
+//! Reference-side terms (norms, normalised distributions, centred spectra)
+//! are computed once; pixels are then scored in parallel over the band lanes.
+
+use crate::kernels::{self, Operand, Real, Sample, Scaling};
+use crate::CoreError;
+use ndarray::{Array1, Array2, ArrayD, ArrayView1, ArrayView2, ArrayViewD, Axis, IxDyn, Zip};
+use numpy::{IntoPyArray, PyReadonlyArray2};
+use pyo3::prelude::*;
+
+/// Floor applied to band values before SID takes logarithms.
+const SID_FLOOR: f64 = 1e-12;
+
+/// Spectral similarity measure.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Metric {
+    /// Spectral angle in radians; 0 is a perfect match.
+    Sam,
+    /// Spectral information divergence; 0 is a perfect match.
+    Sid,
+    /// Spectral correlation similarity (Pearson r); 1 is a perfect match.
+    Scs,
+}
+
+impl Metric {
+    pub fn parse(metric: &str) -> Result<Self, CoreError> {
+        match metric.to_ascii_lowercase().as_str() {
+            "sam" => Ok(Metric::Sam),
+            "sid" => Ok(Metric::Sid),
+            "scs" => Ok(Metric::Scs),
+            _ => Err(CoreError::InvalidArgument(format!(
+                "Unknown similarity metric '{}'; expected 'sam', 'sid' or 'scs'",
+                metric
+            ))),
+        }
+    }
+
+    /// Whether a score beats `other`.
+    fn better(self, score: f64, other: f64) -> bool {
+        match self {
+            Metric::Sam | Metric::Sid => score < other,
+            Metric::Scs => score > other,
+        }
+    }
+}
+
+/// Reference spectra prepared for one metric.
+pub(crate) struct Library {
+    metric: Metric,
+    /// `(n_refs, n_bands)`: raw spectra for SAM, band distributions for SID,
+    /// centred spectra for SCS.
+    spectra: Array2<f64>,
+    /// Per-reference norm (SAM, SCS); unused for SID.
+    norms: Array1<f64>,
+}
+
+impl Library {
+    pub fn new(library: ArrayView2<f64>, metric: Metric) -> Result<Self, CoreError> {
+        let (refs, bands) = library.dim();
+        if refs == 0 || bands < 2 {
+            return Err(CoreError::InvalidArgument(format!(
+                "Spectral library must have at least one reference and two bands, got shape \
+                 ({}, {})",
+                refs, bands
+            )));
+        }
+        if library.iter().any(|v| !v.is_finite()) {
+            return Err(CoreError::InvalidArgument(
+                "Spectral library must be finite".to_string(),
+            ));
+        }
+        let mut spectra = library.to_owned();
+        for mut row in spectra.rows_mut() {
+            match metric {
+                Metric::Sam => {}
+                Metric::Sid => distribution(&mut row),
+                Metric::Scs => {
+                    let mean = row.mean().unwrap_or(0.0);
+                    row -= mean;
+                }
+            }
+        }
+        let norms = spectra
+            .rows()
+            .into_iter()
+            .map(|r| r.dot(&r).sqrt())
+            .collect();
+        Ok(Library {
+            metric,
+            spectra,
+            norms,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.spectra.nrows()
+    }
+
+    pub fn n_bands(&self) -> usize {
+        self.spectra.ncols()
+    }
+
+    /// Score the pixel lane `px` against reference `k`.
+    fn score<A: Sample>(&self, k: usize, px: ArrayView1<A>, pixel: &PixelTerms) -> f64 {
+        let values = px
+            .iter()
+            .map(|x| self.transform(pixel.scaling.apply(x.to_f64()), pixel));
+        let reference = self.spectra.row(k);
+        match self.metric {
+            Metric::Sam | Metric::Scs => {
+                let denom = pixel.norm * self.norms[k];
+                if denom == 0.0 {
+                    return f64::NAN;
+                }
+                let dot: f64 = values.zip(reference).map(|(p, &q)| p * q).sum();
+                let cos = (dot / denom).clamp(-1.0, 1.0);
+                match self.metric {
+                    Metric::Sam => cos.acos(),
+                    _ => cos,
+                }
+            }
+            Metric::Sid => values
+                .zip(reference)
+                .map(|(p, &q)| (p - q) * (p / q).ln())
+                .sum(),
+        }
+    }
+
+    /// Terms that transform the pixel lane `px` the way the references were
+    /// transformed, or `None` when a band is not finite.
+    fn prepare<A: Sample>(&self, px: ArrayView1<A>, scaling: Scaling) -> Option<PixelTerms> {
+        let values = || px.iter().map(|x| scaling.apply(x.to_f64()));
+        if values().any(|x| !x.is_finite()) {
+            return None;
+        }
+        let mut pixel = PixelTerms {
+            scaling,
+            shift: 0.0,
+            divisor: 1.0,
+            norm: 0.0,
+        };
+        match self.metric {
+            Metric::Sam => {}
+            Metric::Sid => pixel.divisor = values().map(|x| x.max(SID_FLOOR)).sum(),
+            Metric::Scs => pixel.shift = values().sum::<f64>() / px.len() as f64,
+        }
+        pixel.norm = values()
+            .map(|x| self.transform(x, &pixel).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        Some(pixel)
+    }
+
+    /// One scaled band value of a pixel after the metric's transform.
+    fn transform(&self, x: f64, pixel: &PixelTerms) -> f64 {
+        let x = match self.metric {
+            Metric::Sid => x.max(SID_FLOOR),
+            _ => x,
+        };
+        (x - pixel.shift) / pixel.divisor
+    }
+}
+
+/// Per-pixel terms of the metric transform, so a pixel is scored straight
+/// from its band lane: band value `x` becomes `(x - shift) / divisor`.
+struct PixelTerms {
+    scaling: Scaling,
+    /// Band mean for SCS, 0 otherwise.
+    shift: f64,
+    /// Band total for SID, 1 otherwise.
+    divisor: f64,
+    /// Norm of the transformed spectrum (SAM, SCS).
+    norm: f64,
+}
+
+/// Normalise a spectrum to a probability distribution over bands, flooring
+/// non-positive values.
+fn distribution(v: &mut ndarray::ArrayViewMut1<f64>) {
+    v.mapv_inplace(|x| x.max(SID_FLOOR));
+    let total = v.sum();
+    *v /= total;
+}
+
+/// Per-reference scores, best-match class and best score.
+pub(crate) type Similarity<R> = (ArrayD<R>, ArrayD<i32>, ArrayD<R>);
+
+/// Score every pixel of a `(band, ...)` cube against `library`. Pixels with a
+/// non-finite band, or whose best score fails `threshold`, get class -1.
+pub(crate) fn similarity_cube<A: Sample>(
+    cube: ArrayViewD<A>,
+    library: &Library,
+    threshold: Option<f64>,
+    scaling: Scaling,
+) -> Result<Similarity<A::Out>, CoreError> {
+    if let Some(threshold) = threshold {
+        if threshold.is_nan() {
+            return Err(CoreError::InvalidArgument(format!(
+                "Similarity threshold must be a number, got {}",
+                threshold
+            )));
+        }
+    }
+    if cube.ndim() == 0 || cube.shape()[0] != library.n_bands() {
+        return Err(CoreError::InvalidArgument(format!(
+            "Cube of shape {:?} does not match the spectral library: expected {} bands along \
+             axis 0",
+            cube.shape(),
+            library.n_bands()
+        )));
+    }
+    let pixel_shape = &cube.shape()[1..];
+    let mut shape = cube.shape().to_vec();
+    shape[0] = library.len();
+    let nan = A::Out::from_f64(f64::NAN);
+    let mut scores = ArrayD::from_elem(IxDyn(&shape), nan);
+    let mut classes = ArrayD::from_elem(IxDyn(pixel_shape), -1i32);
+    let mut best = ArrayD::from_elem(IxDyn(pixel_shape), nan);
+    let metric = library.metric;
+    Zip::from(scores.lanes_mut(Axis(0)))
+        .and(&mut classes)
+        .and(&mut best)
+        .and(cube.lanes(Axis(0)))
+        .par_for_each(|mut s, class, best, px| {
+            let Some(pixel) = library.prepare(px, scaling) else {
+                return;
+            };
+            let mut top: Option<(usize, f64)> = None;
+            for (k, out) in s.iter_mut().enumerate() {
+                let score = library.score(k, px, &pixel);
+                *out = Real::from_f64(score);
+                if score.is_nan() {
+                    continue;
+                }
+                if top.is_none_or(|(_, t)| metric.better(score, t)) {
+                    top = Some((k, score));
+                }
+            }
+            if let Some((k, score)) = top {
+                *best = Real::from_f64(score);
+                let passes = threshold.is_none_or(|t| !metric.better(t, score));
+                if passes {
+                    *class = k as i32;
+                }
+            }
+        });
+    Ok((scores, classes, best))
+}
+
+/// Compare each pixel spectrum with a library of reference spectra.
+///
+/// Metrics:
+/// - `"sam"`: spectral angle `arccos(x . r / (|x| |r|))` in radians; lower is
+///   more similar and the result is insensitive to illumination scaling.
+/// - `"sid"`: spectral information divergence, the symmetric Kullback-Leibler
+///   divergence between the spectra normalised to band distributions; lower
+///   is more similar.
+/// - `"scs"`: spectral correlation similarity, the Pearson correlation of the
+///   two spectra; higher is more similar.
+///
+/// # Arguments
+/// * `cube` - `(band, y, x)` array (any rank with bands first)
+/// * `library` - `(n_refs, n_bands)` reference spectra, one per row
+/// * `metric` - `"sam"` (default), `"sid"` or `"scs"`
+/// * `threshold` - Pixels whose best score is worse than this (angle or
+///   divergence above it, correlation below it) are left unclassified
+/// * `scale`, `offset` - DN conversion applied to the cube
+///
+/// # Returns
+/// `(scores, classes, best)`: `(n_refs, y, x)` scores, the `(y, x)` int32 index
+/// of the best-matching reference (-1 for unclassified or NaN pixels) and the
+/// `(y, x)` best score (the minimum angle for SAM).
+#[pyfunction]
+#[pyo3(signature = (cube, library, metric="sam", threshold=None, scale=None, offset=None))]
+pub fn spectral_similarity(
+    py: Python<'_>,
+    cube: &PyAny,
+    library: PyReadonlyArray2<f64>,
+    metric: &str,
+    threshold: Option<f64>,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<(PyObject, PyObject, PyObject)> {
+    let library = Library::new(library.as_array(), Metric::parse(metric)?)?;
+    let scaling = Scaling::from_args(scale, offset)?;
+    let (scores, classes, best) = kernels::dispatch!(Operand::extract(cube)?, |a| {
+        let (s, c, b) = py.allow_threads(|| similarity_cube(a, &library, threshold, scaling))?;
+        (kernels::into_py(py, s), c, kernels::into_py(py, b))
+    });
+    Ok((scores, classes.into_pyarray(py).into_py(py), best))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use ndarray::{array, Array3};
+
+    fn library() -> Array2<f64> {
+        array![
+            [0.05, 0.08, 0.06, 0.45], // vegetation
+            [0.20, 0.25, 0.30, 0.35], // soil
+            [0.10, 0.06, 0.03, 0.01], // water
+        ]
+    }
+
+    fn cube() -> Array3<f64> {
+        // Pixel (0, 0): scaled vegetation; (0, 1): soil; (1, 0): NaN; (1, 1): water.
+        let lib = library();
+        let mut cube = Array3::zeros((4, 2, 2));
+        for b in 0..4 {
+            cube[[b, 0, 0]] = 0.5 * lib[[0, b]];
+            cube[[b, 0, 1]] = lib[[1, b]] + 0.01;
+            cube[[b, 1, 0]] = f64::NAN;
+            cube[[b, 1, 1]] = lib[[2, b]];
+        }
+        cube
+    }
+
+    #[test]
+    fn test_sam_angles_and_classes() {
+        let lib = Library::new(library().view(), Metric::Sam).unwrap();
+        let (scores, classes, best) =
+            similarity_cube(cube().view().into_dyn(), &lib, None, Scaling::IDENTITY).unwrap();
+        assert_eq!(scores.shape(), &[3, 2, 2]);
+        assert_eq!(classes, array![[0, 1], [-1, 2]].into_dyn());
+        // Brightness scaling does not change the angle.
+        assert!(scores[[0, 0, 0]].abs() < 1e-7);
+        assert_relative_eq!(best[[0, 0]], scores[[0, 0, 0]]);
+        assert!(best[[1, 0]].is_nan() && scores[[2, 1, 0]].is_nan());
+
+        let (v, s) = (library().row(0).to_owned(), library().row(1).to_owned());
+        let angle = (v.dot(&s) / (v.dot(&v).sqrt() * s.dot(&s).sqrt())).acos();
+        let (scores, _, _) =
+            similarity_cube(library().t().into_dyn(), &lib, None, Scaling::IDENTITY).unwrap();
+        assert_relative_eq!(scores[[1, 0]], angle, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_threshold_leaves_pixels_unclassified() {
+        let lib = Library::new(library().view(), Metric::Sam).unwrap();
+        let (_, classes, best) = similarity_cube(
+            cube().view().into_dyn(),
+            &lib,
+            Some(1e-3),
+            Scaling::IDENTITY,
+        )
+        .unwrap();
+        assert_eq!(classes[[0, 0]], 0);
+        assert_eq!(classes[[0, 1]], -1);
+        assert!(best[[0, 1]] > 1e-3);
+    }
+
+    #[test]
+    fn test_sid_and_scs() {
+        let data = cube().into_dyn();
+        let sid = Library::new(library().view(), Metric::Sid).unwrap();
+        let (scores, classes, _) =
+            similarity_cube(data.view(), &sid, None, Scaling::IDENTITY).unwrap();
+        assert_eq!(classes, array![[0, 1], [-1, 2]].into_dyn());
+        assert!(scores[[0, 0, 0]].abs() < 1e-12);
+        assert!(scores.iter().filter(|v| !v.is_nan()).all(|&v| v >= -1e-15));
+
+        let scs = Library::new(library().view(), Metric::Scs).unwrap();
+        let (scores, classes, best) =
+            similarity_cube(data.view(), &scs, None, Scaling::IDENTITY).unwrap();
+        assert_eq!(classes, array![[0, 1], [-1, 2]].into_dyn());
+        assert_relative_eq!(best[[1, 1]], 1.0, epsilon = 1e-12);
+        assert!(scores[[2, 0, 1]] < 0.0); // soil rises, water falls
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(Metric::parse("euclid").is_err());
+        assert!(Library::new(array![[1.0]].view(), Metric::Sam).is_err());
+        assert!(Library::new(array![[1.0, f64::NAN]].view(), Metric::Sam).is_err());
+        let lib = Library::new(library().view(), Metric::Sam).unwrap();
+        let err = similarity_cube(
+            Array3::<f64>::zeros((3, 1, 1)).into_dyn().view(),
+            &lib,
+            None,
+            Scaling::IDENTITY,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("expected 4 bands"));
+        let err = similarity_cube(
+            cube().into_dyn().view(),
+            &lib,
+            Some(f64::NAN),
+            Scaling::IDENTITY,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("threshold"));
+    }
+}
@@ -1,260 +1,10 @@
+use crate::morphology::feature_transform;
 use crate::CoreError;
-use ndarray::{s, Array1, Array2, Array3, Axis};
-use numpy::{
-    IntoPyArray, PyArray1, PyArray2, PyArray3, PyReadonlyArray1, PyReadonlyArray2,
-    PyReadonlyArray3, PyReadonlyArray4,
-};
+use ndarray::{Array2, Array3, Axis};
+use numpy::{IntoPyArray, PyArray2, PyReadonlyArray2};
 use pyo3::prelude::*;
 use rayon::prelude::*;
 
-/// Spatial processing functions for Earth Observation data.
-/// Dispatches median to 3D or 4D implementation based on array dimensions.
-#[pyfunction]
-#[pyo3(signature = (arr, axis=None, skip_na=true))]
-pub fn median(
-    py: Python<'_>,
-    arr: &PyAny,
-    axis: Option<usize>,
-    skip_na: bool,
-) -> PyResult<PyObject> {
-    if let Some(axis) = axis {
-        if let Ok(arr4d) = arr.downcast::<numpy::PyArray4<f64>>() {
-            Ok(median_along_axis(py, arr4d.readonly(), axis, skip_na)?.into_py(py))
-        } else {
-            Err(pyo3::exceptions::PyTypeError::new_err(
-                "median_along_axis is only supported for 4D arrays.",
-            ))
-        }
-    } else if let Ok(arr1d) = arr.downcast::<numpy::PyArray1<f64>>() {
-        Ok(median_1d(arr1d.readonly(), skip_na).into_py(py))
-    } else if let Ok(arr2d) = arr.downcast::<numpy::PyArray2<f64>>() {
-        Ok(median_2d(py, arr2d.readonly(), skip_na).into_py(py))
-    } else if let Ok(arr3d) = arr.downcast::<numpy::PyArray3<f64>>() {
-        Ok(median_3d(py, arr3d.readonly(), skip_na).into_py(py))
-    } else if let Ok(arr4d) = arr.downcast::<numpy::PyArray4<f64>>() {
-        Ok(median_4d(py, arr4d.readonly(), skip_na)?.into_py(py))
-    } else {
-        Err(
-            CoreError::InvalidArgument("Expected a 1D, 2D, 3D, or 4D NumPy array.".to_string())
-                .into(),
-        )
-    }
-}
-
-/// Computes the median for a 3D array (time, y, x).
-fn median_3d<'py>(
-    py: Python<'py>,
-    arr: PyReadonlyArray3<f64>,
-    skip_na: bool,
-) -> &'py PyArray2<f64> {
-    let array = arr.as_array();
-    let shape = array.shape();
-    let (height, width) = (shape[1], shape[2]);
-    let mut result = Array2::<f64>::zeros((height, width));
-
-    result
-        .indexed_iter_mut()
-        .par_bridge()
-        .for_each(|((r, c), pixel)| {
-            let mut series: Vec<f64> = array.slice(s![.., r, c]).to_vec();
-            if series.iter().any(|v| v.is_nan()) {
-                if skip_na {
-                    series.retain(|v| !v.is_nan());
-                } else {
-                    *pixel = f64::NAN;
-                    return;
-                }
-            }
-
-            if series.is_empty() {
-                *pixel = f64::NAN;
-            } else {
-                series.sort_by(|a, b| a.total_cmp(b));
-                let mid = series.len() / 2;
-                *pixel = if series.len().is_multiple_of(2) {
-                    (series[mid - 1] + series[mid]) / 2.0
-                } else {
-                    series[mid]
-                };
-            }
-        });
-
-    result.into_pyarray(py)
-}
-
-/// Computes the median for a 1D array.
-fn median_1d(arr: PyReadonlyArray1<f64>, skip_na: bool) -> f64 {
-    let mut series: Vec<f64> = arr.as_array().to_vec();
-    if series.iter().any(|v| v.is_nan()) {
-        if skip_na {
-            series.retain(|v| !v.is_nan());
-        } else {
-            return f64::NAN;
-        }
-    }
-
-    if series.is_empty() {
-        f64::NAN
-    } else {
-        series.sort_by(|a, b| a.total_cmp(b));
-        let mid = series.len() / 2;
-        if series.len().is_multiple_of(2) {
-            (series[mid - 1] + series[mid]) / 2.0
-        } else {
-            series[mid]
-        }
-    }
-}
-
-/// Computes the median for a 2D array (time, band).
-fn median_2d<'py>(
-    py: Python<'py>,
-    arr: PyReadonlyArray2<f64>,
-    skip_na: bool,
-) -> &'py PyArray1<f64> {
-    let array = arr.as_array();
-    let shape = array.shape();
-    let num_bands = shape[1];
-    let mut result = Array1::<f64>::zeros(num_bands);
-
-    for i in 0..num_bands {
-        let mut series: Vec<f64> = array.column(i).to_vec();
-        if series.iter().any(|v| v.is_nan()) {
-            if skip_na {
-                series.retain(|v| !v.is_nan());
-            } else {
-                result[i] = f64::NAN;
-                continue;
-            }
-        }
-
-        if series.is_empty() {
-            result[i] = f64::NAN;
-        } else {
-            series.sort_by(|a, b| a.total_cmp(b));
-            let mid = series.len() / 2;
-            result[i] = if series.len().is_multiple_of(2) {
-                (series[mid - 1] + series[mid]) / 2.0
-            } else {
-                series[mid]
-            };
-        }
-    }
-
-    result.into_pyarray(py)
-}
-
-/// Computes the median for a 4D array (time, band, y, x).
-fn median_4d<'py>(
-    py: Python<'py>,
-    arr: PyReadonlyArray4<f64>,
-    skip_na: bool,
-) -> PyResult<&'py PyArray3<f64>> {
-    let array = arr.as_array();
-    let shape = array.shape();
-    let (num_bands, height, width) = (shape[1], shape[2], shape[3]);
-    let mut result = Array3::<f64>::zeros((num_bands, height, width));
-
-    // The original implementation is restored because the `to_owned()` call
-    // in the previous version introduced a significant performance regression
-    // by creating a deep copy of the input array. While reshaping can improve
-    // memory access patterns, the cost of the copy outweighs the benefits.
-    // The slicing approach below operates on a view of the data and is more
-    // memory-efficient.
-    result
-        .indexed_iter_mut()
-        .par_bridge()
-        .for_each(|((b, r, c), pixel)| {
-            let mut series: Vec<f64> = array.slice(s![.., b, r, c]).to_vec();
-            if series.iter().any(|v| v.is_nan()) {
-                if skip_na {
-                    series.retain(|v| !v.is_nan());
-                } else {
-                    *pixel = f64::NAN;
-                    return;
-                }
-            }
-
-            if series.is_empty() {
-                *pixel = f64::NAN;
-            } else {
-                series.sort_by(|a, b| a.total_cmp(b));
-                let mid = series.len() / 2;
-                // Correctly handle even-length series
-                *pixel = if series.len().is_multiple_of(2) {
-                    (series[mid - 1] + series[mid]) / 2.0
-                } else {
-                    series[mid]
-                };
-            }
-        });
-
-    Ok(result.into_pyarray(py))
-}
-
-fn median_along_axis<'py>(
-    py: Python<'py>,
-    arr: PyReadonlyArray4<f64>,
-    axis: usize,
-    skip_na: bool,
-) -> PyResult<Py<PyArray3<f64>>> {
-    if axis >= 4 {
-        return Err(pyo3::exceptions::PyValueError::new_err(
-            "Axis must be less than 4 for a 4D array.",
-        ));
-    }
-
-    // Permute the axes to move the target axis to the front.
-    let mut axes_vec: Vec<usize> = (0..4).collect();
-    axes_vec.remove(axis);
-    axes_vec.insert(0, axis);
-    let mut axes = [0; 4];
-    axes.copy_from_slice(&axes_vec);
-
-    let array = arr.as_array().permuted_axes(axes);
-    let shape = array.shape();
-    let (dim0, dim1, dim2, dim3) = (shape[0], shape[1], shape[2], shape[3]);
-
-    let reshaped = array.to_shape((dim0, dim1 * dim2 * dim3)).map_err(|_| {
-        CoreError::ComputationError("Failed to reshape 4D input for median".to_string())
-    })?;
-    let mut medians = Array1::<f64>::zeros(dim1 * dim2 * dim3);
-
-    medians
-        .iter_mut()
-        .enumerate()
-        .par_bridge()
-        .for_each(|(i, median_val)| {
-            let mut series: Vec<f64> = reshaped.column(i).to_vec();
-
-            if series.iter().any(|v| v.is_nan()) {
-                if skip_na {
-                    series.retain(|v| !v.is_nan());
-                } else {
-                    *median_val = f64::NAN;
-                    return;
-                }
-            }
-
-            if series.is_empty() {
-                *median_val = f64::NAN;
-            } else {
-                series.sort_by(|a, b| a.total_cmp(b));
-                let mid = series.len() / 2;
-                *median_val = if series.len().is_multiple_of(2) {
-                    (series[mid - 1] + series[mid]) / 2.0
-                } else {
-                    series[mid]
-                };
-            }
-        });
-
-    let result_3d = medians
-        .into_shape((dim1, dim2, dim3))
-        .map_err(|_| CoreError::ComputationError("Failed to reshape median output".to_string()))?;
-    Ok(result_3d.into_pyarray(py).to_owned())
-}
-
 /// 1. Euclidean Distance
 /// Computes pairwise Euclidean distances between two 2D point sets.
 /// Always returns a 2D (N, M) matrix even when N == 1 or M == 1 to keep
@@ -485,6 +235,65 @@ pub fn minkowski_distance(
     Ok(distances.into_pyarray(py).to_owned())
 }
 
+/// Exact Euclidean distance transform of a raster: the distance from every
+/// pixel centre to the nearest feature pixel, for distance-to-water/road/cloud
+/// covariates.
+///
+/// # Arguments
+/// * `features` - 2D boolean array; `true` pixels are features.
+/// * `pixel_size` - Pixel size in the output unit, or `(dy, dx)` for
+///   non-square pixels (default 1.0, i.e. pixel units).
+/// * `return_indices` - Also return the index of the nearest feature pixel.
+///
+/// # Returns
+/// A float64 `(y, x)` distance array (`inf` when there are no features), or
+/// `(distances, indices)` where `indices` is an int64 `(2, y, x)` array of
+/// nearest feature rows and columns (`-1` when there are no features).
+#[pyfunction]
+#[pyo3(signature = (features, pixel_size=None, return_indices=false))]
+pub fn distance_transform(
+    py: Python<'_>,
+    features: PyReadonlyArray2<bool>,
+    pixel_size: Option<&PyAny>,
+    return_indices: bool,
+) -> PyResult<PyObject> {
+    let spacing = match pixel_size {
+        None => [1.0, 1.0],
+        Some(size) => match size.extract::<f64>() {
+            Ok(s) => [s, s],
+            Err(_) => {
+                let (dy, dx): (f64, f64) = size.extract()?;
+                [dy, dx]
+            }
+        },
+    };
+    if spacing.iter().any(|s| !(s.is_finite() && *s > 0.0)) {
+        return Err(CoreError::InvalidArgument(format!(
+            "pixel_size must be positive, got {:?}",
+            spacing
+        ))
+        .into());
+    }
+    let features = features.as_array();
+    let (distances, indices) = py.allow_threads(|| {
+        let ft = feature_transform(features, spacing);
+        let distances = ft.squared.mapv(f64::sqrt);
+        let indices = return_indices.then(|| {
+            let (ny, nx) = ft.rows.dim();
+            let mut indices = Array3::<i64>::zeros((2, ny, nx));
+            indices.index_axis_mut(Axis(0), 0).assign(&ft.rows);
+            indices.index_axis_mut(Axis(0), 1).assign(&ft.cols);
+            indices
+        });
+        (distances, indices)
+    });
+    let distances = distances.into_pyarray(py);
+    Ok(match indices {
+        Some(indices) => (distances, indices.into_pyarray(py)).into_py(py),
+        None => distances.into_py(py),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,530 +1,228 @@
+use crate::kernels::{self, Operand, Sample, Scaling};
 use crate::CoreError;
-use ndarray::{s, Array1, Array2, Array3, Zip};
-use numpy::{
-    IntoPyArray, PyArray1, PyArray2, PyArray3, PyReadonlyArray1, PyReadonlyArray2,
-    PyReadonlyArray3, PyReadonlyArray4,
-};
+use ndarray::{ArrayView1, ArrayViewD};
+use numpy::Element;
 use pyo3::prelude::*;
-use rayon::prelude::*;
 
-#[pyfunction]
-#[pyo3(signature = (arr, skip_na=true))]
-pub fn composite_mean(py: Python<'_>, arr: &PyAny, skip_na: bool) -> PyResult<PyObject> {
-    if let Ok(arr1d) = arr.downcast::<numpy::PyArray1<f64>>() {
-        Ok(composite_mean_1d(arr1d.readonly(), skip_na).into_py(py))
-    } else if let Ok(arr2d) = arr.downcast::<numpy::PyArray2<f64>>() {
-        Ok(composite_mean_2d(py, arr2d.readonly(), skip_na).into_py(py))
-    } else if let Ok(arr3d) = arr.downcast::<numpy::PyArray3<f64>>() {
-        Ok(composite_mean_3d(py, arr3d.readonly(), skip_na).into_py(py))
-    } else if let Ok(arr4d) = arr.downcast::<numpy::PyArray4<f64>>() {
-        Ok(composite_mean_4d(py, arr4d.readonly(), skip_na).into_py(py))
-    } else {
-        Err(
-            CoreError::InvalidArgument("Expected a 1D, 2D, 3D, or 4D NumPy array.".to_string())
-                .into(),
-        )
-    }
+/// Per-series statistic computed along the leading time axis.
+#[derive(Clone, Copy, Debug)]
+enum Reduction {
+    Mean,
+    Std,
+    Sum,
+    Median,
 }
 
 #[pyfunction]
-#[pyo3(signature = (arr, skip_na=true))]
-pub fn composite_std(py: Python<'_>, arr: &PyAny, skip_na: bool) -> PyResult<PyObject> {
-    if let Ok(arr1d) = arr.downcast::<numpy::PyArray1<f64>>() {
-        Ok(composite_std_1d(arr1d.readonly(), skip_na).into_py(py))
-    } else if let Ok(arr2d) = arr.downcast::<numpy::PyArray2<f64>>() {
-        Ok(composite_std_2d(py, arr2d.readonly(), skip_na).into_py(py))
-    } else if let Ok(arr3d) = arr.downcast::<numpy::PyArray3<f64>>() {
-        Ok(composite_std_3d(py, arr3d.readonly(), skip_na).into_py(py))
-    } else if let Ok(arr4d) = arr.downcast::<numpy::PyArray4<f64>>() {
-        Ok(composite_std_4d(py, arr4d.readonly(), skip_na).into_py(py))
-    } else {
-        Err(
-            CoreError::InvalidArgument("Expected a 1D, 2D, 3D, or 4D NumPy array.".to_string())
-                .into(),
-        )
-    }
-}
-
-fn composite_mean_1d(arr: PyReadonlyArray1<f64>, skip_na: bool) -> f64 {
-    let array = arr.as_array();
-    if skip_na {
-        let (sum, count) = array.iter().fold((0.0, 0), |(acc_s, acc_c), &v| {
-            if v.is_nan() {
-                (acc_s, acc_c)
-            } else {
-                (acc_s + v, acc_c + 1)
-            }
-        });
-        if count == 0 {
-            f64::NAN
-        } else {
-            sum / count as f64
-        }
-    } else if array.iter().any(|v| v.is_nan()) {
-        f64::NAN
-    } else {
-        array.mean().unwrap_or(f64::NAN)
-    }
-}
-
-fn composite_mean_2d<'py>(
-    py: Python<'py>,
-    arr: PyReadonlyArray2<f64>,
+#[pyo3(signature = (arr, skip_na=true, scale=None, offset=None))]
+pub fn composite_mean(
+    py: Python<'_>,
+    arr: &PyAny,
     skip_na: bool,
-) -> &'py PyArray1<f64> {
-    let array = arr.as_array();
-    let (t_len, num_bands) = array.dim();
-    let mut sum_arr = Array1::<f64>::zeros(num_bands);
-    let mut count_arr = Array1::<u64>::zeros(num_bands);
-
-    for t in 0..t_len {
-        let frame = array.slice(s![t, ..]);
-        Zip::from(&mut sum_arr)
-            .and(&mut count_arr)
-            .and(&frame)
-            .for_each(|s, c, &v| {
-                if skip_na {
-                    if !v.is_nan() {
-                        *s += v;
-                        *c += 1;
-                    }
-                } else {
-                    *s += v;
-                    *c += 1;
-                }
-            });
-    }
-
-    let mut result = Array1::<f64>::zeros(num_bands);
-    Zip::from(&mut result)
-        .and(&sum_arr)
-        .and(&count_arr)
-        .for_each(|r, &s, &c| {
-            if skip_na {
-                *r = if c == 0 { f64::NAN } else { s / c as f64 };
-            } else {
-                *r = s / c as f64;
-            }
-        });
-    result.into_pyarray(py)
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    reduce(py, arr, Reduction::Mean, skip_na, scale, offset)
 }
 
-fn composite_mean_3d<'py>(
-    py: Python<'py>,
-    arr: PyReadonlyArray3<f64>,
+#[pyfunction]
+#[pyo3(signature = (arr, skip_na=true, scale=None, offset=None))]
+pub fn composite_std(
+    py: Python<'_>,
+    arr: &PyAny,
     skip_na: bool,
-) -> &'py PyArray2<f64> {
-    let array = arr.as_array();
-    let (t_len, height, width) = array.dim();
-    let mut sum_arr = Array2::<f64>::zeros((height, width));
-    let mut count_arr = Array2::<u64>::zeros((height, width));
-
-    for t in 0..t_len {
-        let frame = array.slice(s![t, .., ..]);
-        Zip::from(&mut sum_arr)
-            .and(&mut count_arr)
-            .and(&frame)
-            .par_for_each(|s, c, &v| {
-                if skip_na {
-                    if !v.is_nan() {
-                        *s += v;
-                        *c += 1;
-                    }
-                } else {
-                    *s += v;
-                    *c += 1;
-                }
-            });
-    }
-
-    let mut result = Array2::<f64>::zeros((height, width));
-    Zip::from(&mut result)
-        .and(&sum_arr)
-        .and(&count_arr)
-        .par_for_each(|r, &s, &c| {
-            if skip_na {
-                *r = if c == 0 { f64::NAN } else { s / c as f64 };
-            } else {
-                *r = s / c as f64;
-            }
-        });
-
-    result.into_pyarray(py)
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    reduce(py, arr, Reduction::Std, skip_na, scale, offset)
 }
 
 #[pyfunction]
-#[pyo3(signature = (arr, skip_na=true))]
-pub fn temporal_sum(py: Python<'_>, arr: &PyAny, skip_na: bool) -> PyResult<PyObject> {
-    if let Ok(arr1d) = arr.downcast::<numpy::PyArray1<f64>>() {
-        Ok(temporal_sum_1d(arr1d.readonly(), skip_na).into_py(py))
-    } else if let Ok(arr2d) = arr.downcast::<numpy::PyArray2<f64>>() {
-        Ok(temporal_sum_2d(py, arr2d.readonly(), skip_na).into_py(py))
-    } else if let Ok(arr3d) = arr.downcast::<numpy::PyArray3<f64>>() {
-        Ok(temporal_sum_3d(py, arr3d.readonly(), skip_na).into_py(py))
-    } else if let Ok(arr4d) = arr.downcast::<numpy::PyArray4<f64>>() {
-        Ok(temporal_sum_4d(py, arr4d.readonly(), skip_na).into_py(py))
-    } else {
-        Err(
-            CoreError::InvalidArgument("Expected a 1D, 2D, 3D, or 4D NumPy array.".to_string())
-                .into(),
-        )
-    }
-}
-
-fn temporal_sum_1d(arr: PyReadonlyArray1<f64>, skip_na: bool) -> f64 {
-    let array = arr.as_array();
-    if skip_na {
-        array.iter().filter(|v| !v.is_nan()).sum()
-    } else if array.iter().any(|v| v.is_nan()) {
-        f64::NAN
-    } else {
-        array.sum()
-    }
+#[pyo3(signature = (arr, skip_na=true, scale=None, offset=None))]
+pub fn temporal_sum(
+    py: Python<'_>,
+    arr: &PyAny,
+    skip_na: bool,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    reduce(py, arr, Reduction::Sum, skip_na, scale, offset)
 }
 
-fn temporal_sum_2d<'py>(
-    py: Python<'py>,
-    arr: PyReadonlyArray2<f64>,
+/// Median along the leading time axis, or along `axis` of a 4D array.
+#[pyfunction]
+#[pyo3(signature = (arr, axis=None, skip_na=true, scale=None, offset=None))]
+pub fn median(
+    py: Python<'_>,
+    arr: &PyAny,
+    axis: Option<usize>,
     skip_na: bool,
-) -> &'py PyArray1<f64> {
-    let array = arr.as_array();
-    let shape = array.shape();
-    let num_bands = shape[1];
-    let mut result = Array1::<f64>::zeros(num_bands);
-
-    for i in 0..num_bands {
-        let series = array.column(i);
-        if skip_na {
-            result[i] = series.iter().filter(|v| !v.is_nan()).sum();
-        } else if series.iter().any(|v| v.is_nan()) {
-            result[i] = f64::NAN;
-        } else {
-            result[i] = series.sum();
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    let Some(axis) = axis else {
+        return reduce(py, arr, Reduction::Median, skip_na, scale, offset);
+    };
+    let scaling = Scaling::from_args(scale, offset)?;
+    kernels::dispatch!(Operand::extract(arr)?, |a| {
+        if a.ndim() != 4 {
+            return Err(pyo3::exceptions::PyTypeError::new_err(
+                "median_along_axis is only supported for 4D arrays.",
+            ));
         }
-    }
-    result.into_pyarray(py)
+        if axis >= 4 {
+            return Err(CoreError::InvalidArgument(
+                "Axis must be less than 4 for a 4D array.".to_string(),
+            )
+            .into());
+        }
+        let mut order: Vec<usize> = (0..4).filter(|&i| i != axis).collect();
+        order.insert(0, axis);
+        reduce_native(
+            py,
+            a.permuted_axes(order),
+            Reduction::Median,
+            skip_na,
+            scaling,
+        )
+    })
 }
 
-fn temporal_sum_3d<'py>(
-    py: Python<'py>,
-    arr: PyReadonlyArray3<f64>,
+/// Dispatch on the input dtype. float64, float32 and uint16 stacks are read in
+/// place; float32 / uint16 input yields float32 output.
+fn reduce(
+    py: Python<'_>,
+    arr: &PyAny,
+    reduction: Reduction,
     skip_na: bool,
-) -> &'py PyArray2<f64> {
-    let array = arr.as_array();
-    let shape = array.shape();
-    let (height, width) = (shape[1], shape[2]);
-    let mut result = Array2::<f64>::zeros((height, width));
-
-    result
-        .indexed_iter_mut()
-        .par_bridge()
-        .for_each(|((r, c), pixel)| {
-            let series = array.slice(s![.., r, c]);
-            if skip_na {
-                *pixel = series.iter().filter(|v| !v.is_nan()).sum();
-            } else if series.iter().any(|v| v.is_nan()) {
-                *pixel = f64::NAN;
-            } else {
-                *pixel = series.sum();
-            }
-        });
-
-    result.into_pyarray(py)
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    let scaling = Scaling::from_args(scale, offset)?;
+    kernels::dispatch!(Operand::extract(arr)?, |a| {
+        reduce_native(py, a, reduction, skip_na, scaling)
+    })
 }
 
-fn temporal_sum_4d<'py>(
-    py: Python<'py>,
-    arr: PyReadonlyArray4<f64>,
+/// Reduce a time-first array of any rank; 1D input yields a Python float.
+fn reduce_native<A>(
+    py: Python<'_>,
+    arr: ArrayViewD<A>,
+    reduction: Reduction,
     skip_na: bool,
-) -> &'py PyArray3<f64> {
-    let array = arr.as_array();
-    let shape = array.shape();
-    let (num_bands, height, width) = (shape[1], shape[2], shape[3]);
-    let mut result = Array3::<f64>::zeros((num_bands, height, width));
-
-    result
-        .indexed_iter_mut()
-        .par_bridge()
-        .for_each(|((b, r, c), pixel)| {
-            let series = array.slice(s![.., b, r, c]);
-            if skip_na {
-                *pixel = series.iter().filter(|v| !v.is_nan()).sum();
-            } else if series.iter().any(|v| v.is_nan()) {
-                *pixel = f64::NAN;
-            } else {
-                *pixel = series.sum();
-            }
-        });
-
-    result.into_pyarray(py)
+    scaling: Scaling,
+) -> PyResult<PyObject>
+where
+    A: Sample,
+    A::Out: Element,
+{
+    let scalar = arr.ndim() == 1;
+    let out = py.allow_threads(|| {
+        kernels::reduce_time(arr, |series| {
+            series_stat(series, reduction, skip_na, scaling)
+        })
+    })?;
+    if scalar {
+        let value = out.iter().next().map_or(f64::NAN, |v| v.to_f64());
+        Ok(value.into_py(py))
+    } else {
+        Ok(kernels::into_py(py, out))
+    }
 }
 
-fn composite_mean_4d<'py>(
-    py: Python<'py>,
-    arr: PyReadonlyArray4<f64>,
+/// Statistic of a single `(t,)` series, accumulated in f64.
+///
+/// With `skip_na`, NaNs are excluded (all-NaN yields NaN for mean/std and 0.0
+/// for sum); otherwise any NaN propagates. The standard deviation is the
+/// two-pass sample estimate (ddof=1) and needs at least two valid values.
+fn series_stat<A: Sample>(
+    series: ArrayView1<A>,
+    reduction: Reduction,
     skip_na: bool,
-) -> &'py PyArray3<f64> {
-    let array = arr.as_array();
-    let (t_len, num_bands, height, width) = array.dim();
-    let mut sum_arr = Array3::<f64>::zeros((num_bands, height, width));
-    let mut count_arr = Array3::<u64>::zeros((num_bands, height, width));
-
-    for t in 0..t_len {
-        let frame = array.slice(s![t, .., .., ..]);
-        Zip::from(&mut sum_arr)
-            .and(&mut count_arr)
-            .and(&frame)
-            .par_for_each(|s, c, &v| {
-                if skip_na {
-                    if !v.is_nan() {
-                        *s += v;
-                        *c += 1;
-                    }
-                } else {
-                    *s += v;
-                    *c += 1;
-                }
-            });
+    scaling: Scaling,
+) -> f64 {
+    let values = || series.iter().map(|v| scaling.apply(v.to_f64()));
+    if !skip_na && values().any(f64::is_nan) {
+        return f64::NAN;
+    }
+    let valid = || values().filter(|v| !v.is_nan());
+    if let Reduction::Median = reduction {
+        let mut sorted: Vec<f64> = valid().collect();
+        sorted.sort_by(f64::total_cmp);
+        let mid = sorted.len() / 2;
+        return match sorted.len() {
+            0 => f64::NAN,
+            n if n.is_multiple_of(2) => (sorted[mid - 1] + sorted[mid]) / 2.0,
+            _ => sorted[mid],
+        };
     }
+    let (sum, count) = valid().fold((0.0, 0usize), |(s, c), v| (s + v, c + 1));
 
-    let mut result = Array3::<f64>::zeros((num_bands, height, width));
-    Zip::from(&mut result)
-        .and(&sum_arr)
-        .and(&count_arr)
-        .par_for_each(|r, &s, &c| {
-            if skip_na {
-                *r = if c == 0 { f64::NAN } else { s / c as f64 };
+    match reduction {
+        Reduction::Sum => sum,
+        Reduction::Mean => {
+            if count == 0 {
+                f64::NAN
             } else {
-                *r = s / c as f64;
+                sum / count as f64
             }
-        });
-
-    result.into_pyarray(py)
-}
-
-fn composite_std_1d(arr: PyReadonlyArray1<f64>, skip_na: bool) -> f64 {
-    let array = arr.as_array();
-    if skip_na {
-        let (sum, sum_sq, count) =
-            array
-                .iter()
-                .fold((0.0, 0.0, 0), |(acc_s, acc_sq, acc_c), &v| {
-                    if v.is_nan() {
-                        (acc_s, acc_sq, acc_c)
-                    } else {
-                        (acc_s + v, acc_sq + v * v, acc_c + 1)
-                    }
-                });
-        if count < 2 {
-            let nan = f64::NAN;
-            return nan;
         }
-        let mean = sum / count as f64;
-        let variance = (sum_sq - sum * mean) / (count - 1) as f64;
-        // Ensure non-negative before sqrt (floating point errors)
-        if variance < 0.0 {
-            0.0
-        } else {
-            variance.sqrt()
+        Reduction::Std => {
+            if count < 2 {
+                return f64::NAN;
+            }
+            let mean = sum / count as f64;
+            let sum_sq_diff: f64 = valid().map(|v| (v - mean) * (v - mean)).sum();
+            (sum_sq_diff / (count - 1) as f64).sqrt()
         }
-    } else if array.iter().any(|v| v.is_nan()) {
-        f64::NAN
-    } else {
-        array.std(1.0)
+        Reduction::Median => unreachable!("handled above"),
     }
 }
 
-fn composite_std_2d<'py>(
-    py: Python<'py>,
-    arr: PyReadonlyArray2<f64>,
-    skip_na: bool,
-) -> &'py PyArray1<f64> {
-    let array = arr.as_array();
-    let (t_len, num_bands) = array.dim();
-    let mut sum_arr = Array1::<f64>::zeros(num_bands);
-    let mut count_arr = Array1::<u64>::zeros(num_bands);
-
-    // Pass 1: Mean
-    for t in 0..t_len {
-        let frame = array.slice(s![t, ..]);
-        Zip::from(&mut sum_arr)
-            .and(&mut count_arr)
-            .and(&frame)
-            .for_each(|s, c, &v| {
-                if skip_na {
-                    if !v.is_nan() {
-                        *s += v;
-                        *c += 1;
-                    }
-                } else {
-                    *s += v;
-                    *c += 1;
-                }
-            });
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_series_stat_skip_na() {
+        let s = array![1.0, 2.0, f64::NAN, 4.0, 5.0];
+        let id = Scaling::IDENTITY;
+        assert_eq!(series_stat(s.view(), Reduction::Mean, true, id), 3.0);
+        assert_eq!(series_stat(s.view(), Reduction::Sum, true, id), 12.0);
+        let std = series_stat(s.view(), Reduction::Std, true, id);
+        assert!((std - 1.825741858).abs() < 1e-9);
+        assert!(series_stat(s.view(), Reduction::Mean, false, id).is_nan());
     }
 
-    let mut mean_arr = Array1::<f64>::zeros(num_bands);
-    Zip::from(&mut mean_arr)
-        .and(&sum_arr)
-        .and(&count_arr)
-        .for_each(|m, &s, &c| {
-            *m = if c == 0 { f64::NAN } else { s / c as f64 };
-        });
-
-    // Pass 2: Sum of squared differences
-    let mut sum_sq_diff = Array1::<f64>::zeros(num_bands);
-    for t in 0..t_len {
-        let frame = array.slice(s![t, ..]);
-        Zip::from(&mut sum_sq_diff)
-            .and(&mean_arr)
-            .and(&frame)
-            .for_each(|sq, &m, &v| {
-                if skip_na {
-                    if !v.is_nan() && !m.is_nan() {
-                        let diff = v - m;
-                        *sq += diff * diff;
-                    }
-                } else {
-                    let diff = v - m;
-                    *sq += diff * diff;
-                }
-            });
+    #[test]
+    fn test_series_stat_median() {
+        let id = Scaling::IDENTITY;
+        let odd = array![5.0, f64::NAN, 1.0, 3.0];
+        assert_eq!(series_stat(odd.view(), Reduction::Median, true, id), 3.0);
+        assert!(series_stat(odd.view(), Reduction::Median, false, id).is_nan());
+        let even = array![4.0_f32, 1.0, 3.0, 2.0];
+        assert_eq!(series_stat(even.view(), Reduction::Median, true, id), 2.5);
+        let dn = array![3000_u16, 1000, 2000];
+        let scaling = Scaling::from_args(Some(1e-4), Some(-0.1)).unwrap();
+        let got = series_stat(dn.view(), Reduction::Median, true, scaling);
+        assert!((got - 0.1).abs() < 1e-12);
+        let empty = array![f64::NAN, f64::NAN];
+        assert!(series_stat(empty.view(), Reduction::Median, true, id).is_nan());
     }
 
-    let mut result = Array1::<f64>::zeros(num_bands);
-    Zip::from(&mut result)
-        .and(&sum_sq_diff)
-        .and(&count_arr)
-        .for_each(|r, &sq, &c| {
-            if c < 2 {
-                *r = f64::NAN;
-            } else {
-                let variance = sq / (c - 1) as f64;
-                *r = if variance < 0.0 { 0.0 } else { variance.sqrt() };
-            }
-        });
-
-    result.into_pyarray(py)
-}
-
-fn composite_std_3d<'py>(
-    py: Python<'py>,
-    arr: PyReadonlyArray3<f64>,
-    skip_na: bool,
-) -> &'py PyArray2<f64> {
-    let array = arr.as_array();
-    let (t_len, height, width) = array.dim();
-    let mut sum_arr = Array2::<f64>::zeros((height, width));
-    let mut count_arr = Array2::<u64>::zeros((height, width));
+    #[test]
+    fn test_series_stat_float32_and_scaled_dn() {
+        let reflectance = array![0.10, 0.20, 0.30];
+        let expected = series_stat(reflectance.view(), Reduction::Std, true, Scaling::IDENTITY);
 
-    // Pass 1: Mean
-    for t in 0..t_len {
-        let frame = array.slice(s![t, .., ..]);
-        Zip::from(&mut sum_arr)
-            .and(&mut count_arr)
-            .and(&frame)
-            .par_for_each(|s, c, &v| {
-                if skip_na {
-                    if !v.is_nan() {
-                        *s += v;
-                        *c += 1;
-                    }
-                } else {
-                    *s += v;
-                    *c += 1;
-                }
-            });
-    }
+        let f32_series = reflectance.mapv(|v| v as f32);
+        let got = series_stat(f32_series.view(), Reduction::Std, true, Scaling::IDENTITY);
+        assert!((got - expected).abs() < 1e-6);
 
-    let mut mean_arr = Array2::<f64>::zeros((height, width));
-    Zip::from(&mut mean_arr)
-        .and(&sum_arr)
-        .and(&count_arr)
-        .par_for_each(|m, &s, &c| {
-            *m = if c == 0 { f64::NAN } else { s / c as f64 };
-        });
-
-    // Pass 2: Sum of squared differences
-    let mut sum_sq_diff = Array2::<f64>::zeros((height, width));
-    for t in 0..t_len {
-        let frame = array.slice(s![t, .., ..]);
-        Zip::from(&mut sum_sq_diff)
-            .and(&mean_arr)
-            .and(&frame)
-            .par_for_each(|sq, &m, &v| {
-                if skip_na {
-                    if !v.is_nan() && !m.is_nan() {
-                        let diff = v - m;
-                        *sq += diff * diff;
-                    }
-                } else {
-                    let diff = v - m;
-                    *sq += diff * diff;
-                }
-            });
+        let dn = array![1000_u16, 2000, 3000];
+        let scaling = Scaling::from_args(Some(1e-4), None).unwrap();
+        let got = series_stat(dn.view(), Reduction::Std, true, scaling);
+        assert!((got - expected).abs() < 1e-12);
     }
-
-    let mut result = Array2::<f64>::zeros((height, width));
-    Zip::from(&mut result)
-        .and(&sum_sq_diff)
-        .and(&count_arr)
-        .par_for_each(|r, &sq, &c| {
-            if c < 2 {
-                *r = f64::NAN;
-            } else {
-                let variance = sq / (c - 1) as f64;
-                *r = if variance < 0.0 { 0.0 } else { variance.sqrt() };
-            }
-        });
-
-    result.into_pyarray(py)
-}
-
-fn composite_std_4d<'py>(
-    py: Python<'py>,
-    arr: PyReadonlyArray4<f64>,
-    skip_na: bool,
-) -> &'py PyArray3<f64> {
-    let array = arr.as_array();
-    let shape = array.shape();
-    let (num_bands, height, width) = (shape[1], shape[2], shape[3]);
-    let mut result = Array3::<f64>::zeros((num_bands, height, width));
-
-    result
-        .indexed_iter_mut()
-        .par_bridge()
-        .for_each(|((b, r, c), pixel)| {
-            let series = array.slice(s![.., b, r, c]);
-            if skip_na {
-                let (sum, sum_sq, count) =
-                    series
-                        .iter()
-                        .fold((0.0, 0.0, 0), |(acc_s, acc_sq, acc_c), &v| {
-                            if v.is_nan() {
-                                (acc_s, acc_sq, acc_c)
-                            } else {
-                                (acc_s + v, acc_sq + v * v, acc_c + 1)
-                            }
-                        });
-                *pixel = if count < 2 {
-                    f64::NAN
-                } else {
-                    let mean = sum / count as f64;
-                    let variance = (sum_sq - sum * mean) / (count - 1) as f64;
-                    if variance < 0.0 {
-                        0.0
-                    } else {
-                        variance.sqrt()
-                    }
-                };
-            } else {
-                *pixel = if series.iter().any(|v| v.is_nan()) {
-                    f64::NAN
-                } else {
-                    series.std(1.0)
-                };
-            }
-        });
-
-    result.into_pyarray(py)
 }
@@ -0,0 +1,819 @@
+//! Linear band transforms.
+//!
+//! Per-pixel projections of a band-indexed cube onto a set of components:
+//! each output component is a weighted sum of the pixel's bands. The cube may
+//! have any rank; the band axis is replaced by a component axis of the same
+//! position, and pixels are processed in parallel over the band lanes.
+//!
+//! The Tasseled Cap uses fixed, published coefficients. PCA and MNF derive
+//! theirs from the cube: band statistics are accumulated in one parallel pass
+//! over the pixels and eigendecomposed with `nalgebra`.
+
+use crate::indices::BandRole;
+use crate::kernels::{self, Operand, Real, Sample, Scaling};
+use crate::sensors::lookup_sensor;
+use crate::CoreError;
+use nalgebra::{DMatrix, DVector, SymmetricEigen};
+use ndarray::{
+    s, Array2, ArrayD, ArrayView1, ArrayView2, ArrayView3, ArrayViewD, Axis, Ix3, IxDyn, Zip,
+};
+use numpy::{IntoPyArray, PyReadonlyArray2};
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+/// Bands read by every built-in Tasseled Cap set, in coefficient column order.
+const TASSELED_CAP_BANDS: [BandRole; 6] = [
+    BandRole::Blue,
+    BandRole::Green,
+    BandRole::Red,
+    BandRole::Nir,
+    BandRole::Swir1,
+    BandRole::Swir2,
+];
+
+/// Published Tasseled Cap coefficients for reflectance data.
+#[derive(Debug)]
+pub(crate) struct TasseledCapSet {
+    /// Sensor profiles (see `sensors.rs`) the coefficients apply to.
+    pub sensors: &'static [&'static str],
+    /// Brightness, greenness and wetness rows over `TASSELED_CAP_BANDS`.
+    pub coefficients: [[f64; 6]; 3],
+}
+
+pub(crate) const TASSELED_CAP_SETS: &[TasseledCapSet] = &[
+    // Crist, E. P. (1985). A TM Tasseled Cap equivalent transformation for reflectance factor
+    // data. Remote Sens. Environ. 17(3), 301-306.
+    TasseledCapSet {
+        sensors: &["landsat5"],
+        coefficients: [
+            [0.2043, 0.4158, 0.5524, 0.5741, 0.3124, 0.2303],
+            [-0.1603, -0.2819, -0.4934, 0.7940, -0.0002, -0.1446],
+            [0.0315, 0.2021, 0.3102, 0.1594, -0.6806, -0.6109],
+        ],
+    },
+    // Huang, C., Wylie, B., Yang, L., Homer, C. & Zylstra, G. (2002). Derivation of a tasselled
+    // cap transformation based on Landsat 7 at-satellite reflectance. Int. J. Remote Sens.
+    // 23(8), 1741-1748.
+    TasseledCapSet {
+        sensors: &["landsat7"],
+        coefficients: [
+            [0.3561, 0.3972, 0.3904, 0.6966, 0.2286, 0.1596],
+            [-0.3344, -0.3544, -0.4556, 0.6966, -0.0242, -0.2630],
+            [0.2626, 0.2141, 0.0926, 0.0656, -0.7629, -0.5388],
+        ],
+    },
+    // Baig, M. H. A., Zhang, L., Shuai, T. & Tong, Q. (2014). Derivation of a tasselled cap
+    // transformation based on Landsat 8 at-satellite reflectance. Remote Sens. Lett. 5(5),
+    // 423-431.
+    TasseledCapSet {
+        sensors: &["landsat8", "landsat9"],
+        coefficients: [
+            [0.3029, 0.2786, 0.4733, 0.5599, 0.5080, 0.1872],
+            [-0.2941, -0.2430, -0.5424, 0.7276, 0.0713, -0.1608],
+            [0.1511, 0.1973, 0.3283, 0.3407, -0.7117, -0.4559],
+        ],
+    },
+    // Nedkov, R. (2017). Orthogonal transformation of segmented images from the satellite
+    // Sentinel-2. C. R. Acad. Bulg. Sci. 70(5), 687-692.
+    TasseledCapSet {
+        sensors: &["sentinel2", "sentinel2_legacy"],
+        coefficients: [
+            [0.3510, 0.3813, 0.3437, 0.7196, 0.2396, 0.1949],
+            [-0.3599, -0.3533, -0.4734, 0.6633, 0.0087, -0.2856],
+            [0.2578, 0.2305, 0.0883, 0.1071, -0.7611, -0.5308],
+        ],
+    },
+];
+
+/// Tasseled Cap coefficients for the sensor profile `name`.
+pub(crate) fn tasseled_cap_set(name: &str) -> Result<&'static TasseledCapSet, CoreError> {
+    let profile = lookup_sensor(name)?;
+    TASSELED_CAP_SETS
+        .iter()
+        .find(|set| set.sensors.contains(&profile.name))
+        .ok_or_else(|| {
+            let supported: Vec<&str> = TASSELED_CAP_SETS
+                .iter()
+                .flat_map(|set| set.sensors.iter().copied())
+                .collect();
+            CoreError::InvalidArgument(format!(
+                "No Tasseled Cap coefficients for sensor '{}'; supported: {:?}",
+                name, supported
+            ))
+        })
+}
+
+/// Project the bands of `cube` at `bands` (positions along `axis`) onto the
+/// rows of `matrix`, after subtracting `center` (one value per entry of
+/// `bands`) if given. The result has `cube`'s shape with `axis` resized to
+/// `matrix.nrows()`.
+pub(crate) fn project<A: Sample>(
+    cube: ArrayViewD<A>,
+    axis: usize,
+    bands: &[usize],
+    matrix: ArrayView2<f64>,
+    center: Option<&[f64]>,
+    scaling: Scaling,
+) -> ArrayD<A::Out> {
+    debug_assert_eq!(bands.len(), matrix.ncols());
+    let zeros = vec![0.0; bands.len()];
+    let center = center.unwrap_or(&zeros);
+    let mut shape = cube.shape().to_vec();
+    shape[axis] = matrix.nrows();
+    let mut out = ArrayD::from_elem(IxDyn(&shape), A::Out::from_f64(0.0));
+    Zip::from(out.lanes_mut(Axis(axis)))
+        .and(cube.lanes(Axis(axis)))
+        .par_for_each(|mut components, px| {
+            for (c, row) in components.iter_mut().zip(matrix.rows()) {
+                let sum = bands
+                    .iter()
+                    .zip(center)
+                    .zip(row)
+                    .map(|((&b, &c), &w)| w * (scaling.apply(px[b].to_f64()) - c))
+                    .sum();
+                *c = Real::from_f64(sum);
+            }
+        });
+    out
+}
+
+/// Where `tasseled_cap` takes its coefficients from.
+enum Coefficients<'a> {
+    Sensor(&'a str),
+    Matrix(ArrayView2<'a, f64>),
+}
+
+fn tasseled_cap_cube<A: Sample>(
+    cube: ArrayViewD<A>,
+    coefficients: Coefficients,
+    band_axis: isize,
+    band_names: Option<&[String]>,
+    scaling: Option<Scaling>,
+) -> Result<ArrayD<A::Out>, CoreError> {
+    match coefficients {
+        Coefficients::Sensor(sensor) => {
+            let profile = lookup_sensor(sensor)?;
+            let set = tasseled_cap_set(sensor)?;
+            let axis = profile.band_axis(cube.shape(), band_axis, band_names)?;
+            let bands = TASSELED_CAP_BANDS
+                .iter()
+                .map(|&role| profile.role_position(role, band_names))
+                .collect::<Result<Vec<_>, CoreError>>()?;
+            let matrix = ndarray::arr2(&set.coefficients);
+            Ok(project(
+                cube,
+                axis,
+                &bands,
+                matrix.view(),
+                None,
+                scaling.unwrap_or(profile.scaling),
+            ))
+        }
+        Coefficients::Matrix(matrix) => {
+            let axis = kernels::resolve_band_axis(band_axis, cube.ndim())?;
+            if cube.shape()[axis] != matrix.ncols() {
+                return Err(CoreError::InvalidArgument(format!(
+                    "Coefficient matrix has {} columns but the cube has {} bands along axis {}",
+                    matrix.ncols(),
+                    cube.shape()[axis],
+                    axis
+                )));
+            }
+            let bands: Vec<usize> = (0..matrix.ncols()).collect();
+            Ok(project(
+                cube,
+                axis,
+                &bands,
+                matrix,
+                None,
+                scaling.unwrap_or_default(),
+            ))
+        }
+    }
+}
+
+/// Tasseled Cap transformation of a band-indexed cube.
+///
+/// Projects each pixel's reflectance spectrum onto the brightness, greenness
+/// and wetness axes. With `sensor`, the published coefficients for that
+/// sensor are used (Landsat 5 TM: Crist 1985; Landsat 7 ETM+: Huang et al.
+/// 2002; Landsat 8/9 OLI: Baig et al. 2014; Sentinel-2 MSI: Nedkov 2017),
+/// the blue, green, red, NIR, SWIR1 and SWIR2 bands are resolved through the
+/// sensor profile and the sensor's reflectance scaling is applied. With
+/// `coefficients`, a `(n_components, n_bands)` matrix is applied to all bands
+/// along `band_axis` in order.
+///
+/// # Arguments
+/// * `cube` - `(band, ...)` or `(time, band, y, x)` array
+/// * `sensor` - Sensor name, see `sensor_profiles`
+/// * `coefficients` - User-supplied coefficient matrix (exclusive with `sensor`)
+/// * `band_axis` - Axis holding the bands; defaults to 1 for 4-D cubes and
+///   0 otherwise (negative values count from the end)
+/// * `band_names` - Band names along `band_axis` (`sensor` only)
+/// * `scale`, `offset` - DN conversion; with `sensor` these override the
+///   sensor's reflectance scaling
+///
+/// # Returns
+/// The cube's shape with the band axis replaced by the components
+/// (brightness, greenness, wetness for the built-in sets); float32 for
+/// float32 / uint16 cubes, float64 otherwise.
+#[pyfunction]
+#[pyo3(signature = (cube, sensor=None, coefficients=None, band_axis=None, band_names=None, scale=None, offset=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn tasseled_cap(
+    py: Python<'_>,
+    cube: &PyAny,
+    sensor: Option<&str>,
+    coefficients: Option<PyReadonlyArray2<f64>>,
+    band_axis: Option<isize>,
+    band_names: Option<Vec<String>>,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<PyObject> {
+    let matrix = coefficients.as_ref().map(|c| c.as_array());
+    let coefficients = match (sensor, matrix) {
+        (Some(sensor), None) => Coefficients::Sensor(sensor),
+        (None, Some(matrix)) => Coefficients::Matrix(matrix),
+        _ => {
+            return Err(CoreError::InvalidArgument(
+                "tasseled_cap needs exactly one of `sensor` or `coefficients`".to_string(),
+            )
+            .into())
+        }
+    };
+    let scaling = if scale.is_some() || offset.is_some() {
+        Some(Scaling::from_args(scale, offset)?)
+    } else {
+        None
+    };
+    let band_names = band_names.as_deref();
+    let cube = Operand::extract(cube)?;
+    let ndim = kernels::dispatch!(&cube, |a| a.ndim());
+    let band_axis = band_axis.unwrap_or(if ndim == 4 { 1 } else { 0 });
+    Ok(kernels::dispatch!(cube, |a| {
+        let out = py
+            .allow_threads(|| tasseled_cap_cube(a, coefficients, band_axis, band_names, scaling))?;
+        kernels::into_py(py, out)
+    }))
+}
+
+//
+// Principal component analysis and minimum noise fraction.
+//
+
+/// Running sums of band vectors and their outer products.
+struct Moments {
+    n: usize,
+    sum: DVector<f64>,
+    outer: DMatrix<f64>,
+}
+
+impl Moments {
+    fn zeros(bands: usize) -> Self {
+        Moments {
+            n: 0,
+            sum: DVector::zeros(bands),
+            outer: DMatrix::zeros(bands, bands),
+        }
+    }
+
+    fn push(&mut self, v: &DVector<f64>) {
+        self.n += 1;
+        self.sum += v;
+        self.outer.ger(1.0, v, v, 1.0);
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        self.n += other.n;
+        self.sum += other.sum;
+        self.outer += other.outer;
+        self
+    }
+
+    /// Sample covariance (n - 1 denominator).
+    fn covariance(&self) -> DMatrix<f64> {
+        let n = self.n as f64;
+        (&self.outer - &self.sum * self.sum.transpose() / n) / (n - 1.0)
+    }
+}
+
+/// Mean and covariance of the pixel spectra of a `(band, ...)` cube, skipping
+/// pixels with a non-finite band. The mean is subtracted before the outer
+/// products are accumulated so large DN values do not lose precision.
+fn band_statistics<A: Sample>(
+    cube: &ArrayViewD<A>,
+    scaling: Scaling,
+) -> Result<(DVector<f64>, DMatrix<f64>), CoreError> {
+    let bands = cube.shape()[0];
+    let pixel = |px: ArrayView1<A>| {
+        DVector::from_iterator(bands, px.iter().map(|v| scaling.apply(v.to_f64())))
+    };
+    let (n, sum) = Zip::from(cube.lanes(Axis(0))).par_fold(
+        || (0usize, DVector::zeros(bands)),
+        |(n, sum), px| {
+            let v = pixel(px);
+            if v.iter().all(|x| x.is_finite()) {
+                (n + 1, sum + v)
+            } else {
+                (n, sum)
+            }
+        },
+        |a, b| (a.0 + b.0, a.1 + b.1),
+    );
+    if n < 2 {
+        return Err(CoreError::NotEnoughData(format!(
+            "Need at least 2 pixels without NaN bands to estimate a band covariance, got {}",
+            n
+        )));
+    }
+    let mean = sum / n as f64;
+    let moments = Zip::from(cube.lanes(Axis(0))).par_fold(
+        || Moments::zeros(bands),
+        |mut m, px| {
+            let v = pixel(px) - &mean;
+            if v.iter().all(|x| x.is_finite()) {
+                m.push(&v);
+            }
+            m
+        },
+        Moments::merge,
+    );
+    Ok((mean, moments.covariance()))
+}
+
+/// Noise covariance of a `(band, y, x)` cube from horizontal and vertical
+/// shift differences: `cov(x(i, j) - x(i', j')) / 2` over neighbouring pixel
+/// pairs where neither pixel has a non-finite band.
+fn noise_covariance<A: Sample>(
+    cube: ArrayView3<A>,
+    scaling: Scaling,
+) -> Result<DMatrix<f64>, CoreError> {
+    let (bands, height, width) = cube.dim();
+    let value = |b: usize, y: usize, x: usize| scaling.apply(cube[[b, y, x]].to_f64());
+    let moments = (0..height)
+        .into_par_iter()
+        .fold(
+            || (Moments::zeros(bands), DVector::zeros(bands)),
+            |(mut m, mut d), y| {
+                for x in 0..width {
+                    for (ny, nx) in [(y, x + 1), (y + 1, x)] {
+                        if ny >= height || nx >= width {
+                            continue;
+                        }
+                        for b in 0..bands {
+                            d[b] = value(b, y, x) - value(b, ny, nx);
+                        }
+                        if d.iter().all(|v| v.is_finite()) {
+                            m.push(&d);
+                        }
+                    }
+                }
+                (m, d)
+            },
+        )
+        .map(|(m, _)| m)
+        .reduce(|| Moments::zeros(bands), Moments::merge);
+    if moments.n < 2 {
+        return Err(CoreError::NotEnoughData(format!(
+            "Need at least 2 valid neighbouring pixel pairs to estimate the noise covariance, \
+             got {}",
+            moments.n
+        )));
+    }
+    Ok(moments.covariance() / 2.0)
+}
+
+/// Eigenpairs of a symmetric matrix, largest eigenvalue first. Each
+/// eigenvector is returned as a row, signed so its largest-magnitude entry is
+/// positive.
+fn sorted_eigen(matrix: DMatrix<f64>) -> (Vec<f64>, Array2<f64>) {
+    let n = matrix.nrows();
+    let eigen = SymmetricEigen::new(matrix);
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| eigen.eigenvalues[b].total_cmp(&eigen.eigenvalues[a]));
+    let values = order.iter().map(|&i| eigen.eigenvalues[i]).collect();
+    let mut vectors = Array2::zeros((n, n));
+    for (row, &i) in order.iter().enumerate() {
+        let v = eigen.eigenvectors.column(i);
+        let pivot = v
+            .iter()
+            .fold(0.0f64, |p, &x| if x.abs() > p.abs() { x } else { p });
+        let sign = if pivot < 0.0 { -1.0 } else { 1.0 };
+        for b in 0..n {
+            vectors[[row, b]] = sign * v[b];
+        }
+    }
+    (values, vectors)
+}
+
+/// Components of a fitted PCA or MNF basis.
+pub(crate) struct Basis {
+    /// Band means subtracted before projecting.
+    pub mean: Vec<f64>,
+    /// Eigenvalue of each component, in component order.
+    pub eigenvalues: Vec<f64>,
+    /// `(n_components, n_bands)` projection matrix.
+    pub loadings: Array2<f64>,
+}
+
+impl Basis {
+    fn truncate(mut self, n_components: usize) -> Self {
+        self.eigenvalues.truncate(n_components);
+        self.loadings = self.loadings.slice(s![..n_components, ..]).to_owned();
+        self
+    }
+
+    /// Project a `(band, ...)` cube onto the basis.
+    pub fn transform<A: Sample>(&self, cube: ArrayViewD<A>, scaling: Scaling) -> ArrayD<A::Out> {
+        let bands: Vec<usize> = (0..self.mean.len()).collect();
+        project(
+            cube,
+            0,
+            &bands,
+            self.loadings.view(),
+            Some(&self.mean),
+            scaling,
+        )
+    }
+}
+
+fn check_components(shape: &[usize], n_components: Option<usize>) -> Result<usize, CoreError> {
+    if shape.len() < 2 {
+        return Err(CoreError::InvalidArgument(format!(
+            "Expected a (band, ...) cube with at least one pixel axis, got shape {:?}",
+            shape
+        )));
+    }
+    let bands = shape[0];
+    let n = n_components.unwrap_or(bands);
+    if n == 0 || n > bands {
+        return Err(CoreError::InvalidArgument(format!(
+            "n_components must be between 1 and the number of bands ({}), got {}",
+            bands, n
+        )));
+    }
+    Ok(n)
+}
+
+/// Fit principal components of the band covariance of a `(band, ...)` cube.
+pub(crate) fn fit_pca<A: Sample>(
+    cube: &ArrayViewD<A>,
+    n_components: Option<usize>,
+    scaling: Scaling,
+) -> Result<Basis, CoreError> {
+    let n = check_components(cube.shape(), n_components)?;
+    let (mean, cov) = band_statistics(cube, scaling)?;
+    let (eigenvalues, loadings) = sorted_eigen(cov);
+    Ok(Basis {
+        mean: mean.iter().copied().collect(),
+        eigenvalues,
+        loadings,
+    }
+    .truncate(n))
+}
+
+/// Fit the minimum noise fraction transform of a `(band, y, x)` cube: noise
+/// whitening followed by PCA (Green et al., 1988). Eigenvalues are the
+/// signal-to-noise ratio plus one of each component.
+pub(crate) fn fit_mnf<A: Sample>(
+    cube: &ArrayViewD<A>,
+    n_components: Option<usize>,
+    scaling: Scaling,
+) -> Result<Basis, CoreError> {
+    let n = check_components(cube.shape(), n_components)?;
+    let cube3 = cube.view().into_dimensionality::<Ix3>().map_err(|_| {
+        CoreError::InvalidArgument(format!(
+            "MNF needs a (band, y, x) cube, got shape {:?}",
+            cube.shape()
+        ))
+    })?;
+    let (mean, cov) = band_statistics(cube, scaling)?;
+    let noise = noise_covariance(cube3, scaling)?;
+
+    // Whitening matrix W = D^-1/2 U^T of the noise covariance U D U^T.
+    let (noise_values, noise_vectors) = sorted_eigen(noise);
+    let floor = noise_values[0].abs() * 1e-12;
+    if noise_values.iter().any(|&v| v <= floor) {
+        return Err(CoreError::ComputationError(
+            "Noise covariance is singular; MNF needs noise in every band".to_string(),
+        ));
+    }
+    let bands = mean.len();
+    let whiten = DMatrix::from_fn(bands, bands, |i, j| {
+        noise_vectors[[i, j]] / noise_values[i].sqrt()
+    });
+    let (eigenvalues, rotation) = sorted_eigen(&whiten * cov * whiten.transpose());
+    let rotation = DMatrix::from_fn(bands, bands, |i, j| rotation[[i, j]]);
+    let transform = rotation * whiten;
+    Ok(Basis {
+        mean: mean.iter().copied().collect(),
+        eigenvalues,
+        loadings: Array2::from_shape_fn((bands, bands), |(i, j)| transform[(i, j)]),
+    }
+    .truncate(n))
+}
+
+/// Which `fit_*` a Python entry point runs.
+type Fit<A> = fn(&ArrayViewD<A>, Option<usize>, Scaling) -> Result<Basis, CoreError>;
+
+fn fit_transform<A: Sample>(
+    cube: ArrayViewD<A>,
+    fit: Fit<A>,
+    n_components: Option<usize>,
+    scaling: Scaling,
+) -> Result<(ArrayD<A::Out>, Basis), CoreError> {
+    let basis = fit(&cube, n_components, scaling)?;
+    Ok((basis.transform(cube, scaling), basis))
+}
+
+fn py_fit_transform(
+    py: Python<'_>,
+    cube: &PyAny,
+    pca: bool,
+    n_components: Option<usize>,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<(PyObject, PyObject, PyObject)> {
+    let scaling = Scaling::from_args(scale, offset)?;
+    let (components, basis) = kernels::dispatch!(Operand::extract(cube)?, |a| {
+        let fit = if pca { fit_pca } else { fit_mnf };
+        let (c, basis) = py.allow_threads(|| fit_transform(a, fit, n_components, scaling))?;
+        (kernels::into_py(py, c), basis)
+    });
+    let eigenvalues = basis.eigenvalues.into_pyarray(py).into_py(py);
+    let loadings = basis.loadings.into_pyarray(py).into_py(py);
+    Ok((components, eigenvalues, loadings))
+}
+
+/// Principal component analysis of a band cube.
+///
+/// The band covariance is accumulated over every pixel without a NaN band
+/// and eigendecomposed; each pixel's mean-centred spectrum is then projected
+/// onto the leading eigenvectors. Pixels with a NaN band are NaN in every
+/// component.
+///
+/// # Arguments
+/// * `cube` - `(band, ...)` array with bands first
+/// * `n_components` - Number of components to keep (default: all bands)
+/// * `scale`, `offset` - DN conversion applied to the cube
+///
+/// # Returns
+/// `(components, eigenvalues, loadings)`: the `(n_components, ...)` component
+/// images (float32 for float32 / uint16 cubes, float64 otherwise), the
+/// `(n_components,)` eigenvalues in decreasing order and the
+/// `(n_components, n_bands)` eigenvectors.
+#[pyfunction]
+#[pyo3(signature = (cube, n_components=None, scale=None, offset=None))]
+pub fn pca_transform(
+    py: Python<'_>,
+    cube: &PyAny,
+    n_components: Option<usize>,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<(PyObject, PyObject, PyObject)> {
+    py_fit_transform(py, cube, true, n_components, scale, offset)
+}
+
+/// Minimum noise fraction transform of a band cube.
+///
+/// Noise is estimated from the differences between horizontally and
+/// vertically adjacent pixels. The data are whitened with respect to that
+/// noise and then rotated onto their principal components, so components are
+/// ordered by decreasing signal-to-noise ratio rather than variance.
+///
+/// # Arguments
+/// * `cube` - `(band, y, x)` array
+/// * `n_components` - Number of components to keep (default: all bands)
+/// * `scale`, `offset` - DN conversion applied to the cube
+///
+/// # Returns
+/// `(components, eigenvalues, loadings)` as for `pca_transform`; eigenvalues
+/// are the signal-to-noise ratio plus one of each component.
+#[pyfunction]
+#[pyo3(signature = (cube, n_components=None, scale=None, offset=None))]
+pub fn mnf_transform(
+    py: Python<'_>,
+    cube: &PyAny,
+    n_components: Option<usize>,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<(PyObject, PyObject, PyObject)> {
+    py_fit_transform(py, cube, false, n_components, scale, offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use ndarray::{array, Array4};
+
+    #[test]
+    fn test_every_set_resolves_its_bands() {
+        for set in TASSELED_CAP_SETS {
+            for &sensor in set.sensors {
+                let profile = lookup_sensor(sensor).unwrap();
+                for role in TASSELED_CAP_BANDS {
+                    assert!(profile.role_position(role, None).is_ok(), "{sensor}");
+                }
+                assert_eq!(tasseled_cap_set(sensor).unwrap().sensors, set.sensors);
+            }
+        }
+        let err = tasseled_cap_set("modis").unwrap_err();
+        assert!(err.to_string().contains("No Tasseled Cap coefficients"));
+    }
+
+    #[test]
+    fn test_landsat8_matches_manual_projection() {
+        // (band, y, x) reflectance cube in canonical SR_B1..SR_B7 order.
+        let cube = ndarray::Array3::from_shape_fn((7, 2, 2), |(b, y, x)| {
+            0.02 + 0.05 * b as f64 + 0.01 * (y * 2 + x) as f64
+        })
+        .into_dyn();
+        let out: ArrayD<f64> = tasseled_cap_cube(
+            cube.view(),
+            Coefficients::Sensor("landsat8"),
+            0,
+            None,
+            Some(Scaling::IDENTITY),
+        )
+        .unwrap();
+        assert_eq!(out.shape(), &[3, 2, 2]);
+        let set = tasseled_cap_set("landsat8").unwrap();
+        for c in 0..3 {
+            let expected: f64 = (0..6)
+                .map(|i| set.coefficients[c][i] * cube[[i + 1, 1, 0]])
+                .sum();
+            assert_relative_eq!(out[[c, 1, 0]], expected, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_time_first_cube_and_dn_scaling() {
+        // (time, band, y, x) Sentinel-2 uint16 cube.
+        let cube = Array4::from_shape_fn((2, 12, 1, 3), |(t, b, _, x)| {
+            1200 + (t * 300 + b * 150 + x * 40) as u16
+        })
+        .into_dyn();
+        let s2 = lookup_sensor("sentinel2").unwrap();
+        let out: ArrayD<f32> = tasseled_cap_cube(
+            cube.view(),
+            Coefficients::Sensor("sentinel2"),
+            1,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(out.shape(), &[2, 3, 1, 3]);
+        let set = tasseled_cap_set("sentinel2").unwrap();
+        let bands = [1, 2, 3, 7, 10, 11];
+        let expected: f64 = bands
+            .iter()
+            .zip(set.coefficients[2])
+            .map(|(&b, w)| w * s2.scaling.apply(cube[[1, b, 0, 2]] as f64))
+            .sum();
+        assert_relative_eq!(out[[1, 2, 0, 2]] as f64, expected, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_custom_matrix_and_validation() {
+        let cube = array![[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]].into_dyn();
+        let matrix = array![[1.0, 0.0, -1.0], [0.5, 0.5, 0.5]];
+        let out: ArrayD<f64> = tasseled_cap_cube(
+            cube.view(),
+            Coefficients::Matrix(matrix.view()),
+            0,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(out, array![[-4.0, -4.0], [4.5, 6.0]].into_dyn());
+
+        // Bands last.
+        let out: ArrayD<f64> = tasseled_cap_cube(
+            cube.t(),
+            Coefficients::Matrix(matrix.view()),
+            -1,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(out, array![[-4.0, 4.5], [-4.0, 6.0]].into_dyn());
+
+        let err = tasseled_cap_cube(cube.view(), Coefficients::Matrix(matrix.t()), 0, None, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("has 2 columns"));
+        assert!(
+            tasseled_cap_cube(cube.view(), Coefficients::Sensor("landsat8"), 0, None, None)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_nan_pixels_propagate() {
+        let mut cube = ndarray::Array2::<f64>::from_elem((6, 2), 0.1).into_dyn();
+        cube[[3, 1]] = f64::NAN;
+        let matrix = ndarray::arr2(&TASSELED_CAP_SETS[0].coefficients);
+        let out = project(
+            cube.view(),
+            0,
+            &[0, 1, 2, 3, 4, 5],
+            matrix.view(),
+            None,
+            Scaling::IDENTITY,
+        );
+        assert!(out[[0, 0]].is_finite());
+        assert!(out.index_axis(Axis(1), 1).iter().all(|v| v.is_nan()));
+    }
+
+    fn pca_cube() -> ArrayD<f64> {
+        // Three correlated bands plus a NaN pixel.
+        let mut cube = ndarray::Array3::from_shape_fn((3, 6, 7), |(b, y, x)| {
+            let t = (y * 7 + x) as f64;
+            let signal = (t * 0.37).sin();
+            let wiggle = (t * 1.91).cos() * 0.1;
+            match b {
+                0 => signal,
+                1 => 2.0 * signal + wiggle,
+                _ => -0.5 * signal + wiggle + 0.05 * (t * 0.7).sin(),
+            }
+        })
+        .into_dyn();
+        cube[[1, 2, 3]] = f64::NAN;
+        cube
+    }
+
+    #[test]
+    fn test_pca_eigenpairs() {
+        let cube = pca_cube();
+        let basis = fit_pca(&cube.view(), None, Scaling::IDENTITY).unwrap();
+        let (mean, cov) = band_statistics(&cube.view(), Scaling::IDENTITY).unwrap();
+        assert_eq!(basis.mean.len(), 3);
+        assert_relative_eq!(basis.mean[0], mean[0]);
+        assert!(basis.eigenvalues.windows(2).all(|w| w[0] >= w[1]));
+        assert_relative_eq!(
+            basis.eigenvalues.iter().sum::<f64>(),
+            cov.trace(),
+            epsilon = 1e-10
+        );
+        let l = &basis.loadings;
+        let gram = l.dot(&l.t());
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert_relative_eq!(gram[[i, j]], expected, epsilon = 1e-10);
+            }
+        }
+
+        // Components are uncorrelated with variances equal to the eigenvalues.
+        let comps = basis.transform(cube.view(), Scaling::IDENTITY);
+        assert!(comps[[0, 2, 3]].is_nan());
+        let (_, comp_cov) = band_statistics(&comps.view(), Scaling::IDENTITY).unwrap();
+        for i in 0..3 {
+            assert_relative_eq!(comp_cov[(i, i)], basis.eigenvalues[i], epsilon = 1e-10);
+            for j in 0..i {
+                assert!(comp_cov[(i, j)].abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pca_truncation_and_validation() {
+        let cube = pca_cube();
+        let basis = fit_pca(&cube.view(), Some(2), Scaling::IDENTITY).unwrap();
+        assert_eq!(basis.loadings.dim(), (2, 3));
+        assert_eq!(basis.eigenvalues.len(), 2);
+        assert_eq!(
+            basis.transform(cube.view(), Scaling::IDENTITY).shape(),
+            &[2, 6, 7]
+        );
+        assert!(fit_pca(&cube.view(), Some(0), Scaling::IDENTITY).is_err());
+        assert!(fit_pca(&cube.view(), Some(4), Scaling::IDENTITY).is_err());
+        let nan = ArrayD::from_elem(IxDyn(&[2, 3]), f64::NAN);
+        assert!(matches!(
+            fit_pca(&nan.view(), None, Scaling::IDENTITY),
+            Err(CoreError::NotEnoughData(_))
+        ));
+    }
+
+    #[test]
+    fn test_mnf_whitens_noise() {
+        let cube = pca_cube();
+        let basis = fit_mnf(&cube.view(), None, Scaling::IDENTITY).unwrap();
+        assert!(basis.eigenvalues.windows(2).all(|w| w[0] >= w[1]));
+        let noise = noise_covariance(
+            cube.view().into_dimensionality::<Ix3>().unwrap(),
+            Scaling::IDENTITY,
+        )
+        .unwrap();
+        let t = DMatrix::from_fn(3, 3, |i, j| basis.loadings[[i, j]]);
+        let whitened = &t * noise * t.transpose();
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert_relative_eq!(whitened[(i, j)], expected, epsilon = 1e-8);
+            }
+        }
+        let flat = cube.index_axis(Axis(2), 0).to_owned();
+        assert!(fit_mnf(&flat.view(), None, Scaling::IDENTITY).is_err());
+    }
+}
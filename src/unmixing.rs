@@ -0,0 +1,459 @@
+//! Linear spectral unmixing.
+//!
+//! Each pixel spectrum `y` is modelled as a mixture `E^T f` of endmember
+//! spectra (the rows of `E`), and the fractions `f` are estimated by least
+//! squares under optional non-negativity and sum-to-one constraints. All
+//! variants work on the normal equations, so the `m x m` Gram matrix
+//! `E E^T` is built once and each pixel only forms `E y`.
+
+use crate::kernels::{self, Operand, Real, Sample, Scaling};
+use crate::CoreError;
+use nalgebra::{DMatrix, DVector};
+use ndarray::{ArrayD, ArrayView2, ArrayViewD, Axis, IxDyn, Zip};
+use numpy::PyReadonlyArray2;
+use pyo3::prelude::*;
+
+/// Values at or below this are treated as zero by the NNLS active set; the
+/// gradient test scales it by the largest Gram entry.
+const NNLS_TOLERANCE: f64 = 1e-12;
+
+/// Least-squares constraint set.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Constraint {
+    /// Unconstrained least squares.
+    Ucls,
+    /// Sum-to-one.
+    Scls,
+    /// Non-negative.
+    Nnls,
+    /// Fully constrained: non-negative and sum-to-one.
+    Fcls,
+}
+
+impl Constraint {
+    pub fn parse(method: &str) -> Result<Self, CoreError> {
+        match method.to_ascii_lowercase().as_str() {
+            "ucls" => Ok(Constraint::Ucls),
+            "scls" => Ok(Constraint::Scls),
+            "nnls" => Ok(Constraint::Nnls),
+            "fcls" => Ok(Constraint::Fcls),
+            _ => Err(CoreError::InvalidArgument(format!(
+                "Unknown unmixing method '{}'; expected 'fcls', 'nnls', 'scls' or 'ucls'",
+                method
+            ))),
+        }
+    }
+}
+
+/// Endmember matrix prepared for per-pixel solves.
+pub(crate) struct Unmixer {
+    constraint: Constraint,
+    /// `(n_endmembers, n_bands)`, one spectrum per row.
+    endmembers: DMatrix<f64>,
+    /// Gram matrix `E E^T`.
+    gram: DMatrix<f64>,
+    /// Inverse Gram matrix for the closed-form variants.
+    gram_inv: Option<DMatrix<f64>>,
+}
+
+impl Unmixer {
+    pub fn new(endmembers: ArrayView2<f64>, constraint: Constraint) -> Result<Self, CoreError> {
+        let (m, n) = endmembers.dim();
+        if m == 0 || n == 0 {
+            return Err(CoreError::InvalidArgument(
+                "Endmember matrix must have at least one endmember and one band".to_string(),
+            ));
+        }
+        if endmembers.iter().any(|v| !v.is_finite()) {
+            return Err(CoreError::InvalidArgument(
+                "Endmember matrix must be finite".to_string(),
+            ));
+        }
+        let endmembers = DMatrix::from_fn(m, n, |i, j| endmembers[[i, j]]);
+        let gram = &endmembers * endmembers.transpose();
+        let gram_inv = match constraint {
+            Constraint::Ucls | Constraint::Scls => {
+                let chol = gram.clone().cholesky().ok_or_else(|| {
+                    CoreError::InvalidArgument(format!(
+                        "Endmember matrix ({} endmembers x {} bands) is rank deficient; \
+                         endmembers must be linearly independent",
+                        m, n
+                    ))
+                })?;
+                Some(chol.inverse())
+            }
+            Constraint::Nnls | Constraint::Fcls => None,
+        };
+        Ok(Unmixer {
+            constraint,
+            endmembers,
+            gram,
+            gram_inv,
+        })
+    }
+
+    pub fn n_endmembers(&self) -> usize {
+        self.endmembers.nrows()
+    }
+
+    pub fn n_bands(&self) -> usize {
+        self.endmembers.ncols()
+    }
+
+    /// Fractions for one pixel spectrum.
+    pub fn solve(&self, y: &DVector<f64>) -> DVector<f64> {
+        let rhs = &self.endmembers * y;
+        match self.constraint {
+            Constraint::Ucls => self.gram_inv.as_ref().unwrap() * rhs,
+            Constraint::Scls => {
+                let inv = self.gram_inv.as_ref().unwrap();
+                let x = inv * rhs;
+                let g = inv.column_sum();
+                x.clone() - &g * ((x.sum() - 1.0) / g.sum())
+            }
+            Constraint::Nnls => nnls(&self.gram, &rhs),
+            Constraint::Fcls => fcls(&self.gram, &rhs),
+        }
+    }
+
+    /// Root-mean-square reconstruction error of `fractions` over the bands.
+    pub fn rmse(&self, y: &DVector<f64>, fractions: &DVector<f64>) -> f64 {
+        let residual = y - self.endmembers.tr_mul(fractions);
+        (residual.norm_squared() / y.len() as f64).sqrt()
+    }
+}
+
+/// Lawson-Hanson non-negative least squares on the normal equations
+/// `gram x = rhs`.
+fn nnls(gram: &DMatrix<f64>, rhs: &DVector<f64>) -> DVector<f64> {
+    let m = rhs.len();
+    let mut x = DVector::zeros(m);
+    let mut passive = vec![false; m];
+    let w_tol = NNLS_TOLERANCE * gram.amax().max(1.0);
+    for _ in 0..3 * m.max(1) {
+        let w = rhs - gram * &x;
+        let next = (0..m)
+            .filter(|&j| !passive[j] && w[j] > w_tol)
+            .max_by(|&a, &b| w[a].total_cmp(&w[b]));
+        let Some(j) = next else { break };
+        passive[j] = true;
+        loop {
+            let s = solve_passive(gram, rhs, &passive);
+            if (0..m).all(|i| !passive[i] || s[i] > NNLS_TOLERANCE) {
+                x = s;
+                break;
+            }
+            let alpha = (0..m)
+                .filter(|&i| passive[i] && s[i] <= NNLS_TOLERANCE)
+                .map(|i| x[i] / (x[i] - s[i]))
+                .fold(f64::INFINITY, f64::min);
+            x += (s - &x) * alpha;
+            for i in 0..m {
+                if passive[i] && x[i] <= NNLS_TOLERANCE {
+                    passive[i] = false;
+                    x[i] = 0.0;
+                }
+            }
+        }
+    }
+    x
+}
+
+/// Fully constrained least squares (Heinz & Chang, 2001) on the normal
+/// equations `gram x = rhs` subject to `x >= 0` and `sum(x) = 1`. A primal
+/// active-set method that starts from equal fractions and solves the
+/// sum-to-one subproblem exactly on the passive set, so every iterate is
+/// feasible and the fractions sum to one up to rounding.
+fn fcls(gram: &DMatrix<f64>, rhs: &DVector<f64>) -> DVector<f64> {
+    let m = rhs.len();
+    let mut x = DVector::from_element(m, 1.0 / m as f64);
+    let mut passive = vec![true; m];
+    let w_tol = NNLS_TOLERANCE * gram.amax().max(1.0);
+    for _ in 0..6 * m.max(1) {
+        let s = solve_passive_sum_to_one(gram, rhs, &passive);
+        if (0..m).all(|i| !passive[i] || s[i] > NNLS_TOLERANCE) {
+            x = s;
+            // At the optimum the gradient is equal (the sum-to-one
+            // multiplier) on the passive set and no larger elsewhere.
+            let w = rhs - gram * &x;
+            let n_passive = passive.iter().filter(|&&p| p).count();
+            let lambda =
+                (0..m).filter(|&i| passive[i]).map(|i| w[i]).sum::<f64>() / n_passive as f64;
+            let next = (0..m)
+                .filter(|&j| !passive[j] && w[j] - lambda > w_tol)
+                .max_by(|&a, &b| w[a].total_cmp(&w[b]));
+            let Some(j) = next else { break };
+            passive[j] = true;
+            continue;
+        }
+        let alpha = (0..m)
+            // Skip indices already sitting on the bound (x == s), where the
+            // ratio would be 0/0; the full step then drops them below.
+            .filter(|&i| passive[i] && s[i] <= NNLS_TOLERANCE && x[i] != s[i])
+            .map(|i| x[i] / (x[i] - s[i]))
+            .fold(1.0, f64::min);
+        x += (s - &x) * alpha;
+        for i in 0..m {
+            if passive[i] && x[i] <= NNLS_TOLERANCE {
+                passive[i] = false;
+                x[i] = 0.0;
+            }
+        }
+    }
+    x
+}
+
+/// Solve the normal equations restricted to the passive set under
+/// `sum(x) = 1` through the KKT system `[G 1; 1^T 0] [x; mu] = [rhs; 1]`;
+/// other entries are zero.
+fn solve_passive_sum_to_one(
+    gram: &DMatrix<f64>,
+    rhs: &DVector<f64>,
+    passive: &[bool],
+) -> DVector<f64> {
+    let idx: Vec<usize> = (0..passive.len()).filter(|&i| passive[i]).collect();
+    let k = idx.len();
+    let kkt = DMatrix::from_fn(k + 1, k + 1, |r, c| match (r < k, c < k) {
+        (true, true) => gram[(idx[r], idx[c])],
+        (false, false) => 0.0,
+        _ => 1.0,
+    });
+    let kkt_rhs = DVector::from_fn(k + 1, |r, _| if r < k { rhs[idx[r]] } else { 1.0 });
+    let z = kkt.clone().lu().solve(&kkt_rhs).unwrap_or_else(|| {
+        // Collinear endmembers: fall back to the minimum-norm solution.
+        kkt.svd(true, true)
+            .solve(&kkt_rhs, NNLS_TOLERANCE)
+            .unwrap_or_else(|_| {
+                DVector::from_fn(k + 1, |r, _| if r < k { 1.0 / k as f64 } else { 0.0 })
+            })
+    });
+    let mut s = DVector::zeros(passive.len());
+    for (j, &i) in idx.iter().enumerate() {
+        s[i] = z[j];
+    }
+    s
+}
+
+/// Solve the normal equations restricted to the passive set; other entries
+/// are zero.
+fn solve_passive(gram: &DMatrix<f64>, rhs: &DVector<f64>, passive: &[bool]) -> DVector<f64> {
+    let idx: Vec<usize> = (0..passive.len()).filter(|&i| passive[i]).collect();
+    let sub = gram.select_rows(&idx).select_columns(&idx);
+    let sub_rhs = rhs.select_rows(&idx);
+    let z = match sub.clone().cholesky() {
+        Some(chol) => chol.solve(&sub_rhs),
+        // Collinear endmembers: fall back to the minimum-norm solution.
+        None => sub
+            .svd(true, true)
+            .solve(&sub_rhs, NNLS_TOLERANCE)
+            .unwrap_or_else(|_| DVector::zeros(idx.len())),
+    };
+    let mut s = DVector::zeros(passive.len());
+    for (k, &i) in idx.iter().enumerate() {
+        s[i] = z[k];
+    }
+    s
+}
+
+/// Fraction maps and per-pixel RMSE.
+pub(crate) type Unmixed<R> = (ArrayD<R>, ArrayD<R>);
+
+/// Unmix a `(band, ...)` cube. Returns the `(endmember, ...)` fractions and
+/// the per-pixel RMSE; pixels with a non-finite band are NaN in both.
+pub(crate) fn unmix_cube<A: Sample>(
+    cube: ArrayViewD<A>,
+    unmixer: &Unmixer,
+    scaling: Scaling,
+) -> Result<Unmixed<A::Out>, CoreError> {
+    if cube.ndim() == 0 || cube.shape()[0] != unmixer.n_bands() {
+        return Err(CoreError::InvalidArgument(format!(
+            "Cube of shape {:?} does not match the endmember matrix: expected {} bands \
+             along axis 0",
+            cube.shape(),
+            unmixer.n_bands()
+        )));
+    }
+    let mut shape = cube.shape().to_vec();
+    shape[0] = unmixer.n_endmembers();
+    let mut fractions = ArrayD::from_elem(IxDyn(&shape), A::Out::from_f64(0.0));
+    let mut rmse = ArrayD::from_elem(IxDyn(&cube.shape()[1..]), A::Out::from_f64(0.0));
+    Zip::from(fractions.lanes_mut(Axis(0)))
+        .and(&mut rmse)
+        .and(cube.lanes(Axis(0)))
+        .par_for_each(|mut f, e, px| {
+            let y = DVector::from_iterator(px.len(), px.iter().map(|v| scaling.apply(v.to_f64())));
+            if y.iter().any(|v| !v.is_finite()) {
+                f.fill(A::Out::from_f64(f64::NAN));
+                *e = A::Out::from_f64(f64::NAN);
+                return;
+            }
+            let x = unmixer.solve(&y);
+            for (o, &v) in f.iter_mut().zip(x.iter()) {
+                *o = Real::from_f64(v);
+            }
+            *e = Real::from_f64(unmixer.rmse(&y, &x));
+        });
+    Ok((fractions, rmse))
+}
+
+/// Per-pixel linear spectral unmixing.
+///
+/// # Arguments
+/// * `cube` - `(band, y, x)` array (any rank with bands first)
+/// * `endmembers` - `(n_endmembers, n_bands)` matrix, one spectrum per row
+/// * `method` - `"fcls"` (non-negative and sum-to-one, default), `"nnls"`
+///   (non-negative), `"scls"` (sum-to-one) or `"ucls"` (unconstrained)
+/// * `scale`, `offset` - DN conversion applied to the cube
+///
+/// # Returns
+/// `(fractions, rmse)`: `(n_endmembers, y, x)` fraction maps and the `(y, x)`
+/// reconstruction RMSE; float32 for float32 / uint16 cubes, float64 otherwise.
+#[pyfunction]
+#[pyo3(signature = (cube, endmembers, method="fcls", scale=None, offset=None))]
+pub fn linear_unmixing(
+    py: Python<'_>,
+    cube: &PyAny,
+    endmembers: PyReadonlyArray2<f64>,
+    method: &str,
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<(PyObject, PyObject)> {
+    let unmixer = Unmixer::new(endmembers.as_array(), Constraint::parse(method)?)?;
+    let scaling = Scaling::from_args(scale, offset)?;
+    Ok(kernels::dispatch!(Operand::extract(cube)?, |a| {
+        let (f, e) = py.allow_threads(|| unmix_cube(a, &unmixer, scaling))?;
+        (kernels::into_py(py, f), kernels::into_py(py, e))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use ndarray::{array, Array3};
+
+    fn library() -> ndarray::Array2<f64> {
+        // Vegetation, soil and impervious spectra over six bands.
+        array![
+            [0.04, 0.08, 0.05, 0.45, 0.25, 0.12],
+            [0.10, 0.15, 0.20, 0.28, 0.35, 0.30],
+            [0.18, 0.20, 0.22, 0.24, 0.26, 0.25],
+        ]
+    }
+
+    fn mix(fractions: &[f64]) -> DVector<f64> {
+        let lib = library();
+        DVector::from_fn(lib.ncols(), |b, _| {
+            fractions
+                .iter()
+                .enumerate()
+                .map(|(k, f)| f * lib[[k, b]])
+                .sum()
+        })
+    }
+
+    #[test]
+    fn test_all_methods_recover_exact_mixture() {
+        let truth = [0.6, 0.3, 0.1];
+        let y = mix(&truth);
+        for method in ["ucls", "scls", "nnls", "fcls"] {
+            let u = Unmixer::new(library().view(), Constraint::parse(method).unwrap()).unwrap();
+            let f = u.solve(&y);
+            for k in 0..3 {
+                assert_relative_eq!(f[k], truth[k], epsilon = 1e-6);
+            }
+            assert!(u.rmse(&y, &f) < 1e-6, "{method}");
+        }
+    }
+
+    #[test]
+    fn test_constraints_hold_for_out_of_simplex_pixel() {
+        // A bright pixel outside the endmember simplex.
+        let y = mix(&[1.2, -0.3, 0.4]);
+        let ucls = Unmixer::new(library().view(), Constraint::Ucls).unwrap();
+        assert!(ucls.solve(&y).iter().any(|&v| v < 0.0));
+
+        let nnls = Unmixer::new(library().view(), Constraint::Nnls).unwrap();
+        assert!(nnls.solve(&y).iter().all(|&v| v >= 0.0));
+
+        let scls = Unmixer::new(library().view(), Constraint::Scls).unwrap();
+        assert_relative_eq!(scls.solve(&y).sum(), 1.0, epsilon = 1e-12);
+
+        let fcls = Unmixer::new(library().view(), Constraint::Fcls).unwrap();
+        let f = fcls.solve(&y);
+        assert!(f.iter().all(|&v| v >= 0.0));
+        assert_relative_eq!(f.sum(), 1.0, epsilon = 1e-12);
+        assert!(fcls.rmse(&y, &f) > 0.0);
+    }
+
+    #[test]
+    fn test_nnls_matches_brute_force_active_set() {
+        // Single endmember pinned to zero by non-negativity.
+        let gram = DMatrix::from_row_slice(2, 2, &[2.0, 1.0, 1.0, 2.0]);
+        let rhs = DVector::from_vec(vec![3.0, -1.0]);
+        let x = nnls(&gram, &rhs);
+        assert_relative_eq!(x[0], 1.5, epsilon = 1e-12);
+        assert_eq!(x[1], 0.0);
+    }
+
+    #[test]
+    fn test_fcls_projects_onto_simplex() {
+        // With an identity Gram matrix FCLS is the Euclidean projection of
+        // `rhs` onto the probability simplex.
+        let gram = DMatrix::identity(3, 3);
+        let x = fcls(&gram, &DVector::from_vec(vec![1.0, 0.5, -2.0]));
+        assert_relative_eq!(x[0], 0.75, epsilon = 1e-12);
+        assert_relative_eq!(x[1], 0.25, epsilon = 1e-12);
+        assert_eq!(x[2], 0.0);
+        let x = fcls(&gram, &DVector::from_vec(vec![5.0, 0.0, 0.0]));
+        assert_eq!(x.as_slice(), &[1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_unmix_cube_shapes_and_nan() {
+        let lib = library();
+        let mut cube = Array3::<f64>::zeros((6, 2, 3));
+        for y in 0..2 {
+            for x in 0..3 {
+                let v = mix(&[0.5, 0.25 * y as f64, 0.5 - 0.25 * y as f64]);
+                for b in 0..6 {
+                    cube[[b, y, x]] = v[b];
+                }
+            }
+        }
+        cube[[2, 1, 2]] = f64::NAN;
+        let u = Unmixer::new(lib.view(), Constraint::Fcls).unwrap();
+        let (f, e) = unmix_cube(cube.view().into_dyn(), &u, Scaling::IDENTITY).unwrap();
+        assert_eq!(f.shape(), &[3, 2, 3]);
+        assert_eq!(e.shape(), &[2, 3]);
+        assert_relative_eq!(f[[1, 1, 0]], 0.25, epsilon = 1e-6);
+        assert!(f[[0, 1, 2]].is_nan() && e[[1, 2]].is_nan());
+
+        let err = unmix_cube(
+            cube.slice(ndarray::s![..5, .., ..]).into_dyn(),
+            &u,
+            Scaling::IDENTITY,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("expected 6 bands"));
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        assert!(Constraint::parse("lasso").is_err());
+        let collinear = array![[0.1, 0.2, 0.3], [0.2, 0.4, 0.6]];
+        assert!(Unmixer::new(collinear.view(), Constraint::Ucls).is_err());
+        // NNLS tolerates collinear endmembers.
+        let u = Unmixer::new(collinear.view(), Constraint::Nnls).unwrap();
+        let y = DVector::from_vec(vec![0.3, 0.6, 0.9]);
+        assert!(u.rmse(&y, &u.solve(&y)) < 1e-9);
+        // So does FCLS, keeping the fractions on the simplex.
+        let u = Unmixer::new(collinear.view(), Constraint::Fcls).unwrap();
+        let f = u.solve(&DVector::from_vec(vec![0.15, 0.3, 0.45]));
+        assert!(f.iter().all(|&v| v >= 0.0));
+        assert_relative_eq!(f.sum(), 1.0, epsilon = 1e-12);
+        assert!(u.rmse(&DVector::from_vec(vec![0.15, 0.3, 0.45]), &f) < 1e-9);
+        let nan = array![[0.1, f64::NAN]];
+        assert!(Unmixer::new(nan.view(), Constraint::Fcls).is_err());
+    }
+}
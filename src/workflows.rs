@@ -1,7 +1,10 @@
+use crate::indices::{delta_nd_px, normalized_difference_px};
+use crate::kernels::{self, Operand, Real, Sample, Scaling};
+use crate::masking::{project_cloud_shadows, shadow_offsets};
 use crate::CoreError;
 use nalgebra::{DMatrix, DVector};
-use ndarray::{Axis, IxDyn};
-use numpy::{IntoPyArray, PyArrayDyn, PyReadonlyArrayDyn};
+use ndarray::{Array2, ArrayD, ArrayView2, ArrayViewD, Axis, Ix2, IxDyn, Zip};
+use numpy::{IntoPyArray, PyArrayDyn, PyReadonlyArray2, PyReadonlyArrayDyn};
 use pyo3::prelude::*;
 use rayon::prelude::*;
 
@@ -456,3 +459,947 @@ fn classify_pixel(b: f64, g: f64, r: f64, n: f64, s1: f64, s2: f64, t: f64) -> u
 
     UNCLASSIFIED
 }
+
+// --- 3. Burn Severity Workflow ---
+
+/// Key & Benson (2006) dNBR class breaks: enhanced regrowth high / low,
+/// unburned, low, moderate-low, moderate-high and high severity.
+const KEY_BENSON_THRESHOLDS: [f64; 6] = [-0.25, -0.1, 0.1, 0.27, 0.44, 0.66];
+
+/// Parks et al. (2014) RBR class breaks (35, 130 and 298 on the x1000
+/// scale): unchanged, low, moderate and high severity.
+const PARKS_RBR_THRESHOLDS: [f64; 3] = [0.035, 0.130, 0.298];
+
+/// Miller & Thode (2007) RdNBR class breaks: unchanged, low, moderate and
+/// high severity.
+const MILLER_THODE_THRESHOLDS: [f64; 3] = [0.069, 0.316, 0.641];
+
+/// Floor on `|NBR_pre|` in the RdNBR denominator.
+const RDNBR_MIN_PRE_NBR: f64 = 1e-3;
+
+/// Continuous burn severity index.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum SeverityIndex {
+    /// `NBR_pre - NBR_post`.
+    Dnbr,
+    /// `dNBR / (NBR_pre + 1)`.
+    Rbr,
+    /// `dNBR / sqrt(|NBR_pre|)`.
+    Rdnbr,
+}
+
+impl SeverityIndex {
+    pub fn parse(index: &str) -> Result<Self, CoreError> {
+        match index.to_ascii_lowercase().as_str() {
+            "dnbr" => Ok(SeverityIndex::Dnbr),
+            "rbr" => Ok(SeverityIndex::Rbr),
+            "rdnbr" => Ok(SeverityIndex::Rdnbr),
+            _ => Err(CoreError::InvalidArgument(format!(
+                "Unknown burn severity index '{}'; expected 'dnbr', 'rbr' or 'rdnbr'",
+                index
+            ))),
+        }
+    }
+
+    pub fn default_thresholds(self) -> &'static [f64] {
+        match self {
+            SeverityIndex::Dnbr => &KEY_BENSON_THRESHOLDS,
+            SeverityIndex::Rbr => &PARKS_RBR_THRESHOLDS,
+            SeverityIndex::Rdnbr => &MILLER_THODE_THRESHOLDS,
+        }
+    }
+
+    fn apply(self, dnbr: f64, pre_nbr: f64) -> f64 {
+        match self {
+            SeverityIndex::Dnbr => dnbr,
+            SeverityIndex::Rbr => {
+                let denom = pre_nbr + 1.0;
+                if denom.abs() < 1e-10 {
+                    0.0
+                } else {
+                    dnbr / denom
+                }
+            }
+            SeverityIndex::Rdnbr => dnbr / pre_nbr.abs().max(RDNBR_MIN_PRE_NBR).sqrt(),
+        }
+    }
+}
+
+/// Severity index, class map, per-class pixel counts and the dNBR offset.
+pub(crate) struct BurnSeverity<R> {
+    pub index: ArrayD<R>,
+    pub classes: ArrayD<u8>,
+    pub counts: Vec<u64>,
+    pub offset: f64,
+}
+
+fn check_thresholds(thresholds: &[f64]) -> Result<(), CoreError> {
+    if thresholds.is_empty() || thresholds.len() > 254 {
+        return Err(CoreError::InvalidArgument(format!(
+            "Burn severity needs between 1 and 254 class thresholds, got {}",
+            thresholds.len()
+        )));
+    }
+    if thresholds.iter().any(|t| !t.is_finite()) || thresholds.windows(2).any(|w| w[0] >= w[1]) {
+        return Err(CoreError::InvalidArgument(format!(
+            "Burn severity thresholds must be finite and strictly increasing, got {:?}",
+            thresholds
+        )));
+    }
+    Ok(())
+}
+
+fn check_mask(
+    name: &str,
+    mask: Option<&ArrayViewD<bool>>,
+    shape: &[usize],
+) -> Result<(), CoreError> {
+    match mask {
+        Some(m) if m.shape() != shape => Err(CoreError::InvalidArgument(format!(
+            "{} of shape {:?} does not match the band shape {:?}",
+            name,
+            m.shape(),
+            shape
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Compute a burn severity index from pre/post NIR and SWIR2 bands and
+/// classify it.
+///
+/// Pixels with a non-finite band or flagged in `water` get a NaN index and
+/// class 0; the others get class `1 + (number of thresholds <= index)`. When
+/// `reference` marks unburned pixels, the mean dNBR over them is subtracted
+/// before the index is formed. `counts[c]` is the number of pixels of class
+/// `c`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn burn_severity_impl<A: Sample>(
+    pre_nir: ArrayViewD<A>,
+    pre_swir2: ArrayViewD<A>,
+    post_nir: ArrayViewD<A>,
+    post_swir2: ArrayViewD<A>,
+    index: SeverityIndex,
+    thresholds: &[f64],
+    reference: Option<ArrayViewD<bool>>,
+    water: Option<ArrayViewD<bool>>,
+    scaling: Scaling,
+) -> Result<BurnSeverity<A::Out>, CoreError> {
+    check_thresholds(thresholds)?;
+    let shape = pre_nir.shape();
+    for other in [pre_swir2.shape(), post_nir.shape(), post_swir2.shape()] {
+        if other != shape {
+            return Err(CoreError::InvalidArgument(format!(
+                "Burn severity bands must have the same shape, got {:?} and {:?}",
+                shape, other
+            )));
+        }
+    }
+    check_mask("reference mask", reference.as_ref(), shape)?;
+    check_mask("water mask", water.as_ref(), shape)?;
+
+    let mut pre_nbr = ArrayD::<f64>::zeros(pre_nir.raw_dim());
+    let mut dnbr = ArrayD::<f64>::zeros(pre_nir.raw_dim());
+    Zip::from(&mut pre_nbr)
+        .and(&mut dnbr)
+        .and(&pre_nir)
+        .and(&pre_swir2)
+        .and(&post_nir)
+        .and(&post_swir2)
+        .par_for_each(|pre, d, &a, &b, &c, &e| {
+            let [a, b, c, e] = [a, b, c, e].map(|v| scaling.apply(v.to_f64()));
+            if ![a, b, c, e].iter().all(|v| v.is_finite()) {
+                *pre = f64::NAN;
+                *d = f64::NAN;
+                return;
+            }
+            *pre = normalized_difference_px(a, b);
+            *d = delta_nd_px(a, b, c, e);
+        });
+    if let Some(water) = &water {
+        Zip::from(&mut dnbr).and(water).par_for_each(|d, &w| {
+            if w {
+                *d = f64::NAN;
+            }
+        });
+    }
+
+    let offset = match &reference {
+        Some(reference) => {
+            let (sum, n) = Zip::from(&dnbr).and(reference).par_fold(
+                || (0.0, 0usize),
+                |(s, n), &d, &r| {
+                    if r && d.is_finite() {
+                        (s + d, n + 1)
+                    } else {
+                        (s, n)
+                    }
+                },
+                |a, b| (a.0 + b.0, a.1 + b.1),
+            );
+            if n == 0 {
+                return Err(CoreError::NotEnoughData(
+                    "Reference mask selects no valid unburned pixels".to_string(),
+                ));
+            }
+            sum / n as f64
+        }
+        None => 0.0,
+    };
+
+    let mut values = ArrayD::<A::Out>::from_elem(pre_nir.raw_dim(), A::Out::from_f64(f64::NAN));
+    let mut classes = ArrayD::<u8>::zeros(pre_nir.raw_dim());
+    Zip::from(&mut values)
+        .and(&mut classes)
+        .and(&dnbr)
+        .and(&pre_nbr)
+        .par_for_each(|v, c, &d, &pre| {
+            if d.is_nan() {
+                return;
+            }
+            let value = index.apply(d - offset, pre);
+            *v = A::Out::from_f64(value);
+            *c = 1 + thresholds.partition_point(|&t| t <= value) as u8;
+        });
+    let n_classes = thresholds.len() + 2;
+    let counts = classes
+        .par_iter()
+        .fold(
+            || vec![0u64; n_classes],
+            |mut h, &c| {
+                h[c as usize] += 1;
+                h
+            },
+        )
+        .reduce(
+            || vec![0u64; n_classes],
+            |mut a, b| {
+                a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+                a
+            },
+        );
+    Ok(BurnSeverity {
+        index: values,
+        classes,
+        counts,
+        offset,
+    })
+}
+
+/// Burn severity index and classes from pre/post-fire NIR and SWIR2.
+///
+/// # Arguments
+/// * `pre_nir`, `pre_swir2`, `post_nir`, `post_swir2` - Bands of equal shape
+/// * `index` - `"dnbr"` (default), `"rbr"` (`dNBR / (NBR_pre + 1)`) or
+///   `"rdnbr"` (`dNBR / sqrt(|NBR_pre|)`, Miller & Thode 2007)
+/// * `thresholds` - Increasing class breaks; defaults to Key & Benson (2006)
+///   for dNBR (7 classes: enhanced regrowth high/low, unburned, low,
+///   moderate-low, moderate-high, high), Parks et al. (2014) for RBR and
+///   Miller & Thode (2007) for RdNBR (both 4 classes: unchanged, low,
+///   moderate, high)
+/// * `reference` - Boolean mask of unburned pixels; their mean dNBR is
+///   subtracted as an offset before the index is formed
+/// * `water_mask` - Boolean mask of pixels to exclude (class 0, NaN index)
+/// * `scale`, `offset` - DN conversion applied to the bands
+/// * `pixel_area` - Area of one pixel (e.g. 900.0 m² for 30 m pixels); when
+///   given, the per-class areas are returned as well
+///
+/// # Returns
+/// `(index, classes, counts, dnbr_offset)`: the float index, the uint8 class
+/// map (0 = excluded or NaN, 1.. = classes in threshold order), the int64
+/// pixel count of each class code and the subtracted dNBR offset. With
+/// `pixel_area`, a fifth float64 array holds the area of each class code.
+#[pyfunction]
+#[pyo3(signature = (
+    pre_nir,
+    pre_swir2,
+    post_nir,
+    post_swir2,
+    index="dnbr",
+    thresholds=None,
+    reference=None,
+    water_mask=None,
+    scale=None,
+    offset=None,
+    pixel_area=None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn burn_severity(
+    py: Python<'_>,
+    pre_nir: &PyAny,
+    pre_swir2: &PyAny,
+    post_nir: &PyAny,
+    post_swir2: &PyAny,
+    index: &str,
+    thresholds: Option<Vec<f64>>,
+    reference: Option<PyReadonlyArrayDyn<bool>>,
+    water_mask: Option<PyReadonlyArrayDyn<bool>>,
+    scale: Option<f64>,
+    offset: Option<f64>,
+    pixel_area: Option<f64>,
+) -> PyResult<PyObject> {
+    let index = SeverityIndex::parse(index)?;
+    if let Some(area) = pixel_area.filter(|a| !(a.is_finite() && *a > 0.0)) {
+        return Err(CoreError::InvalidArgument(format!(
+            "pixel_area must be positive, got {}",
+            area
+        ))
+        .into());
+    }
+    let thresholds = thresholds.unwrap_or_else(|| index.default_thresholds().to_vec());
+    let scaling = Scaling::from_args(scale, offset)?;
+    let reference = reference.as_ref().map(|r| r.as_array());
+    let water = water_mask.as_ref().map(|w| w.as_array());
+
+    fn finish<R: Real + numpy::Element>(
+        py: Python<'_>,
+        result: BurnSeverity<R>,
+        pixel_area: Option<f64>,
+    ) -> PyObject {
+        let counts: Vec<i64> = result.counts.iter().map(|&c| c as i64).collect();
+        let index = kernels::into_py(py, result.index);
+        let classes = kernels::into_py(py, result.classes);
+        let counts_py: PyObject = counts.into_pyarray(py).into_py(py);
+        match pixel_area {
+            Some(area) => {
+                let areas: Vec<f64> = result.counts.iter().map(|&c| c as f64 * area).collect();
+                let areas: PyObject = areas.into_pyarray(py).into_py(py);
+                (index, classes, counts_py, result.offset, areas).into_py(py)
+            }
+            None => (index, classes, counts_py, result.offset).into_py(py),
+        }
+    }
+
+    let operands = Operand::extract_common(&[pre_nir, pre_swir2, post_nir, post_swir2])?;
+    kernels::dispatch!(all operands, |views| {
+        let result = py.allow_threads(|| {
+            burn_severity_impl(
+                views[0].clone(),
+                views[1].clone(),
+                views[2].clone(),
+                views[3].clone(),
+                index,
+                &thresholds,
+                reference.clone(),
+                water.clone(),
+                scaling,
+            )
+        })?;
+        Ok(finish(py, result, pixel_area))
+    })
+}
+
+// --- 4. Fmask-style Cloud Detection ---
+
+/// SCL-compatible codes written by `fmask`.
+const FMASK_NODATA: u8 = 0;
+const FMASK_SHADOW: u8 = 3;
+const FMASK_VEGETATION: u8 = 4;
+const FMASK_NOT_VEGETATED: u8 = 5;
+const FMASK_WATER: u8 = 6;
+const FMASK_CLOUD: u8 = 9;
+const FMASK_SNOW: u8 = 11;
+
+const KELVIN: f64 = 273.15;
+/// Clear-land NDVI above which a pixel is coded as vegetation.
+const FMASK_VEGETATION_NDVI: f64 = 0.2;
+/// Percentile of the clear-sky distributions used for the dynamic
+/// thresholds (Zhu & Woodcock 2012).
+const FMASK_PERCENTILE: f64 = 82.5;
+/// Below this share of clear-land pixels every potential cloud pixel is cloud.
+const FMASK_MIN_CLEAR_LAND: f64 = 0.001;
+
+/// Pass-one Fmask tests of one pixel (Zhu & Woodcock 2012, Fmask 3.3 cirrus
+/// term). The test outcomes are packed into one bitmask and the probability
+/// terms kept in f32, so a scene costs 20 bytes per pixel. Temperatures are
+/// in °C.
+#[derive(Clone, Copy, Debug, Default)]
+struct FmaskPixel {
+    /// Bitwise OR of the passed tests (`FmaskPixel::VALID`, ...).
+    tests: u8,
+    /// Spectral variability probability `1 - max(|NDVI|, |NDSI|, whiteness)`.
+    variability: f32,
+    /// Water brightness probability `min(SWIR1, 0.11) / 0.11`.
+    brightness: f32,
+    /// Cirrus probability `cirrus / 0.04`, 0 without a cirrus band.
+    cirrus: f32,
+    /// Brightness temperature, NaN without a thermal band.
+    bt: f32,
+}
+
+impl FmaskPixel {
+    const VALID: u8 = 1;
+    /// Potential cloud pixel: basic, whiteness, HOT and B4/B5 tests.
+    const PCP: u8 = 1 << 1;
+    const WATER: u8 = 1 << 2;
+    /// Water clear enough to calibrate the water temperature.
+    const CLEAR_WATER: u8 = 1 << 3;
+    const SNOW: u8 = 1 << 4;
+    /// NDVI above `FMASK_VEGETATION_NDVI`.
+    const VEGETATED: u8 = 1 << 5;
+
+    fn has(&self, test: u8) -> bool {
+        self.tests & test != 0
+    }
+
+    fn clear_land(&self) -> bool {
+        self.has(Self::VALID) && !self.has(Self::PCP | Self::WATER)
+    }
+
+    fn bt(&self) -> f64 {
+        f64::from(self.bt)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn fmask_pixel(
+    blue: f64,
+    green: f64,
+    red: f64,
+    nir: f64,
+    swir1: f64,
+    swir2: f64,
+    cirrus: Option<f64>,
+    bt: Option<f64>,
+) -> FmaskPixel {
+    let bands = [blue, green, red, nir, swir1, swir2];
+    if !bands
+        .iter()
+        .chain(&cirrus)
+        .chain(&bt)
+        .all(|v| v.is_finite())
+    {
+        return FmaskPixel::default();
+    }
+    let bt = bt.map_or(f64::NAN, |t| t - KELVIN);
+    let ndvi = normalized_difference_px(nir, red);
+    let ndsi = normalized_difference_px(green, swir1);
+    let mean_vis = (blue + green + red) / 3.0;
+    let whiteness = if mean_vis > 0.0 {
+        [blue, green, red]
+            .iter()
+            .map(|v| (v - mean_vis).abs())
+            .sum::<f64>()
+            / mean_vis
+    } else {
+        f64::INFINITY
+    };
+    // Without a thermal band (NaN) the temperature tests pass.
+    let colder = |limit: f64| bt.is_nan() || bt < limit;
+    let basic = swir2 > 0.03 && ndsi < 0.8 && ndvi < 0.8 && colder(27.0);
+    let hot = blue - 0.5 * red - 0.08 > 0.0;
+    let b4_b5 = swir1 > 0.0 && nir / swir1 > 0.75;
+    let water = (ndvi < 0.01 && nir < 0.11) || (ndvi < 0.1 && ndvi > 0.0 && nir < 0.05);
+    let tests = [
+        (true, FmaskPixel::VALID),
+        (basic && whiteness < 0.7 && hot && b4_b5, FmaskPixel::PCP),
+        (water, FmaskPixel::WATER),
+        (water && swir2 < 0.03, FmaskPixel::CLEAR_WATER),
+        (
+            ndsi > 0.15 && nir > 0.11 && green > 0.1 && colder(3.8),
+            FmaskPixel::SNOW,
+        ),
+        (ndvi > FMASK_VEGETATION_NDVI, FmaskPixel::VEGETATED),
+    ];
+    FmaskPixel {
+        tests: tests
+            .iter()
+            .filter(|(passed, _)| *passed)
+            .fold(0, |bits, (_, bit)| bits | bit),
+        variability: (1.0 - ndvi.abs().max(ndsi.abs()).max(whiteness)) as f32,
+        brightness: (swir1.min(0.11) / 0.11) as f32,
+        cirrus: cirrus.map_or(0.0, |c| c / 0.04) as f32,
+        bt: bt as f32,
+    }
+}
+
+/// Linearly interpolated percentile `q` (0-100) of `values`, or `None` when
+/// empty. `values` is reordered in place.
+fn percentile(values: &mut [f64], q: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let pos = q / 100.0 * (values.len() - 1) as f64;
+    let k = pos.floor() as usize;
+    let (_, &mut low, upper) = values.select_nth_unstable_by(k, f64::total_cmp);
+    let high = upper.iter().copied().min_by(f64::total_cmp).unwrap_or(low);
+    Some(low + (pos - k as f64) * (high - low))
+}
+
+/// Fmask class codes and cloud probability of a scene.
+pub(crate) struct Fmask<R> {
+    /// SCL-compatible codes: 0 nodata, 3 cloud shadow, 4 vegetation,
+    /// 5 not vegetated, 6 water, 9 cloud, 11 snow.
+    pub classes: Array2<u8>,
+    /// Land or water cloud probability (NaN where invalid).
+    pub probability: Array2<R>,
+}
+
+/// Fmask-style rule-based cloud, cloud shadow and snow detection.
+///
+/// Potential cloud pixels are refined with land/water cloud probabilities
+/// whose temperature terms are calibrated on the scene's clear-sky pixels;
+/// shadows are projected from the cloud layer when `shadow_offsets` is
+/// given. `bands` are blue, green, red, NIR, SWIR1, SWIR2 reflectance and
+/// `thermal` is brightness temperature in kelvin.
+pub(crate) fn fmask_impl<A: Sample>(
+    bands: [ArrayView2<A>; 6],
+    cirrus: Option<ArrayView2<A>>,
+    thermal: Option<ArrayView2<f64>>,
+    shadow_offsets: Option<&[(isize, isize)]>,
+    cloud_probability: f64,
+    scaling: Scaling,
+) -> Result<Fmask<A::Out>, CoreError> {
+    let dim = bands[0].dim();
+    let shapes_match = bands.iter().all(|b| b.dim() == dim)
+        && cirrus.is_none_or(|c| c.dim() == dim)
+        && thermal.is_none_or(|t| t.dim() == dim);
+    if !shapes_match {
+        return Err(CoreError::InvalidArgument(
+            "Fmask bands, cirrus and thermal inputs must have the same shape".to_string(),
+        ));
+    }
+    if !cloud_probability.is_finite() {
+        return Err(CoreError::InvalidArgument(format!(
+            "cloud_probability must be finite, got {}",
+            cloud_probability
+        )));
+    }
+    let reflectance = |band: &ArrayView2<A>, idx: (usize, usize)| scaling.apply(band[idx].to_f64());
+    let mut pixels = Array2::<FmaskPixel>::default(dim);
+    Zip::indexed(&mut pixels).par_for_each(|idx, p| {
+        let [b, g, r, n, s1, s2] = bands.each_ref().map(|band| reflectance(band, idx));
+        let c = cirrus.as_ref().map(|band| reflectance(band, idx));
+        *p = fmask_pixel(b, g, r, n, s1, s2, c, thermal.map(|t| t[idx]));
+    });
+
+    let valid = pixels.iter().filter(|p| p.has(FmaskPixel::VALID)).count();
+    let clear_land = pixels.iter().filter(|p| p.clear_land()).count();
+    let enough_clear = clear_land > 0 && clear_land as f64 >= FMASK_MIN_CLEAR_LAND * valid as f64;
+    let temperatures = |keep: fn(&FmaskPixel) -> bool| -> Vec<f64> {
+        pixels
+            .iter()
+            .filter(|p| keep(p) && p.bt.is_finite())
+            .map(FmaskPixel::bt)
+            .collect()
+    };
+    let mut land_temperatures = temperatures(FmaskPixel::clear_land);
+    let t_low = percentile(&mut land_temperatures, 100.0 - FMASK_PERCENTILE);
+    let t_high = percentile(&mut land_temperatures, FMASK_PERCENTILE);
+    let t_water = percentile(
+        &mut temperatures(|p| p.has(FmaskPixel::CLEAR_WATER)),
+        FMASK_PERCENTILE,
+    );
+
+    let land_probability = |p: &FmaskPixel| {
+        let temp = match (t_low, t_high) {
+            (Some(lo), Some(hi)) => ((hi + 4.0 - p.bt()) / (hi - lo + 8.0)).max(0.0),
+            _ => 1.0,
+        };
+        temp * f64::from(p.variability) + f64::from(p.cirrus)
+    };
+    let water_probability = |p: &FmaskPixel| {
+        let temp = t_water.map_or(1.0, |tw| ((tw - p.bt()) / 4.0).max(0.0));
+        temp * f64::from(p.brightness) + f64::from(p.cirrus)
+    };
+    let land_threshold = percentile(
+        &mut pixels
+            .iter()
+            .filter(|p| p.clear_land())
+            .map(land_probability)
+            .collect::<Vec<_>>(),
+        FMASK_PERCENTILE,
+    )
+    .map_or(f64::INFINITY, |t| t + cloud_probability);
+
+    let mut probability = Array2::from_elem(dim, A::Out::from_f64(f64::NAN));
+    let mut cloud = Array2::from_elem(dim, false);
+    Zip::from(&mut probability)
+        .and(&mut cloud)
+        .and(&pixels)
+        .par_for_each(|prob, is_cloud, p| {
+            if !p.has(FmaskPixel::VALID) {
+                return;
+            }
+            let is_water = p.has(FmaskPixel::WATER);
+            let land = land_probability(p);
+            let water = water_probability(p);
+            *prob = A::Out::from_f64(if is_water { water } else { land });
+            let cold = t_low.is_some_and(|lo| p.bt() < lo - 35.0);
+            *is_cloud = if enough_clear {
+                (p.has(FmaskPixel::PCP)
+                    && if is_water {
+                        water > 0.5
+                    } else {
+                        land > land_threshold
+                    })
+                    || (!is_water && land > 0.99)
+                    || cold
+            } else {
+                p.has(FmaskPixel::PCP)
+            };
+        });
+
+    let shadow = match shadow_offsets {
+        Some(offsets) => Some(project_cloud_shadows(
+            cloud.view(),
+            bands[3],
+            Some(bands[4]),
+            offsets,
+            0.15,
+            0.10,
+            scaling,
+        )?),
+        None => None,
+    };
+    let mut classes = Array2::<u8>::zeros(dim);
+    Zip::indexed(&mut classes).par_for_each(|idx, code| {
+        let p = &pixels[idx];
+        *code = if !p.has(FmaskPixel::VALID) {
+            FMASK_NODATA
+        } else if cloud[idx] {
+            FMASK_CLOUD
+        } else if shadow.as_ref().is_some_and(|s| s[idx]) {
+            FMASK_SHADOW
+        } else if p.has(FmaskPixel::SNOW) {
+            FMASK_SNOW
+        } else if p.has(FmaskPixel::WATER) {
+            FMASK_WATER
+        } else if p.has(FmaskPixel::VEGETATED) {
+            FMASK_VEGETATION
+        } else {
+            FMASK_NOT_VEGETATED
+        };
+    });
+    Ok(Fmask {
+        classes,
+        probability,
+    })
+}
+
+/// Fmask-style cloud, cloud shadow and snow mask from reflectance bands.
+///
+/// Implements the Fmask pass-one tests (Zhu & Woodcock 2012): potential
+/// cloud pixels (basic, whiteness, HOT and NIR/SWIR1 ratio tests), the
+/// water test and land/water cloud probabilities whose temperature terms
+/// are calibrated on the scene's clear pixels, plus the Fmask 3.3 cirrus
+/// probability. Without a thermal band the probabilities use spectral terms
+/// only. Cloud shadows are projected from the cloud layer when the sun
+/// angles are given (see `cloud_shadow_mask`).
+///
+/// # Arguments
+/// * `blue`, `green`, `red`, `nir`, `swir1`, `swir2` - `(y, x)` reflectance
+/// * `cirrus` - Optional cirrus band reflectance (Landsat 8/9 B9, S2 B10)
+/// * `thermal` - Optional brightness temperature in kelvin
+/// * `sun_azimuth`, `sun_zenith` - Solar angles in degrees for shadow
+///   projection; both or neither
+/// * `pixel_size` - Pixel size in metres (shadow projection)
+/// * `cloud_probability` - Added to the 82.5th percentile of clear-land
+///   cloud probability to give the land cloud threshold (default 0.2)
+/// * `cloud_heights` - Candidate cloud heights in metres
+/// * `scale`, `offset` - DN conversion applied to the reflectance bands
+///
+/// # Returns
+/// `(classes, probability)`: a uint8 SCL-compatible mask (0 nodata,
+/// 3 cloud shadow, 4 vegetation, 5 not vegetated, 6 water, 9 cloud,
+/// 11 snow) usable with `mask_with_scl`, and the land/water cloud
+/// probability.
+#[pyfunction]
+#[pyo3(signature = (
+    blue,
+    green,
+    red,
+    nir,
+    swir1,
+    swir2,
+    cirrus=None,
+    thermal=None,
+    sun_azimuth=None,
+    sun_zenith=None,
+    pixel_size=30.0,
+    cloud_probability=0.2,
+    cloud_heights=(200.0, 12000.0),
+    scale=None,
+    offset=None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn fmask(
+    py: Python<'_>,
+    blue: &PyAny,
+    green: &PyAny,
+    red: &PyAny,
+    nir: &PyAny,
+    swir1: &PyAny,
+    swir2: &PyAny,
+    cirrus: Option<&PyAny>,
+    thermal: Option<PyReadonlyArray2<f64>>,
+    sun_azimuth: Option<f64>,
+    sun_zenith: Option<f64>,
+    pixel_size: f64,
+    cloud_probability: f64,
+    cloud_heights: (f64, f64),
+    scale: Option<f64>,
+    offset: Option<f64>,
+) -> PyResult<(PyObject, PyObject)> {
+    let scaling = Scaling::from_args(scale, offset)?;
+    let offsets = match (sun_azimuth, sun_zenith) {
+        (Some(az), Some(zen)) => Some(shadow_offsets(az, zen, pixel_size, cloud_heights)?),
+        (None, None) => None,
+        _ => {
+            return Err(CoreError::InvalidArgument(
+                "sun_azimuth and sun_zenith must be given together".to_string(),
+            )
+            .into())
+        }
+    };
+    let thermal = thermal.as_ref().map(|t| t.as_array());
+    let mut inputs = vec![blue, green, red, nir, swir1, swir2];
+    inputs.extend(cirrus);
+    let operands = Operand::extract_common(&inputs)?;
+
+    fn as_2d<A>(band: ArrayViewD<'_, A>) -> Result<ArrayView2<'_, A>, CoreError> {
+        band.into_dimensionality::<Ix2>()
+            .map_err(|_| CoreError::InvalidArgument("fmask expects 2-D (y, x) bands".to_string()))
+    }
+    kernels::dispatch!(all operands, |views| {
+        let views = views.into_iter().map(as_2d).collect::<Result<Vec<_>, _>>()?;
+        let bands = [0, 1, 2, 3, 4, 5].map(|i| views[i]);
+        let cirrus = views.get(6).cloned();
+        let result = py.allow_threads(|| {
+            fmask_impl(
+                bands,
+                cirrus,
+                thermal,
+                offsets.as_deref(),
+                cloud_probability,
+                scaling,
+            )
+        })?;
+        Ok((
+            kernels::into_py(py, result.classes.into_dyn()),
+            kernels::into_py(py, result.probability.into_dyn()),
+        ))
+    })
+}
+
+#[cfg(test)]
+mod burn_severity_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use ndarray::{array, Array1};
+
+    /// Pre-fire vegetation (NBR 0.6) burned to post-fire NBR values `post`.
+    fn bands(post: &[f64]) -> [ArrayD<f64>; 4] {
+        let n = post.len();
+        let pre_nir = Array1::from_elem(n, 0.4).into_dyn();
+        let pre_swir2 = Array1::from_elem(n, 0.1).into_dyn();
+        // NBR = (nir - s) / (nir + s) with nir + s = 0.5.
+        let post_nir: Array1<f64> = post.iter().map(|p| 0.25 * (1.0 + p)).collect();
+        let post_swir2: Array1<f64> = post.iter().map(|p| 0.25 * (1.0 - p)).collect();
+        [
+            pre_nir,
+            pre_swir2,
+            post_nir.into_dyn(),
+            post_swir2.into_dyn(),
+        ]
+    }
+
+    fn run(
+        b: &[ArrayD<f64>; 4],
+        index: SeverityIndex,
+        reference: Option<ArrayViewD<bool>>,
+        water: Option<ArrayViewD<bool>>,
+    ) -> BurnSeverity<f64> {
+        burn_severity_impl(
+            b[0].view(),
+            b[1].view(),
+            b[2].view(),
+            b[3].view(),
+            index,
+            index.default_thresholds(),
+            reference,
+            water,
+            Scaling::IDENTITY,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_key_benson_classes_and_counts() {
+        // dNBR = 0.6 - post.
+        let b = bands(&[1.0, 0.8, 0.6, 0.4, 0.2, 0.0, -0.2, f64::NAN]);
+        let r = run(&b, SeverityIndex::Dnbr, None, None);
+        assert_relative_eq!(r.index[3], 0.2, epsilon = 1e-12);
+        assert!(r.index[7].is_nan());
+        assert_eq!(r.classes, array![1, 2, 3, 4, 5, 6, 7, 0].into_dyn());
+        assert_eq!(r.counts, vec![1; 8]);
+        assert_eq!(r.offset, 0.0);
+    }
+
+    #[test]
+    fn test_reference_offset_and_water_mask() {
+        let b = bands(&[0.55, 0.55, 0.0, 0.0]);
+        let reference = array![true, true, false, false].into_dyn();
+        let water = array![false, false, false, true].into_dyn();
+        let r = run(
+            &b,
+            SeverityIndex::Dnbr,
+            Some(reference.view()),
+            Some(water.view()),
+        );
+        assert_relative_eq!(r.offset, 0.05, epsilon = 1e-12);
+        assert_relative_eq!(r.index[2], 0.55, epsilon = 1e-12);
+        assert!(r.index[3].is_nan());
+        assert_eq!(r.classes, array![3, 3, 6, 0].into_dyn());
+
+        let nothing = array![false, false, false, true].into_dyn();
+        let err = burn_severity_impl(
+            b[0].view(),
+            b[1].view(),
+            b[2].view(),
+            b[3].view(),
+            SeverityIndex::Dnbr,
+            &KEY_BENSON_THRESHOLDS,
+            Some(nothing.view()),
+            Some(water.view()),
+            Scaling::IDENTITY,
+        );
+        assert!(matches!(err, Err(CoreError::NotEnoughData(_))));
+    }
+
+    #[test]
+    fn test_relative_indices() {
+        let b = bands(&[0.0]);
+        let rbr = run(&b, SeverityIndex::Rbr, None, None);
+        assert_relative_eq!(rbr.index[0], 0.6 / 1.6, epsilon = 1e-12);
+        let rdnbr = run(&b, SeverityIndex::Rdnbr, None, None);
+        assert_relative_eq!(rdnbr.index[0], 0.6 / 0.6f64.sqrt(), epsilon = 1e-12);
+        assert_eq!(rdnbr.classes[0], 4);
+        assert_eq!(rdnbr.counts.len(), 5);
+    }
+
+    #[test]
+    fn test_rbr_uses_parks_thresholds() {
+        // dNBR 0.2 and 0.6 from pre-fire NBR 0.6: RBR 0.125 and 0.375.
+        let b = bands(&[0.4, 0.0]);
+        let dnbr = run(&b, SeverityIndex::Dnbr, None, None);
+        let rbr = run(&b, SeverityIndex::Rbr, None, None);
+        assert_eq!(dnbr.classes, array![4, 6].into_dyn());
+        // Low and high severity on the 4-class Parks et al. scale.
+        assert_eq!(rbr.classes, array![2, 4].into_dyn());
+        assert_ne!(rbr.classes, dnbr.classes);
+        assert_eq!(rbr.counts.len(), 5);
+    }
+
+    #[test]
+    fn test_thresholds_are_validated() {
+        assert!(check_thresholds(&[]).is_err());
+        assert!(check_thresholds(&[0.1, 0.1]).is_err());
+        assert!(check_thresholds(&[0.1, f64::NAN]).is_err());
+        assert!(SeverityIndex::parse("nbr").is_err());
+    }
+}
+
+#[cfg(test)]
+mod fmask_tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use ndarray::Array3;
+
+    const CLOUD: [f64; 6] = [0.45, 0.45, 0.45, 0.5, 0.45, 0.35];
+    const VEGETATION: [f64; 6] = [0.03, 0.06, 0.04, 0.35, 0.18, 0.08];
+    const WATER: [f64; 6] = [0.06, 0.05, 0.03, 0.02, 0.01, 0.005];
+    const SNOW: [f64; 6] = [0.8, 0.8, 0.78, 0.7, 0.1, 0.05];
+    const DARK: [f64; 6] = [0.02, 0.03, 0.02, 0.06, 0.05, 0.03];
+
+    fn pixel(b: [f64; 6]) -> FmaskPixel {
+        fmask_pixel(b[0], b[1], b[2], b[3], b[4], b[5], None, None)
+    }
+
+    fn scene(fill: impl Fn(usize, usize) -> [f64; 6]) -> Array3<f64> {
+        Array3::from_shape_fn((6, 10, 10), |(b, y, x)| fill(y, x)[b])
+    }
+
+    fn run(
+        cube: &Array3<f64>,
+        thermal: Option<ArrayView2<f64>>,
+        offsets: Option<&[(isize, isize)]>,
+    ) -> Array2<u8> {
+        let bands = [0, 1, 2, 3, 4, 5].map(|b| cube.index_axis(Axis(0), b));
+        fmask_impl(bands, None, thermal, offsets, 0.2, Scaling::IDENTITY)
+            .unwrap()
+            .classes
+    }
+
+    #[test]
+    fn test_pixel_tests() {
+        let cloud = pixel(CLOUD);
+        assert!(cloud.has(FmaskPixel::VALID | FmaskPixel::PCP));
+        assert!(!cloud.has(FmaskPixel::WATER));
+        let veg = pixel(VEGETATION);
+        assert!(veg.clear_land() && veg.has(FmaskPixel::VEGETATED));
+        let water = pixel(WATER);
+        assert!(water.has(FmaskPixel::WATER) && water.has(FmaskPixel::CLEAR_WATER));
+        assert!(!water.has(FmaskPixel::PCP));
+        let snow = pixel(SNOW);
+        assert!(snow.has(FmaskPixel::SNOW) && snow.has(FmaskPixel::PCP));
+        assert!(!pixel([f64::NAN, 0.1, 0.1, 0.1, 0.1, 0.1]).has(FmaskPixel::VALID));
+        // A warm thermal reading fails the basic test.
+        let warm = fmask_pixel(0.45, 0.45, 0.45, 0.5, 0.45, 0.35, None, Some(310.0));
+        assert!(!warm.has(FmaskPixel::PCP));
+        let cirrus = fmask_pixel(0.45, 0.45, 0.45, 0.5, 0.45, 0.35, Some(0.02), None);
+        assert_relative_eq!(cirrus.cirrus, 0.5);
+    }
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(percentile(&mut [], 50.0), None);
+        assert_eq!(percentile(&mut [3.0], 82.5), Some(3.0));
+        assert_relative_eq!(percentile(&mut [4.0, 1.0, 3.0, 2.0], 50.0).unwrap(), 2.5);
+        assert_relative_eq!(percentile(&mut [0.0, 10.0], 82.5).unwrap(), 8.25);
+    }
+
+    #[test]
+    fn test_scene_classes() {
+        let cube = scene(|y, x| match (y, x) {
+            (2..=4, 2..=4) => CLOUD,
+            (0, 9) => SNOW,
+            (9, 0) => [f64::NAN; 6],
+            (8.., _) => WATER,
+            _ => VEGETATION,
+        });
+        let classes = run(&cube, None, None);
+        assert_eq!(classes[[3, 3]], FMASK_CLOUD);
+        assert_eq!(classes[[0, 9]], FMASK_SNOW);
+        assert_eq!(classes[[8, 5]], FMASK_WATER);
+        assert_eq!(classes[[0, 0]], FMASK_VEGETATION);
+        assert_eq!(classes.iter().filter(|&&c| c == FMASK_CLOUD).count(), 9);
+    }
+
+    #[test]
+    fn test_shadow_projection_and_cold_cloud() {
+        let cube = scene(|y, x| match (y, x) {
+            (4..=5, 6..=7) => CLOUD,
+            (4..=5, 3..=4) => DARK,
+            (0, 0) => DARK,
+            _ => VEGETATION,
+        });
+        // Sun due east at 45 degrees, 90 m clouds on 30 m pixels: 3 px west.
+        let offsets = crate::masking::shadow_offsets(90.0, 45.0, 30.0, (90.0, 90.0)).unwrap();
+        let classes = run(&cube, None, Some(&offsets));
+        assert!(classes
+            .slice(ndarray::s![4..=5, 3..=4])
+            .iter()
+            .all(|&c| c == FMASK_SHADOW));
+        assert_eq!(classes[[0, 0]], FMASK_VEGETATION);
+
+        // A vegetation-like pixel far colder than the clear land is cloud.
+        let mut thermal = Array2::from_elem((10, 10), 295.0);
+        thermal[[4, 6]] = 250.0;
+        thermal[[9, 9]] = 240.0;
+        let classes = run(&cube, Some(thermal.view()), None);
+        assert_eq!(classes[[9, 9]], FMASK_CLOUD);
+        assert_eq!(classes[[4, 6]], FMASK_CLOUD);
+        assert_eq!(classes[[0, 5]], FMASK_VEGETATION);
+    }
+}
@@ -0,0 +1,87 @@
+import numpy as np
+import pytest
+
+from eo_processor import band_math, ndvi, savi
+
+
+def test_band_math_matches_ndvi():
+    nir = np.array([0.8, 0.7, 0.6], dtype=np.float64)
+    red = np.array([0.2, 0.1, 0.3], dtype=np.float64)
+    result = band_math("(nir - red) / (nir + red)", {"nir": nir, "red": red})
+    assert result.dtype == np.float64
+    assert np.allclose(result, ndvi(nir, red), rtol=1e-12, atol=0.0)
+
+
+def test_band_math_matches_savi_2d():
+    nir = np.array([[0.6, 0.7], [0.5, 0.4]])
+    red = np.array([[0.2, 0.3], [0.1, 0.2]])
+    result = band_math(
+        "(nir - red) / (nir + red + 0.5) * 1.5", {"nir": nir, "red": red}
+    )
+    assert result.shape == nir.shape
+    assert np.allclose(result, savi(nir, red, L=0.5), rtol=1e-12, atol=0.0)
+
+
+def test_band_math_operators_and_functions():
+    x = np.array([-4.0, 1.0, 9.0])
+    result = band_math("sqrt(abs(x)) + x ^ 2 - max(x, 0) * 2 + min(x, 1)", {"x": x})
+    expected = np.sqrt(np.abs(x)) + x**2 - np.maximum(x, 0) * 2 + np.minimum(x, 1)
+    assert np.allclose(result, expected)
+
+
+def test_band_math_power_operator_alias_and_unary_minus():
+    x = np.array([2.0, 3.0])
+    assert np.allclose(band_math("-x ** 2", {"x": x}), -(x**2))
+    assert np.allclose(band_math("2 ^ 3 ^ 2 + x * 0", {"x": x}), 2.0 ** 9)
+
+
+def test_band_math_zero_denominator():
+    a = np.array([0.0, 0.5])
+    b = np.array([0.0, -0.5])
+    result = band_math("(a - b) / (a + b)", {"a": a, "b": b})
+    assert np.array_equal(result, np.zeros(2))
+
+
+def test_band_math_nan_propagates():
+    a = np.array([np.nan, 1.0])
+    result = band_math("a * 2", {"a": a})
+    assert np.isnan(result[0])
+    assert result[1] == 2.0
+
+
+def test_band_math_high_rank():
+    rng = np.random.default_rng(0)
+    a = rng.random((2, 3, 4, 5, 6))
+    b = rng.random((2, 3, 4, 5, 6))
+    result = band_math("a - b", {"a": a, "b": b})
+    assert result.shape == a.shape
+    assert np.allclose(result, a - b)
+
+
+def test_band_math_integer_input_coerced():
+    a = np.array([1, 2, 3], dtype=np.uint16)
+    result = band_math("a / 2", {"a": a})
+    assert result.dtype == np.float64
+    assert np.allclose(result, [0.5, 1.0, 1.5])
+
+
+def test_band_math_extra_bands_ignored():
+    a = np.array([1.0, 2.0])
+    result = band_math("a + 1", {"a": a, "unused": np.zeros(7)})
+    assert np.allclose(result, [2.0, 3.0])
+
+
+def test_band_math_shape_mismatch():
+    with pytest.raises(ValueError, match="Shape mismatch"):
+        band_math("a + b", {"a": np.zeros(3), "b": np.zeros(4)})
+
+
+def test_band_math_unknown_band():
+    with pytest.raises(ValueError, match="unknown band 'swir'"):
+        band_math("nir - swir", {"nir": np.zeros(3)})
+
+
+@pytest.mark.parametrize("expr", ["", "(a + 1", "a +", "a $ 1", "foo(a)", "a a"])
+def test_band_math_invalid_expression(expr):
+    with pytest.raises(ValueError):
+        band_math(expr, {"a": np.zeros(3)})
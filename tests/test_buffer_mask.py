@@ -0,0 +1,48 @@
+import numpy as np
+import pytest
+
+from eo_processor import buffer_mask, mask_with_scl
+
+
+def _disk(shape, center, radius_px):
+    yy, xx = np.indices(shape)
+    return (yy - center[0]) ** 2 + (xx - center[1]) ** 2 <= radius_px**2
+
+
+def test_circular_buffer():
+    mask = np.zeros((21, 21), dtype=bool)
+    mask[10, 10] = True
+    out = buffer_mask(mask, 100, 20)
+    assert out.dtype == np.bool_
+    np.testing.assert_array_equal(out, _disk(mask.shape, (10, 10), 5))
+
+
+def test_anisotropic_pixels():
+    mask = np.zeros((11, 11), dtype=np.uint8)
+    mask[5, 5] = 1
+    out = buffer_mask(mask, 30, (10, 30))
+    assert out[2, 5] and out[5, 6] and not out[5, 7] and not out[1, 5]
+
+
+def test_per_class_stack_and_mask_with_scl():
+    scl = np.full((3, 15, 15), 4, dtype=np.uint8)
+    scl[0, 7, 7] = 9
+    scl[1, 7, 7] = 3
+    out = buffer_mask(scl, {9: 60, 3: 20}, 10)
+    assert out.shape == scl.shape
+    np.testing.assert_array_equal(out[0], _disk((15, 15), (7, 7), 6))
+    np.testing.assert_array_equal(out[1], _disk((15, 15), (7, 7), 2))
+    assert not out[2].any()
+
+    data = np.ones((3, 2, 15, 15), dtype=np.float32)
+    clear = mask_with_scl(data, out.astype(np.uint8), mask_codes=[1])
+    np.testing.assert_array_equal(np.isnan(clear), np.broadcast_to(out[:, None], data.shape))
+
+
+def test_errors():
+    with pytest.raises(ValueError):
+        buffer_mask(np.zeros(5, dtype=bool), 10, 10)
+    with pytest.raises(ValueError, match="pixel_size"):
+        buffer_mask(np.zeros((3, 3), dtype=bool), 10, 0)
+    with pytest.raises(ValueError, match="0..255"):
+        buffer_mask(np.array([[300]]), 10, 10)
@@ -0,0 +1,67 @@
+import numpy as np
+import pytest
+
+from eo_processor import burn_severity, dnbr, rbr
+
+
+def _bands(post_nbr):
+    post_nbr = np.asarray(post_nbr, dtype=np.float64)
+    pre_nir = np.full(post_nbr.shape, 0.4)
+    pre_swir2 = np.full(post_nbr.shape, 0.1)
+    return pre_nir, pre_swir2, 0.25 * (1 + post_nbr), 0.25 * (1 - post_nbr)
+
+
+def test_matches_dnbr_and_key_benson_classes():
+    bands = _bands([[1.0, 0.8, 0.6, 0.4], [0.2, 0.0, -0.2, np.nan]])
+    index, classes, counts, offset = burn_severity(*bands)
+    np.testing.assert_allclose(index, dnbr(*bands))
+    assert classes.dtype == np.uint8
+    np.testing.assert_array_equal(classes, [[1, 2, 3, 4], [5, 6, 7, 0]])
+    assert counts.tolist() == [1] * 8
+    assert offset == 0.0
+    np.testing.assert_allclose(burn_severity(*bands, index="rbr")[0], rbr(*bands))
+
+
+def test_reference_offset_water_and_custom_thresholds():
+    bands = _bands([0.55, 0.55, 0.0, 0.0])
+    reference = np.array([1, 1, 0, 0])
+    water = np.array([False, False, False, True])
+    index, classes, counts, offset = burn_severity(
+        *bands, reference=reference, water_mask=water, thresholds=[0.1, 0.5]
+    )
+    assert offset == pytest.approx(0.05)
+    assert index[2] == pytest.approx(0.55)
+    assert np.isnan(index[3])
+    np.testing.assert_array_equal(classes, [1, 1, 3, 0])
+    assert counts.tolist() == [1, 2, 0, 1]
+
+
+def test_rdnbr_and_errors():
+    bands = _bands([0.0])
+    index, classes, counts, _ = burn_severity(*bands, index="rdnbr")
+    assert index[0] == pytest.approx(0.6 / np.sqrt(0.6))
+    assert classes[0] == 4 and len(counts) == 5
+    with pytest.raises(ValueError, match="increasing"):
+        burn_severity(*bands, thresholds=[0.3, 0.1])
+    with pytest.raises(ValueError, match="index"):
+        burn_severity(*bands, index="nbr")
+
+
+def test_rbr_defaults_to_parks_thresholds():
+    bands = _bands([0.4, 0.0])  # RBR 0.125 and 0.375
+    _, dnbr_classes, _, _ = burn_severity(*bands)
+    _, rbr_classes, counts, _ = burn_severity(*bands, index="rbr")
+    np.testing.assert_array_equal(dnbr_classes, [4, 6])
+    np.testing.assert_array_equal(rbr_classes, [2, 4])
+    assert len(counts) == 5
+
+
+def test_pixel_area_returns_class_areas():
+    bands = _bands([1.0, 0.8, 0.6, 0.6])
+    result = burn_severity(*bands, pixel_area=0.09)
+    assert len(result) == 5
+    counts, areas = result[2], result[4]
+    assert areas.dtype == np.float64
+    np.testing.assert_allclose(areas, counts * 0.09)
+    with pytest.raises(ValueError, match="pixel_area"):
+        burn_severity(*bands, pixel_area=0.0)
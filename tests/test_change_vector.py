@@ -0,0 +1,61 @@
+import numpy as np
+import pytest
+
+from eo_processor import change_vector_analysis
+
+
+def _pair(seed=0):
+    rng = np.random.default_rng(seed)
+    pre = rng.uniform(0.1, 0.3, size=(3, 20, 20))
+    post = pre + rng.normal(0, 0.005, size=pre.shape)
+    post[:, :5, :5] += np.array([0.2, -0.1, 0.3])[:, None, None]
+    return pre, post
+
+
+def test_magnitude_and_angle():
+    pre, post = _pair()
+    magnitude, angle, change, t = change_vector_analysis(pre, post)
+    assert magnitude.shape == angle.shape == (20, 20)
+    np.testing.assert_allclose(magnitude, np.linalg.norm(post - pre, axis=0))
+    d = (post - pre)[:, 0, 0]
+    expected = np.degrees(np.arccos(d.sum() / (np.sqrt(3) * np.linalg.norm(d))))
+    assert angle[0, 0] == pytest.approx(expected)
+    assert change is None and t is None
+
+    two = change_vector_analysis(pre[:2], post[:2])[1]
+    d2 = (post - pre)[:2]
+    polar = np.degrees(np.arctan2(d2[1], d2[0])) % 360
+    np.testing.assert_allclose(two, polar)
+
+
+def test_sectors_and_nan():
+    pre, post = _pair()
+    post[1, 10, 10] = np.nan
+    magnitude, sector, _, _ = change_vector_analysis(pre, post, direction="sector")
+    assert sector.dtype == np.int32
+    # Bands 0 and 2 increased, band 1 decreased.
+    assert np.all(sector[:5, :5] == 1 + 1 + 4)
+    assert sector[10, 10] == 0 and np.isnan(magnitude[10, 10])
+
+
+@pytest.mark.parametrize("method", ["otsu", "kittler"])
+def test_automatic_threshold_finds_changed_block(method):
+    pre, post = _pair()
+    magnitude, _, change, t = change_vector_analysis(pre, post, threshold=method)
+    assert change.dtype == np.bool_
+    expected = np.zeros((20, 20), dtype=bool)
+    expected[:5, :5] = True
+    np.testing.assert_array_equal(change, expected)
+    np.testing.assert_array_equal(change, magnitude > t)
+
+
+def test_fixed_threshold_and_errors():
+    pre, post = _pair()
+    _, _, change, t = change_vector_analysis(pre, post, threshold=1.0)
+    assert t == 1.0 and not change.any()
+    with pytest.raises(ValueError, match="shape"):
+        change_vector_analysis(pre, post[:, :10])
+    with pytest.raises(ValueError, match="threshold"):
+        change_vector_analysis(pre, post, threshold="mean")
+    with pytest.raises(ValueError, match="NaN"):
+        change_vector_analysis(pre, post, threshold=float("nan"))
@@ -0,0 +1,69 @@
+import numpy as np
+import pytest
+from eo_processor import fill_holes, majority_filter, sieve
+
+
+def test_sieve_merges_small_regions():
+    classes = np.array(
+        [
+            [1, 1, 2, 2, 2],
+            [1, 3, 1, 2, 2],
+            [1, 1, 1, 2, 2],
+            [4, 4, 2, 2, 2],
+        ],
+        dtype=np.uint8,
+    )
+    out = sieve(classes, threshold=3)
+    assert out.dtype == np.uint8
+    np.testing.assert_array_equal(
+        out,
+        [
+            [1, 1, 2, 2, 2],
+            [1, 1, 1, 2, 2],
+            [1, 1, 1, 2, 2],
+            [2, 2, 2, 2, 2],
+        ],
+    )
+    np.testing.assert_array_equal(sieve(classes, threshold=1), classes)
+
+
+def test_sieve_nodata_and_connectivity():
+    classes = np.zeros((5, 5), dtype=np.int32)
+    classes[1:4, 1:4] = 1
+    classes[2, 2] = 2
+    classes[0, 0] = 3  # touches the 1s only diagonally
+
+    # The nodata background never absorbs the speckle in the corner.
+    out = sieve(classes, threshold=2, nodata=0)
+    assert out[2, 2] == 1
+    assert out[0, 0] == 3
+    out = sieve(classes, threshold=2, connectivity=8, nodata=0)
+    assert out[0, 0] == 1
+
+
+def test_fill_holes():
+    mask = np.ones((5, 5), dtype=bool)
+    mask[1:3, 1:3] = False
+    mask[4, 2] = False  # open to the border
+    out = fill_holes(mask)
+    assert out.dtype == np.uint8
+    assert out[1:3, 1:3].all()
+    assert out[4, 2] == 0
+
+
+def test_majority_filter():
+    classes = np.ones((5, 5), dtype=np.int64)
+    classes[2, 2] = 7
+    classes[0, :] = 0
+    out = majority_filter(classes)
+    assert out[2, 2] == 1
+    out = majority_filter(classes, kernel_size=3, footprint="cross", nodata=0)
+    np.testing.assert_array_equal(out[0], 0)
+    assert out[2, 2] == 1
+
+
+def test_class_map_requires_integers():
+    with pytest.raises(TypeError, match="integer"):
+        sieve(np.ones((3, 3)), threshold=2)
+    with pytest.raises(ValueError, match="connectivity"):
+        fill_holes(np.ones((3, 3)), connectivity=6)
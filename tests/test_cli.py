@@ -499,5 +499,37 @@ def test_cli_logging(tmp_path):
     assert "All requested indices processed" in log_output
 
 
+def test_cli_list_covers_registry(capsys):
+    from eo_processor import index_registry
+
+    assert cli(["--list"]) == 0
+    listed = capsys.readouterr().out
+    for name, info in index_registry().items():
+        assert name in listed
+        assert info.formula in listed
+
+
+def test_registry_index_with_rededge_flag(tmp_path):
+    # Indices and band flags come from the registry, e.g. NDRE via --rededge.
+    nir = make_band(tmp_path, "nir", [0.8, 0.6])
+    rededge = make_band(tmp_path, "rededge", [0.4, 0.5])
+    out_path = tmp_path / "ndre.npy"
+    code = cli(
+        [
+            "--index",
+            "ndre",
+            "--nir",
+            nir,
+            "--rededge",
+            rededge,
+            "--out",
+            str(out_path),
+        ]
+    )
+    assert code == 0
+    expected = (load_npy(nir) - load_npy(rededge)) / (load_npy(nir) + load_npy(rededge))
+    assert np.allclose(load_npy(out_path), expected, rtol=1e-12)
+
+
 if __name__ == "__main__":  # pragma: no cover
     pytest.main([__file__])
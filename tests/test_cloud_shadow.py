@@ -0,0 +1,56 @@
+import numpy as np
+import pytest
+
+from eo_processor import cloud_shadow_mask
+
+
+def _scene():
+    cloud = np.zeros((20, 20), dtype=bool)
+    cloud[8:11, 12:15] = True
+    nir = np.full((20, 20), 0.3)
+    swir = np.full((20, 20), 0.25)
+    # Sun due east at 45 deg: a cloud at 30-50 m casts its shadow 3-5 px west.
+    nir[8:11, 7:12] = 0.05
+    swir[8:11, 7:12] = 0.05
+    nir[2, 2] = 0.05  # dark water-like pixel away from any cloud
+    return cloud, nir, swir
+
+
+def test_projects_away_from_sun():
+    cloud, nir, swir = _scene()
+    out = cloud_shadow_mask(cloud, nir, 90.0, 45.0, 10.0, cloud_heights=(30, 50))
+    assert out.dtype == np.bool_
+    expected = np.zeros_like(cloud)
+    expected[8:11, 7:12] = True
+    np.testing.assert_array_equal(out, expected)
+    assert not (out & cloud).any()
+    assert not out[2, 2]
+
+
+def test_swir_and_dtype():
+    cloud, nir, swir = _scene()
+    swir[9, 9] = 0.4
+    nir_dn = (nir * 10000).astype(np.uint16)
+    swir_dn = (swir * 10000).astype(np.uint16)
+    out = cloud_shadow_mask(
+        cloud, nir_dn, 90.0, 45.0, 10.0, swir=swir_dn, cloud_heights=(30, 50), scale=1e-4
+    )
+    assert out[9, 8] and not out[9, 9]
+
+
+def test_low_sun_casts_longer_shadows():
+    cloud, nir, _ = _scene()
+    nir[:] = 0.05
+    high = cloud_shadow_mask(cloud, nir, 90.0, 20.0, 10.0, cloud_heights=(50, 50))
+    low = cloud_shadow_mask(cloud, nir, 90.0, 60.0, 10.0, cloud_heights=(50, 50))
+    assert np.argwhere(high)[:, 1].min() > np.argwhere(low)[:, 1].min()
+
+
+def test_invalid_arguments():
+    cloud, nir, _ = _scene()
+    with pytest.raises(ValueError):
+        cloud_shadow_mask(cloud, nir, 90.0, 95.0, 10.0)
+    with pytest.raises(ValueError):
+        cloud_shadow_mask(cloud, nir[:5], 90.0, 45.0, 10.0)
+    with pytest.raises(ValueError):
+        cloud_shadow_mask(cloud, nir, 90.0, 45.0, 10.0, cloud_heights=(500, 100))
@@ -0,0 +1,71 @@
+import numpy as np
+import pytest
+from eo_processor import binary_opening, label_components, zonal_stats
+
+
+def _mask(rows):
+    return np.array([[c == "#" for c in row] for row in rows])
+
+
+def test_label_components_connectivity():
+    mask = _mask(["##..#", "#..#.", "..##.", "#...."])
+
+    labels4, props4 = label_components(mask, connectivity=4)
+    assert labels4.dtype == np.int64
+    assert sorted(props4) == [1, 2, 3, 4]
+    np.testing.assert_array_equal(
+        labels4,
+        [[1, 1, 0, 0, 2], [1, 0, 0, 3, 0], [0, 0, 3, 3, 0], [4, 0, 0, 0, 0]],
+    )
+
+    labels8, props8 = label_components(mask)
+    assert sorted(props8) == [1, 2, 3]
+    assert props8[2].area == 4
+
+
+def test_label_components_properties():
+    mask = _mask(["##...", "##...", "....#"])
+    values = np.ones(mask.shape)
+    values[0, 0] = 5.0
+    values[1, 1] = np.nan
+
+    _, props = label_components(mask.astype(np.uint8), values=values)
+    square = props[1]
+    assert square.label == 1
+    assert square.area == 4
+    assert square.bbox == (0, 0, 2, 2)
+    assert square.centroid == (0.5, 0.5)
+    assert square.perimeter == 8
+    assert np.isclose(square.mean, 7.0 / 3.0)
+
+    dot = props[2]
+    assert dot.bbox == (2, 4, 3, 5)
+    assert dot.perimeter == 4
+
+    _, props = label_components(mask)
+    assert props[1].mean is None
+
+
+def test_label_components_feeds_zonal_stats():
+    mask = np.zeros((20, 20), dtype=np.uint8)
+    mask[2:8, 2:8] = 1
+    mask[12:18, 10:19] = 1
+    mask[0, 19] = 1  # speckle removed by the opening
+    values = np.arange(400, dtype=np.float64).reshape(20, 20)
+
+    labels, props = label_components(binary_opening(mask, 3), values=values)
+    stats = zonal_stats(values, labels)
+
+    assert sorted(props) == [1, 2]
+    for label, p in props.items():
+        assert stats[label].count == p.area
+        assert np.isclose(stats[label].mean, p.mean)
+    assert props[2].area == 54
+
+
+def test_label_components_validation():
+    mask = np.ones((3, 3), dtype=bool)
+    with pytest.raises(ValueError, match="connectivity"):
+        label_components(mask, connectivity=6)
+    with pytest.raises(ValueError, match="shape"):
+        label_components(mask, values=np.ones((2, 3)))
@@ -15,6 +15,22 @@ def test_composite_median_1d_with_nan_skip():
     assert result == 3.0
 
 
+def test_composite_median_float32():
+    stack = np.random.default_rng(20).random((5, 2, 3, 4)).astype(np.float32)
+    stack[2, 0, 1, 1] = np.nan
+    result = composite(stack, method="median")
+    assert result.dtype == np.float32
+    np.testing.assert_allclose(result, np.nanmedian(stack.astype(np.float64), axis=0), rtol=1e-6)
+
+
+def test_composite_median_uint16_scaled():
+    dn = np.random.default_rng(21).integers(0, 10000, size=(6, 3, 3), dtype=np.uint16)
+    result = composite(dn, method="median", scale=0.0000275, offset=-0.2)
+    assert result.dtype == np.float32
+    expected = np.median(dn.astype(np.float64) * 0.0000275 - 0.2, axis=0)
+    np.testing.assert_allclose(result, expected, rtol=1e-5, atol=1e-6)
+
+
 def test_composite_mean_1d():
     arr = np.array([1.0, 2.0, 3.0, 4.0, 5.0])
     result = composite(arr, method="mean")
@@ -0,0 +1,108 @@
+import numpy as np
+import pytest
+
+from eo_processor import (
+    ci_re,
+    compute_indices,
+    enhanced_vegetation_index,
+    evi2,
+    gci,
+    gndvi,
+    lai,
+    msavi,
+    mtci,
+    nbr,
+    nbr2,
+    ndmi,
+    ndre,
+    ndsi,
+    ndvi,
+    ndvi_re2,
+    ndwi,
+    osavi,
+    savi,
+)
+
+SINGLE = {
+    "ndvi": lambda b: ndvi(b["nir"], b["red"]),
+    "ndwi": lambda b: ndwi(b["green"], b["nir"]),
+    "ndsi": lambda b: ndsi(b["green"], b["swir1"]),
+    "nbr": lambda b: nbr(b["nir"], b["swir2"]),
+    "ndmi": lambda b: ndmi(b["nir"], b["swir1"]),
+    "nbr2": lambda b: nbr2(b["swir1"], b["swir2"]),
+    "gndvi": lambda b: gndvi(b["nir"], b["green"]),
+    "ndre": lambda b: ndre(b["nir"], b["rededge"]),
+    "gci": lambda b: gci(b["nir"], b["green"]),
+    "ci_re": lambda b: ci_re(b["nir"], b["rededge"]),
+    "savi": lambda b: savi(b["nir"], b["red"]),
+    "osavi": lambda b: osavi(b["nir"], b["red"]),
+    "msavi": lambda b: msavi(b["nir"], b["red"]),
+    "evi": lambda b: enhanced_vegetation_index(b["nir"], b["red"], b["blue"]),
+    "evi2": lambda b: evi2(b["nir"], b["red"]),
+    "ndvi_re2": lambda b: ndvi_re2(b["nir"], b["rededge"], b["red"]),
+    "lai": lambda b: lai(b["nir"], b["red"], b["blue"]),
+    "mtci": lambda b: mtci(b["rededge"], b["red"], b["green"]),
+}
+
+
+def _scene(shape=(3, 17, 19), dtype=np.float64, seed=0):
+    rng = np.random.default_rng(seed)
+    names = ["nir", "red", "green", "blue", "swir1", "swir2", "rededge"]
+    bands = {n: rng.uniform(0.0, 0.9, size=shape).astype(dtype) for n in names}
+    # Zero-denominator guard at a fixed pixel.
+    bands["nir"].flat[0] = 0.0
+    bands["red"].flat[0] = 0.0
+    return bands
+
+
+def test_matches_single_index_functions_exactly():
+    bands = _scene()
+    names = list(SINGLE)
+    stack = compute_indices(bands, names)
+    assert stack.shape == (len(names),) + bands["nir"].shape
+    assert stack.dtype == np.float64
+    for plane, name in zip(stack, names):
+        np.testing.assert_array_equal(plane, SINGLE[name](bands), err_msg=name)
+
+
+def test_requested_order_and_case_insensitive():
+    bands = _scene(shape=(5, 6))
+    stack = compute_indices(bands, ["NBR", "ndvi", "Ndwi"])
+    np.testing.assert_array_equal(stack[0], SINGLE["nbr"](bands))
+    np.testing.assert_array_equal(stack[1], SINGLE["ndvi"](bands))
+    np.testing.assert_array_equal(stack[2], SINGLE["ndwi"](bands))
+
+
+def test_only_required_bands_needed_and_broadcast():
+    nir = np.random.default_rng(1).random((2, 4, 4))
+    red = np.full((2, 1, 1), 0.1)
+    stack = compute_indices({"nir": nir, "red": red}, ["ndvi", "savi"])
+    assert stack.shape == (2, 2, 4, 4)
+    np.testing.assert_array_equal(stack[0], ndvi(nir, red))
+    np.testing.assert_array_equal(stack[1], savi(nir, red))
+
+
+def test_native_float32_and_scaled_uint16():
+    bands32 = _scene(shape=(8, 9), dtype=np.float32, seed=2)
+    stack = compute_indices(bands32, ["ndvi", "evi"])
+    assert stack.dtype == np.float32
+    np.testing.assert_array_equal(stack[0], SINGLE["ndvi"](bands32))
+    np.testing.assert_array_equal(stack[1], SINGLE["evi"](bands32))
+
+    rng = np.random.default_rng(3)
+    dn = {n: rng.integers(1000, 9000, size=(8, 9), dtype=np.uint16) for n in ("nir", "swir2")}
+    stack = compute_indices(dn, ["nbr"], scale=1e-4, offset=-0.1)
+    assert stack.dtype == np.float32
+    np.testing.assert_array_equal(stack[0], nbr(dn["nir"], dn["swir2"], scale=1e-4, offset=-0.1))
+
+
+def test_errors():
+    bands = _scene(shape=(4,))
+    with pytest.raises(ValueError, match="Unknown index 'foo'"):
+        compute_indices(bands, ["foo"])
+    with pytest.raises(ValueError, match="requires band 'blue'"):
+        compute_indices({"nir": bands["nir"], "red": bands["red"]}, ["evi"])
+    with pytest.raises(ValueError, match="at least one"):
+        compute_indices(bands, [])
+    with pytest.raises(ValueError, match="Shape mismatch"):
+        compute_indices({"nir": np.ones(3), "red": np.ones(4)}, ["ndvi"])
@@ -0,0 +1,87 @@
+import numpy as np
+import pytest
+
+from eo_processor import fmask, mask_with_scl
+
+CLOUD = [0.45, 0.45, 0.45, 0.5, 0.45, 0.35]
+VEGETATION = [0.03, 0.06, 0.04, 0.35, 0.18, 0.08]
+BARE = [0.12, 0.15, 0.2, 0.28, 0.35, 0.3]
+WATER = [0.06, 0.05, 0.03, 0.02, 0.01, 0.005]
+SNOW = [0.8, 0.8, 0.78, 0.7, 0.1, 0.05]
+DARK = [0.02, 0.03, 0.02, 0.06, 0.05, 0.03]
+
+
+def _scene():
+    bands = np.empty((6, 12, 12))
+    bands[:] = np.array(VEGETATION)[:, None, None]
+    bands[:, 6:, 8:] = np.array(BARE)[:, None, None]
+    bands[:, 2:5, 6:9] = np.array(CLOUD)[:, None, None]
+    bands[:, 2:5, 3:6] = np.array(DARK)[:, None, None]
+    bands[:, 10:, :4] = np.array(WATER)[:, None, None]
+    bands[:, 0, 11] = SNOW
+    bands[:, 11, 11] = np.nan
+    return bands
+
+
+def test_classes_are_scl_codes():
+    bands = _scene()
+    classes, prob = fmask(*bands)
+    assert classes.dtype == np.uint8
+    assert classes.shape == prob.shape == (12, 12)
+    assert (classes[2:5, 6:9] == 9).all()
+    assert (classes[10:, :4] == 6).all()
+    assert classes[0, 11] == 11
+    assert classes[11, 11] == 0 and np.isnan(prob[11, 11])
+    assert classes[0, 0] == 4 and classes[7, 9] == 5
+    # No sun angles: dark pixels next to the cloud are not shadow.
+    assert not (classes == 3).any()
+    assert prob[3, 7] > prob[0, 0]
+
+
+def test_shadow_projection_and_mask_with_scl():
+    bands = _scene()
+    # Sun due east at 45 degrees, 90 m clouds on 30 m pixels: 3 px west.
+    classes, _ = fmask(
+        *bands, sun_azimuth=90.0, sun_zenith=45.0, pixel_size=30.0, cloud_heights=(90, 90)
+    )
+    assert (classes[2:5, 3:6] == 3).all()
+
+    data = np.ones((2, 12, 12))
+    clear = mask_with_scl(data, classes)
+    flagged = np.isin(classes, [0, 3, 9])
+    np.testing.assert_array_equal(np.isnan(clear), np.broadcast_to(flagged, data.shape))
+
+
+def test_dn_scaling_matches_reflectance():
+    bands = _scene()
+    ref, _ = fmask(*bands)
+    dn = np.round(np.nan_to_num(bands * 10000)).astype(np.uint16)
+    classes, _ = fmask(*dn, scale=1e-4)
+    mask = np.isfinite(bands).all(axis=0)
+    np.testing.assert_array_equal(classes[mask], ref[mask])
+
+
+def test_thermal_and_cirrus_inputs():
+    bands = _scene()
+    thermal = np.full((12, 12), 295.0)
+    thermal[2:5, 6:9] = 250.0
+    thermal[8, 2] = 230.0  # vegetation spectra but far colder than clear land
+    classes, _ = fmask(*bands, thermal=thermal)
+    assert (classes[2:5, 6:9] == 9).all()
+    assert classes[8, 2] == 9
+
+    cirrus = np.zeros((12, 12))
+    cirrus[6, 0] = 0.2
+    classes, prob = fmask(*bands, cirrus=cirrus)
+    assert classes[6, 0] == 9
+    assert prob[6, 0] > 4.0
+
+
+def test_invalid_arguments():
+    bands = _scene()
+    with pytest.raises(ValueError):
+        fmask(*bands, sun_azimuth=90.0)
+    with pytest.raises(ValueError):
+        fmask(*bands[:5], bands[5][:4])
+    with pytest.raises(ValueError):
+        fmask(*bands[:, 0])
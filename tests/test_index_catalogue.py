@@ -0,0 +1,114 @@
+import numpy as np
+import pytest
+
+from eo_processor import (
+    arvi,
+    awei_nsh,
+    awei_sh,
+    bsi,
+    compute_indices,
+    kndvi,
+    mndwi,
+    ndbi,
+    ndci,
+    ndvi,
+    nirv,
+    normalized_difference,
+    psri,
+    sipi,
+    tcari_osavi,
+    vari,
+)
+
+
+@pytest.fixture
+def bands():
+    rng = np.random.default_rng(7)
+    shape = (4, 5)
+    return {
+        name: rng.uniform(0.02, 0.5, size=shape)
+        for name in ("blue", "green", "red", "rededge", "nir", "swir1", "swir2")
+    }
+
+
+def test_normalized_difference_aliases(bands):
+    b = bands
+    assert np.allclose(ndbi(b["swir1"], b["nir"]), normalized_difference(b["swir1"], b["nir"]))
+    assert np.allclose(mndwi(b["green"], b["swir1"]), normalized_difference(b["green"], b["swir1"]))
+    assert np.allclose(ndci(b["rededge"], b["red"]), normalized_difference(b["rededge"], b["red"]))
+
+
+def test_water_and_soil_indices(bands):
+    b = bands
+    expected = 4 * (b["green"] - b["swir1"]) - (0.25 * b["nir"] + 2.75 * b["swir2"])
+    assert np.allclose(awei_nsh(b["green"], b["swir1"], b["nir"], b["swir2"]), expected)
+    expected = (
+        b["blue"] + 2.5 * b["green"] - 1.5 * (b["nir"] + b["swir1"]) - 0.25 * b["swir2"]
+    )
+    assert np.allclose(
+        awei_sh(b["blue"], b["green"], b["nir"], b["swir1"], b["swir2"]), expected
+    )
+    x, y = b["swir1"] + b["red"], b["nir"] + b["blue"]
+    assert np.allclose(bsi(b["swir1"], b["red"], b["nir"], b["blue"]), (x - y) / (x + y))
+
+
+def test_kndvi_default_and_sigma(bands):
+    nir, red = bands["nir"], bands["red"]
+    assert np.allclose(kndvi(nir, red), np.tanh(ndvi(nir, red) ** 2))
+    expected = np.tanh(((nir - red) / (2 * 0.2)) ** 2)
+    assert np.allclose(kndvi(nir, red, sigma=0.2), expected)
+    with pytest.raises(ValueError):
+        kndvi(nir, red, sigma=-1.0)
+
+
+def test_vegetation_and_pigment_indices(bands):
+    b = bands
+    assert np.allclose(nirv(b["nir"], b["red"]), ndvi(b["nir"], b["red"]) * b["nir"])
+    assert np.allclose(
+        sipi(b["nir"], b["blue"], b["red"]),
+        (b["nir"] - b["blue"]) / (b["nir"] - b["red"]),
+    )
+    assert np.allclose(psri(b["red"], b["blue"], b["nir"]), (b["red"] - b["blue"]) / b["nir"])
+    rb = 2 * b["red"] - b["blue"]
+    assert np.allclose(arvi(b["nir"], b["red"], b["blue"]), (b["nir"] - rb) / (b["nir"] + rb))
+    assert np.allclose(
+        vari(b["green"], b["red"], b["blue"]),
+        (b["green"] - b["red"]) / (b["green"] + b["red"] - b["blue"]),
+    )
+    re, red, green, nir = b["rededge"], b["red"], b["green"], b["nir"]
+    tcari = 3 * ((re - red) - 0.2 * (re - green) * (re / red))
+    osavi = 1.16 * (nir - red) / (nir + red + 0.16)
+    assert np.allclose(tcari_osavi(re, red, green, nir), tcari / osavi)
+
+
+def test_zero_denominators_return_zero():
+    z = np.zeros(3)
+    assert np.all(sipi(z, z, z) == 0.0)
+    assert np.all(psri(z, z, z) == 0.0)
+    assert np.all(vari(z, z, z) == 0.0)
+    assert np.all(tcari_osavi(z, z, z, z) == 0.0)
+
+
+def test_float32_and_higher_rank(bands):
+    nir = bands["nir"].astype(np.float32)[None, None]
+    red = bands["red"].astype(np.float32)
+    out = nirv(nir, red)
+    assert out.dtype == np.float32
+    assert out.shape == (1, 1, 4, 5)
+
+
+def test_shape_mismatch_raises():
+    with pytest.raises(ValueError):
+        bsi(np.ones(3), np.ones(4), np.ones(3), np.ones(3))
+
+
+def test_catalogue_in_compute_indices(bands):
+    names = ["ndbi", "mndwi", "awei_sh", "bsi", "kndvi", "tcari_osavi"]
+    stack = compute_indices(bands, names)
+    assert stack.shape == (len(names), 4, 5)
+    b = bands
+    assert np.array_equal(stack[0], ndbi(b["swir1"], b["nir"]))
+    assert np.array_equal(
+        stack[2], awei_sh(b["blue"], b["green"], b["nir"], b["swir1"], b["swir2"])
+    )
+    assert np.array_equal(stack[4], kndvi(b["nir"], b["red"]))
@@ -0,0 +1,78 @@
+import math
+
+import numpy as np
+import pytest
+
+import eo_processor
+from eo_processor import (
+    UnknownBandRoleError,
+    band_roles,
+    compute_indices,
+    index_registry,
+)
+
+
+def _bands(roles, shape=(4, 5), seed=0):
+    rng = np.random.default_rng(seed)
+    return {r: rng.uniform(0.05, 0.9, size=shape) for r in roles}
+
+
+def test_registry_entries_complete():
+    registry = index_registry()
+    roles = set(band_roles())
+    assert {"ndvi", "evi", "savi", "mtci", "delta_nbr", "rbr"} <= set(registry)
+    for name, info in registry.items():
+        assert info.name == name
+        assert info.formula and info.long_name
+        assert info.bands and set(info.bands) <= roles
+        lo, hi = info.valid_range
+        assert lo < hi
+        assert callable(info.func)
+        assert info.func is getattr(eo_processor, name)
+
+
+def test_registry_func_matches_compute_indices():
+    registry = index_registry()
+    bands = _bands(band_roles())
+    names = list(registry)
+    stack = compute_indices(bands, names)
+    for plane, name in zip(stack, names):
+        info = registry[name]
+        expected = info.func(*[bands[b] for b in info.bands])
+        np.testing.assert_array_equal(plane, expected, err_msg=name)
+
+
+def test_normalized_difference_indices_within_valid_range():
+    registry = index_registry()
+    bands = _bands(band_roles(), shape=(64,), seed=1)
+    for name in ("ndvi", "ndwi", "nbr", "savi", "delta_nbr"):
+        info = registry[name]
+        out = info.func(*[bands[b] for b in info.bands])
+        lo, hi = info.valid_range
+        assert np.all((out >= lo) & (out <= hi)), name
+    assert registry["mtci"].valid_range == (-math.inf, math.inf)
+
+
+def test_missing_bands_helper():
+    info = index_registry()["evi"]
+    assert info.missing_bands({"nir": 1, "red": 1}) == ["blue"]
+
+
+def test_unknown_band_role_structured_error():
+    bands = _bands(["nir", "red"])
+    bands["nri"] = bands.pop("nir")
+    with pytest.raises(UnknownBandRoleError, match="Unknown band role 'nri'") as exc:
+        compute_indices(bands, ["ndvi"])
+    assert isinstance(exc.value, ValueError)
+    assert exc.value.role == "nri"
+    assert exc.value.valid_roles == band_roles()
+
+
+def test_band_roles_case_insensitive():
+    bands = _bands(["nir", "red"])
+    upper = {k.upper(): v for k, v in bands.items()}
+    np.testing.assert_array_equal(
+        compute_indices(upper, ["ndvi"]), compute_indices(bands, ["ndvi"])
+    )
+    with pytest.raises(ValueError, match="more than once"):
+        compute_indices({**bands, "NIR": bands["nir"]}, ["ndvi"])
@@ -183,23 +183,24 @@ def test_shape_mismatch_ndvi():
 def test_shape_mismatch_evi():
     nir = np.array([0.6, 0.7])
     red = np.array([0.3, 0.2])
-    blue = np.array([0.1])  # mismatched
+    blue = np.array([0.1, 0.05, 0.02])  # not broadcastable
     with pytest.raises(ValueError):
         evi(nir, red, blue)
 
 
 def test_shape_mismatch_evi2():
     nir = np.array([0.6, 0.7])
-    red = np.array([0.3])  # mismatched
+    red = np.array([0.3, 0.2, 0.1])  # not broadcastable
     with pytest.raises(ValueError):
         evi2(nir, red)
 
 
-def test_invalid_dimension():
+def test_five_dimensional_input():
     nir = np.random.rand(2, 2, 2, 2, 2)
     red = np.random.rand(2, 2, 2, 2, 2)
-    with pytest.raises(ValueError):
-        ndvi(nir, red)
+    out = ndvi(nir, red)
+    assert out.shape == nir.shape
+    assert np.allclose(out, (nir - red) / (nir + red))
 
 
 def test_dtype_coercion():
@@ -272,7 +273,7 @@ def test_savi_2d():
 
 def test_savi_shape_mismatch():
     nir = np.array([0.7, 0.6], dtype=np.float64)
-    red = np.array([0.2], dtype=np.float64)
+    red = np.array([0.2, 0.3, 0.4], dtype=np.float64)
     with pytest.raises(ValueError):
         savi(nir, red)
 
@@ -303,7 +304,7 @@ def test_nbr_2d():
 
 def test_nbr_shape_mismatch():
     nir = np.array([0.8, 0.7], dtype=np.float64)
-    swir2 = np.array([0.3], dtype=np.float64)
+    swir2 = np.array([0.3, 0.2, 0.1], dtype=np.float64)
     with pytest.raises(ValueError):
         nbr(nir, swir2)
 
@@ -367,7 +368,7 @@ def test_osavi_shape_mismatch():
     from eo_processor import osavi
 
     nir = np.array([0.7, 0.6], dtype=np.float64)
-    red = np.array([0.2], dtype=np.float64)
+    red = np.array([0.2, 0.3, 0.4], dtype=np.float64)
     with pytest.raises(ValueError):
         osavi(nir, red)
 
@@ -406,7 +407,7 @@ def test_msavi_shape_mismatch():
     from eo_processor import msavi
 
     nir = np.array([0.7, 0.6], dtype=np.float64)
-    red = np.array([0.2], dtype=np.float64)
+    red = np.array([0.2, 0.3, 0.4], dtype=np.float64)
     with pytest.raises(ValueError):
         msavi(nir, red)
 
@@ -444,7 +445,7 @@ def test_gndvi_shape_mismatch():
     from eo_processor import gndvi
 
     nir = np.array([0.7, 0.6], dtype=np.float64)
-    green = np.array([0.2], dtype=np.float64)
+    green = np.array([0.2, 0.3, 0.4], dtype=np.float64)
     with pytest.raises(ValueError):
         gndvi(nir, green)
 
@@ -482,7 +483,7 @@ def test_ndre_shape_mismatch():
     from eo_processor import ndre
 
     nir = np.array([0.7, 0.6], dtype=np.float64)
-    rededge = np.array([0.2], dtype=np.float64)
+    rededge = np.array([0.2, 0.3, 0.4], dtype=np.float64)
     with pytest.raises(ValueError):
         ndre(nir, rededge)
 
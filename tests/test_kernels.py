@@ -0,0 +1,196 @@
+"""Regression tests for the shared N-D element-wise kernel layer.
+
+The per-rank ``_1d``..``_4d`` implementations were replaced by a single
+dynamic-rank path. The reference functions below transcribe the former
+per-pixel formulas verbatim so outputs can be compared bit-for-bit.
+"""
+
+import numpy as np
+import pytest
+
+from eo_processor import (
+    ci_re,
+    delta_nbr,
+    delta_ndvi,
+    dnbr,
+    enhanced_vegetation_index,
+    evi2,
+    gci,
+    gndvi,
+    lai,
+    mask_in_range,
+    mask_invalid,
+    mask_out_range,
+    mask_scl,
+    mask_vals,
+    mask_with_scl,
+    msavi,
+    mtci,
+    nbr,
+    nbr2,
+    ndmi,
+    ndre,
+    ndsi,
+    ndvi,
+    ndvi_re2,
+    ndwi,
+    normalized_difference,
+    osavi,
+    rbr,
+    replace_nans,
+    savi,
+)
+
+EPSILON = 1e-10
+SHAPES = [(7,), (4, 5), (3, 4, 5), (2, 3, 4, 5)]
+
+
+def _safe(num, denom):
+    with np.errstate(divide="ignore", invalid="ignore"):
+        return np.where(np.abs(denom) < EPSILON, 0.0, num / denom)
+
+
+def _nd(a, b):
+    return _safe(a - b, a + b)
+
+
+def _evi(nir, red, blue):
+    return _safe(2.5 * (nir - red), nir + 6.0 * red - 7.5 * blue + 1.0)
+
+
+def _msavi(nir, red):
+    term = 2.0 * nir + 1.0
+    disc = term * term - 8.0 * (nir - red)
+    with np.errstate(invalid="ignore"):
+        return np.where(disc < 0.0, 0.0, (term - np.sqrt(disc)) / 2.0)
+
+
+def _rbr(pn, ps, qn, qs):
+    pre = _nd(pn, ps)
+    return _safe(pre - _nd(qn, qs), pre + 1.0)
+
+
+def _ratio_minus_one(n, d):
+    with np.errstate(divide="ignore", invalid="ignore"):
+        return np.where(np.abs(d) < EPSILON, 0.0, n / d - 1.0)
+
+
+# (function, n_inputs, reference)
+INDEX_CASES = [
+    (normalized_difference, 2, _nd),
+    (ndvi, 2, _nd),
+    (ndwi, 2, _nd),
+    (ndsi, 2, _nd),
+    (nbr, 2, _nd),
+    (ndmi, 2, _nd),
+    (nbr2, 2, _nd),
+    (gndvi, 2, _nd),
+    (ndre, 2, _nd),
+    (gci, 2, _ratio_minus_one),
+    (ci_re, 2, _ratio_minus_one),
+    (savi, 2, lambda n, r: _safe(n - r, n + r + 0.5) * (1.0 + 0.5)),
+    (osavi, 2, lambda n, r: _safe(n - r, n + r + 0.16)),
+    (msavi, 2, _msavi),
+    (evi2, 2, lambda n, r: _safe(2.5 * (n - r), n + 2.4 * r + 1.0)),
+    (enhanced_vegetation_index, 3, _evi),
+    (lai, 3, lambda n, r, b: 3.618 * _evi(n, r, b) + -0.118),
+    (ndvi_re2, 3, lambda n, re, r: _safe(n - re, n - r)),
+    (mtci, 3, lambda re, r, g: _safe(re - r, r - g)),
+    (dnbr, 4, lambda pn, ps, qn, qs: _nd(pn, ps) - _nd(qn, qs)),
+    (delta_nbr, 4, lambda pn, ps, qn, qs: _nd(pn, ps) - _nd(qn, qs)),
+    (delta_ndvi, 4, lambda pn, pr, qn, qr: _nd(pn, pr) - _nd(qn, qr)),
+    (rbr, 4, _rbr),
+]
+INDEX_IDS = [case[0].__name__ for case in INDEX_CASES]
+
+
+def _bands(shape, n, seed=0):
+    rng = np.random.default_rng(seed)
+    bands = [rng.uniform(-0.2, 1.0, size=shape) for _ in range(n)]
+    # Exercise the zero-denominator guard at a fixed location.
+    flat = (0,) * len(shape)
+    bands[0][flat] = 0.0
+    bands[1][flat] = 0.0
+    return bands
+
+
+@pytest.mark.parametrize("shape", SHAPES, ids=lambda s: f"{len(s)}d")
+@pytest.mark.parametrize("func,n,ref", INDEX_CASES, ids=INDEX_IDS)
+def test_index_outputs_unchanged(func, n, ref, shape):
+    bands = _bands(shape, n)
+    out = func(*bands)
+    assert out.shape == shape
+    assert out.dtype == np.float64
+    np.testing.assert_array_equal(out, ref(*bands))
+
+
+@pytest.mark.parametrize("func,n,ref", INDEX_CASES, ids=INDEX_IDS)
+def test_index_five_dimensional(func, n, ref):
+    shape = (2, 2, 3, 4, 2)  # (time, band, y, x, ensemble)
+    bands = _bands(shape, n, seed=1)
+    np.testing.assert_array_equal(func(*bands), ref(*bands))
+
+
+@pytest.mark.parametrize("func,n,ref", INDEX_CASES, ids=INDEX_IDS)
+def test_index_broadcasts_size_one_axes(func, n, ref):
+    full = _bands((3, 4, 5), n, seed=2)
+    # Every operand after the first collapses one axis to length 1.
+    bands = [full[0]] + [b[:, :1, :] if i % 2 else b[:1] for i, b in enumerate(full[1:])]
+    out = func(*bands)
+    assert out.shape == (3, 4, 5)
+    np.testing.assert_array_equal(out, ref(*np.broadcast_arrays(*bands)))
+
+
+@pytest.mark.parametrize("func,n,ref", INDEX_CASES, ids=INDEX_IDS)
+def test_index_incompatible_shapes_raise(func, n, ref):
+    bands = _bands((4, 5), n)
+    bands[-1] = bands[-1][:, :3]
+    with pytest.raises(ValueError, match="Shape mismatch"):
+        func(*bands)
+
+
+def test_non_contiguous_input():
+    rng = np.random.default_rng(3)
+    nir = rng.random((6, 8))[:, ::2].T
+    red = rng.random((4, 6))
+    np.testing.assert_array_equal(ndvi(nir, red), _nd(nir, red))
+
+
+@pytest.mark.parametrize("shape", SHAPES + [(2, 2, 2, 2, 2)], ids=lambda s: f"{len(s)}d")
+def test_mask_outputs_unchanged(shape):
+    rng = np.random.default_rng(4)
+    arr = rng.integers(0, 12, size=shape).astype(np.float64)
+    arr.flat[1] = np.nan
+
+    expected = np.where(np.isin(arr, [0, 11]), np.nan, arr)
+    np.testing.assert_array_equal(mask_vals(arr, values=[0, 11]), expected)
+    np.testing.assert_array_equal(
+        mask_vals(arr, values=[3], fill_value=-1.0, nan_to=-9.0),
+        np.where(np.isnan(arr), -9.0, np.where(arr == 3, -9.0, arr)),
+    )
+    np.testing.assert_array_equal(replace_nans(arr, 0.0), np.nan_to_num(arr, nan=0.0))
+    np.testing.assert_array_equal(
+        mask_invalid(arr, invalid_values=[5], fill_value=-5.0),
+        np.where(arr == 5, -5.0, arr),
+    )
+    with np.errstate(invalid="ignore"):
+        outside = (arr < 2) | (arr > 9)
+        inside = (arr >= 2) & (arr <= 9)
+    np.testing.assert_array_equal(
+        mask_out_range(arr, min_val=2, max_val=9), np.where(outside, np.nan, arr)
+    )
+    np.testing.assert_array_equal(
+        mask_in_range(arr, min_val=2, max_val=9), np.where(inside, np.nan, arr)
+    )
+    np.testing.assert_array_equal(
+        mask_scl(arr), np.where(np.isin(arr, [4, 5, 6, 7, 11]), arr, np.nan)
+    )
+
+
+def test_mask_with_scl_broadcasts():
+    rng = np.random.default_rng(5)
+    data = rng.random((2, 3, 4, 4, 2))
+    scl = rng.integers(0, 12, size=(2, 1, 4, 4, 1)).astype(np.float64)
+    out = mask_with_scl(data, scl)
+    bad = np.isin(np.broadcast_to(scl, data.shape), [0, 1, 2, 3, 8, 9, 10])
+    np.testing.assert_array_equal(out, np.where(bad, np.nan, data))
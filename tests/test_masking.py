@@ -130,7 +130,7 @@ def test_replace_nans_preserves_shape_and_type():
     arr = np.array([[[np.nan, 1.0]]], dtype=np.float32)  # (1,1,2)
     out = replace_nans(arr, 2.5)
     assert out.shape == (1, 1, 2)
-    assert out.dtype == np.float64
+    assert out.dtype == np.float32  # float32 in -> float32 out
     assert np.array_equal(out, np.array([[[2.5, 1.0]]]))
 
 
@@ -428,3 +428,26 @@ def test_mask_with_scl_integer_input_coerced():
     assert out.dtype == np.float64
     assert out[0, 0] == 1.0
     assert np.isnan(out[0, 1])
+
+
+@pytest.mark.parametrize("shape", [(9,), (3, 3), (2, 3, 3), (2, 2, 3, 3)])
+@pytest.mark.parametrize("dtype", [np.uint8, np.uint16, np.float32, np.float64])
+def test_mask_scl_nd_and_valid_mask(shape, dtype):
+    rng = np.random.default_rng(0)
+    scl = rng.integers(0, 12, size=shape).astype(dtype)
+    keep = np.isin(scl, [4, 5, 6, 7, 11])
+    out = mask_scl(scl)
+    assert out.shape == shape
+    np.testing.assert_array_equal(np.isnan(out), ~keep)
+    np.testing.assert_array_equal(out[keep], scl[keep])
+    valid = mask_scl(scl, output="bool")
+    assert valid.dtype == np.bool_
+    np.testing.assert_array_equal(valid, keep)
+    valid_u8 = mask_scl(scl, keep_codes=[4], output="uint8")
+    assert valid_u8.dtype == np.uint8
+    np.testing.assert_array_equal(valid_u8, (scl == 4).astype(np.uint8))
+
+
+def test_mask_scl_rejects_unknown_output():
+    with pytest.raises(ValueError, match="output"):
+        mask_scl(np.array([4, 8]), output="float")
@@ -1,6 +1,19 @@
 import numpy as np
 import pytest
-from eo_processor import binary_dilation, binary_erosion, binary_opening, binary_closing
+from eo_processor import (
+    binary_closing,
+    binary_dilation,
+    binary_erosion,
+    binary_opening,
+    black_tophat,
+    grey_closing,
+    grey_dilation,
+    grey_erosion,
+    grey_opening,
+    morphological_gradient,
+    structuring_element,
+    white_tophat,
+)
 
 
 def test_binary_dilation_basic():
@@ -87,3 +100,98 @@ def test_boundary_handling():
     expected[0:2, 0:2] = 1
 
     np.testing.assert_array_equal(dilated, expected)
+
+
+def test_structuring_elements():
+    disk = structuring_element("disk", 7)
+    assert disk.dtype == np.bool_ and disk.shape == (7, 7)
+    yy, xx = np.indices((7, 7)) - 3
+    np.testing.assert_array_equal(disk, yy**2 + xx**2 <= 9)
+    np.testing.assert_array_equal(structuring_element("diamond", 5), np.abs(yy[1:6, 1:6]) + np.abs(xx[1:6, 1:6]) <= 2)
+    np.testing.assert_array_equal(
+        structuring_element("cross", 3), np.array([[0, 1, 0], [1, 1, 1], [0, 1, 0]], dtype=bool)
+    )
+    np.testing.assert_array_equal(structuring_element("line", 5, angle=45), np.eye(5, dtype=bool)[::-1])
+    np.testing.assert_array_equal(structuring_element("line", 5, angle=90), structuring_element("line", 5).T)
+    with pytest.raises(ValueError):
+        structuring_element("hexagon")
+
+
+def test_named_footprint_dilation():
+    input_arr = np.zeros((9, 9), dtype=np.uint8)
+    input_arr[4, 4] = 1
+    dilated = binary_dilation(input_arr, kernel_size=7, footprint="disk")
+    expected = np.zeros((9, 9), dtype=np.uint8)
+    expected[1:8, 1:8] = structuring_element("disk", 7)
+    np.testing.assert_array_equal(dilated, expected)
+
+    eroded = binary_erosion(dilated, kernel_size=7, footprint="disk")
+    np.testing.assert_array_equal(eroded, input_arr)
+
+
+def test_custom_footprint_and_opening():
+    # A diagonal line survives opening with a matching line but not a square.
+    input_arr = np.eye(9, dtype=np.uint8)
+    line = structuring_element("line", 5, angle=-45)
+    np.testing.assert_array_equal(binary_opening(input_arr, footprint=line), input_arr)
+    assert not binary_opening(input_arr, kernel_size=3).any()
+
+    # Asymmetric footprints shift the mask; closing restores it.
+    shift = np.array([[0, 0, 1]], dtype=np.uint8)
+    single = np.zeros((5, 5), dtype=np.uint8)
+    single[2, 2] = 1
+    shifted = binary_dilation(single, footprint=shift)
+    assert shifted[2, 3] == 1 and shifted.sum() == 1
+    np.testing.assert_array_equal(binary_closing(single, footprint=shift), single)
+
+    with pytest.raises(ValueError):
+        binary_dilation(single, footprint=np.zeros((3, 3), dtype=bool))
+
+
+def _window_reduce(arr, size, reduce):
+    """Reference square-window reduction ignoring NaN and the border."""
+    r = size // 2
+    padded = np.pad(arr.astype(np.float64), r, constant_values=np.nan)
+    windows = np.lib.stride_tricks.sliding_window_view(padded, (size, size))
+    out = reduce(windows, axis=(-2, -1))
+    out[np.isnan(arr)] = np.nan
+    return out
+
+
+def test_grey_erosion_dilation_match_reference():
+    rng = np.random.default_rng(0)
+    arr = rng.uniform(0, 1, size=(20, 17))
+    arr[5, 5] = np.nan
+    np.testing.assert_allclose(grey_dilation(arr, 5), _window_reduce(arr, 5, np.nanmax))
+    np.testing.assert_allclose(grey_erosion(arr, 5), _window_reduce(arr, 5, np.nanmin))
+    assert np.isnan(grey_dilation(arr, 5)[5, 5])
+
+
+def test_grey_dtypes_and_footprints():
+    arr = np.zeros((9, 9), dtype=np.float32)
+    arr[4, 4] = 1.0
+    out = grey_dilation(arr, 5, footprint="disk")
+    assert out.dtype == np.float32
+    expected = np.zeros((9, 9), dtype=np.float32)
+    expected[2:7, 2:7] = structuring_element("disk", 5)
+    np.testing.assert_array_equal(out, expected)
+    assert grey_erosion(np.full((4, 4), 7, dtype=np.uint16)).dtype == np.float32
+    assert grey_closing(arr.astype(np.float64)).dtype == np.float64
+
+
+def test_tophat_and_gradient():
+    ramp = np.tile(np.arange(12, dtype=np.float64), (12, 1))
+    spot = ramp.copy()
+    spot[3:5, 3:5] += 10.0
+    pit = ramp.copy()
+    pit[8, 8] -= 5.0
+
+    white = white_tophat(spot, 5)
+    assert (white[3:5, 3:5] >= 10.0).all()
+    assert (white >= 0).all()
+    np.testing.assert_allclose(grey_opening(ramp, 5)[2:-2, 2:-2], ramp[2:-2, 2:-2])
+
+    black = black_tophat(pit, 3)
+    assert black[8, 8] >= 5.0 and (black >= 0).all()
+
+    np.testing.assert_allclose(morphological_gradient(ramp, 3)[1:-1, 1:-1], 2.0)
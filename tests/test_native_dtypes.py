@@ -0,0 +1,115 @@
+"""float32 / uint16 inputs are read natively and produce float32 output.
+
+The float64 path is the reference; native results must agree within float32
+tolerance.
+"""
+
+import numpy as np
+import pytest
+
+from eo_processor import (
+    mask_in_range,
+    mask_vals,
+    mask_with_scl,
+    ndvi,
+    savi,
+    temporal_mean,
+    temporal_std,
+    temporal_sum,
+)
+
+from .test_kernels import INDEX_CASES, INDEX_IDS, _bands
+
+RTOL, ATOL = 1e-5, 1e-6
+
+
+@pytest.mark.parametrize("func,n,ref", INDEX_CASES, ids=INDEX_IDS)
+def test_index_float32_in_float32_out(func, n, ref):
+    bands64 = [np.abs(b) + 0.05 for b in _bands((3, 4, 5), n, seed=7)]
+    bands32 = [b.astype(np.float32) for b in bands64]
+    out = func(*bands32)
+    assert out.dtype == np.float32
+    expected = func(*[b.astype(np.float64) for b in bands32])
+    assert expected.dtype == np.float64
+    np.testing.assert_allclose(out, expected, rtol=RTOL, atol=ATOL)
+
+
+@pytest.mark.parametrize("func,n,ref", INDEX_CASES, ids=INDEX_IDS)
+def test_index_uint16_dn_with_scale_offset(func, n, ref):
+    rng = np.random.default_rng(8)
+    dn = [rng.integers(1500, 9000, size=(4, 5), dtype=np.uint16) for _ in range(n)]
+    out = func(*dn, scale=1e-4, offset=-0.1)
+    assert out.dtype == np.float32
+    reflectance = [d.astype(np.float64) * 1e-4 - 0.1 for d in dn]
+    np.testing.assert_allclose(out, func(*reflectance), rtol=RTOL, atol=ATOL)
+
+
+def test_scale_offset_float64_unchanged_when_omitted():
+    nir = np.array([0.8, 0.7, 0.6])
+    red = np.array([0.2, 0.1, 0.3])
+    np.testing.assert_array_equal(ndvi(nir, red, scale=1.0, offset=0.0), ndvi(nir, red))
+    assert ndvi(nir, red).dtype == np.float64
+
+
+def test_mixed_dtypes_promote():
+    nir = np.array([8000, 6000], dtype=np.uint16)
+    red32 = np.array([0.2, 0.3], dtype=np.float32)
+    red64 = red32.astype(np.float64)
+    assert ndvi(nir, red32).dtype == np.float32
+    assert ndvi(nir, red64).dtype == np.float64
+    assert savi(red32, red32).dtype == np.float32
+
+
+def test_non_finite_scale_rejected():
+    a = np.ones(3, dtype=np.uint16)
+    with pytest.raises(ValueError, match="finite"):
+        ndvi(a, a, scale=np.nan)
+
+
+@pytest.mark.parametrize("dtype", [np.float32, np.uint16])
+def test_masks_native(dtype):
+    arr = np.array([[0, 3, 5], [7, 11, 0]], dtype=dtype)
+    out = mask_vals(arr, values=[0])
+    assert out.dtype == np.float32
+    np.testing.assert_array_equal(
+        out, mask_vals(arr.astype(np.float64), values=[0]).astype(np.float32)
+    )
+    out = mask_in_range(arr, min_val=4, max_val=8)
+    assert out.dtype == np.float32
+    assert np.isnan(out[0, 2]) and np.isnan(out[1, 0])
+
+
+def test_mask_with_scl_float32_data():
+    data = np.random.default_rng(9).random((2, 3, 4, 4)).astype(np.float32)
+    scl = np.full((2, 4, 4), 4, dtype=np.uint8)
+    scl[0, 0, 0] = 9
+    out = mask_with_scl(data, scl)
+    assert out.dtype == np.float32
+    assert np.isnan(out[0, :, 0, 0]).all()
+    np.testing.assert_array_equal(out[1], data[1])
+
+
+@pytest.mark.parametrize("reducer", [temporal_mean, temporal_std, temporal_sum])
+def test_temporal_float32(reducer):
+    stack = np.random.default_rng(10).random((6, 2, 3, 4))
+    stack[1, 0, 0, 0] = np.nan
+    out = reducer(stack.astype(np.float32))
+    assert out.dtype == np.float32
+    expected = reducer(stack.astype(np.float32).astype(np.float64))
+    np.testing.assert_allclose(out, expected, rtol=RTOL, atol=ATOL)
+
+
+@pytest.mark.parametrize("reducer", [temporal_mean, temporal_std, temporal_sum])
+def test_temporal_uint16_dn(reducer):
+    dn = np.random.default_rng(11).integers(0, 10000, size=(5, 3, 3), dtype=np.uint16)
+    out = reducer(dn, scale=0.0000275, offset=-0.2)
+    assert out.dtype == np.float32
+    expected = reducer(dn.astype(np.float64) * 0.0000275 - 0.2)
+    np.testing.assert_allclose(out, expected, rtol=RTOL, atol=ATOL)
+
+
+def test_temporal_1d_scalar_and_five_dimensional():
+    assert temporal_mean(np.array([1, 2, 3], dtype=np.float32)) == pytest.approx(2.0)
+    stack = np.random.default_rng(12).random((4, 2, 2, 3, 2))
+    np.testing.assert_allclose(temporal_mean(stack), stack.mean(axis=0))
+    np.testing.assert_allclose(temporal_std(stack), stack.std(axis=0, ddof=1))
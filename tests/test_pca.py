@@ -0,0 +1,59 @@
+import numpy as np
+import pytest
+
+from eo_processor import mnf_transform, pca_transform
+
+
+def _cube(seed=0, shape=(20, 24)):
+    rng = np.random.default_rng(seed)
+    yy, xx = np.mgrid[: shape[0], : shape[1]]
+    signal = np.stack([np.sin(xx / 4.0), np.cos(yy / 5.0)])
+    mixing = rng.normal(size=(5, 2))
+    cube = np.einsum("bk,kyx->byx", mixing, signal)
+    return cube + rng.normal(0, 0.05, size=cube.shape)
+
+
+def test_pca_matches_numpy_eigendecomposition():
+    cube = _cube()
+    comps, eigvals, loadings = pca_transform(cube)
+    x = cube.reshape(5, -1)
+    cov = np.cov(x)
+    expected = np.sort(np.linalg.eigvalsh(cov))[::-1]
+    assert np.allclose(eigvals, expected)
+    assert np.allclose(loadings @ loadings.T, np.eye(5), atol=1e-10)
+    centred = x - x.mean(axis=1, keepdims=True)
+    assert np.allclose(comps.reshape(5, -1), loadings @ centred)
+    # Two latent signals dominate the variance.
+    assert eigvals[:2].sum() / eigvals.sum() > 0.99
+
+
+def test_pca_n_components_and_nan_pixels():
+    cube = _cube()
+    cube[3, 4, 5] = np.nan
+    comps, eigvals, loadings = pca_transform(cube, n_components=2)
+    assert comps.shape == (2, 20, 24)
+    assert eigvals.shape == (2,)
+    assert loadings.shape == (2, 5)
+    assert np.all(np.isnan(comps[:, 4, 5]))
+    assert np.isfinite(comps[:, 0, 0]).all()
+    with pytest.raises(ValueError, match="n_components"):
+        pca_transform(cube, n_components=6)
+
+
+def test_pca_float32_dtype():
+    comps, _, _ = pca_transform(_cube().astype(np.float32), n_components=1)
+    assert comps.dtype == np.float32
+
+
+def test_mnf_orders_by_signal_to_noise():
+    cube = _cube(seed=1)
+    comps, eigvals, loadings = mnf_transform(cube)
+    assert comps.shape == (5, 20, 24)
+    assert np.all(np.diff(eigvals) <= 1e-12)
+    # Two smooth signals: large SNR in the first two components, ~noise after.
+    assert eigvals[1] > 10 * eigvals[2]
+
+
+def test_mnf_requires_band_y_x_cube():
+    with pytest.raises(ValueError, match=r"\(band, y, x\)"):
+        mnf_transform(np.ones((3, 10)))
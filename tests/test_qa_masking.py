@@ -0,0 +1,67 @@
+import numpy as np
+import pytest
+
+from eo_processor import mask_with_qa, qa_mask
+
+# Landsat C2 QA_PIXEL: clear land, clear water, high-confidence cloud, fill,
+# cloud shadow (bit 4 with high shadow confidence).
+CLEAR, WATER, CLOUD, FILL = 21824, 21952, 22280, 1
+SHADOW = (1 << 4) | (3 << 10) | (1 << 8)
+
+
+def test_default_qa_pixel_flags():
+    qa = np.array([CLEAR, WATER, CLOUD, FILL, SHADOW], dtype=np.uint16)
+    np.testing.assert_array_equal(qa_mask(qa), [False, False, True, True, True])
+
+
+def test_flag_specs():
+    qa = np.array([[CLEAR, WATER], [CLOUD, SHADOW]], dtype=np.int32)
+    np.testing.assert_array_equal(qa_mask(qa, flags=["water"]), [[False, True], [False, False]])
+    high_shadow = qa_mask(qa, flags=[("cloud_shadow_confidence", 3)])
+    np.testing.assert_array_equal(high_shadow, [[False, False], [False, True]])
+    custom = qa_mask(qa, flags=[[8, 2, [3]]])
+    np.testing.assert_array_equal(custom, [[False, False], [True, False]])
+
+
+def test_radsat():
+    radsat = np.array([0, 1 << 3, 1 << 7, 1 << 11], dtype=np.uint16)
+    np.testing.assert_array_equal(
+        qa_mask(radsat, qa_band="qa_radsat"), [False, True, False, True]
+    )
+
+
+@pytest.mark.parametrize(
+    "data_shape, qa_shape, kwargs",
+    [
+        ((3, 4), (3, 4), {}),
+        ((2, 3, 4), (2, 3, 4), {}),
+        ((2, 5, 3, 4), (2, 3, 4), {}),
+        ((5, 3, 4), (3, 4), {"band_axis": 0}),
+    ],
+)
+def test_mask_with_qa_shapes(data_shape, qa_shape, kwargs):
+    rng = np.random.default_rng(0)
+    qa = rng.choice([CLEAR, CLOUD], size=qa_shape).astype(np.uint16)
+    data = np.ones(data_shape, dtype=np.float32)
+    out = mask_with_qa(data, qa, **kwargs)
+    assert out.shape == data_shape and out.dtype == np.float32
+    flagged = qa == CLOUD
+    if kwargs.get("band_axis") == 0:
+        expected = np.broadcast_to(flagged, data_shape)
+    elif len(data_shape) == 4:
+        expected = np.broadcast_to(flagged[:, None], data_shape)
+    else:
+        expected = flagged
+    np.testing.assert_array_equal(np.isnan(out), expected)
+
+
+def test_errors():
+    qa = np.array([CLEAR], dtype=np.uint16)
+    with pytest.raises(ValueError, match="Unknown QA flag"):
+        qa_mask(qa, flags=["haze"])
+    with pytest.raises(ValueError, match="16 bits"):
+        qa_mask(qa, flags=[(15, 2, 1)])
+    with pytest.raises(ValueError, match="integer dtype"):
+        qa_mask(qa.astype(float))
+    with pytest.raises(ValueError, match="QA shape"):
+        mask_with_qa(np.ones((3, 3)), np.zeros((2, 2), dtype=np.uint16))
@@ -0,0 +1,64 @@
+import numpy as np
+import pytest
+
+from eo_processor import scene_cloud_stats
+
+CLASSES = ["clear", "cloud", "shadow", "snow", "nodata"]
+
+
+def _scl_stack():
+    scl = np.full((3, 4, 5), 4, dtype=np.uint8)
+    scl[1, :2] = 9  # top half cloudy
+    scl[1, 2, :] = 3
+    scl[2, 0, 0] = 11
+    scl[2, 3] = 0
+    return scl
+
+
+def test_fractions_match_numpy():
+    scl = _scl_stack()
+    stats = scene_cloud_stats(scl)
+    assert stats["aoi_pixels"] == 20
+    np.testing.assert_allclose(stats["clear"], [1.0, 0.25, 0.7])
+    np.testing.assert_allclose(stats["cloud"], [0.0, 0.5, 0.0])
+    np.testing.assert_allclose(stats["shadow"], [0.0, 0.25, 0.0])
+    np.testing.assert_allclose(stats["snow"], [0.0, 0.0, 0.05])
+    np.testing.assert_allclose(stats["nodata"], [0.0, 0.0, 0.25])
+    total = sum(stats[c] for c in CLASSES)
+    np.testing.assert_allclose(total, 1.0)
+
+    clear = np.isin(scl, [4, 5, 6, 7]).sum(axis=0)
+    assert stats["clear_count"].dtype == np.uint32
+    np.testing.assert_array_equal(stats["clear_count"], clear)
+
+
+def test_aoi_restricts_counts():
+    scl = _scl_stack()
+    aoi = np.zeros((4, 5), dtype=bool)
+    aoi[2:, :] = True
+    stats = scene_cloud_stats(scl, aoi=aoi)
+    assert stats["aoi_pixels"] == 10
+    np.testing.assert_allclose(stats["clear"], [1.0, 0.5, 0.5])
+    np.testing.assert_allclose(stats["shadow"], [0.0, 0.5, 0.0])
+    np.testing.assert_allclose(stats["nodata"], [0.0, 0.0, 0.5])
+    assert not stats["clear_count"][~aoi].any()
+
+
+def test_qa_pixel_and_single_scene():
+    qa = np.array([[21824, 1], [21824 | 1 << 3, 1 << 4]], dtype=np.uint16)
+    stats = scene_cloud_stats(qa, source="qa_pixel")
+    for name, expected in zip(CLASSES, [0.25, 0.25, 0.25, 0.0, 0.25]):
+        np.testing.assert_allclose(stats[name], [expected])
+    np.testing.assert_array_equal(stats["clear_count"], [[1, 0], [0, 0]])
+
+
+def test_empty_aoi_and_errors():
+    scl = _scl_stack()
+    stats = scene_cloud_stats(scl, aoi=np.zeros((4, 5), dtype=bool))
+    assert np.isnan(stats["clear"]).all()
+    with pytest.raises(ValueError):
+        scene_cloud_stats(scl, aoi=np.ones((3, 3), dtype=bool))
+    with pytest.raises(ValueError):
+        scene_cloud_stats(scl, source="fmask")
+    with pytest.raises(ValueError):
+        scene_cloud_stats(scl.astype(float))
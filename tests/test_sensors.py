@@ -0,0 +1,114 @@
+import numpy as np
+import pytest
+
+from eo_processor import (
+    compute_indices,
+    index_registry,
+    mask_with_scl,
+    ndvi,
+    nbr,
+    sensor_profiles,
+)
+
+
+def _dn_cube(nbands, shape=(4, 5), seed=0):
+    rng = np.random.default_rng(seed)
+    return rng.integers(1100, 9000, size=(nbands, *shape)).astype(np.uint16)
+
+
+def test_sensor_profiles_listed():
+    profiles = sensor_profiles()
+    assert {"sentinel2", "sentinel2_legacy", "landsat8", "landsat9", "modis"} <= set(
+        profiles
+    )
+    s2 = profiles["sentinel2"]
+    assert s2.roles["nir"] == "B08"
+    assert s2.roles["red"] == "B04"
+    assert s2.scl_band == "SCL"
+    assert (s2.scale, s2.offset) == (1e-4, -0.1)
+    assert profiles["landsat8"].scl_band is None
+    assert set(s2.roles.values()) <= set(s2.bands)
+
+
+def test_sentinel2_cube_matches_manual_scaling():
+    s2 = sensor_profiles()["sentinel2"]
+    cube = _dn_cube(len(s2.bands))
+    out = compute_indices(cube, ["ndvi", "nbr"], sensor="sentinel2")
+    assert out.shape == (2, 4, 5)
+    assert out.dtype == np.float32
+
+    def band(role):
+        return cube[s2.bands.index(s2.roles[role])].astype(np.float64) * 1e-4 - 0.1
+
+    np.testing.assert_allclose(out[0], ndvi(band("nir"), band("red")), rtol=1e-5)
+    np.testing.assert_allclose(out[1], nbr(band("nir"), band("swir2")), rtol=1e-5)
+
+
+def test_landsat_scaling_and_override():
+    l8 = sensor_profiles()["landsat8"]
+    cube = _dn_cube(len(l8.bands), seed=1).astype(np.float64)
+    nir = cube[l8.bands.index(l8.roles["nir"])]
+    red = cube[l8.bands.index(l8.roles["red"])]
+    out = compute_indices(cube, ["ndvi"], sensor="landsat8")[0]
+    expected = ndvi(nir * 0.0000275 - 0.2, red * 0.0000275 - 0.2)
+    np.testing.assert_allclose(out, expected)
+    raw = compute_indices(cube, ["ndvi"], sensor="landsat9", scale=1.0, offset=0.0)[0]
+    np.testing.assert_allclose(raw, ndvi(nir, red))
+
+
+def test_band_axis_and_band_names():
+    names = ["B08", "B04", "B12"]
+    cube = _dn_cube(3, seed=2).astype(np.float64)
+    last = np.moveaxis(cube, 0, -1)
+    out = compute_indices(
+        last, ["ndvi"], sensor="sentinel2_legacy", band_axis=-1, band_names=names
+    )[0]
+    np.testing.assert_allclose(out, ndvi(cube[0] * 1e-4, cube[1] * 1e-4))
+
+
+def test_from_cube_matches_compute_indices():
+    s2 = sensor_profiles()["sentinel2"]
+    cube = _dn_cube(len(s2.bands), seed=3)
+    info = index_registry()["evi"]
+    np.testing.assert_array_equal(
+        info.from_cube(cube, "sentinel2"),
+        compute_indices(cube, ["evi"], sensor="sentinel2")[0],
+    )
+
+
+def test_sensor_errors():
+    l8 = sensor_profiles()["landsat8"]
+    cube = _dn_cube(len(l8.bands))
+    with pytest.raises(ValueError, match="has no 'rededge' band"):
+        compute_indices(cube, ["ndre"], sensor="landsat8")
+    with pytest.raises(ValueError, match="Unknown sensor"):
+        compute_indices(cube, ["ndvi"], sensor="spot6")
+    with pytest.raises(ValueError, match="expects"):
+        compute_indices(cube[:3], ["ndvi"], sensor="landsat8")
+    with pytest.raises(ValueError, match="not found in band_names"):
+        compute_indices(
+            cube[:2], ["ndvi"], sensor="landsat8", band_names=["SR_B4", "SR_B3"]
+        )
+
+
+def test_mask_with_scl_sensor_cube():
+    names = ["B04", "B08", "SCL"]
+    cube = np.full((3, 2, 2), 1000.0)
+    cube[2] = [[4, 9], [8, 5]]
+    out = mask_with_scl(cube, sensor="sentinel2", band_names=names)
+    assert np.isnan(out[:2, 0, 1]).all()
+    assert np.isnan(out[:2, 1, 0]).all()
+    np.testing.assert_array_equal(out[:2, 0, 0], [1000.0, 1000.0])
+    np.testing.assert_array_equal(out[2], cube[2])
+    with pytest.raises(ValueError, match="no scene classification"):
+        mask_with_scl(cube, sensor="landsat8", band_names=names)
+    with pytest.raises(ValueError, match="requires `scl`"):
+        mask_with_scl(cube)
+
+
+def test_mask_with_scl_band_axis_broadcast():
+    data = np.ones((2, 2, 3))
+    scl = np.array([[4, 9], [3, 5]], dtype=np.float64)
+    out = mask_with_scl(data, scl, band_axis=-1)
+    assert np.isnan(out[0, 1]).all() and np.isnan(out[1, 0]).all()
+    assert not np.isnan(out[0, 0]).any()
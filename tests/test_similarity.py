@@ -0,0 +1,64 @@
+import numpy as np
+import pytest
+
+from eo_processor import spectral_similarity
+
+LIBRARY = np.array(
+    [
+        [0.04, 0.08, 0.05, 0.45, 0.25],  # vegetation
+        [0.10, 0.15, 0.20, 0.28, 0.35],  # soil
+        [0.08, 0.06, 0.04, 0.02, 0.01],  # water
+    ]
+)
+
+
+def _cube():
+    # Row 0: the references scaled by 0.5, 1 and 2; row 1: NaN, soil, water.
+    cube = np.empty((5, 2, 3))
+    for k, gain in enumerate([0.5, 1.0, 2.0]):
+        cube[:, 0, k] = LIBRARY[k] * gain
+    cube[:, 1, 0] = np.nan
+    cube[:, 1, 1] = LIBRARY[1]
+    cube[:, 1, 2] = LIBRARY[2]
+    return cube
+
+
+def test_sam_matches_reference_formula():
+    cube = _cube()
+    angles, classes, best = spectral_similarity(cube, LIBRARY)
+    assert angles.shape == (3, 2, 3)
+    assert classes.dtype == np.int32
+    np.testing.assert_array_equal(classes, [[0, 1, 2], [-1, 1, 2]])
+    px = cube[:, 0, 0]
+    ref = LIBRARY[1]
+    expected = np.arccos(px @ ref / (np.linalg.norm(px) * np.linalg.norm(ref)))
+    assert angles[1, 0, 0] == pytest.approx(expected)
+    assert np.allclose(best[0], 0.0, atol=1e-6)
+    assert np.isnan(best[1, 0])
+    np.testing.assert_allclose(np.nanmin(angles, axis=0)[0], best[0])
+
+
+@pytest.mark.parametrize("metric", ["sid", "scs"])
+def test_other_metrics_pick_the_same_class(metric):
+    _, classes, _ = spectral_similarity(_cube(), LIBRARY, metric=metric)
+    np.testing.assert_array_equal(classes, [[0, 1, 2], [-1, 1, 2]])
+
+
+def test_threshold_and_dtypes():
+    cube = _cube()
+    cube[:, 1, 1] += 0.05
+    _, classes, _ = spectral_similarity(cube, LIBRARY, threshold=1e-3)
+    assert classes[1, 1] == -1 and classes[0, 1] == 1
+    dn = np.nan_to_num(_cube() * 10000).astype(np.uint16)
+    angles, classes, best = spectral_similarity(dn, LIBRARY, scale=1e-4)
+    assert angles.dtype == np.float32 and best.dtype == np.float32
+    assert classes[0, 2] == 2
+
+
+def test_invalid_arguments():
+    with pytest.raises(ValueError, match="metric"):
+        spectral_similarity(_cube(), LIBRARY, metric="euclid")
+    with pytest.raises(ValueError, match="bands"):
+        spectral_similarity(_cube()[:4], LIBRARY)
+    with pytest.raises(ValueError, match="threshold"):
+        spectral_similarity(_cube(), LIBRARY, threshold=float("nan"))
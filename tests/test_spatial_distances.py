@@ -6,6 +6,7 @@ from eo_processor import (
     manhattan_distance,
     chebyshev_distance,
     minkowski_distance,
+    distance_transform,
 )
 
 
@@ -184,3 +185,43 @@ def test_euclidean_distance_rejects_non_2d():
 
 if __name__ == "__main__":  # pragma: no cover
     pytest.main([__file__])
+
+
+def _brute_force_edt(features, spacing):
+    sites = np.argwhere(features).astype(np.float64)
+    ys, xs = np.indices(features.shape)
+    dy = (ys[..., None] - sites[:, 0]) * spacing[0]
+    dx = (xs[..., None] - sites[:, 1]) * spacing[1]
+    return np.sqrt(dy**2 + dx**2).min(axis=-1)
+
+
+@pytest.mark.parametrize("pixel_size", [None, 10.0, (30.0, 20.0)])
+def test_distance_transform_matches_brute_force(pixel_size):
+    rng = np.random.default_rng(3)
+    features = rng.random((23, 31)) < 0.05
+    features[0, 0] = True
+    spacing = (1.0, 1.0) if pixel_size is None else np.broadcast_to(pixel_size, 2)
+
+    dist, idx = distance_transform(features, pixel_size, return_indices=True)
+    expected = _brute_force_edt(features, spacing)
+    np.testing.assert_allclose(dist, expected, rtol=1e-12, atol=1e-12)
+
+    assert idx.shape == (2,) + features.shape
+    assert idx.dtype == np.int64
+    assert features[tuple(idx)].all()
+    ys, xs = np.indices(features.shape)
+    via_index = np.hypot((ys - idx[0]) * spacing[0], (xs - idx[1]) * spacing[1])
+    np.testing.assert_allclose(via_index, expected, rtol=1e-12, atol=1e-12)
+
+
+def test_distance_transform_without_features():
+    features = np.zeros((3, 4), dtype=np.uint8)
+    dist = distance_transform(features)
+    assert np.isinf(dist).all()
+    _, idx = distance_transform(features, return_indices=True)
+    assert (idx == -1).all()
+
+
+def test_distance_transform_rejects_bad_pixel_size():
+    with pytest.raises(ValueError, match="pixel_size"):
+        distance_transform(np.ones((2, 2), dtype=bool), pixel_size=0.0)
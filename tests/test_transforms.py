@@ -0,0 +1,76 @@
+import numpy as np
+import pytest
+
+from eo_processor import sensor_profiles, tasseled_cap
+
+# Baig et al. (2014) Landsat 8 OLI coefficients over bands 2-7.
+L8_TC = np.array(
+    [
+        [0.3029, 0.2786, 0.4733, 0.5599, 0.5080, 0.1872],
+        [-0.2941, -0.2430, -0.5424, 0.7276, 0.0713, -0.1608],
+        [0.1511, 0.1973, 0.3283, 0.3407, -0.7117, -0.4559],
+    ]
+)
+
+
+def _reflectance(nbands, shape=(4, 5), seed=0):
+    rng = np.random.default_rng(seed)
+    return rng.uniform(0.01, 0.5, size=(nbands, *shape))
+
+
+def test_landsat8_matches_published_coefficients():
+    cube = _reflectance(7)
+    out = tasseled_cap(cube, sensor="landsat8", scale=1.0, offset=0.0)
+    assert out.shape == (3, 4, 5)
+    expected = np.einsum("cb,byx->cyx", L8_TC, cube[1:7])
+    assert np.allclose(out, expected, atol=1e-12)
+
+
+def test_landsat8_dn_scaling_applied():
+    rng = np.random.default_rng(1)
+    dn = rng.integers(8000, 20000, size=(7, 3, 3)).astype(np.uint16)
+    l8 = sensor_profiles()["landsat8"]
+    out = tasseled_cap(dn, sensor="landsat8")
+    assert out.dtype == np.float32
+    refl = dn.astype(np.float64) * l8.scale + l8.offset
+    expected = np.einsum("cb,byx->cyx", L8_TC, refl[1:7])
+    assert np.allclose(out, expected, atol=1e-5)
+
+
+def test_time_first_stack_keeps_time_axis():
+    stack = np.stack([_reflectance(12, seed=s) for s in range(3)])  # (time, band, y, x)
+    out = tasseled_cap(stack, sensor="sentinel2_legacy", scale=1.0)
+    assert out.shape == (3, 3, 4, 5)
+    single = tasseled_cap(stack[2], sensor="sentinel2_legacy", scale=1.0)
+    assert np.allclose(out[2], single)
+
+
+def test_band_names_and_band_axis():
+    cube = _reflectance(7)
+    expected = tasseled_cap(cube, sensor="landsat9", scale=1.0)
+    names = ["SR_B7", "SR_B6", "SR_B5", "SR_B4", "SR_B3", "SR_B2"]
+    reordered = np.moveaxis(cube[[6, 5, 4, 3, 2, 1]], 0, -1)  # (y, x, band)
+    out = tasseled_cap(
+        reordered, sensor="landsat9", band_axis=-1, band_names=names, scale=1.0
+    )
+    assert np.allclose(np.moveaxis(out, -1, 0), expected)
+
+
+def test_custom_coefficients():
+    cube = _reflectance(4)
+    matrix = np.array([[1.0, -1.0, 0.0, 0.0], [0.25, 0.25, 0.25, 0.25]])
+    out = tasseled_cap(cube, coefficients=matrix)
+    assert np.allclose(out[0], cube[0] - cube[1])
+    assert np.allclose(out[1], cube.mean(axis=0))
+
+
+def test_argument_validation():
+    cube = _reflectance(7)
+    with pytest.raises(ValueError, match="exactly one"):
+        tasseled_cap(cube)
+    with pytest.raises(ValueError, match="exactly one"):
+        tasseled_cap(cube, sensor="landsat8", coefficients=L8_TC)
+    with pytest.raises(ValueError, match="No Tasseled Cap coefficients"):
+        tasseled_cap(cube, sensor="modis")
+    with pytest.raises(ValueError, match="columns"):
+        tasseled_cap(cube, coefficients=L8_TC)
@@ -0,0 +1,67 @@
+import numpy as np
+import pytest
+
+from eo_processor import linear_unmixing
+
+ENDMEMBERS = np.array(
+    [
+        [0.04, 0.08, 0.05, 0.45, 0.25, 0.12],  # vegetation
+        [0.10, 0.15, 0.20, 0.28, 0.35, 0.30],  # soil
+        [0.18, 0.20, 0.22, 0.24, 0.26, 0.25],  # impervious
+    ]
+)
+
+
+def _mixed_cube(shape=(5, 4), seed=0):
+    rng = np.random.default_rng(seed)
+    fractions = rng.dirichlet(np.ones(3), size=shape).transpose(2, 0, 1)
+    cube = np.einsum("kb,kyx->byx", ENDMEMBERS, fractions)
+    return cube, fractions
+
+
+@pytest.mark.parametrize("method", ["fcls", "nnls", "scls", "ucls"])
+def test_recovers_fractions_of_pure_mixtures(method):
+    cube, truth = _mixed_cube()
+    fractions, rmse = linear_unmixing(cube, ENDMEMBERS, method=method)
+    assert fractions.shape == (3, 5, 4)
+    assert rmse.shape == (5, 4)
+    assert np.allclose(fractions, truth, atol=1e-5)
+    assert np.all(rmse < 1e-5)
+
+
+def test_fcls_constraints_with_noise():
+    cube, _ = _mixed_cube(seed=1)
+    rng = np.random.default_rng(2)
+    noisy = cube + rng.normal(0, 0.02, size=cube.shape)
+    fractions, rmse = linear_unmixing(noisy, ENDMEMBERS)
+    assert np.all(fractions >= 0)
+    assert np.allclose(fractions.sum(axis=0), 1.0, atol=1e-10)
+    assert np.all(rmse > 0)
+    nnls, _ = linear_unmixing(noisy, ENDMEMBERS, method="nnls")
+    assert np.all(nnls >= 0)
+    scls, _ = linear_unmixing(noisy, ENDMEMBERS, method="scls")
+    assert np.allclose(scls.sum(axis=0), 1.0, atol=1e-10)
+
+
+def test_dn_cube_and_nan_pixels():
+    cube, truth = _mixed_cube()
+    dn = np.round(cube / 1e-4).astype(np.uint16)
+    fractions, rmse = linear_unmixing(dn, ENDMEMBERS, scale=1e-4)
+    assert fractions.dtype == np.float32
+    assert np.allclose(fractions, truth, atol=2e-3)
+
+    cube[2, 1, 1] = np.nan
+    fractions, rmse = linear_unmixing(cube, ENDMEMBERS)
+    assert np.all(np.isnan(fractions[:, 1, 1]))
+    assert np.isnan(rmse[1, 1])
+    assert np.isfinite(rmse[0, 0])
+
+
+def test_invalid_inputs():
+    cube, _ = _mixed_cube()
+    with pytest.raises(ValueError, match="expected 6 bands"):
+        linear_unmixing(cube[:5], ENDMEMBERS)
+    with pytest.raises(ValueError, match="Unknown unmixing method"):
+        linear_unmixing(cube, ENDMEMBERS, method="lasso")
+    with pytest.raises(ValueError, match="rank deficient"):
+        linear_unmixing(cube, np.vstack([ENDMEMBERS, ENDMEMBERS[0]]), method="ucls")